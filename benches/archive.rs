@@ -0,0 +1,116 @@
+// Benchmarks for opening, looking up, extracting, and packing Asar archive files.
+//
+// A synthetic tree is generated in the temporary folder before running. Its shape is configured
+// with environment variables:
+//
+// - RUST_ASAR_BENCH_FILES: number of files (default 1000)
+//
+// - RUST_ASAR_BENCH_FILE_SIZE: size of each file in bytes (default 4096)
+//
+// - RUST_ASAR_BENCH_FOLDER_LEN: number of files per folder (default 100)
+//
+// Run with `cargo bench`, or `cargo test --benches` to check every benchmark's result running each once.
+
+use std::{
+    env,
+    fs::{self, File},
+    hint::black_box,
+    path::{Path, PathBuf},
+    process,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rust_asar::{asar::Asar, index::EntryRef};
+
+struct Synthetic {
+    root: PathBuf,
+    dir: PathBuf,
+    archive: PathBuf,
+    paths: Vec<PathBuf>,
+    bytes: u64,
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+// Writes the synthetic tree and packs it, once per benchmark run.
+fn synthetic() -> Synthetic {
+    let files = env_or("RUST_ASAR_BENCH_FILES", 1000);
+    let file_size = env_or("RUST_ASAR_BENCH_FILE_SIZE", 4096);
+    let folder_len = env_or("RUST_ASAR_BENCH_FOLDER_LEN", 100).max(1);
+
+    let root = env::temp_dir().join(format!("rust_asar-bench-{}", process::id()));
+    let dir = root.join("tree");
+    let archive = root.join("tree.asar");
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(files);
+
+    for i in 0..files {
+        let path = Path::new(&format!("folder{}", i / folder_len)).join(format!("file{}.bin", i));
+        let content: Vec<u8> = (0..file_size).map(|byte| (byte + i) as u8).collect();
+
+        fs::create_dir_all(dir.join(&path).parent().unwrap()).unwrap();
+        fs::write(dir.join(&path), content).unwrap();
+        paths.push(path);
+    }
+
+    Asar::open(&dir).unwrap().pack(&archive).unwrap();
+
+    Synthetic {
+        root,
+        dir,
+        archive,
+        paths,
+        bytes: (files * file_size) as u64,
+    }
+}
+
+// The content of the synthetic file at `path`, as written by `synthetic`.
+fn expected(tree: &Synthetic, path: &Path) -> Vec<u8> {
+    fs::read(tree.dir.join(path)).unwrap()
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let tree = synthetic();
+
+    c.bench_function("header_parse", |b| {
+        let file = File::open(&tree.archive).unwrap();
+        b.iter(|| Asar::get_asar_header(black_box(&file)).unwrap())
+    });
+
+    c.bench_function("open", |b| b.iter(|| Asar::open(black_box(&tree.archive)).unwrap()));
+
+    let asar = Asar::open(&tree.archive).unwrap();
+    let middle = &tree.paths[tree.paths.len() / 2];
+    assert!(tree.paths.iter().all(|path| matches!(asar.stat(path), Some(EntryRef::File(..)))));
+    assert_eq!(asar.get_file(middle), Some(expected(&tree, middle)));
+
+    c.bench_function("lookup_stat", |b| b.iter(|| asar.stat(black_box(middle)).unwrap()));
+    c.bench_function("lookup_get_file", |b| b.iter(|| asar.get_file(black_box(middle)).unwrap()));
+
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Bytes(tree.bytes));
+    group.sample_size(10);
+
+    let extracted = tree.root.join("extracted");
+    group.bench_function("extract", |b| {
+        b.iter(|| {
+            asar.extract(&extracted).unwrap();
+            assert_eq!(fs::read(extracted.join(middle)).unwrap(), expected(&tree, middle));
+            fs::remove_dir_all(&extracted).unwrap();
+        })
+    });
+
+    let packed = tree.root.join("packed.asar");
+    let folder = Asar::open(&tree.dir).unwrap();
+    group.bench_function("pack", |b| b.iter(|| folder.pack(&packed).unwrap()));
+
+    group.finish();
+
+    assert_eq!(fs::read(&packed).unwrap(), fs::read(&tree.archive).unwrap());
+
+    fs::remove_dir_all(&tree.root).ok();
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+// Tests of the addon, run against the archive and folder fixtures at the repository root.
+//
+// Run with `npm test` once the addon is built (`npm run build`).
+
+const assert = require("node:assert");
+const fs = require("node:fs");
+const os = require("node:os");
+const path = require("node:path");
+const test = require("node:test");
+
+const asar = require("..");
+
+const root = path.join(__dirname, "..", "..", "..");
+const archive = path.join(root, "test_asar.asar");
+const folder = path.join(root, "test_folder");
+
+function fixture(file) {
+  return fs.readFileSync(path.join(folder, file));
+}
+
+function tempDir() {
+  return fs.mkdtempSync(path.join(os.tmpdir(), "rust-asar-"));
+}
+
+// Writes an Asar archive file with the header `files` and body `body`, in the pickle layout.
+function writeArchive(file, files, body) {
+  const json = Buffer.from(JSON.stringify({ files }));
+  const padded = Math.ceil(json.length / 4) * 4;
+  const prefix = Buffer.alloc(16);
+
+  prefix.writeUInt32LE(4, 0);
+  prefix.writeUInt32LE(padded + 8, 4);
+  prefix.writeUInt32LE(padded + 4, 8);
+  prefix.writeUInt32LE(json.length, 12);
+
+  fs.writeFileSync(file, Buffer.concat([prefix, json, Buffer.alloc(padded - json.length), body]));
+}
+
+test("listPackage lists sorted paths from the archive root", () => {
+  const paths = asar.listPackage(archive);
+
+  assert.deepStrictEqual(paths, [...paths].sort());
+  assert.ok(paths.includes(path.sep + "test1.txt"));
+  assert.ok(paths.includes(path.sep + "folder1"));
+  assert.ok(paths.includes(path.sep + path.join("folder1", "test_image.jpg")));
+});
+
+test("listPackage lists unpacked files", () => {
+  const dest = tempDir();
+  const unpacked = path.join(dest, "unpacked.asar");
+
+  try {
+    writeArchive(unpacked, { "packed.txt": { size: 1, offset: "0" }, "native.node": { size: 1, unpacked: true } }, Buffer.from("a"));
+    assert.deepStrictEqual(asar.listPackage(unpacked), [path.sep + "native.node", path.sep + "packed.txt"]);
+  } finally {
+    fs.rmSync(dest, { recursive: true });
+  }
+});
+
+test("extractFile reads a file with or without a leading separator", () => {
+  assert.deepStrictEqual(asar.extractFile(archive, "test1.txt"), fixture("test1.txt"));
+  assert.deepStrictEqual(asar.extractFile(archive, "/folder1/script.py"), fixture("folder1/script.py"));
+  assert.throws(() => asar.extractFile(archive, "missing.txt"), /was not found in this archive/);
+});
+
+test("extractAll extracts every file", () => {
+  const dest = tempDir();
+
+  try {
+    asar.extractAll(archive, dest);
+    assert.deepStrictEqual(fs.readFileSync(path.join(dest, "folder1", "test_image.jpg")), fixture("folder1/test_image.jpg"));
+  } finally {
+    fs.rmSync(dest, { recursive: true });
+  }
+});
+
+test("createPackage packs a folder", async () => {
+  const dest = tempDir();
+  const packed = path.join(dest, "packed.asar");
+
+  try {
+    await asar.createPackage(folder, packed);
+    assert.ok(asar.listPackage(packed).includes(path.sep + "another.txt"));
+    assert.deepStrictEqual(asar.extractFile(packed, "another.txt"), fixture("another.txt"));
+  } finally {
+    fs.rmSync(dest, { recursive: true });
+  }
+});
+
+test("a folder or missing file is not an archive", async () => {
+  const dest = tempDir();
+
+  try {
+    assert.throws(() => asar.listPackage(folder), /is not an Asar archive file/);
+    assert.throws(() => asar.extractAll(path.join(root, "missing.asar"), dest));
+    await assert.rejects(asar.createPackage(path.join(root, "missing"), path.join(dest, "packed.asar")));
+  } finally {
+    fs.rmSync(dest, { recursive: true });
+  }
+});
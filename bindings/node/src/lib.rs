@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+use napi::{bindgen_prelude::*, Task};
+use napi_derive::napi;
+use rust_asar::{asar::Asar, asar_error};
+
+fn to_napi_err(err: asar_error::Error) -> Error {
+    Error::new(Status::GenericFailure, err.to_string())
+}
+
+fn open_archive(archive_path: &str) -> Result<Asar> {
+    if Path::new(archive_path).is_dir() {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{} is not an Asar archive file", archive_path),
+        ));
+    }
+
+    Asar::open(archive_path).map_err(to_napi_err)
+}
+
+pub struct CreatePackage {
+    src: String,
+    dest: String,
+}
+
+impl Task for CreatePackage {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        Asar::open(&self.src)
+            .and_then(|asar| asar.pack(&self.dest))
+            .map_err(to_napi_err)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Packs the directory `src` into the Asar archive file `dest`, as does `asar.createPackage`.
+/// Packing runs on the libuv thread pool.
+#[napi(js_name = "createPackage", ts_return_type = "Promise<void>")]
+pub fn create_package(src: String, dest: String) -> AsyncTask<CreatePackage> {
+    AsyncTask::new(CreatePackage { src, dest })
+}
+
+/// Extracts the Asar archive file `archive_path` to the folder `dest`, as does `asar.extractAll`.
+#[napi(js_name = "extractAll")]
+pub fn extract_all(archive_path: String, dest: String) -> Result<()> {
+    open_archive(&archive_path)?.extract(dest).map_err(to_napi_err)
+}
+
+// Appends the full path of every entry below the folder, walking the header itself so unpacked
+// files and links are listed too, as `asar.listPackage` lists them.
+fn list_entries(asar: &Asar, folder: &Path, paths: &mut Vec<PathBuf>) {
+    let Some(entries) = asar.content.folder_contents(folder) else {
+        return;
+    };
+
+    for (name, entry) in entries {
+        let path = folder.join(name);
+
+        if entry.get("files").is_some() {
+            list_entries(asar, &path, paths);
+        }
+
+        paths.push(path);
+    }
+}
+
+/// Lists every file and folder within the Asar archive file `archive_path`, as does
+/// `asar.listPackage`: sorted, each path starting with the platform path separator.
+#[napi(js_name = "listPackage")]
+pub fn list_package(archive_path: String) -> Result<Vec<String>> {
+    let asar = open_archive(&archive_path)?;
+
+    let mut entries: Vec<PathBuf> = Vec::new();
+    list_entries(&asar, Path::new(""), &mut entries);
+
+    let mut paths: Vec<String> = entries
+        .into_iter()
+        .map(|path| format!("{}{}", MAIN_SEPARATOR, path.display()))
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Returns the content of the file `filename` within the Asar archive file `archive_path`,
+/// as does `asar.extractFile`.
+#[napi(js_name = "extractFile")]
+pub fn extract_file(archive_path: String, filename: String) -> Result<Buffer> {
+    let asar = open_archive(&archive_path)?;
+    let filename = filename.trim_start_matches(['/', '\\']);
+
+    match asar.get_file(filename) {
+        Some(content) => Ok(content.into()),
+        None => Err(Error::new(
+            Status::InvalidArg,
+            format!("\"{}\" was not found in this archive", filename),
+        )),
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use pyo3::{create_exception, exceptions::{PyException, PyFileNotFoundError, PyOSError}, prelude::*, types::PyBytes};
+use rust_asar::{asar, asar_error};
+
+create_exception!(pyasar, AsarError, PyException, "Raised when an Asar archive file can not be parsed or packed.");
+
+fn to_py_err(err: asar_error::Error) -> PyErr {
+    match err {
+        asar_error::Error::IoError(err) => PyOSError::new_err(err.to_string()),
+        err => AsarError::new_err(err.to_string()),
+    }
+}
+
+/// An opened Asar archive file, or a directory to be packed into one.
+#[pyclass(frozen)]
+struct Asar {
+    inner: asar::Asar,
+}
+
+#[pymethods]
+impl Asar {
+    /// Opens the Asar archive file or directory at `path`.
+    #[staticmethod]
+    fn open(path: PathBuf) -> PyResult<Asar> {
+        Ok(Asar {
+            inner: asar::Asar::open(path).map_err(to_py_err)?,
+        })
+    }
+
+    /// Returns the full path of every file and folder within the archive.
+    fn list(&self) -> PyResult<Vec<String>> {
+        self.inner.list().map_err(to_py_err)
+    }
+
+    /// Returns the content of the file at `path` within the archive.
+    fn read<'py>(&self, py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyBytes>> {
+        match self.inner.get_file(&path) {
+            Some(content) => Ok(PyBytes::new(py, &content)),
+            None => Err(PyFileNotFoundError::new_err(format!(
+                "File not found in archive: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Extracts the archive to the folder at `destination`.
+    fn extract(&self, py: Python<'_>, destination: PathBuf) -> PyResult<()> {
+        py.detach(|| self.inner.extract(destination)).map_err(to_py_err)
+    }
+
+    /// Packs the opened directory to the Asar archive file at `destination`.
+    fn pack(&self, py: Python<'_>, destination: PathBuf) -> PyResult<()> {
+        py.detach(|| self.inner.pack(destination)).map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+fn pyasar(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Asar>()?;
+    m.add("AsarError", m.py().get_type::<AsarError>())?;
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+# Tests of the pyasar bindings, run against the archive and folder fixtures at the repository root.
+#
+# Run with `python -m unittest discover bindings/python/tests` once pyasar is installed
+# (e.g. `maturin develop` within bindings/python).
+
+import os
+import tempfile
+import unittest
+
+import pyasar
+
+ROOT = os.path.join(os.path.dirname(__file__), "..", "..", "..")
+ARCHIVE = os.path.join(ROOT, "test_asar.asar")
+FOLDER = os.path.join(ROOT, "test_folder")
+
+
+def fixture(path):
+    with open(os.path.join(FOLDER, path), "rb") as file:
+        return file.read()
+
+
+class TestAsar(unittest.TestCase):
+    def test_list(self):
+        paths = pyasar.Asar.open(ARCHIVE).list()
+
+        self.assertIn("test1.txt", paths)
+        self.assertIn("folder1", paths)
+        self.assertIn(os.path.join("folder1", "test_image.jpg"), paths)
+
+    def test_read(self):
+        asar = pyasar.Asar.open(ARCHIVE)
+
+        self.assertEqual(asar.read("test1.txt"), fixture("test1.txt"))
+        self.assertEqual(asar.read("folder1/test_image.jpg"), fixture("folder1/test_image.jpg"))
+
+        with self.assertRaises(FileNotFoundError):
+            asar.read("missing.txt")
+
+    def test_extract(self):
+        with tempfile.TemporaryDirectory() as destination:
+            pyasar.Asar.open(ARCHIVE).extract(destination)
+
+            with open(os.path.join(destination, "folder1", "script.py"), "rb") as file:
+                self.assertEqual(file.read(), fixture("folder1/script.py"))
+
+    def test_pack(self):
+        with tempfile.TemporaryDirectory() as destination:
+            packed = os.path.join(destination, "packed.asar")
+            pyasar.Asar.open(FOLDER).pack(packed)
+
+            asar = pyasar.Asar.open(packed)
+            self.assertIn("another.txt", asar.list())
+            self.assertEqual(asar.read("another.txt"), fixture("another.txt"))
+
+    def test_errors(self):
+        with self.assertRaises(OSError):
+            pyasar.Asar.open(os.path.join(ROOT, "missing.asar"))
+
+        with tempfile.NamedTemporaryFile(suffix=".asar", delete=False) as file:
+            file.write(b"not an archive")
+
+        try:
+            with self.assertRaises(pyasar.AsarError):
+                pyasar.Asar.open(file.name)
+        finally:
+            os.remove(file.name)
+
+
+if __name__ == "__main__":
+    unittest.main()
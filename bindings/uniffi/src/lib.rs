@@ -0,0 +1,134 @@
+use std::{fmt, sync::Arc};
+
+use rust_asar::{asar::Asar, asar_error};
+
+uniffi::setup_scaffolding!();
+
+/// Errors raised to Swift and Kotlin.
+#[derive(Debug, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum AsarError {
+    /// Reading or writing a file failed.
+    Io(String),
+    /// The path is not a file within the archive.
+    NotFound(String),
+    /// The archive could not be parsed or packed.
+    Archive(String),
+}
+
+impl fmt::Display for AsarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(message) | Self::NotFound(message) | Self::Archive(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AsarError {}
+
+impl From<asar_error::Error> for AsarError {
+    fn from(err: asar_error::Error) -> Self {
+        match err {
+            asar_error::Error::IoError(err) => AsarError::Io(err.to_string()),
+            err => AsarError::Archive(err.to_string()),
+        }
+    }
+}
+
+/// An opened Asar archive file.
+#[derive(uniffi::Object)]
+pub struct AsarArchive {
+    asar: Asar,
+}
+
+#[uniffi::export]
+impl AsarArchive {
+    /// Opens the Asar archive file at `path`.
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, AsarError> {
+        if std::path::Path::new(&path).is_dir() {
+            return Err(AsarError::Archive(format!("{} is not an Asar archive file", path)));
+        }
+
+        Ok(Arc::new(AsarArchive { asar: Asar::open(path)? }))
+    }
+
+    /// Returns the full path of every file and folder within the archive, sorted.
+    pub fn list(&self) -> Result<Vec<String>, AsarError> {
+        let mut paths = self.asar.list()?;
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    /// Returns the content of the file at `path` within the archive.
+    pub fn read(&self, path: String) -> Result<Vec<u8>, AsarError> {
+        self.asar
+            .get_file(&path)
+            .ok_or_else(|| AsarError::NotFound(format!("File not found in archive: {}", path)))
+    }
+
+    /// Extracts the archive to the folder at `destination`.
+    pub fn extract(&self, destination: String) -> Result<(), AsarError> {
+        Ok(self.asar.extract(destination)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{AsarArchive, AsarError};
+
+    fn root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+    }
+
+    fn archive() -> String {
+        root().join("test_asar.asar").to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_list() -> Result<(), AsarError> { // tests listing the archive, sorted
+        let paths = AsarArchive::open(archive())?.list()?;
+
+        let mut sorted = paths.clone();
+        sorted.sort();
+        assert_eq!(paths, sorted);
+        assert!(paths.contains(&"folder1/test_image.jpg".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read() -> Result<(), AsarError> { // tests reading a file, or a missing one
+        let asar = AsarArchive::open(archive())?;
+
+        assert_eq!(asar.read("test1.txt".to_string())?, std::fs::read(root().join("test_folder/test1.txt")).unwrap());
+        assert!(matches!(asar.read("missing.txt".to_string()), Err(AsarError::NotFound(_))));
+        assert!(matches!(asar.read("folder1".to_string()), Err(AsarError::NotFound(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract() -> Result<(), AsarError> { // tests extracting the archive
+        let destination = std::env::temp_dir().join(format!("rust_asar_uniffi-{}", std::process::id()));
+        AsarArchive::open(archive())?.extract(destination.to_string_lossy().into_owned())?;
+
+        assert_eq!(
+            std::fs::read(destination.join("folder1/script.py")).unwrap(),
+            std::fs::read(root().join("test_folder/folder1/script.py")).unwrap()
+        );
+
+        std::fs::remove_dir_all(destination).unwrap();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_errors() { // tests opening a folder or a missing file
+        assert!(matches!(AsarArchive::open(root().join("test_folder").to_string_lossy().into_owned()), Err(AsarError::Archive(_))));
+        assert!(matches!(AsarArchive::open(root().join("missing.asar").to_string_lossy().into_owned()), Err(AsarError::Io(_))));
+    }
+}
@@ -0,0 +1,20 @@
+fn main() {
+    // regenerate the C header of the ffi module within OUT_DIR, leaving the source tree untouched
+    // (include/rust_asar.h is a committed copy, checked against it by the tests)
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+
+        let config = cbindgen::Config::from_file("cbindgen.toml").expect("failed to read cbindgen.toml");
+
+        cbindgen::Builder::new()
+            .with_src("src/ffi.rs")
+            .with_config(config)
+            .generate()
+            .expect("failed to generate C header")
+            .write_to_file(std::path::Path::new(&std::env::var("OUT_DIR").expect("OUT_DIR not set")).join("rust_asar.h"));
+    }
+
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,77 @@
+#ifndef RUST_ASAR_H
+#define RUST_ASAR_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// An Asar archive file opened by `asar_open`, freed by `asar_close`.
+typedef struct AsarArchive AsarArchive;
+
+// Returns a description of the last failure on the calling thread, or NULL if nothing failed.
+//
+// The string is owned by the library and valid until the next call on the same thread.
+const char *asar_last_error(void);
+
+// Opens the Asar archive file at `path` (a null-terminated UTF-8 string).
+//
+// Returns the archive, to be freed with `asar_close`, otherwise NULL.
+//
+// # Safety
+//
+// `path` must be NULL or a valid null-terminated string.
+struct AsarArchive *asar_open(const char *path);
+
+// Frees an archive returned by `asar_open`. Passing NULL does nothing.
+//
+// # Safety
+//
+// `archive` must be NULL or returned by `asar_open`, and not freed already.
+void asar_close(struct AsarArchive *archive);
+
+// Lists the full path of every file and folder within the archive, sorted, storing the number
+// of paths in `len`.
+//
+// Returns an array of null-terminated strings, to be freed with `asar_list_free`, otherwise NULL.
+//
+// # Safety
+//
+// `archive` must be returned by `asar_open`, and `len` must point to a writable `size_t`.
+char **asar_list(const struct AsarArchive *archive, size_t *len);
+
+// Frees an array returned by `asar_list`, along with its strings.
+//
+// # Safety
+//
+// `list` must be NULL or returned by `asar_list` with the same `len`, and not freed already.
+void asar_list_free(char **list, size_t len);
+
+// Reads the file at `path` within the archive into memory, storing its size in `len`.
+//
+// Returns the content, to be freed with `asar_bytes_free`, otherwise NULL.
+//
+// # Safety
+//
+// `archive` must be returned by `asar_open`, `path` must be a valid null-terminated string,
+// and `len` must point to a writable `size_t`.
+uint8_t *asar_read_file(const struct AsarArchive *archive, const char *path, size_t *len);
+
+// Frees content returned by `asar_read_file`.
+//
+// # Safety
+//
+// `bytes` must be NULL or returned by `asar_read_file` with the same `len`, and not freed already.
+void asar_bytes_free(uint8_t *bytes, size_t len);
+
+// Extracts the archive to the folder at `destination`, creating it if needed.
+//
+// Returns 0, otherwise -1.
+//
+// # Safety
+//
+// `archive` must be returned by `asar_open`, and `destination` must be a valid null-terminated string.
+int asar_extract(const struct AsarArchive *archive,
+                 const char *destination);
+
+#endif  /* RUST_ASAR_H */
@@ -1,21 +1,42 @@
 use std::{
-    fs::{File, self, OpenOptions, remove_file},
-    path::{Path, PathBuf}, io::Write,
+    fs::{File, self},
+    path::{Path, PathBuf}, io::{self, BufWriter, Read, Seek, SeekFrom, Write}, sync::Arc, time::Instant,
 };
 
 use byteorder::{LittleEndian, WriteBytesExt};
-use positioned_io::{ReadAt, ReadBytesExt};
+use positioned_io::{Cursor, ReadAt, Slice};
 use serde_json::{Value, Map, json};
 
 use crate::{
     asar_error::{self, Error},
-    content::Content,
+    cas,
+    content::{self, Content},
+    copy::CopiedEntry,
+    extract::ExtractOptions,
+    header::{self, ArchiveFormat},
+    hooks::RunContext,
+    index::{build_index, EntryRef, PathIndex},
+    kinds,
+    layout,
+    links,
+    locked,
+    metrics::{Operation, Recorder},
+    mode::OpenMode,
+    pack::{PackHooks, PackOptions, Packer, PolicyDecision},
+    repack,
+    temp::TempGuard,
+    throttle::Throttled,
+    trace,
+    transform::{Transformed, TransformResult},
+    verify::Verification,
+    volume::{self, ArchiveFile},
 };
 
 
-const JSON_LEN_OFFSET: u64 = 12;
-const JSON_OFFSET: u64 = 16;
-const HEADER_LEN_OFFSET: u64 = 8;
+
+// A header generated from a directory, the files of its body `(full_file_path, file_size)` in
+// order, the full paths of its unpacked files, and of the files skipped while locked.
+type GeneratedHeader = (Value, Vec<(PathBuf, u64)>, Vec<PathBuf>, Vec<PathBuf>);
 
 /// Asar represents the structure of an Asar archive file, allowing for extraction, modification, and creation.
 ///
@@ -24,13 +45,47 @@ const HEADER_LEN_OFFSET: u64 = 8;
 /// - content: Content enum to represent the file structure within an Asar archive file
 /// - start: Offset at which content begins (after the header) in archive file.
 /// - header: JSON value of header stored only if Asar is instantiated as a directory, otherwise remains None.
-
+/// - index: Path index of every file and folder, built when an Asar archive file is opened (see `Asar::index`).
+/// - options: Options applied while packing (see `Asar::open_with`), such as the alignment of file bodies.
+/// - metrics: Metrics called while reading or writing files (see `Asar::set_metrics`).
+/// - copies: Files copied from other archives, written after the files of a directory (see `Asar::copy_entry_from`).
+/// - transformed: Transformed content of the files of a directory, written in their place (see `PackOptions::transform`).
+/// - mode: Whether packing and mutation are allowed (see `Asar::open_mode`).
+/// - unpacked: Files of a directory copied next to the archive rather than within it (see `PackOptions::unpack`).
+/// - skipped: Files of a directory left out while locked (see `Asar::skipped_locked`).
+/// - source: Where the archive is read from: a file, the volumes of a split archive, decrypted plaintext, or a nested region.
 #[derive(Clone, Debug)]
 pub struct Asar {
     pub src_path: PathBuf,
     pub content: Content,
     pub start: u64,
-    pub header: Option<Value>
+    pub header: Option<Value>,
+    pub(crate) index: PathIndex,
+    pub(crate) options: PackOptions,
+    pub(crate) metrics: Recorder,
+    pub(crate) copies: Vec<CopiedEntry>,
+    pub(crate) transformed: Arc<Transformed>,
+    pub(crate) mode: OpenMode,
+    pub(crate) unpacked: Vec<PathBuf>,
+    pub(crate) skipped: Vec<PathBuf>,
+    pub(crate) source: Source,
+}
+
+/// Where the bytes of an opened Asar archive are read from:
+/// - Path: The file at `src_path` (or a directory being packed)
+/// - Volumes: The volumes `(path, size)` of a split archive, read as one file (see `Asar::volumes`)
+/// - Decrypted: Plaintext of an encrypted archive at `src_path`, held in memory and shared by
+///   every clone (see `Asar::open_encrypted`)
+/// - Nested (outermost, base, len): An archive nested within another, stored as the `len` bytes at
+///   offset `base` of the outermost archive, read from the source `outermost` (see `Asar::open_nested`)
+#[derive(Clone, Debug, Default)]
+pub(crate) enum Source {
+    #[default]
+    Path,
+    Volumes(Vec<(PathBuf, u64)>),
+    #[cfg(feature = "encryption")]
+    Decrypted(Arc<[u8]>),
+    Nested(Box<Source>, u64, u64),
 }
 
 impl Asar {
@@ -40,59 +95,134 @@ impl Asar {
     /// Takes in one argument of type Path that represents either the Asar archive file or a directory/folder.
     /// 
     /// Initializes necessary fields within Asar struct, returning instantiated struct or Error.
-
     pub fn open<P: AsRef<Path>>(src_path: P) -> Result<Asar, asar_error::Error> {
+        Self::open_with(src_path, &PackOptions::default())
+    }
+
+    /// Opens either an Asar archive file or a directory, as does `open`.
+    /// 
+    /// If a directory is opened, the provided PackOptions are applied while generating its header,
+    /// otherwise only their split size and throttle (see `PackOptions::split_size` and
+    /// `PackOptions::throttle`) are applied, when repacking.
+    /// 
+    /// Returns instantiated struct, otherwise Error.
+    pub fn open_with<P: AsRef<Path>>(src_path: P, options: &PackOptions) -> Result<Asar, asar_error::Error> {
         let src_path = src_path.as_ref();
+        let _span = trace::span!(DEBUG, "open", path = %src_path.display());
 
         if src_path.is_dir() {
             
-            let (header, list) = Self::gen_header_from_dir(src_path)?;
+            let mut transformed = Transformed::new();
+            let (header, list, unpacked, skipped) = Self::gen_header_transformed(src_path, options, Some(&mut transformed))?;
+            let mut asar = Self::from_header(src_path, header, list)?;
 
-            Ok(Asar {
-                src_path: src_path.to_path_buf(),
-                content: Content::new_list(list),
-                start: (serde_json::to_vec(&header)?.len() + 16) as u64, //account for padding
-                header: Some(header)
-            })
+            asar.options = options.clone();
+            asar.transformed = Arc::new(transformed);
+            asar.unpacked = unpacked;
+            asar.skipped = skipped;
+            asar.start = content::align_up(asar.start, asar.align()); //header padded to alignment
+
+            Ok(asar)
 
         } else {
-            //src must be asar
+            //src must be asar, or the first volume of a split archive
+            let first = volume::first_volume(src_path);
+            let src_path = first.as_deref().unwrap_or(src_path);
             let file = File::open(src_path)?;
 
-            if let Ok((header, start)) = Self::get_asar_header(&file) {
-                
-                Ok(Asar {
-                    src_path: src_path.to_path_buf(),
-                    content: Content::new_json(header)?,
-                    start: start,
-                    header: None
-                })
-            } else {
-                Err(Error::ParseHeaderError(
-                    "Failed to parse archive header, check format".to_string(),
-                ))
+            match Self::get_asar_header(&file) {
+                Ok((header, start)) => {
+                    let source = match volume::volume_sizes(src_path, &header)? {
+                        volumes if volumes.is_empty() => Source::Path,
+                        volumes => Source::Volumes(volumes),
+                    };
+
+                    Self::from_archive(src_path, header, start, source, options)
+                }
+                Err(_err) => {
+                    trace::event!(WARN, error = %_err, "failed to parse archive header");
+
+                    Err(Error::ParseHeaderError(
+                        "Failed to parse archive header, check format".to_string(),
+                    ))
+                }
             }
         }
     }
 
+    // Instantiates an opened Asar archive file at `src_path` from its header, read from the source.
+    pub(crate) fn from_archive(
+        src_path: &Path,
+        header: Value,
+        start: u64,
+        source: Source,
+        options: &PackOptions,
+    ) -> Result<Asar, asar_error::Error> {
+        let content = Content::new_json(header)?;
+
+        Ok(Asar {
+            src_path: src_path.to_path_buf(),
+            index: build_index(&content)?,
+            content,
+            start,
+            header: None,
+            options: options.repacking(),
+            metrics: Recorder::default(),
+            copies: Vec::new(),
+            transformed: Arc::default(),
+            mode: OpenMode::default(),
+            unpacked: Vec::new(),
+            skipped: Vec::new(),
+            source,
+        })
+    }
+
+    /// Instantiates an Asar struct ready to be packed from a generated header and its ordered list of
+    /// files `(full_file_path, file_size)`, as returned by `gen_header_from_dir`.
+    /// 
+    /// The offsets within the header must follow the order of the list.
+    /// 
+    /// Returns instantiated struct, otherwise Error.
+    pub fn from_header<P: AsRef<Path>>(src_path: P, header: Value, list: Vec<(PathBuf, u64)>) -> Result<Asar, asar_error::Error> {
+        Ok(Asar {
+            src_path: src_path.as_ref().to_path_buf(),
+            content: Content::new_list(list),
+            start: json_len(&header)? + 16, //account for padding
+            header: Some(header),
+            index: PathIndex::new(),
+            options: PackOptions::default(),
+            metrics: Recorder::default(),
+            copies: Vec::new(),
+            transformed: Arc::default(),
+            mode: OpenMode::default(),
+            unpacked: Vec::new(),
+            skipped: Vec::new(),
+            source: Source::Path,
+        })
+    }
+
     /// Returns a tuple of the header of an Asar archive file as `serde_json::Value`, and the start offset as `u64`, 
     /// otherwise Error.
     ///
+    /// The start offset is read from the header size, so any padding after the JSON value 
+    /// (as written by aligning packers) is skipped. Trailing null bytes counted within the JSON length are ignored.
+    ///
     /// The file provided must be an Asar archive file, otherwise unintended behavior may occur.
-    
     pub fn get_asar_header(file: &File) -> Result<(Value, u64), asar_error::Error> {
-        let json_len = file.read_u32_at::<LittleEndian>(JSON_LEN_OFFSET)?;
-        let mut json_u8: Vec<u8> = vec![0; json_len as usize];
-
-        file.read_exact_at(JSON_OFFSET, &mut json_u8)?;
+        Ok(header::read_header(file)?)
+    }
 
-        let value = serde_json::from_slice(&json_u8)?;
-        let start = {
-            // 12 bytes prior to header must be included
-            (file.read_u32_at::<LittleEndian>(HEADER_LEN_OFFSET)? + 12) as u64
-        };
+    /// Returns the ArchiveFormat of an opened Asar archive file, read from its prefix.
+    /// 
+    /// Returns the format, otherwise Error if a directory is open.
+    pub fn format(&self) -> Result<ArchiveFormat, asar_error::Error> {
+        if self.src_path.is_dir() {
+            return Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
 
-        Ok((value, start))
+        Ok(header::read_format(&self.archive_file()?)?)
     }
 
     /// Generates a header for the Asar archive file from the provided directory, along with, 
@@ -105,25 +235,129 @@ impl Asar {
     /// 
     /// Returns a tuple of `serde_json::Value` and `Vec<(PathBuf, u64)>`, otherwise Error.
     /// 
-
     pub fn gen_header_from_dir<P: AsRef<Path>>(path: P) -> Result<(Value, Vec<(PathBuf, u64)>), asar_error::Error> {
-        let mut offset: u64 = 0;
-        let mut list_of_paths: Vec<(PathBuf, u64)> = Vec::new();
+        Self::gen_header_from_dir_with(path, &PackOptions::default())
+    }
 
-        /*let header = {
-            let mut header = Map::new();
-            
-            header.insert("files".to_string(), Self::dir_to_value(path, &mut json_length, &mut list_of_paths)?);
+    /// Generates a header from the provided directory, as does `gen_header_from_dir`, applying the 
+    /// policy and layout of the provided PackOptions to every file.
+    /// 
+    /// Offsets are assigned by `layout::assign_offsets`, with files sorted by path (after any
+    /// ordering hints), so the layout does not depend on the order the filesystem lists files in.
+    /// 
+    /// The transformer of the options is not called, as the transformed content could not be
+    /// returned; `open_with` applies it.
+    /// 
+    /// Returns `Error::PolicyViolation` listing every rejected file if the policy rejects any file.
+    pub fn gen_header_from_dir_with<P: AsRef<Path>>(path: P, options: &PackOptions) -> Result<(Value, Vec<(PathBuf, u64)>), asar_error::Error> {
+        let (header, list, _, _) = Self::gen_header_transformed(path.as_ref(), options, None)?;
+
+        Ok((header, list))
+    }
+
+    // gen_header_from_dir_with, calling the transformer of the options (if any) on every file and
+    // collecting its content into `transformed`, if provided. Also returns the files left out of
+    // the body by `PackOptions::unpack`.
+    fn gen_header_transformed(
+        root: &Path,
+        options: &PackOptions,
+        mut transformed: Option<&mut Transformed>,
+    ) -> Result<GeneratedHeader, asar_error::Error> {
+        let options = &options.with_ignore_files(root)?;
+
+        let mut found: Vec<(PathBuf, u64)> = Vec::new();
+        let mut violations: Vec<(PathBuf, String)> = Vec::new();
+        let mut skipped: Vec<PathBuf> = Vec::new();
 
-            Value::Object(header)
-        };*/
+        let mut header = Self::dir_to_value(root, root, options, &mut found, &mut violations, &mut skipped, transformed.as_deref_mut())?;
 
-        Ok((Self::dir_to_value(path, &mut offset, &mut list_of_paths)?, list_of_paths))
+        if !violations.is_empty() {
+            return Err(Error::PolicyViolation(violations));
+        }
+
+        let is_unpacked: Vec<bool> = found
+            .iter()
+            .map(|(path, _)| options.unpacks(path.strip_prefix(root).unwrap_or(path)))
+            .collect();
+
+        let mut leaders: Vec<Option<usize>> = if options.dedupes_hard_links() || options.resolves_pnpm() {
+            links::shared_leaders(&found, transformed.as_deref(), options.resolves_pnpm())?
+        } else {
+            vec![None; found.len()]
+        };
+
+        for index in 0..found.len() {
+            if is_unpacked[index] || leaders[index].is_some_and(|leader| is_unpacked[leader]) {
+                leaders[index] = None; // stored itself
+            }
+        }
+
+        let mut unpacked: Vec<PathBuf> = Vec::new();
+
+        for (index, (path, _)) in found.iter().enumerate().filter(|(index, _)| is_unpacked[*index]) {
+            if let Some(entry) = content::entry_mut(&mut header, path.strip_prefix(root).unwrap_or(path)) {
+                entry.insert("unpacked".to_string(), Value::Bool(true));
+                unpacked.push(found[index].0.clone());
+            }
+        }
+
+        // files stored in place of another (hard links), or next to the archive, are left out of the layout
+        let stored: Vec<usize> = (0..found.len())
+            .filter(|&index| leaders[index].is_none() && !is_unpacked[index])
+            .collect();
+
+        let relative: Vec<(PathBuf, u64)> = stored
+            .iter()
+            .map(|&index| {
+                let (path, size) = &found[index];
+                (path.strip_prefix(root).unwrap_or(path).to_path_buf(), *size)
+            })
+            .collect();
+
+        let placements = layout::assign_offsets(&relative, &options.layout());
+        drop(relative);
+
+        let mut offsets: Vec<Option<u64>> = vec![None; found.len()];
+
+        for placement in placements.iter() {
+            offsets[stored[placement.index]] = placement.offset;
+        }
+
+        for (index, leader) in leaders.iter().enumerate() {
+            let (Some(leader), Some(path)) = (leader, found[index].0.strip_prefix(root).ok()) else {
+                continue;
+            };
+
+            if let (Some(offset), Some(entry)) = (offsets[*leader], content::entry_mut(&mut header, path)) {
+                entry.insert("offset".to_string(), Value::String(offset.to_string()));
+            }
+        }
+
+        // every stored file is moved (not cloned) into the list, in layout order
+        let mut found: Vec<Option<(PathBuf, u64)>> = found.into_iter().map(Some).collect();
+        let mut list_of_paths: Vec<(PathBuf, u64)> = Vec::with_capacity(stored.len());
+
+        for placement in placements {
+            if let (Some(offset), Some(entry)) = (placement.offset, content::entry_mut(&mut header, &placement.path)) {
+                entry.insert("offset".to_string(), Value::String(offset.to_string()));
+                list_of_paths.extend(found[stored[placement.index]].take());
+            }
+        }
+
+        Ok((header, list_of_paths, unpacked, skipped))
     }
 
     
     // Returns serde_json::Value from a directory, in Asar archive JSON format.
-    fn dir_to_value<P: AsRef<Path>>(path: P, offset: &mut u64, list: &mut Vec<(PathBuf, u64)>) -> Result<Value, asar_error::Error> {
+    fn dir_to_value<P: AsRef<Path>>(
+        path: P,
+        root: &Path,
+        options: &PackOptions,
+        list: &mut Vec<(PathBuf, u64)>,
+        violations: &mut Vec<(PathBuf, String)>,
+        skipped: &mut Vec<PathBuf>,
+        mut transformed: Option<&mut Transformed>,
+    ) -> Result<Value, asar_error::Error> {
         let mut result = Map::new(); //result -> will be object
         
         let path = path.as_ref(); //current path
@@ -136,20 +370,84 @@ impl Asar {
 
             for entry in fs::read_dir(path)? {
                 let entry = entry?;
-                folder_content.insert(entry.file_name().to_str().unwrap().to_string(), Self::dir_to_value(entry.path(), offset, list)?);
+                let entry_path = entry.path();
+
+                if options.resolves_pnpm() && entry.file_type()?.is_symlink() && !links::follows_symlink(path, &entry_path)? {
+                    continue; // dangling, or back to a folder being packed
+                }
+
+                let entry_metadata = entry_path.metadata()?;
+                let relative = entry_path.strip_prefix(root).unwrap_or(&entry_path);
+
+                if options.ignores(relative, entry_metadata.is_dir()) {
+                    continue;
+                }
+
+                if let Some(custom) = kinds::pack_entry(relative, &entry_path)? {
+                    folder_content.insert(entry.file_name().to_string_lossy().into_owned(), Value::Object(custom));
+                    continue;
+                }
+
+                if entry_metadata.is_file() { // check file against policy
+                    match options.check(relative, entry_metadata.len()) {
+                        PolicyDecision::Allow => {}
+                        PolicyDecision::Skip => continue,
+                        PolicyDecision::Reject(reason) => {
+                            violations.push((relative.to_path_buf(), reason));
+                            continue;
+                        }
+                    }
+
+                    if options.skips_locked() {
+                        match options.lock_retry().open(&entry_path) {
+                            Err(err) if locked::is_locked(&err) => {
+                                trace::event!(WARN, path = %entry_path.display(), "skipped locked file");
+                                skipped.push(entry_path);
+                                continue;
+                            }
+                            opened => drop(opened?),
+                        }
+                    }
+
+                    if let (Some(transformed), Some(transformer)) = (transformed.as_deref_mut(), options.transformer()) {
+                        match transformer(relative, options.lock_retry().run(|| fs::read(&entry_path))?) {
+                            TransformResult::Content(content) => {
+                                transformed.insert(entry_path.clone(), content);
+                            }
+                            TransformResult::Skip => continue,
+                        }
+                    }
+                }
+
+                folder_content.insert(
+                    entry.file_name().to_str().unwrap().to_string(),
+                    Self::dir_to_value(&entry_path, root, options, list, violations, skipped, transformed.as_deref_mut())?,
+                );
             }
 
             result.insert("files".to_string(), Value::Object(folder_content));
 
-        } else if metadata.is_file() { //add file
+        } else if metadata.is_file() { //add file, its offset is assigned once every file is found
 
-            result.insert("size".to_string(), json!(metadata.len()));
-            result.insert("offset".to_string(), Value::String(offset.to_string()));
+            let size = match transformed.as_deref() {
+                Some(transformed) => transformed.get(path).map_or(metadata.len(), |content| content.len() as u64),
+                None => metadata.len(),
+            };
 
-            // push relevant data to list
-            list.push((path.to_path_buf(), metadata.len()));
+            result.insert("size".to_string(), json!(size));
 
-            *offset += metadata.len();
+            if options.records_integrity() {
+                let hasher = options.hasher();
+                let integrity = match transformed.as_deref().and_then(|transformed| transformed.get(path)) {
+                    Some(content) => cas::integrity_with(hasher.0.as_ref(), content.as_slice())?,
+                    None => cas::integrity_with(hasher.0.as_ref(), options.lock_retry().open(path)?)?,
+                };
+
+                result.insert("integrity".to_string(), integrity);
+            }
+
+            // push relevant data to list
+            list.push((path.to_path_buf(), size));
         }
 
         Ok(Value::Object(result))
@@ -158,7 +456,6 @@ impl Asar {
     /// Returns a vector of all Paths within an archive as Strings, otherwise an Error.
     /// 
     /// If a path is unable to be casted to a String, it will add as the default string `""`.
-    
     pub fn list(&self) -> Result<Vec<String>, asar_error::Error> {
         Ok(self
             .content
@@ -175,12 +472,9 @@ impl Asar {
     /// 
     /// At the moment, calling this function on an improperly instantiated Asar struct may
     /// result in unintended consequences.
-     
     pub fn extract<P: AsRef<Path>>(&self, destination: P) -> Result<(), asar_error::Error> {
-        let file = File::open(self.src_path.as_path())?;
-
-        self.content
-            .asar_to_dir(destination, &file, self.start)?;
+        self.extract_with(destination.as_ref(), &ExtractOptions::default())?; // checks every name first
+        self.extract_custom(destination.as_ref())?;
 
         Ok(())
     }
@@ -189,35 +483,274 @@ impl Asar {
     /// Take one argument of type Path, representing a destination Asar archive file, 
     /// where the opened directory will be packed- using the pack() associated function.
     /// 
+    /// If an Asar archive file is open, it is repacked instead: a canonical header is regenerated
+    /// from its Content and every file is streamed from its body, normalizing the archive (e.g.
+    /// dropping gaps and padding, or repairing its prefix). The destination must not be the
+    /// opened archive itself.
+    /// 
     /// Returns (), otherwise Error.
-    
     pub fn pack<P: AsRef<Path>>(&self, destination: P) -> Result<(), asar_error::Error> {
+        self.pack_run(destination, &self.options.run_context())
+    }
+
+    // pack within the provided run, checking its cancel token (if any) between and within files.
+    pub(crate) fn pack_run<P: AsRef<Path>>(&self, destination: P, run: &RunContext) -> Result<(), asar_error::Error> {
+        self.ensure_writable()?;
+
+        if self.header.is_none() {
+            if repack::same_file(&self.src_path, destination.as_ref())? {
+                return Err(Error::UnknownContentType(
+                    "Can not repack an Asar archive file onto itself".to_string(),
+                ));
+            }
 
-        if destination.as_ref().try_exists()? {
-            remove_file(&destination)?; //asar will be replaced
+            return self.repacked()?.pack_run(destination, run);
         }
 
-        let mut asar = OpenOptions::new().create(true).append(true).open(destination)?;
-        
-        let mut header_value: Vec<u8> = {
-            if let Some(header) = &self.header {
+        let _span = trace::span!(DEBUG, "pack", path = %destination.as_ref().display());
+
+        // written aside and moved over the destination once complete, removed on any failure or panic
+        let mut guard = TempGuard::new_for(destination.as_ref(), self.options.temp_directory())?;
+
+        preallocate(guard.file(), self.packed_size_estimate()?)?; // fail early if the disk is full
 
-                serde_json::to_vec(header)?
-            } else {
-                return Err(Error::UnknownContentType("Can not have Asar archive file open".to_string()))
+        // the archive as packed, once the hooks skipped some of its files
+        let relaid = match self.options.entry_hooks().recovers(run) {
+            true => self.write_recovering(guard.file(), run)?,
+            false => {
+                self.write_archive(guard.file(), run)?;
+                None
             }
         };
 
+        let packed = relaid.as_ref().unwrap_or(self);
+        guard.file().flush()?;
+
+        trace::event!(
+            DEBUG,
+            start = packed.start,
+            size = guard.file().stream_position()?,
+            "packed archive"
+        );
+
+        if packed.options.verification() != Verification::Off {
+            packed.verify_packed(guard.path())?; // a corrupt archive never replaces the destination
+
+            trace::event!(DEBUG, "verified archive");
+        }
+
+        match packed.options.volume_size() {
+            Some(split_size) => {
+                volume::write_volumes(guard.path(), destination.as_ref(), split_size, packed.align(), packed.options.temp_directory())?;
+            }
+            None => guard.persist(destination.as_ref())?,
+        }
+
+        packed.write_unpacked(destination.as_ref())?;
+
+        Ok(())
+    }
+
+    // Copies the files left out of the body (see `PackOptions::unpack`) to `<destination>.unpacked/`.
+    fn write_unpacked(&self, destination: &Path) -> Result<(), asar_error::Error> {
+        if self.unpacked.is_empty() {
+            return Ok(());
+        }
+
+        let mut dir = destination.as_os_str().to_os_string();
+        dir.push(".unpacked");
+        let dir = PathBuf::from(dir);
+
+        for path in self.unpacked.iter() {
+            let target = dir.join(path.strip_prefix(&self.src_path).unwrap_or(path));
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::copy(path, &target)?;
+        }
+
+        Ok(())
+    }
+
+    // Writes the header and body of an opened directory to the writer, checking the cancel token
+    // of the run (if any) between and within files. Hooks that skip files must be written by
+    // `write_recovering`.
+    pub(crate) fn write_archive<W: Write>(&self, out: &mut W, run: &RunContext) -> Result<(), asar_error::Error> {
+        let out = &mut Throttled::new(out, run.throttle.as_deref());
+
+        if let Some(header) = &self.header {
+            Self::write_header_padded(out, header, self.start)?;
+        }
+
+        let started = Instant::now();
+
+        self.write_files(out, run)?; // concatenates all files.
+        self.write_copies(out, run.cancel)?;
+        self.record_pack(started);
+
+        Ok(())
+    }
+
+    // Writes the body of an opened directory to the file before its header, so the files skipped
+    // by the hooks are left out of both, in a single pass. Returns the Asar as packed, if any file
+    // was skipped.
+    pub(crate) fn write_recovering(&self, file: &mut File, run: &RunContext) -> Result<Option<Asar>, asar_error::Error> {
+        let started = Instant::now();
+        file.seek(SeekFrom::Start(self.start))?;
+
+        let relaid = {
+            let out = &mut Throttled::new(&mut *file, run.throttle.as_deref());
+            let skipped = self.write_files(out, run)?;
+
+            let relaid = (!skipped.is_empty()).then(|| {
+                let mut relaid = self.clone();
+                relaid.relayout(&skipped);
+                relaid
+            });
+
+            relaid.as_ref().unwrap_or(self).write_copies(out, run.cancel)?;
+            relaid
+        };
+
+        let packed = relaid.as_ref().unwrap_or(self);
+        packed.record_pack(started);
+
+        if let Some(header) = &packed.header {
+            file.seek(SeekFrom::Start(0))?;
+            Self::write_header_padded(&mut *file, header, packed.start)?;
+        }
+
+        file.set_len(packed.packed_size_estimate()?)?; // past any preallocated space left by skipped files
+        file.seek(SeekFrom::End(0))?;
+
+        Ok(relaid)
+    }
+
+    // Concatenates the files of an opened directory to the writer, returning the indices within
+    // its list of those the hooks skipped.
+    fn write_files<W: Write>(&self, out: &mut W, run: &RunContext) -> Result<Vec<usize>, asar_error::Error> {
+        let hooks = PackHooks {
+            scanner: self.options.scanner(),
+            transformed: &self.transformed,
+            root: &self.src_path,
+            retry: self.options.lock_retry(),
+            entries: self.options.entry_hooks(),
+            run,
+        };
+
+        self.content.dir_to_asar_recorded(out, self.align(), &self.metrics, &hooks)
+    }
+
+    // The alignment of file bodies when a directory is packed (see `PackOptions::align`).
+    pub(crate) fn align(&self) -> u64 {
+        self.options.alignment()
+    }
+
+    // Reports the files and bytes packed since `started` to the metrics.
+    fn record_pack(&self, started: Instant) {
+        if let Content::List(list) = &self.content {
+            let files = (list.len() + self.copies.len()) as u64;
+            let bytes = list.iter().map(|(_, size)| size).sum::<u64>()
+                + self.copies.iter().map(|copy| copy.size).sum::<u64>();
+
+            self.metrics.operation(Operation::Pack, files, bytes, started);
+        }
+    }
+
+    /// Returns the exact size in bytes of the Asar archive file that `pack` would write
+    /// (header, padding, and body), without writing anything.
+    /// 
+    /// If an Asar archive file is open, the size of its header and body is returned instead.
+    /// 
+    /// Returns the size, otherwise Error.
+    pub fn packed_size_estimate(&self) -> Result<u64, asar_error::Error> {
+        let body: u64 = match &self.content {
+            Content::List(_) => self.body_len(),
+            content => content
+                .files_to_vec()?
+                .iter()
+                .map(|(_, offset, size)| offset + size)
+                .max()
+                .unwrap_or(0),
+        };
+
+        Ok(self.start + body)
+    }
+
+    /// Writes the header (sizes followed by the JSON value) of an Asar archive file to the provided writer.
+    /// 
+    /// Returns the offset at which file content begins (start), otherwise Error.
+    pub fn write_header<W: Write>(asar: &mut W, header: &Value) -> Result<u64, asar_error::Error> {
+        let start = json_len(header)? + 16;
+
+        Self::write_header_padded(asar, header, start)?;
+
+        Ok(start)
+    }
+
+    // Writes the header padded up to `start`, serializing its JSON value straight to the writer
+    // rather than into a buffer, as headers of large directories span hundreds of megabytes.
+    pub(crate) fn write_header_padded<W: Write>(asar: &mut W, header: &Value, start: u64) -> Result<(), asar_error::Error> {
+        let json_len = json_len(header)?;
+        let mut asar = BufWriter::new(asar);
+
+        asar.write_u32::<LittleEndian>(4_u32)?; //Asar default
+        asar.write_u32::<LittleEndian>((start - 8) as u32)?;  // length of header - 8
+        asar.write_u32::<LittleEndian>((start - 12) as u32)?; // length of header - 12
+        asar.write_u32::<LittleEndian>(json_len as u32)?; // length of json header
+
+        serde_json::to_writer(&mut asar, header)?; //json value
+        io::copy(&mut io::repeat(0).take(start - 16 - json_len), &mut asar)?; //padding
+        asar.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns the bytes of the header (sizes followed by the JSON value) of an Asar archive file, 
+    /// followed by `padding` zeroed bytes that are counted within the header size but not the JSON length.
+    /// 
+    /// The length of the returned vector is the offset at which file content begins (start).
+    pub fn header_bytes(header: &Value, padding: u64) -> Result<Vec<u8>, asar_error::Error> {
+        let header_value: Vec<u8> = serde_json::to_vec(header)?;
+        let start = header_value.len() as u64 + padding + 16;
+        let mut asar: Vec<u8> = Vec::with_capacity(start as usize);
+
         // Write header to asar
-        asar.write_u32::<LittleEndian>(4 as u32)?; //Asar default
-        asar.write_u32::<LittleEndian>((self.start - 8) as u32)?;  // length of header - 8 
-        asar.write_u32::<LittleEndian>((self.start - 12) as u32)?; // length of header - 12
-        asar.write_u32::<LittleEndian>((self.start - 16) as u32)?; // length of json header
+        asar.write_u32::<LittleEndian>(4_u32)?; //Asar default
+        asar.write_u32::<LittleEndian>((start - 8) as u32)?;  // length of header - 8 
+        asar.write_u32::<LittleEndian>((start - 12) as u32)?; // length of header - 12
+        asar.write_u32::<LittleEndian>(header_value.len() as u32)?; // length of json header
+
+        asar.write_all(&header_value)?; //json value
+        asar.resize(start as usize, 0); //padding
 
-        asar.write_all(&mut header_value)?; //json value
+        Ok(asar)
+    }
 
-        self.content.dir_to_asar(&mut asar) // concatenates all files.
 
+    /// Packs an Asar archive file at `destination` from an iterator of `(full_file_path, reader, size)`,
+    /// without materializing a directory (e.g. entries of a tarball or a network stream).
+    /// 
+    /// The header is generated from the declared sizes, and each reader is verified to provide 
+    /// exactly its declared size while being copied.
+    /// 
+    /// Returns (), otherwise Error.
+    pub fn pack_stream<I, P>(iter: I, destination: P) -> Result<(), asar_error::Error>
+    where
+        I: IntoIterator<Item = (PathBuf, Box<dyn Read>, u64)>,
+        P: AsRef<Path>,
+    {
+        let mut packer = Packer::new();
+
+        for (path, reader, size) in iter {
+            packer.add_reader(path, reader, size)?;
+        }
+
+        packer.write(destination)?;
+
+        Ok(())
     }
 
 
@@ -228,33 +761,56 @@ impl Asar {
     /// The path provided must be a file and it must exist otherwise `None` will be returned.
     /// If a directory/folder is open, `None` will be returned.
     /// > If an error occures while opening the file, `None` will be returned.
-    
     pub fn get_file<P: AsRef<Path>>(&self, path: P) -> Option<Vec<u8>> {
         
         if self.src_path.is_dir() {
             return None
         }
 
-        if let Some(Content::File(_, offset, size)) = self.content.find(path) {
+        if let Some(EntryRef::File(offset, size)) = self.stat(&path) {
 
-            let file = File::open(self.src_path.as_path());
+            let file = self.archive_file();
 
             if let Ok(file) = file {
+                let started = Instant::now();
                 let mut result: Vec<u8> = vec![0; size as usize];
 
-                if let Ok(()) = file.read_exact_at(self.start + offset, &mut result) {
-                    return Some(result)
+                match file.read_exact_at(self.start + offset, &mut result) {
+                    Ok(()) => {
+                        self.metrics.file_read(path.as_ref(), size, started);
+                        trace::event!(TRACE, path = %path.as_ref().display(), size, "read file");
+                        return Some(result)
+                    }
+                    Err(_err) => {
+                        trace::event!(WARN, path = %path.as_ref().display(), error = %_err, "failed to read file");
+                    }
                 }
             } 
         }
         None
     }
 
+    /// Returns true if the provided path is a file or folder within an opened Asar archive file.
+    /// 
+    /// > An empty path refers to the archive root, which always exists.
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.stat(path).is_some()
+    }
+
+    /// Returns true if the provided path is a folder within an opened Asar archive file.
+    pub fn is_dir<P: AsRef<Path>>(&self, path: P) -> bool {
+        matches!(self.stat(path), Some(EntryRef::Folder))
+    }
+
+    /// Returns true if the provided path is a file within an opened Asar archive file.
+    pub fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
+        matches!(self.stat(path), Some(EntryRef::File(..)))
+    }
+
     /// Takes in one argument of type `&str`, returning a vector of all paths
     /// that contain the provided pattern (argument).
     /// 
     /// Paths are checked to contain the pattern using the contains function with string slices.
-    
     pub fn get_paths_contain(&self, pat: &str) -> Vec<PathBuf> {
         let mut paths: Vec<PathBuf> = Vec::new();
 
@@ -272,4 +828,54 @@ impl Asar {
 
         paths
     }
+
+    // Returns a reader over a single file within the opened Asar archive file, given its offset and size.
+    pub(crate) fn entry_reader<'a>(&self, file: &'a ArchiveFile, offset: u64, size: u64) -> Cursor<Slice<&'a ArchiveFile>> {
+        Cursor::new(Slice::new(file, self.start + offset, Some(size)))
+    }
+}
+
+// Counts the bytes written to it, discarding them.
+#[derive(Default)]
+struct LenCounter(u64);
+
+impl Write for LenCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// Returns the length of the serialized JSON value of a header, without holding it in memory.
+pub(crate) fn json_len(header: &Value) -> Result<u64, asar_error::Error> {
+    let mut counter = LenCounter::default();
+    serde_json::to_writer(&mut counter, header)?;
+
+    Ok(counter.0)
+}
+
+/// Reserves `len` bytes of disk space for the provided file, setting its length.
+/// 
+/// On Linux the space is allocated with `posix_fallocate`, failing early if the disk is full and 
+/// reducing fragmentation, otherwise (or if the filesystem does not support it) `File::set_len` is used.
+pub fn preallocate(file: &File, len: u64) -> std::io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::io::AsRawFd;
+
+        // SAFETY: the file descriptor is valid for the lifetime of `file`
+        let err = unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len as libc::off_t) };
+
+        match err {
+            0 => return Ok(()),
+            libc::EOPNOTSUPP | libc::EINVAL => {}
+            err => return Err(std::io::Error::from_raw_os_error(err)),
+        }
+    }
+
+    file.set_len(len)
 }
@@ -1,4 +1,6 @@
-use std::{fmt::Display, num::ParseIntError};
+use std::{fmt::Display, num::ParseIntError, path::PathBuf};
+
+use crate::header::{HeaderError, OutOfBounds};
 
 
 
@@ -10,13 +12,24 @@ use std::{fmt::Display, num::ParseIntError};
 /// 
 /// - UnknownContentType -> rust_asar
 /// 
+/// - InvalidPattern -> rust_asar
+/// 
+/// - PolicyViolation -> rust_asar, every `(file_path, reason)` rejected by a pack policy
+/// 
+/// - Cancelled -> rust_asar, a cancellable operation was cancelled through its token
+/// 
+/// - TimedOut -> rust_asar, an operation did not complete before its deadline
+/// 
 /// - SerdeJsonError -> `serde_json::Error`
-
 #[derive(Debug)]
 pub enum Error { //poor error handling :/ - might fix
     IoError(std::io::Error),
     ParseHeaderError(String),
     UnknownContentType(String),
+    InvalidPattern(String),
+    PolicyViolation(Vec<(PathBuf, String)>),
+    Cancelled,
+    TimedOut,
     SerdeJsonError(serde_json::Error)
 }
 
@@ -26,11 +39,66 @@ impl Display for Error {
             Self::IoError(err) => write!(f, "{}", err),
             Self::ParseHeaderError(str) => write!(f, "{}", str),
             Self::UnknownContentType(str) => write!(f, "{}", str),
+            Self::InvalidPattern(str) => write!(f, "{}", str),
+            Self::PolicyViolation(violations) => {
+                write!(f, "{} file(s) rejected by pack policy", violations.len())?;
+
+                for (path, reason) in violations {
+                    write!(f, "\n  {}: {}", path.display(), reason)?;
+                }
+
+                Ok(())
+            }
+            Self::Cancelled => write!(f, "Operation cancelled"),
+            Self::TimedOut => write!(f, "Operation timed out"),
             Self::SerdeJsonError(err) => write!(f, "{}", err)
         }
     }
 }
 
+impl Error {
+
+    /// Returns a stable, kebab case name of the kind of Error, e.g. for scripts branching on
+    /// failure causes:
+    ///
+    /// - IoError: `not-found`, `permission-denied`, `invalid-data` (e.g. a failed verification),
+    ///   `invalid-input` (e.g. an illegal file name), `already-exists` (e.g. colliding paths), or `io`
+    ///
+    /// - ParseHeaderError: `invalid-header`, SerdeJsonError: `invalid-json`
+    ///
+    /// - UnknownContentType: `unsupported`, InvalidPattern: `invalid-pattern`
+    ///
+    /// - PolicyViolation: `policy-violation`, Cancelled: `cancelled`, TimedOut: `timed-out`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(err) => match err.kind() {
+                std::io::ErrorKind::NotFound => "not-found",
+                std::io::ErrorKind::PermissionDenied => "permission-denied",
+                std::io::ErrorKind::InvalidData => "invalid-data",
+                std::io::ErrorKind::InvalidInput => "invalid-input",
+                std::io::ErrorKind::AlreadyExists => "already-exists",
+                _ => "io",
+            },
+            Self::ParseHeaderError(_) => "invalid-header",
+            Self::UnknownContentType(_) => "unsupported",
+            Self::InvalidPattern(_) => "invalid-pattern",
+            Self::PolicyViolation(_) => "policy-violation",
+            Self::Cancelled => "cancelled",
+            Self::TimedOut => "timed-out",
+            Self::SerdeJsonError(_) => "invalid-json",
+        }
+    }
+
+    /// Returns the byte offset within a single line of JSON (such as an archive header) at which
+    /// parsing failed, if known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::SerdeJsonError(err) if err.line() == 1 => Some(err.column().saturating_sub(1) as u64),
+            _ => None,
+        }
+    }
+}
+
 /// From<std::io::Error>
 /// 
 impl From<std::io::Error> for Error {
@@ -56,4 +124,41 @@ impl From<serde_json::Error> for Error{
     }
 }
 
+/// From<object_store::Error>
+/// 
+#[cfg(feature = "object-store")]
+impl From<object_store::Error> for Error {
+    fn from(err: object_store::Error) -> Self {
+        Error::IoError(std::io::Error::other(err))
+    }
+}
+
+/// From<OutOfBounds>
+/// 
+impl From<OutOfBounds> for Error {
+    fn from(err: OutOfBounds) -> Self {
+        Error::ParseHeaderError(err.to_string())
+    }
+}
+
+/// From<HeaderError>
+/// 
+impl<E: Into<Error>> From<HeaderError<E>> for Error {
+    fn from(err: HeaderError<E>) -> Self {
+        match err {
+            HeaderError::Source(err) => err.into(),
+            HeaderError::Json(err) => Error::SerdeJsonError(err),
+            HeaderError::SizeMismatch(start, json_len) => Error::ParseHeaderError(format!(
+                "Header size {} is smaller than JSON length {}",
+                start, json_len
+            )),
+            HeaderError::Malformed(reason) => Error::ParseHeaderError(reason),
+            HeaderError::Unsupported(format) => Error::ParseHeaderError(format!(
+                "Unsupported archive format: {}",
+                format
+            )),
+        }
+    }
+}
+
 impl std::error::Error for Error{/* todo */}
\ No newline at end of file
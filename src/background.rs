@@ -0,0 +1,164 @@
+use std::{
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    extract::ExtractOptions,
+    hooks::RunContext,
+    index::EntryRef,
+};
+
+/// The progress of a background extraction, as returned by `ExtractionHandle::progress`.
+///
+/// - files: Number of files extracted so far
+///
+/// - bytes: Number of bytes written so far
+///
+/// - total_files: Number of files to extract (those left out by `ExtractOptions::extensions` are
+///   not counted; those skipped by a transformer or a hook are, so `files` may stop short of it)
+///
+/// - total_bytes: Size in bytes of the files to extract, within the archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Progress {
+    pub files: u64,
+    pub bytes: u64,
+    pub total_files: u64,
+    pub total_bytes: u64,
+}
+
+impl Progress {
+
+    /// Returns the share of bytes written so far, from 0.0 to 1.0 (1.0 if there is nothing to
+    /// extract), e.g. for a progress bar.
+    pub fn fraction(&self) -> f64 {
+        match self.total_bytes {
+            0 => 1.0,
+            total => (self.bytes as f64 / total as f64).min(1.0),
+        }
+    }
+}
+
+// Running totals of the files and bytes written, updated by `Hooks::after`.
+#[derive(Debug, Default)]
+pub(crate) struct Tally {
+    files: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Tally {
+    pub(crate) fn add(&self, bytes: u64) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// An extraction running on a worker thread, as started by `Asar::extract_background`, to be
+/// polled (`progress`, `is_finished`), cancelled (`cancel`), or waited on (`wait`).
+///
+/// Dropping the handle detaches the worker: extraction carries on to completion.
+#[derive(Debug)]
+pub struct ExtractionHandle {
+    tally: Arc<Tally>,
+    token: Arc<AtomicBool>,
+    total: (u64, u64),
+    worker: JoinHandle<Result<(), asar_error::Error>>,
+}
+
+impl ExtractionHandle {
+
+    /// Returns the progress of the extraction so far.
+    pub fn progress(&self) -> Progress {
+        Progress {
+            files: self.tally.files.load(Ordering::Relaxed),
+            bytes: self.tally.bytes.load(Ordering::Relaxed),
+            total_files: self.total.0,
+            total_bytes: self.total.1,
+        }
+    }
+
+    /// Returns true once the extraction completed, failed, or stopped after `cancel`.
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Asks the extraction to stop before its next file; `wait` then returns `Error::Cancelled`.
+    /// Files extracted so far are left at the destination.
+    pub fn cancel(&self) {
+        self.token.store(true, Ordering::Relaxed);
+    }
+
+    /// Blocks until the extraction is finished.
+    ///
+    /// Returns (), otherwise the Error the extraction failed with (`Error::Cancelled` once
+    /// cancelled before completing).
+    pub fn wait(self) -> Result<(), asar_error::Error> {
+        self.worker.join().unwrap_or_else(|_| {
+            Err(Error::IoError(io::Error::other("Extraction worker panicked")))
+        })
+    }
+}
+
+impl Asar {
+
+    /// Extracts an opened Asar archive file to the provided destination folder on a worker thread,
+    /// as does `extract_with`, returning at once with an ExtractionHandle, e.g. so an installer can
+    /// show progress and offer to cancel without blocking its interface.
+    ///
+    /// ```no_run
+    /// use std::{thread, time::Duration};
+    /// use rust_asar::{asar::Asar, extract::ExtractOptions};
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let handle = Asar::open("app.asar")?.extract_background("app", &ExtractOptions::new());
+    ///
+    /// while !handle.is_finished() {
+    ///     println!("{:.0}%", handle.progress().fraction() * 100.0);
+    ///     thread::sleep(Duration::from_millis(100));
+    /// }
+    ///
+    /// handle.wait()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Errors, including those found before anything is written, are returned by `wait`.
+    pub fn extract_background<P: AsRef<Path>>(&self, destination: P, options: &ExtractOptions) -> ExtractionHandle {
+        let tally = Arc::new(Tally::default());
+        let token = Arc::new(AtomicBool::new(false));
+
+        let options = options.clone();
+
+        let total = self
+            .index
+            .iter()
+            .filter_map(|(path, entry)| match entry {
+                EntryRef::File(_, size) if options.includes(path) => Some(*size),
+                _ => None,
+            })
+            .fold((0, 0), |(files, bytes), size| (files + 1, bytes + size));
+
+        let asar = self.clone();
+        let destination = destination.as_ref().to_path_buf();
+        let (counted, cancel) = (tally.clone(), token.clone());
+
+        let worker = thread::spawn(move || {
+            let run = RunContext {
+                tally: Some(counted),
+                cancel: Some(&cancel),
+                ..options.run_context()
+            };
+
+            asar.extract_run(destination, &options, &run)
+        });
+
+        ExtractionHandle { tally, token, total, worker }
+    }
+}
@@ -0,0 +1,548 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+
+use rust_asar::{
+    asar::Asar,
+    asar_error,
+    config::{PackConfig, CONFIG_FILE},
+    extract::ExtractOptions,
+    hashlist,
+    lint::{Rule, RuleSet, Severity},
+    locked::LockPolicy,
+    pack::PackOptions,
+    report::{GroupBy, ReportOptions},
+    roundtrip,
+    search::SearchOptions,
+    verify::Verification,
+};
+
+/// Command line interface to create, list, and extract Asar archive files.
+#[derive(Parser)]
+#[command(name = "rasar", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Print failures to stderr as JSON objects (kind, code, message, path, offset)
+    #[arg(long, global = true)]
+    json_errors: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List all files and folders within an Asar archive file
+    List {
+        /// Asar archive file to list
+        archive: PathBuf,
+    },
+
+    /// Extract an Asar archive file into a folder
+    Extract {
+        /// Asar archive file to extract
+        archive: PathBuf,
+        /// Destination folder
+        destination: PathBuf,
+        /// Extract only files with these extensions (comma separated, e.g. js,json,html)
+        #[arg(short, long, value_delimiter = ',')]
+        ext: Vec<String>,
+        /// Keep extracting past files that can not be read or written, listing them once done
+        #[arg(long)]
+        keep_going: bool,
+    },
+
+    /// Pack a folder into an Asar archive file, or repack an Asar archive file
+    Pack {
+        /// Folder to pack, or Asar archive file to repack (defaults to the source of the config)
+        #[arg(required_unless_present = "config")]
+        source: Option<PathBuf>,
+        /// Destination Asar archive file (defaults to the destination of the config)
+        #[arg(required_unless_present = "config")]
+        destination: Option<PathBuf>,
+        /// Also leave out the files matched by the .gitignore of the folder (.asarignore is always read)
+        #[arg(long)]
+        gitignore: bool,
+        /// Pack as described by a config file (or the asar.toml within a folder)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Profile of the config overriding its top-level fields (e.g. release)
+        #[arg(short, long, requires = "config")]
+        profile: Option<String>,
+        /// Re-open the archive once packed, comparing the content of every file with its source
+        #[arg(long)]
+        verify: bool,
+        /// Leave out files still locked by another process after retrying, rather than failing
+        #[arg(long)]
+        skip_locked: bool,
+        /// Split the archive into volumes of at most this many bytes (destination.001, .002, ...)
+        #[arg(long, value_name = "BYTES")]
+        split_size: Option<u64>,
+    },
+
+    /// Search the contents of every file within an Asar archive file
+    Grep {
+        /// Pattern to search for
+        pattern: String,
+        /// Asar archive file to search
+        archive: PathBuf,
+        /// Match regardless of case
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Treat the pattern as a regular expression
+        #[arg(short = 'E', long)]
+        regex: bool,
+        /// Stop after this many matches
+        #[arg(short, long)]
+        max_count: Option<usize>,
+    },
+
+    /// Report the sizes of the files within an Asar archive file or folder
+    Analyze {
+        /// Asar archive file or folder to analyze
+        archive: PathBuf,
+        /// Group files by directory or by extension
+        #[arg(short, long, value_enum, default_value_t = Group::Dir)]
+        group_by: Group,
+        /// Number of leading folders to group by
+        #[arg(short, long, default_value_t = 1)]
+        depth: usize,
+        /// List at most this many groups and largest files
+        #[arg(short, long)]
+        top: Option<usize>,
+        /// Fail if the files exceed this many bytes combined
+        #[arg(short, long)]
+        budget: Option<u64>,
+    },
+
+    /// Check an Asar archive file for common packaging mistakes
+    Lint {
+        /// Asar archive file to lint
+        archive: PathBuf,
+        /// Report files larger than this many bytes
+        #[arg(short, long)]
+        max_file_size: Option<u64>,
+        /// Set the severity of a rule, or disable it with off (e.g. source-map=error)
+        #[arg(short, long, value_parser = parse_rule)]
+        rule: Vec<(Rule, Option<Severity>)>,
+        /// Fail if any finding is at least this severe
+        #[arg(short, long, value_enum, default_value_t = Level::Error)]
+        fail_on: Level,
+    },
+
+    /// Report the files within an Asar archive file matching a list of known SHA256 hashes
+    Scan {
+        /// Asar archive file to scan
+        archive: PathBuf,
+        /// File listing one SHA256 hash per line, as written by sha256sum
+        #[arg(long)]
+        hash_list: PathBuf,
+    },
+
+    /// Pack a folder, extract it to a temporary folder, and report every difference from the original
+    Selftest {
+        /// Folder to check
+        dir: PathBuf,
+    },
+
+    /// Print a CycloneDX bill of materials of the packages within an Asar archive file
+    Sbom {
+        /// Asar archive file to audit
+        archive: PathBuf,
+    },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        shell: Shell,
+    },
+
+    /// Print the roff man page to stdout
+    Man,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Group {
+    Dir,
+    Ext,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<Level> for Severity {
+    fn from(level: Level) -> Severity {
+        match level {
+            Level::Info => Severity::Info,
+            Level::Warning => Severity::Warning,
+            Level::Error => Severity::Error,
+        }
+    }
+}
+
+// Parses a `rule=severity` override of `rasar lint`, where a severity of `off` disables the rule.
+fn parse_rule(arg: &str) -> Result<(Rule, Option<Severity>), String> {
+    let (name, level) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected rule=severity, found {}", arg))?;
+    let rule = Rule::from_name(name).ok_or_else(|| format!("unknown rule {}", name))?;
+
+    match level {
+        "off" => Ok((rule, None)),
+        level => Severity::from_name(level)
+            .map(|severity| (rule, Some(severity)))
+            .ok_or_else(|| format!("unknown severity {}, expected info, warning, error, or off", level)),
+    }
+}
+
+// Exit code of a command line that can not be parsed, as clap exits with.
+const EXIT_USAGE: u8 = 2;
+
+// A failure of a command: an Error, or a check that did not pass (e.g. `rasar lint` findings or
+// an exceeded `rasar analyze` budget).
+enum Failure {
+    Error(asar_error::Error),
+    Check(String),
+}
+
+impl Failure {
+
+    // Returns the kind of failure, as printed by `--json-errors`: `check-failed`, otherwise the
+    // kind of the Error (see `asar_error::Error::kind`).
+    fn kind(&self) -> &'static str {
+        match self {
+            Failure::Error(err) => err.kind(),
+            Failure::Check(_) => "check-failed",
+        }
+    }
+
+    // Returns the stable exit code of the kind of failure. 0 is success, and 2 a usage error.
+    fn exit_code(&self) -> u8 {
+        match self.kind() {
+            "check-failed" => 1,
+            "not-found" => 3,
+            "permission-denied" => 4,
+            "invalid-data" => 5,
+            "invalid-input" => 6,
+            "already-exists" => 7,
+            "invalid-header" => 8,
+            "invalid-json" => 9,
+            "unsupported" => 10,
+            "invalid-pattern" => 11,
+            "policy-violation" => 12,
+            "cancelled" => 13,
+            "timed-out" => 14,
+            // io, and any kind added later
+            _ => 15,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Error(err) => write!(f, "{}", err),
+            Failure::Check(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<asar_error::Error> for Failure {
+    fn from(err: asar_error::Error) -> Self {
+        Failure::Error(err)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(err: io::Error) -> Self {
+        Failure::Error(err.into())
+    }
+}
+
+impl From<serde_json::Error> for Failure {
+    fn from(err: serde_json::Error) -> Self {
+        Failure::Error(err.into())
+    }
+}
+
+fn run(command: Command) -> Result<(), Failure> {
+    match command {
+        Command::List { archive } => {
+            for path in Asar::open(archive)?.list()? {
+                println!("{}", path);
+            }
+        }
+
+        Command::Extract { archive, destination, ext, keep_going } => {
+            let asar = Asar::open(archive)?;
+
+            let options = match ext.is_empty() {
+                true => ExtractOptions::new(),
+                false => ExtractOptions::new().extensions(&ext),
+            };
+
+            if keep_going {
+                let report = asar.extract_report(destination, &options)?;
+
+                for (path, err) in report.failed.iter() {
+                    eprintln!("rasar: failed to extract {}: {}", path.display(), err);
+                }
+
+                if !report.is_complete() {
+                    return Err(Failure::Check(format!(
+                        "{} of {} file(s) could not be extracted",
+                        report.failed.len(),
+                        report.failed.len() + report.succeeded.len()
+                    )));
+                }
+            } else if ext.is_empty() {
+                asar.extract(destination)?;
+            } else {
+                asar.extract_with(destination, &options)?;
+            }
+        }
+
+        Command::Pack { source, destination, gitignore, config, profile, verify, skip_locked, split_size } => {
+            let (mut options, source, destination) = match config {
+                Some(config) => {
+                    let config = if config.is_dir() { config.join(CONFIG_FILE) } else { config };
+                    let config = match profile {
+                        Some(profile) => PackConfig::from_file_profile(config, &profile)?,
+                        None => PackConfig::from_file(config)?,
+                    };
+
+                    (
+                        config.options()?.asarignore(),
+                        source.unwrap_or_else(|| config.source()),
+                        destination.unwrap_or_else(|| config.destination()),
+                    )
+                }
+                None => (
+                    PackOptions::new().asarignore(),
+                    source.unwrap_or_default(),
+                    destination.unwrap_or_default(),
+                ),
+            };
+
+            if gitignore {
+                options = options.gitignore();
+            }
+
+            if verify {
+                options = options.verify(Verification::Full);
+            }
+
+            if skip_locked {
+                options = options.on_locked(LockPolicy::SkipLocked);
+            }
+
+            if let Some(bytes) = split_size {
+                options = options.split_size(bytes);
+            }
+
+            let asar = Asar::open_with(source, &options)?;
+
+            for path in asar.skipped_locked() {
+                eprintln!("rasar: skipped locked file {}", path.display());
+            }
+
+            asar.pack(destination)?;
+        }
+
+        Command::Grep { pattern, archive, ignore_case, regex, max_count } => {
+            let options = SearchOptions {
+                case_insensitive: ignore_case,
+                regex,
+                max_matches: max_count,
+            };
+
+            for m in Asar::open(archive)?.search(&pattern, &options)? {
+                println!("{}:{}:{}", m.path.display(), m.line, m.text);
+            }
+        }
+
+        Command::Analyze { archive, group_by, depth, top, budget } => {
+            let options = ReportOptions {
+                group_by: match group_by {
+                    Group::Dir => GroupBy::Directory(depth),
+                    Group::Ext => GroupBy::Extension,
+                },
+                top_n: top,
+                threshold: budget,
+            };
+
+            let report = Asar::open(archive)?.size_report(&options)?;
+            print!("{}", report);
+
+            if report.exceeds_threshold() {
+                return Err(Failure::Check(format!(
+                    "{} bytes exceed the budget of {} bytes",
+                    report.size,
+                    budget.unwrap_or_default()
+                )));
+            }
+        }
+
+        Command::Lint { archive, max_file_size, rule, fail_on } => {
+            let mut rules = RuleSet::new();
+
+            if let Some(bytes) = max_file_size {
+                rules = rules.max_file_size(bytes);
+            }
+
+            for (rule, severity) in rule {
+                rules = match severity {
+                    Some(severity) => rules.severity(rule, severity),
+                    None => rules.disable(rule),
+                };
+            }
+
+            let findings = Asar::open(archive)?.lint(&rules)?;
+            let fail_on = Severity::from(fail_on);
+
+            for finding in findings.iter() {
+                println!("{}", finding);
+            }
+
+            let failed = findings.iter().filter(|finding| finding.severity >= fail_on).count();
+
+            if failed > 0 {
+                return Err(Failure::Check(format!(
+                    "{} finding(s) at {} severity or above",
+                    failed,
+                    fail_on
+                )));
+            }
+        }
+
+        Command::Scan { archive, hash_list } => {
+            let matches = Asar::open(archive)?.match_hashes(&hashlist::read_hash_list(hash_list)?)?;
+
+            for (path, digest) in matches.iter() {
+                println!("{}  {}", digest, path.display());
+            }
+
+            if !matches.is_empty() {
+                return Err(Failure::Check(format!(
+                    "{} file(s) match the hash list",
+                    matches.len()
+                )));
+            }
+        }
+
+        Command::Selftest { dir } => {
+            let report = roundtrip::roundtrip_check(dir)?;
+
+            for (kind, paths) in [
+                ("missing", &report.missing),
+                ("extra", &report.extra),
+                ("changed", &report.changed),
+                ("permissions", &report.permissions),
+            ] {
+                for path in paths.iter() {
+                    println!("{}: {}", kind, path.display());
+                }
+            }
+
+            if !report.is_identical() {
+                return Err(Failure::Check(format!(
+                    "{} of {} file(s) differ after packing and extracting",
+                    report.missing.len() + report.extra.len() + report.changed.len() + report.permissions.len(),
+                    report.files
+                )));
+            }
+
+            println!("{} file(s) identical after packing and extracting", report.files);
+        }
+
+        Command::Sbom { archive } => {
+            let sbom = Asar::open(archive)?.sbom()?;
+
+            println!("{}", serde_json::to_string_pretty(&sbom.to_cyclonedx())?);
+        }
+
+        Command::Completions { shell } => {
+            let mut cmd = Cli::command();
+            let name = cmd.get_name().to_string();
+
+            clap_complete::generate(shell, &mut cmd, name, &mut io::stdout());
+        }
+
+        Command::Man => {
+            clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?;
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the file or folder the command operates on, reported along with its failure.
+fn operand(command: &Command) -> Option<&Path> {
+    match command {
+        Command::List { archive }
+        | Command::Extract { archive, .. }
+        | Command::Grep { archive, .. }
+        | Command::Analyze { archive, .. }
+        | Command::Lint { archive, .. }
+        | Command::Scan { archive, .. }
+        | Command::Sbom { archive } => Some(archive),
+        Command::Pack { source, config, .. } => source.as_deref().or(config.as_deref()),
+        Command::Selftest { dir } => Some(dir),
+        Command::Completions { .. } | Command::Man => None,
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() && std::env::args().any(|arg| arg == "--json-errors") => {
+            let rendered = err.to_string();
+            let message = rendered.lines().next().unwrap_or_default().trim_start_matches("error: ");
+
+            print_json_error("usage", EXIT_USAGE, message, None, None);
+            return ExitCode::from(EXIT_USAGE);
+        }
+        Err(err) => err.exit(),
+    };
+
+    let path = operand(&cli.command).map(Path::to_path_buf);
+
+    match run(cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => {
+            let code = failure.exit_code();
+
+            if cli.json_errors {
+                let offset = match &failure {
+                    Failure::Error(err) => err.offset(),
+                    Failure::Check(_) => None,
+                };
+
+                print_json_error(failure.kind(), code, &failure.to_string(), path.as_deref(), offset);
+            } else {
+                eprintln!("rasar: {}", failure);
+            }
+
+            ExitCode::from(code)
+        }
+    }
+}
+
+fn print_json_error(kind: &str, code: u8, message: &str, path: Option<&Path>, offset: Option<u64>) {
+    let error = serde_json::json!({
+        "kind": kind,
+        "code": code,
+        "message": message,
+        "path": path.map(|path| path.display().to_string()),
+        "offset": offset,
+    });
+
+    eprintln!("{}", error);
+}
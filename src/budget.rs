@@ -0,0 +1,109 @@
+use std::{
+    io::{self, Read, Write},
+    sync::{Arc, Condvar, Mutex},
+};
+
+use crate::asar_error::{self, Error};
+
+/// Size of the buffers leased by a MemoryBudget to stream a file, in bytes.
+pub const BUDGET_CHUNK_LEN: usize = 64 * 1024;
+
+/// A cap on the transient buffers used to read and write files while packing and extracting (see
+/// `PackOptions::memory_budget` and `ExtractOptions::memory_budget`), e.g. to run predictably
+/// within a memory-constrained sandbox or CI container.
+///
+/// Clones share the same budget, so one MemoryBudget passed to several operations (e.g. background
+/// extractions running in parallel) caps their buffers combined: a buffer is only allocated once
+/// the budget has room for it, waiting for other operations to release theirs otherwise.
+///
+/// Under a budget, files are streamed in buffers of at most BUDGET_CHUNK_LEN bytes. A file that
+/// must be held whole (by a transformer, or by `Hooks` when extracting) leases its size, or the
+/// whole budget if it is larger, while it is held. The header, and content transformed while a
+/// directory is opened, are not counted.
+///
+/// ```
+/// use rust_asar::{budget::MemoryBudget, extract::ExtractOptions};
+///
+/// let budget = MemoryBudget::new(8 * 1024 * 1024);
+/// let options = ExtractOptions::new().memory_budget(budget.clone());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    inner: Arc<Shared>,
+}
+
+// The state shared by the clones of a MemoryBudget.
+#[derive(Debug)]
+struct Shared {
+    limit: u64,
+    in_use: Mutex<u64>,
+    released: Condvar,
+}
+
+impl MemoryBudget {
+
+    /// Returns a budget of at most `bytes` bytes of buffers (at least 1).
+    pub fn new(bytes: u64) -> MemoryBudget {
+        MemoryBudget {
+            inner: Arc::new(Shared {
+                limit: bytes.max(1),
+                in_use: Mutex::new(0),
+                released: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Returns the size of the budget in bytes.
+    pub fn limit(&self) -> u64 {
+        self.inner.limit
+    }
+
+    /// Returns the number of bytes of buffers currently leased.
+    pub fn in_use(&self) -> u64 {
+        *self.inner.in_use.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Waits until the budget has room for `bytes` (at most the whole budget), then leases them
+    // until the returned Lease is dropped.
+    pub(crate) fn acquire(&self, bytes: u64) -> Lease<'_> {
+        let bytes = bytes.min(self.inner.limit);
+        let mut in_use = self.inner.in_use.lock().unwrap_or_else(|err| err.into_inner());
+
+        while *in_use + bytes > self.inner.limit {
+            in_use = self.inner.released.wait(in_use).unwrap_or_else(|err| err.into_inner());
+        }
+
+        *in_use += bytes;
+
+        Lease { budget: self, bytes }
+    }
+}
+
+// Bytes leased from a MemoryBudget, released once dropped.
+pub(crate) struct Lease<'a> {
+    budget: &'a MemoryBudget,
+    bytes: u64,
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        let mut in_use = self.budget.inner.in_use.lock().unwrap_or_else(|err| err.into_inner());
+        *in_use -= self.bytes;
+
+        self.budget.inner.released.notify_all();
+    }
+}
+
+// Streams `size` bytes from the reader to the writer, through a small buffer on the stack.
+pub(crate) fn stream<R: Read, W: Write>(reader: R, writer: &mut W, size: u64) -> Result<(), asar_error::Error> {
+    let copied = io::copy(&mut reader.take(size), writer)?;
+
+    if copied != size {
+        return Err(Error::IoError(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Expected {} bytes, read {}", size, copied),
+        )));
+    }
+
+    Ok(())
+}
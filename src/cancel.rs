@@ -0,0 +1,81 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    extract::ExtractOptions,
+    hooks::RunContext,
+};
+
+/// Number of bytes copied between checks of a cancel token.
+const CANCEL_CHUNK_LEN: usize = 64 * 1024;
+
+// Returns Error::Cancelled if the token is set.
+fn check(token: &AtomicBool) -> Result<(), asar_error::Error> {
+    if token.load(Ordering::Relaxed) {
+        return Err(Error::Cancelled);
+    }
+
+    Ok(())
+}
+
+// Copies exactly `size` bytes from reader to writer in chunks, checking the token before each chunk.
+pub(crate) fn copy_cancellable<R: Read, W: Write>(
+    mut reader: R,
+    writer: &mut W,
+    size: u64,
+    token: &AtomicBool,
+) -> Result<(), asar_error::Error> {
+    let mut buf: Vec<u8> = vec![0; CANCEL_CHUNK_LEN.min(size as usize)];
+    let mut remaining = size;
+
+    while remaining > 0 {
+        check(token)?;
+
+        let len = remaining.min(buf.len() as u64) as usize;
+        reader.read_exact(&mut buf[..len])?;
+        writer.write_all(&buf[..len])?;
+
+        remaining -= len as u64;
+    }
+
+    Ok(())
+}
+
+impl Asar {
+
+    /// Extracts an opened Asar archive file to the provided destination folder, as does `extract`,
+    /// stopping as soon as `token` is set (checked between files and every 64 KiB within them).
+    ///
+    /// On cancellation, every file and folder created so far is removed and `Error::Cancelled` is
+    /// returned. Files that already existed at the destination are overwritten and not restored.
+    pub fn extract_with_cancel<P: AsRef<Path>>(&self, destination: P, token: &AtomicBool) -> Result<(), asar_error::Error> {
+        let run = RunContext {
+            cancel: Some(token),
+            ..RunContext::default()
+        };
+
+        self.extract_run(destination, &ExtractOptions::default(), &run)
+    }
+
+    /// Packs an opened directory to the Asar archive file at `destination`, as does `pack`,
+    /// stopping as soon as `token` is set (checked between files and every 64 KiB within them).
+    ///
+    /// On cancellation, the partially written archive is removed and `Error::Cancelled` is returned,
+    /// leaving any archive already at the destination untouched (see `temp::TempGuard`).
+    /// If the token is already set, nothing is written or removed.
+    pub fn pack_with_cancel<P: AsRef<Path>>(&self, destination: P, token: &AtomicBool) -> Result<(), asar_error::Error> {
+        check(token)?;
+
+        let run = RunContext {
+            cancel: Some(token),
+            ..self.options.run_context()
+        };
+
+        self.pack_run(&destination, &run)
+    }
+}
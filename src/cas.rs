@@ -0,0 +1,203 @@
+use std::{
+    fs::{DirBuilder, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    content,
+    hash::{self, Hasher, Sha256},
+};
+
+/// Name of the folder holding hashed objects within a content-addressable store.
+pub const OBJECTS_DIR: &str = "objects";
+
+/// Name of the manifest file written at the root of a content-addressable store.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Returns the lowercase hex SHA256 digest of everything read from the provided reader.
+pub fn sha256_hex<R: Read>(reader: R) -> io::Result<String> {
+    hash::hex_digest(&Sha256, reader)
+}
+
+/// Size of the blocks hashed by `integrity`, as used by Electron.
+pub const INTEGRITY_BLOCK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Returns the `integrity` field of a header entry for everything read from the provided reader,
+/// as recorded by Electron's packer and checked by Electron at runtime:
+///
+/// `{ "algorithm": "SHA256", "hash": "ab12...", "blockSize": 4194304, "blocks": ["cd34...", ...] }`
+pub fn integrity<R: Read>(reader: R) -> io::Result<Value> {
+    integrity_with(&Sha256, reader)
+}
+
+/// Returns the `integrity` field of a header entry as `integrity` does, hashed by the provided
+/// Hasher, whose name is recorded as the `algorithm`.
+pub fn integrity_with<R: Read>(hasher: &dyn Hasher, mut reader: R) -> io::Result<Value> {
+    let mut state = hasher.start();
+    let mut blocks: Vec<Value> = Vec::new();
+    let mut block: Vec<u8> = Vec::with_capacity(INTEGRITY_BLOCK_SIZE);
+
+    loop {
+        block.clear();
+        let read = (&mut reader).take(INTEGRITY_BLOCK_SIZE as u64).read_to_end(&mut block)?;
+
+        if read == 0 && !blocks.is_empty() {
+            break;
+        }
+
+        state.update(&block);
+
+        let mut block_state = hasher.start();
+        block_state.update(&block);
+        blocks.push(Value::String(hash::hex(&block_state.finish())));
+
+        if read < INTEGRITY_BLOCK_SIZE {
+            break;
+        }
+    }
+
+    Ok(json!({
+        "algorithm": hasher.name(),
+        "hash": hash::hex(&state.finish()),
+        "blockSize": INTEGRITY_BLOCK_SIZE,
+        "blocks": blocks,
+    }))
+}
+
+/// Returns the path of an object within a content-addressable store, given its hash.
+///
+/// Objects are fanned out by the first two characters of their hash: `objects/ab/cdef...`
+///
+/// The hash is not checked: one read from an untrusted manifest must be checked first (see
+/// `check_hash`), as a hash such as `../../etc/passwd` resolves outside of the store.
+pub fn object_path<P: AsRef<Path>>(cas_dir: P, hash: &str) -> PathBuf {
+    let split = hash.char_indices().nth(2).map_or(hash.len(), |(index, _)| index);
+    let (prefix, rest) = hash.split_at(split);
+
+    cas_dir.as_ref().join(OBJECTS_DIR).join(prefix).join(rest)
+}
+
+/// Checks that a hash is a lowercase hex digest of the provided Hasher (of the length it returns),
+/// as written by `export_cas`.
+///
+/// Returns (), otherwise Error.
+pub fn check_hash(hasher: &dyn Hasher, hash: &str) -> Result<(), asar_error::Error> {
+    let len = hasher.start().finish().len() * 2;
+
+    if hash.len() != len || !hash.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f')) {
+        return Err(Error::ParseHeaderError(format!(
+            "Expected {} hex digest, found: {}",
+            hasher.name(),
+            hash
+        )));
+    }
+
+    Ok(())
+}
+
+impl Asar {
+
+    /// Exports every file within an opened Asar archive file to a content-addressable store at the
+    /// provided directory.
+    ///
+    /// Each unique file is written once under its hash (`objects/ab/cdef...`, see `Asar::set_hasher`),
+    /// and objects that already exist are left untouched, so one store can be shared across many
+    /// archives. A manifest mapping archive paths to hashes is written as `manifest.json`:
+    ///
+    /// `{ "algorithm": "SHA256", "files": { "folder1/script.py": "ab12..." } }`
+    ///
+    /// Returns the manifest as `serde_json::Value`, otherwise Error.
+    pub fn export_cas<P: AsRef<Path>>(&self, dir: P) -> Result<Value, asar_error::Error> {
+        let dir = dir.as_ref();
+        let file = self.archive_file()?;
+        let mut files = Map::new();
+
+        DirBuilder::new().recursive(true).create(dir)?;
+
+        for (path, offset, size) in self.content.files_to_vec()? {
+            let hash = hash::hex_digest(self.hasher(), self.entry_reader(&file, offset, size))?;
+            let object = object_path(dir, &hash);
+
+            if !object.try_exists()? {
+                if let Some(parent) = object.parent() {
+                    DirBuilder::new().recursive(true).create(parent)?;
+                }
+
+                let mut out = File::create(&object)?;
+                io::copy(&mut self.entry_reader(&file, offset, size), &mut out)?;
+            }
+
+            files.insert(path.to_string_lossy().replace('\\', "/"), Value::String(hash));
+        }
+
+        let manifest = json!({
+            "algorithm": self.hasher().name(),
+            "files": Value::Object(files),
+        });
+
+        serde_json::to_writer_pretty(File::create(dir.join(MANIFEST_FILE))?, &manifest)?;
+
+        Ok(manifest)
+    }
+
+    /// Reconstructs an Asar archive file at `destination` from a manifest (as written by `export_cas`)
+    /// and the content-addressable store holding its objects.
+    ///
+    /// Files are packed in manifest order, each read from `objects/ab/cdef...` within `cas_dir`.
+    /// The algorithm of the manifest must be built-in or registered (see `hash::find`), SHA256 if
+    /// it has none, and every hash a lowercase hex digest of it (see `check_hash`), so a manifest
+    /// can not read files outside of the store.
+    ///
+    /// Returns (), otherwise Error if the manifest is malformed or an object is missing.
+    pub fn pack_from_manifest<C, D>(manifest: &Value, cas_dir: C, destination: D) -> Result<(), asar_error::Error>
+    where
+        C: AsRef<Path>,
+        D: AsRef<Path>,
+    {
+        let hasher: Arc<dyn Hasher> = match manifest.get("algorithm") {
+            Some(algorithm) => algorithm.as_str().and_then(hash::find).ok_or_else(|| {
+                Error::ParseHeaderError(format!("Unsupported manifest algorithm: {}", algorithm))
+            })?,
+            None => Arc::new(Sha256),
+        };
+
+        let files = match manifest.get("files") {
+            Some(Value::Object(files)) => files,
+            _ => {
+                return Err(Error::ParseHeaderError(
+                    "'files' not found in manifest".to_string(),
+                ))
+            }
+        };
+
+        let mut header = Map::new();
+        let mut list: Vec<(PathBuf, u64)> = Vec::new();
+        let mut offset: u64 = 0;
+
+        header.insert("files".to_string(), Value::Object(Map::new()));
+
+        for (path, hash) in files {
+            let hash = hash.as_str().ok_or_else(|| {
+                Error::ParseHeaderError(format!("Expected hash string for file: {}", path))
+            })?;
+
+            check_hash(hasher.as_ref(), hash)?;
+
+            let object = object_path(cas_dir.as_ref(), hash);
+            let size = object.metadata()?.len();
+
+            content::insert_path(&mut header, path, json!({ "size": size, "offset": offset.to_string() }))?;
+            list.push((object, size));
+
+            offset += size;
+        }
+
+        Asar::from_header(cas_dir, Value::Object(header), list)?.pack(destination)
+    }
+}
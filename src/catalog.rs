@@ -0,0 +1,192 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use sled::{Db, Tree};
+
+use crate::{asar::Asar, asar_error, cas};
+
+/// A file found within a cataloged archive by `AsarCatalog::archives_with_hash`.
+///
+/// - archive: Path of the archive, as it was added
+///
+/// - path: Full path of the file within the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogMatch {
+    pub archive: PathBuf,
+    pub path: PathBuf,
+}
+
+/// AsarCatalog indexes the files of many Asar archive files into an embedded sled database, so a
+/// fleet of archives can be queried without opening them again, e.g. to find every release
+/// shipping a vulnerable file during incident response.
+///
+/// ```no_run
+/// use rust_asar::catalog::AsarCatalog;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let catalog = AsarCatalog::open("releases.catalog")?;
+/// catalog.add("releases/1.0/app.asar")?;
+/// catalog.add("releases/1.1/app.asar")?;
+///
+/// for archive in catalog.archives_with_path("node_modules/lodash/package.json")? {
+///     println!("{}", archive.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsarCatalog {
+    db: Db,
+    paths: Tree,    // path \0 archive
+    hashes: Tree,   // hash \0 archive \0 path
+    archives: Tree, // archive -> [[path, hash], ...]
+}
+
+// Joins the parts of a key with null bytes, which can not appear within paths or hashes.
+fn key(parts: &[&str]) -> Vec<u8> {
+    parts.join("\0").into_bytes()
+}
+
+// Returns the parts of a key following the provided prefix.
+fn rest(key: &[u8], prefix: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(&key[prefix.len()..])
+        .split('\0')
+        .map(str::to_string)
+        .collect()
+}
+
+// Converts a database Error.
+fn db_error(err: sled::Error) -> asar_error::Error {
+    asar_error::Error::IoError(io::Error::from(err))
+}
+
+impl AsarCatalog {
+
+    /// Opens the catalog stored at the provided path, creating it if it does not exist.
+    ///
+    /// Returns the catalog, otherwise Error if the database can not be opened (e.g. it is already
+    /// open in another process).
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<AsarCatalog, asar_error::Error> {
+        Self::from_db(sled::open(path).map_err(db_error)?)
+    }
+
+    /// Opens an empty catalog removed once dropped.
+    pub fn temporary() -> Result<AsarCatalog, asar_error::Error> {
+        Self::from_db(sled::Config::new().temporary(true).open().map_err(db_error)?)
+    }
+
+    fn from_db(db: Db) -> Result<AsarCatalog, asar_error::Error> {
+        Ok(AsarCatalog {
+            paths: db.open_tree("paths").map_err(db_error)?,
+            hashes: db.open_tree("hashes").map_err(db_error)?,
+            archives: db.open_tree("archives").map_err(db_error)?,
+            db,
+        })
+    }
+
+    /// Indexes every file of the Asar archive file at the provided path by full path and SHA-256
+    /// hash of its content, replacing any previous entries of the same archive path. Every file
+    /// is read once.
+    ///
+    /// Returns the number of files indexed, otherwise Error if the archive can not be read.
+    pub fn add<P: AsRef<Path>>(&self, archive: P) -> Result<usize, asar_error::Error> {
+        let archive = archive.as_ref();
+        let name = archive.to_string_lossy();
+
+        let mut entries: Vec<(String, String)> = Vec::new();
+
+        Asar::open(archive)?.extract_each(|_| true, |path, reader| {
+            entries.push((path.to_string_lossy().into_owned(), cas::sha256_hex(reader)?));
+            Ok(())
+        })?;
+
+        self.remove(archive)?;
+
+        for (path, hash) in entries.iter() {
+            self.paths.insert(key(&[path, &name]), &[]).map_err(db_error)?;
+            self.hashes.insert(key(&[hash, &name, path]), &[]).map_err(db_error)?;
+        }
+
+        self.archives
+            .insert(name.as_bytes(), serde_json::to_vec(&entries)?)
+            .map_err(db_error)?;
+        self.db.flush().map_err(db_error)?;
+
+        Ok(entries.len())
+    }
+
+    /// Removes every entry of the archive at the provided path from the catalog.
+    ///
+    /// Returns true if the archive was cataloged, otherwise Error.
+    pub fn remove<P: AsRef<Path>>(&self, archive: P) -> Result<bool, asar_error::Error> {
+        let name = archive.as_ref().to_string_lossy();
+
+        let Some(entries) = self.archives.remove(name.as_bytes()).map_err(db_error)? else {
+            return Ok(false);
+        };
+
+        let entries: Vec<(String, String)> = serde_json::from_slice(&entries)?;
+
+        for (path, hash) in entries.iter() {
+            self.paths.remove(key(&[path, &name])).map_err(db_error)?;
+            self.hashes.remove(key(&[hash, &name, path])).map_err(db_error)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the path of every cataloged archive, sorted.
+    pub fn archives(&self) -> Result<Vec<PathBuf>, asar_error::Error> {
+        self.archives
+            .iter()
+            .keys()
+            .map(|name| Ok(PathBuf::from(String::from_utf8_lossy(&name.map_err(db_error)?).into_owned())))
+            .collect()
+    }
+
+    /// Returns the path of every cataloged archive containing a file at the provided full path, sorted.
+    pub fn archives_with_path<P: AsRef<Path>>(&self, path: P) -> Result<Vec<PathBuf>, asar_error::Error> {
+        let prefix = key(&[&path.as_ref().to_string_lossy(), ""]);
+
+        self.paths
+            .scan_prefix(&prefix)
+            .keys()
+            .map(|found| Ok(PathBuf::from(&rest(&found.map_err(db_error)?, &prefix)[0])))
+            .collect()
+    }
+
+    /// Returns every file within the cataloged archives whose content has the provided SHA-256
+    /// hash (lowercase hexadecimal, see `cas::sha256_hex`), sorted by archive then path.
+    pub fn archives_with_hash(&self, hash: &str) -> Result<Vec<CatalogMatch>, asar_error::Error> {
+        let prefix = key(&[hash, ""]);
+
+        self.hashes
+            .scan_prefix(&prefix)
+            .keys()
+            .map(|found| {
+                let parts = rest(&found.map_err(db_error)?, &prefix);
+
+                Ok(CatalogMatch {
+                    archive: PathBuf::from(&parts[0]),
+                    path: PathBuf::from(parts.get(1).map_or("", String::as_str)),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the files of the archive at the provided path as `(full_file_path, sha256_hash)`,
+    /// as they were cataloged, or `None` if it is not cataloged.
+    pub fn files<P: AsRef<Path>>(&self, archive: P) -> Result<Option<Vec<(PathBuf, String)>>, asar_error::Error> {
+        let name = archive.as_ref().to_string_lossy();
+
+        let Some(entries) = self.archives.get(name.as_bytes()).map_err(db_error)? else {
+            return Ok(None);
+        };
+
+        let entries: Vec<(String, String)> = serde_json::from_slice(&entries)?;
+
+        Ok(Some(entries.into_iter().map(|(path, hash)| (PathBuf::from(path), hash)).collect()))
+    }
+}
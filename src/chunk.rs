@@ -0,0 +1,202 @@
+use std::{
+    collections::HashSet,
+    io::{self, Read},
+    path::Path,
+};
+
+use crate::{
+    asar::Asar,
+    asar_error,
+    hash::{self, Hasher},
+};
+
+/// Sizes of the chunks cut by `chunk` (FastCDC with normalized chunking). Cut points depend only on
+/// the bytes near them, so an insertion or deletion changes the chunks around it while the rest of
+/// the file keeps the same chunks, even though its bytes shift.
+///
+/// - min_size: No chunk is smaller, except the last chunk of a file
+///
+/// - avg_size: Expected size of a chunk, a power of two (rounded down otherwise)
+///
+/// - max_size: No chunk is larger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkOptions {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            min_size: 2 * 1024,
+            avg_size: 8 * 1024,
+            max_size: 64 * 1024,
+        }
+    }
+}
+
+/// A chunk of a file, as cut by `chunk`.
+///
+/// - offset: Offset of the chunk within the file
+///
+/// - size: Size of the chunk in bytes
+///
+/// - hash: Lowercase hex digest of the chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub offset: u64,
+    pub size: u64,
+    pub hash: String,
+}
+
+/// The chunks of a new version of a file missing from an old version, as returned by `delta`.
+///
+/// - reused: Bytes of the new version found within the chunks of the old version
+///
+/// - added: Chunks of the new version found nowhere within the old version, which a patch must carry
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChunkDelta {
+    pub reused: u64,
+    pub added: Vec<Chunk>,
+}
+
+impl ChunkDelta {
+
+    /// Returns the bytes of every added chunk combined.
+    pub fn added_size(&self) -> u64 {
+        self.added.iter().map(|chunk| chunk.size).sum()
+    }
+}
+
+// Random values mixed into the rolling hash for every byte value, generated by SplitMix64 so the
+// table (and every cut point) is identical across builds.
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x5EED_A5A2_C0DE_CDC0;
+    let mut i = 0;
+
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    table
+}
+
+// Returns a mask of the provided number of high bits, which depend on the most recent bytes of the
+// rolling hash as it is shifted left.
+fn mask(bits: u32) -> u64 {
+    match bits {
+        0 => 0,
+        bits => u64::MAX << (64 - bits.min(64)),
+    }
+}
+
+// Returns the length of the first chunk of the data, given at least max_size bytes unless the data
+// is the end of the file.
+fn cut_point(data: &[u8], options: &ChunkOptions) -> usize {
+    if data.len() <= options.min_size {
+        return data.len();
+    }
+
+    let end = data.len().min(options.max_size);
+    let normal = options.avg_size.min(end);
+    let bits = options.avg_size.ilog2();
+
+    // harder to match before the average size, easier after, so sizes cluster around it
+    let (mask_small, mask_large) = (mask(bits + 1), mask(bits.saturating_sub(1)));
+    let mut hash: u64 = 0;
+
+    for (i, byte) in data.iter().enumerate().take(end).skip(options.min_size) {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+
+        let mask = if i < normal { mask_small } else { mask_large };
+
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+
+    end
+}
+
+/// Cuts everything read from the provided reader into content-defined chunks (see ChunkOptions),
+/// each hashed by the provided Hasher.
+///
+/// Returns the chunks in order, otherwise Error if the reader fails or the sizes are invalid
+/// (`min_size` must be at least 1 and at most `avg_size`, itself at most `max_size`).
+pub fn chunk<R: Read>(mut reader: R, options: &ChunkOptions, hasher: &dyn Hasher) -> io::Result<Vec<Chunk>> {
+    if options.min_size == 0 || options.min_size > options.avg_size || options.avg_size > options.max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Invalid chunk sizes: {} <= {} <= {} is required",
+                options.min_size, options.avg_size, options.max_size
+            ),
+        ));
+    }
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut buf: Vec<u8> = Vec::with_capacity(options.max_size);
+    let mut offset: u64 = 0;
+    let mut eof = false;
+
+    loop {
+        while !eof && buf.len() < options.max_size {
+            let wanted = (options.max_size - buf.len()) as u64;
+            eof = (&mut reader).take(wanted).read_to_end(&mut buf)? == 0;
+        }
+
+        if buf.is_empty() {
+            break;
+        }
+
+        let size = cut_point(&buf, options);
+
+        chunks.push(Chunk {
+            offset,
+            size: size as u64,
+            hash: hash::hex_digest(hasher, &buf[..size])?,
+        });
+
+        offset += size as u64;
+        buf.drain(..size);
+    }
+
+    Ok(chunks)
+}
+
+/// Compares the chunks of two versions of a file (as cut by `chunk` with the same ChunkOptions and
+/// Hasher), returning the chunks of the new version a patch must carry.
+pub fn delta(old: &[Chunk], new: &[Chunk]) -> ChunkDelta {
+    let known: HashSet<&str> = old.iter().map(|chunk| chunk.hash.as_str()).collect();
+    let mut delta = ChunkDelta::default();
+
+    for chunk in new.iter() {
+        if known.contains(chunk.hash.as_str()) {
+            delta.reused += chunk.size;
+        } else {
+            delta.added.push(chunk.clone());
+        }
+    }
+
+    delta
+}
+
+impl Asar {
+
+    /// Cuts the file at the provided full path within an opened Asar archive file into
+    /// content-defined chunks (see `chunk`), hashed by the Hasher of the Asar.
+    ///
+    /// Returns the chunks in order, otherwise Error if the path is not a file within the archive.
+    pub fn chunk_file<P: AsRef<Path>>(&self, path: P, options: &ChunkOptions) -> Result<Vec<Chunk>, asar_error::Error> {
+        Ok(chunk(self.open_entry(path)?, options, self.hasher())?)
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    index::EntryRef,
+};
+
+// Distinguishes the temporary folders of concurrent checks within a process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A conformance check run against every archive of a Corpus.
+///
+/// - Parse: The archive opens, and its header and path index are built
+///
+/// - Extract: Every file extracts to a folder with the same content as `get_file` provides
+///
+/// - Repack: Packing the extracted folder yields an archive holding the same files and content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Check {
+    Parse,
+    Extract,
+    Repack,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Parse => write!(f, "parse"),
+            Check::Extract => write!(f, "extract"),
+            Check::Repack => write!(f, "repack"),
+        }
+    }
+}
+
+/// The results of every check of a single reference archive.
+///
+/// - archive: Path of the reference archive
+///
+/// - passed: Checks that passed, in order
+///
+/// - failed: Checks that failed, with the reason. Later checks are skipped once one fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conformance {
+    pub archive: PathBuf,
+    pub passed: Vec<Check>,
+    pub failed: Vec<(Check, String)>,
+}
+
+impl Conformance {
+
+    /// Returns true if every check passed.
+    pub fn is_conformant(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// A golden-file corpus: a folder of reference Asar archive files (e.g. produced by several
+/// versions of Electron's `@electron/asar`), checked for conformance with `Corpus::check`.
+///
+/// ```no_run
+/// use rust_asar::compat::Corpus;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let corpus = Corpus::load("tests/corpus")?;
+///
+/// for result in corpus.check() {
+///     for (check, reason) in result.failed.iter() {
+///         println!("{}: {} failed: {}", result.archive.display(), check, reason);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    archives: Vec<PathBuf>,
+}
+
+impl Corpus {
+
+    /// Loads every file ending in `.asar` within the provided folder (recursively), sorted by path.
+    ///
+    /// Returns the Corpus, otherwise Error if the folder can not be read or holds no archive.
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Corpus, asar_error::Error> {
+        let mut archives: Vec<PathBuf> = Vec::new();
+        find_archives(dir.as_ref(), &mut archives)?;
+
+        if archives.is_empty() {
+            return Err(Error::UnknownContentType(format!(
+                "No Asar archive file found in {}",
+                dir.as_ref().display()
+            )));
+        }
+
+        archives.sort();
+
+        Ok(Corpus { archives })
+    }
+
+    /// Returns the paths of the loaded reference archives.
+    pub fn archives(&self) -> &[PathBuf] {
+        &self.archives
+    }
+
+    /// Runs every Check against every reference archive, in order.
+    ///
+    /// Temporary files are written to `std::env::temp_dir()` and removed afterwards.
+    pub fn check(&self) -> Vec<Conformance> {
+        self.archives.iter().map(|archive| check_archive(archive)).collect()
+    }
+}
+
+fn find_archives(dir: &Path, archives: &mut Vec<PathBuf>) -> Result<(), asar_error::Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            find_archives(&path, archives)?;
+        } else if path.extension().is_some_and(|ext| ext == "asar") {
+            archives.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn check_archive(archive: &Path) -> Conformance {
+    let mut result = Conformance {
+        archive: archive.to_path_buf(),
+        passed: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    let temp = std::env::temp_dir().join(format!(
+        "rust_asar-compat-{}-{}",
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    for check in [Check::Parse, Check::Extract, Check::Repack] {
+        let outcome = match check {
+            Check::Parse => check_parse(archive),
+            Check::Extract => check_extract(archive, &temp),
+            Check::Repack => check_repack(archive, &temp),
+        };
+
+        match outcome {
+            Ok(()) => result.passed.push(check),
+            Err(reason) => {
+                result.failed.push((check, reason));
+                break;
+            }
+        }
+    }
+
+    fs::remove_dir_all(&temp).ok();
+
+    result
+}
+
+// Returns the content of every file within an opened Asar archive file, sorted by path.
+fn files(asar: &Asar) -> Result<Vec<(PathBuf, Vec<u8>)>, String> {
+    let mut files: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+
+    for (path, entry) in asar.index().iter() {
+        if let EntryRef::File(..) = entry {
+            match asar.get_file(path) {
+                Some(content) => files.push((path.clone(), content)),
+                None => return Err(format!("{} can not be read", path.display())),
+            }
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+fn check_parse(archive: &Path) -> Result<(), String> {
+    Asar::open(archive).map(|_| ()).map_err(|err| err.to_string())
+}
+
+fn check_extract(archive: &Path, temp: &Path) -> Result<(), String> {
+    let asar = Asar::open(archive).map_err(|err| err.to_string())?;
+    let extracted = temp.join("extracted");
+
+    asar.extract(&extracted).map_err(|err| err.to_string())?;
+
+    for (path, content) in files(&asar)? {
+        match fs::read(extracted.join(&path)) {
+            Ok(actual) if actual == content => {}
+            Ok(_) => return Err(format!("{} differs after extracting", path.display())),
+            Err(err) => return Err(format!("{} was not extracted: {}", path.display(), err)),
+        }
+    }
+
+    Ok(())
+}
+
+fn check_repack(archive: &Path, temp: &Path) -> Result<(), String> {
+    let original = Asar::open(archive).map_err(|err| err.to_string())?;
+    let repacked_path = temp.join("repacked.asar");
+
+    Asar::open(temp.join("extracted"))
+        .and_then(|asar| asar.pack(&repacked_path))
+        .map_err(|err| err.to_string())?;
+
+    let repacked = Asar::open(&repacked_path).map_err(|err| err.to_string())?;
+
+    let expected = files(&original)?;
+    let actual = files(&repacked)?;
+
+    if expected.len() != actual.len() {
+        return Err(format!(
+            "repacked archive holds {} files, expected {}",
+            actual.len(),
+            expected.len()
+        ));
+    }
+
+    for ((path, content), (actual_path, actual_content)) in expected.iter().zip(actual.iter()) {
+        if path != actual_path || content != actual_content {
+            return Err(format!("{} differs after repacking", path.display()));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,332 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Deserialize;
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    pack::PackOptions,
+    transform::TransformResult,
+};
+
+/// Name of the config file read by `rasar pack --config` when given a folder.
+pub const CONFIG_FILE: &str = "asar.toml";
+
+/// A declarative transform of a pack config, adding text around every matched file.
+///
+/// - include: Files transformed, as gitignore patterns relative to the packed directory (e.g. `*.js`)
+///
+/// - prepend: Text inserted at the start of every matched file
+///
+/// - append: Text added at the end of every matched file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransformRule {
+    pub include: Vec<String>,
+    pub prepend: String,
+    pub append: String,
+}
+
+/// PackConfig describes how a directory is packed, read from a TOML file committed with the
+/// project (usually `asar.toml`), so the packaging policy lives with the code rather than
+/// within every build script:
+///
+/// ```toml
+/// source = "app"
+/// destination = "dist/app.asar"
+/// exclude = ["*.md", "/test/"]
+/// unpack = ["*.node"]
+/// order = ["main.js", "preload.js"]
+/// integrity = true
+///
+/// [[transform]]
+/// include = ["*.js"]
+/// prepend = "/* (c) Example */\n"
+/// ```
+///
+/// Every field other than `source` and `destination` is optional, each matching the PackOptions
+/// method of the same name. Relative paths are resolved against the folder of the config file.
+///
+/// Strings may reference environment variables as `${VAR}`, or `${VAR:-default}` to fall back to
+/// a default when it is unset. Profiles override any top-level field when selected (see
+/// `from_file_profile`), so one file can drive both development and CI packs:
+///
+/// ```toml
+/// source = "app"
+/// destination = "dist/${CHANNEL:-dev}/app.asar"
+/// exclude = ["*.md"]
+///
+/// [profile.release]
+/// exclude = ["*.md", "*.map", "/test/"]
+/// integrity = true
+///
+/// [profile.debug]
+/// integrity = false
+/// ```
+///
+/// - source: Directory to pack
+///
+/// - destination: Asar archive file to write
+///
+/// - exclude: See `PackOptions::exclude`
+///
+/// - unpack: See `PackOptions::unpack`
+///
+/// - order: See `PackOptions::order`
+///
+/// - integrity: See `PackOptions::integrity`
+///
+/// - align: See `PackOptions::align`
+///
+/// - asarignore, gitignore: See `PackOptions::asarignore` and `PackOptions::gitignore`
+///
+/// - strip_debug_artifacts: See `PackOptions::strip_debug_artifacts`
+///
+/// - dedupe_hard_links, resolve_pnpm: See `PackOptions::dedupe_hard_links` and `PackOptions::resolve_pnpm`
+///
+/// - transform: Transforms applied in order to every file they include (see `TransformRule`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PackConfig {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub exclude: Vec<String>,
+    pub unpack: Vec<String>,
+    pub order: Vec<PathBuf>,
+    pub integrity: bool,
+    pub align: Option<u64>,
+    pub asarignore: bool,
+    pub gitignore: bool,
+    pub strip_debug_artifacts: bool,
+    pub dedupe_hard_links: bool,
+    pub resolve_pnpm: bool,
+    pub transform: Vec<TransformRule>,
+    #[serde(skip)]
+    base: PathBuf,
+}
+
+// Converts a config parsing Error.
+fn config_error(err: toml::de::Error) -> asar_error::Error {
+    Error::IoError(io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// Returns an InvalidData Error of the config.
+fn invalid(message: String) -> asar_error::Error {
+    Error::IoError(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+// Expands every `${VAR}` and `${VAR:-default}` within the string from the environment.
+fn expand(value: &str) -> Result<String, asar_error::Error> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| invalid(format!("Unterminated variable within pack config: {}", value)))?;
+        let reference = &rest[start + 2..start + end];
+
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+
+        match (env::var(name), default) {
+            (Ok(variable), _) => expanded.push_str(&variable),
+            (Err(_), Some(default)) => expanded.push_str(default),
+            (Err(_), None) => {
+                return Err(invalid(format!("Environment variable {} of pack config is not set", name)))
+            }
+        }
+
+        rest = &rest[start + end + 1..];
+    }
+
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+// Expands the variables of every string within the value, recursively.
+fn expand_value(value: &mut toml::Value) -> Result<(), asar_error::Error> {
+    match value {
+        toml::Value::String(string) => *string = expand(string)?,
+        toml::Value::Array(values) => values.iter_mut().try_for_each(expand_value)?,
+        toml::Value::Table(table) => table.iter_mut().try_for_each(|(_, value)| expand_value(value))?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Builds a matcher of gitignore patterns relative to the packed directory.
+fn matcher(patterns: &[String]) -> Result<Gitignore, asar_error::Error> {
+    let mut builder = GitignoreBuilder::new("");
+
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|err| Error::InvalidPattern(format!("{}: {}", pattern, err)))?;
+    }
+
+    builder.build().map_err(|err| Error::InvalidPattern(err.to_string()))
+}
+
+impl PackConfig {
+
+    /// Reads and parses the config file at the provided path, resolving relative paths against its folder.
+    ///
+    /// Returns the config, otherwise Error if the file can not be read or is not a valid config.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<PackConfig, asar_error::Error> {
+        Self::read(path.as_ref(), None)
+    }
+
+    /// Reads and parses the config file at the provided path, as does `from_file`, with the
+    /// fields of the `[profile.<name>]` table overriding those at the top level.
+    ///
+    /// Returns the config, otherwise Error if the profile is not defined.
+    pub fn from_file_profile<P: AsRef<Path>>(path: P, profile: &str) -> Result<PackConfig, asar_error::Error> {
+        Self::read(path.as_ref(), Some(profile))
+    }
+
+    /// Parses a config, resolving relative paths against the current directory.
+    ///
+    /// Returns the config, otherwise Error if it is not valid TOML, has unknown fields, references
+    /// an unset environment variable without a default, or does not set `source` and `destination`.
+    pub fn from_toml(config: &str) -> Result<PackConfig, asar_error::Error> {
+        Self::parse(config, None)
+    }
+
+    /// Parses a config, as does `from_toml`, with the fields of the `[profile.<name>]` table
+    /// overriding those at the top level.
+    pub fn from_toml_profile(config: &str, profile: &str) -> Result<PackConfig, asar_error::Error> {
+        Self::parse(config, Some(profile))
+    }
+
+    fn read(path: &Path, profile: Option<&str>) -> Result<PackConfig, asar_error::Error> {
+        let mut config = Self::parse(&fs::read_to_string(path)?, profile)?;
+
+        config.base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(config)
+    }
+
+    fn parse(config: &str, profile: Option<&str>) -> Result<PackConfig, asar_error::Error> {
+        let mut table: toml::Table = toml::from_str(config).map_err(config_error)?;
+        let profiles = table.remove("profile");
+
+        if let Some(name) = profile {
+            let overrides = profiles
+                .as_ref()
+                .and_then(|profiles| profiles.get(name))
+                .and_then(toml::Value::as_table)
+                .ok_or_else(|| invalid(format!("Profile {} is not defined within pack config", name)))?;
+
+            for (key, value) in overrides.iter() {
+                table.insert(key.clone(), value.clone());
+            }
+        }
+
+        let mut value = toml::Value::Table(table);
+        expand_value(&mut value)?;
+
+        let config: PackConfig = value.try_into().map_err(config_error)?;
+
+        if config.source.as_os_str().is_empty() || config.destination.as_os_str().is_empty() {
+            return Err(invalid("Pack config must set source and destination".to_string()));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the directory to pack, resolved against the folder of the config file.
+    pub fn source(&self) -> PathBuf {
+        self.base.join(&self.source)
+    }
+
+    /// Returns the Asar archive file to write, resolved against the folder of the config file.
+    pub fn destination(&self) -> PathBuf {
+        self.base.join(&self.destination)
+    }
+
+    /// Returns the PackOptions described by the config.
+    ///
+    /// Returns the options, otherwise Error if a pattern of a transform is invalid.
+    pub fn options(&self) -> Result<PackOptions, asar_error::Error> {
+        let mut options = PackOptions::new()
+            .exclude(self.exclude.iter().cloned())
+            .unpack(self.unpack.iter().cloned())
+            .order(self.order.iter().cloned());
+
+        if let Some(align) = self.align {
+            options = options.align(align);
+        }
+
+        if self.integrity {
+            options = options.integrity();
+        }
+
+        if self.gitignore {
+            options = options.gitignore();
+        }
+
+        if self.asarignore {
+            options = options.asarignore();
+        }
+
+        if self.strip_debug_artifacts {
+            options = options.strip_debug_artifacts();
+        }
+
+        if self.dedupe_hard_links {
+            options = options.dedupe_hard_links();
+        }
+
+        if self.resolve_pnpm {
+            options = options.resolve_pnpm();
+        }
+
+        if !self.transform.is_empty() {
+            let rules: Vec<(Gitignore, TransformRule)> = self
+                .transform
+                .iter()
+                .map(|rule| Ok((matcher(&rule.include)?, rule.clone())))
+                .collect::<Result<_, asar_error::Error>>()?;
+
+            options = options.transform(move |path, mut content| {
+                for (include, rule) in rules.iter() {
+                    if include.matched_path_or_any_parents(path, false).is_ignore() {
+                        content.splice(0..0, rule.prepend.bytes());
+                        content.extend(rule.append.bytes());
+                    }
+                }
+
+                TransformResult::Content(content)
+            });
+        }
+
+        Ok(options)
+    }
+
+    /// Packs the source directory to the destination as described by the config.
+    ///
+    /// ```no_run
+    /// use rust_asar::config::PackConfig;
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// PackConfig::from_file("asar.toml")?.pack()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns (), otherwise Error.
+    pub fn pack(&self) -> Result<(), asar_error::Error> {
+        Asar::open_with(self.source(), &self.options()?)?.pack(self.destination())
+    }
+}
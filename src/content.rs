@@ -1,16 +1,30 @@
 use std::{
     fs::{DirBuilder, File},
-    io::{Read, Write},
+    io::{self, Read, Write},
+    ops::ControlFlow,
     path::{Path, PathBuf},
+    time::Instant,
 };
 
 use positioned_io::ReadAt;
-use serde_json::{Map, Value};
-
-use crate::asar_error::{self, Error};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    asar_error::{self, Error},
+    budget::{self, BUDGET_CHUNK_LEN},
+    cancel, header,
+    hooks::{Hooks, RunContext},
+    kinds,
+    layout::{self, LayoutOptions},
+    locked::LockRetry,
+    metrics::Recorder,
+    names::{self, Platform},
+    pack::PackHooks,
+    scan::ScanHook,
+    trace,
+    transform::Transformed,
+};
 
-/// The maximum size of a file within an asar archive.
-const MAX_SAFE_INTEGER: u64 = 9007199254740991; //for compatability with Electron's Asar library
 
 /// Content enum keeps track of an asar file's internal structure, represented by
 /// Files, Folders, and Home (the starting directory) for an Asar archive.
@@ -41,7 +55,6 @@ const MAX_SAFE_INTEGER: u64 = 9007199254740991; //for compatability with Electro
 /// 
 /// - full_file_path (PathBuf):  The full path of a file that will be added to Asar archive file
 /// 
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Content {
     File(PathBuf, u64, u64),             // (name, offset, size)
@@ -60,10 +73,15 @@ impl Content {
     /// Do not use with opened folder.
     ///
     /// Returns instantiated Content enum, otherwise Error.
-
     pub fn new_json(header: Value) -> Result<Content, asar_error::Error> {
-        if let Value::Object(item) = header {
-            Ok(lookahead("", &item)?)
+        if let Value::Object(mut item) = header {
+            // moved out rather than cloned, as the header of a large archive is large
+            match item.remove("files") {
+                Some(Value::Object(dir)) => Ok(Content::Home(dir)),
+                _ => Err(Error::ParseHeaderError(
+                    "'files' not found in Home directory".to_string(),
+                )),
+            }
         } else {
             Err(Error::UnknownContentType(
                 "Expected Map<String, Value> for new content type".to_string(),
@@ -84,7 +102,6 @@ impl Content {
     /// Do not use with opened Asar archive file.
     ///
     /// Returns the List varient of the Content enum.
-
     pub fn new_list(list: Vec<(PathBuf, u64)>) -> Content {
         Content::List(list)
     }
@@ -92,29 +109,164 @@ impl Content {
 
     /// Returns a vector of PathBufs representing all files and folders within Asar archive,
     /// otherwise an Error.
-
     pub fn paths_to_vec(&self) -> Result<Vec<PathBuf>, asar_error::Error> {
-        let mut vec: Vec<PathBuf> = Vec::new(); //problematic for concurrency
+        struct Paths(Vec<PathBuf>);
 
-        match self {
-            Content::Home(dir) => {
-                for (item_name, item_content) in dir.into_iter() {
-                    //item_name
+        impl Visitor for Paths {
+            type Break = ();
 
-                    if let Value::Object(item) = item_content {
-                        let next_content = lookahead(item_name, item)?;
-                        paths_to_vec_aux(&next_content, Path::new(""), &mut vec)?;
-                    }
-                }
+            fn visit_folder(&mut self, path: &Path) -> ControlFlow<()> {
+                self.0.push(path.to_path_buf());
+                ControlFlow::Continue(())
+            }
+
+            fn visit_file(&mut self, path: &Path, _offset: u64, _size: u64) -> ControlFlow<()> {
+                self.0.push(path.to_path_buf());
+                ControlFlow::Continue(())
+            }
+        }
+
+        self.expect_home()?;
+
+        let mut paths = Paths(Vec::new());
+        let _ = self.visit(&mut paths)?;
+
+        Ok(paths.0)
+    }
+
+
+    /// Returns a vector of tuples `(full_file_path, offset, size)` of type `(PathBuf, u64, u64)`
+    /// representing every file (folders excluded) within Asar archive, otherwise an Error.
+    ///
+    /// Files are listed in the same order as `paths_to_vec`.
+    pub fn files_to_vec(&self) -> Result<Vec<(PathBuf, u64, u64)>, asar_error::Error> {
+        struct Files(Vec<(PathBuf, u64, u64)>);
+
+        impl Visitor for Files {
+            type Break = ();
+
+            fn visit_file(&mut self, path: &Path, offset: u64, size: u64) -> ControlFlow<()> {
+                self.0.push((path.to_path_buf(), offset, size));
+                ControlFlow::Continue(())
             }
-            _ => {
+        }
+
+        self.expect_home()?;
+
+        let mut files = Files(Vec::new());
+        let _ = self.visit(&mut files)?;
+
+        Ok(files.0)
+    }
+
+
+    /// Walks every file and folder of the Content once, depth-first in header order with folders
+    /// visited before their contents, calling the provided Visitor with full paths.
+    ///
+    /// The walk stops as soon as the Visitor returns `ControlFlow::Break`, so consumers can collect
+    /// custom data (or find an entry) in a single pass.
+    ///
+    /// ```no_run
+    /// use std::{ops::ControlFlow, path::{Path, PathBuf}};
+    /// use rust_asar::{asar::Asar, content::Visitor};
+    ///
+    /// // finds the first file larger than 1 MiB
+    /// struct FirstLarge;
+    ///
+    /// impl Visitor for FirstLarge {
+    ///     type Break = PathBuf;
+    ///
+    ///     fn visit_file(&mut self, path: &Path, _offset: u64, size: u64) -> ControlFlow<PathBuf> {
+    ///         if size > 1024 * 1024 {
+    ///             return ControlFlow::Break(path.to_path_buf());
+    ///         }
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let asar = Asar::open("app.asar")?;
+    ///
+    /// if let ControlFlow::Break(path) = asar.content.visit(&mut FirstLarge)? {
+    ///     println!("{}", path.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns the ControlFlow of the walk, otherwise Error if the header is malformed or Content is
+    /// Content::List.
+    pub fn visit<B>(&self, visitor: &mut dyn Visitor<Break = B>) -> Result<ControlFlow<B>, asar_error::Error> {
+        visit_aux(self, Path::new(""), visitor)
+    }
+
+    /// Serializes the Content back to an asar-compliant header (JSON value), recomputing the offset
+    /// of every file so they are laid out back to back in header order.
+    ///
+    /// Fields other than the offset (e.g. `executable`, `integrity`) are kept, as are links and
+    /// unpacked files. A Folder or File is serialized as the only entry of the home directory.
+    ///
+    /// > The recomputed offsets no longer match the body of the archive the Content was read from;
+    /// > `Asar::pack` rewrites the body to match when repacking an opened Asar archive file.
+    ///
+    /// Returns the header, otherwise Error if Content is Content::List (which holds filesystem paths,
+    /// see `Asar::gen_header_from_dir`).
+    pub fn to_header_value(&self) -> Result<Value, asar_error::Error> {
+        Ok(self.to_header_layout(1)?.0)
+    }
+
+    // to_header_value, aligning every file to `align`, also returning the layout of every file as
+    // `(full_file_path, previous_offset, offset, size)` in header order.
+    pub(crate) fn to_header_layout(&self, align: u64) -> Result<(Value, Layout), asar_error::Error> {
+        let files = match self {
+            Content::Home(dir) => dir.clone(),
+            Content::Folder(name, dir) => {
+                let mut files = Map::new();
+                files.insert(name.to_string_lossy().into_owned(), json!({ "files": dir }));
+                files
+            }
+            Content::File(name, offset, size) => {
+                let mut files = Map::new();
+                files.insert(name.to_string_lossy().into_owned(), json!({ "size": size, "offset": offset.to_string() }));
+                files
+            }
+            Content::List(_) => {
                 return Err(asar_error::Error::UnknownContentType(
-                    "Unexpected Content Type: expected Content::Home".to_string(),
+                    "Unexpected Content Type: Content::List holds filesystem paths".to_string(),
                 ))
             }
+        };
+
+        let mut header = json!({ "files": files });
+
+        let previous: Vec<(PathBuf, u64, u64)> = header::files::<io::Error>(&header)?
+            .into_iter()
+            .map(|(path, offset, size)| (PathBuf::from(path), offset, size))
+            .collect();
+
+        let entries: Vec<(PathBuf, u64)> = previous.iter().map(|(path, _, size)| (path.clone(), *size)).collect();
+        let options = LayoutOptions { align, ..LayoutOptions::default() };
+
+        let mut layout = Layout::new();
+
+        for placement in layout::assign_offsets(&entries, &options) {
+            if let (Some(offset), Some(entry)) = (placement.offset, entry_mut(&mut header, &placement.path)) {
+                entry.insert("offset".to_string(), Value::String(offset.to_string()));
+                layout.push((placement.path, previous[placement.index].1, offset, placement.size));
+            }
         }
 
-        Ok(vec)
+        Ok((header, layout))
+    }
+
+    // Returns Error unless Content is Content::Home.
+    fn expect_home(&self) -> Result<(), asar_error::Error> {
+        match self {
+            Content::Home(_) => Ok(()),
+            _ => Err(asar_error::Error::UnknownContentType(
+                "Unexpected Content Type: expected Content::Home".to_string(),
+            )),
+        }
     }
     
 
@@ -127,10 +279,10 @@ impl Content {
     /// - start: the offset at which the file content start within Asar archive file
     ///
     /// > The Asar archive file must be passed in the `file` parameter,
-    /// otherwise unintended behavior may occur.
+    /// > otherwise unintended behavior may occur.
     ///
-    /// Returns (), otherwise Error.
-
+    /// Returns (), otherwise Error, also if a name is illegal on the current platform (e.g. `..`,
+    /// see `names::illegal_reason`), so nothing is written outside of base_path.
     pub fn asar_to_dir<P: AsRef<Path>>(
         &self,
         base_path: P,
@@ -144,6 +296,10 @@ impl Content {
             Content::Home(dir) => {
                 for (name, value) in dir.iter() {
                     if let Value::Object(content) = value {
+                        if is_external(content) {
+                            continue;
+                        }
+
                         //cast
                         DirBuilder::new().recursive(true).create(base_path)?; //Create parent directory
                         lookahead(name, content)?.asar_to_dir(base_path, file, start)?;
@@ -155,11 +311,15 @@ impl Content {
 
             // Create folder
             Content::Folder(name, dir) => {
-                let path = base_path.join(name);
+                let path = checked_join(base_path, name)?;
                 DirBuilder::new().recursive(true).create(&path)?; //create folder
 
                 for (name, value) in dir.iter() {
                     if let Value::Object(content) = value {
+                        if is_external(content) {
+                            continue;
+                        }
+
                         lookahead(name, content)?.asar_to_dir(path.as_path(), file, start)?;
                     }
                 }
@@ -169,7 +329,7 @@ impl Content {
 
             //create file
             Content::File(name, offset, size) => {
-                let path = base_path.join(name);
+                let path = checked_join(base_path, name)?;
 
                 let mut file_as_vec: Vec<u8> = vec![0; *size as usize]; //init vec of bytes for file
                                                                         //io.read_exact_at(start + offset, &mut file_as_vec)?;
@@ -178,12 +338,14 @@ impl Content {
                 let mut file = File::create(&path)?;
                 file.write_all(&file_as_vec)?; //write file to fs
 
+                trace::event!(TRACE, path = %path.display(), size = *size, "extracted file");
+
                 Ok(())
             }
 
-            _ => Err(Error::UnknownContentType(format!(
-                "Asar archive file must be src_path"
-            ))),
+            _ => Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            )),
         }
     }
 
@@ -194,35 +356,150 @@ impl Content {
     /// 
     /// Takes one argument of type `&mut File`, which must be the Asar archive file in creation.
     /// > The Asar archive file must have its header written prior to this function call, 
-    /// as only files are concatenated.
+    /// > as only files are concatenated.
     /// 
     /// Function will fail if Content is instantiated as an Archive file.
     /// 
-
     pub fn dir_to_asar(&self, asar: &mut File) -> Result<(), asar_error::Error> {
+        self.dir_to_asar_aligned(asar, 1)
+    }
+
+
+    /// Concatenates all files of a directory to the Asar archive file provided, as does `dir_to_asar`,
+    /// padding with zeroed bytes so each file starts at a multiple of `align` bytes from the first.
+    /// 
+    /// The offsets within the header must have been aligned the same way (see `PackOptions::align`).
+    pub fn dir_to_asar_aligned(&self, asar: &mut File, align: u64) -> Result<(), asar_error::Error> {
+        let hooks = PackHooks {
+            scanner: &ScanHook::default(),
+            transformed: &Transformed::new(),
+            root: Path::new(""),
+            retry: LockRetry::default(),
+            entries: &Hooks::default(),
+            run: &RunContext::default(),
+        };
+
+        self.dir_to_asar_recorded(asar, align, &Recorder::default(), &hooks)?;
+
+        Ok(())
+    }
+
+    // dir_to_asar_aligned, reporting each file written to the recorder, checking the cancel
+    // token of the run (if any) between and within files, and applying the hooks to each file.
+    //
+    // Files the hooks skip are not written, the files following them moved back. Returns the
+    // indices within the list of the skipped files.
+    pub(crate) fn dir_to_asar_recorded<W: Write>(
+        &self,
+        asar: &mut W,
+        align: u64,
+        recorder: &Recorder,
+        hooks: &PackHooks,
+    ) -> Result<Vec<usize>, asar_error::Error> {
 
         if let Content::List(paths) = &self {
+            let cancel = hooks.run.cancel;
+            let mut offset: u64 = 0;
+            let mut skipped: Vec<usize> = Vec::new();
+
+            for (index, (path, size)) in paths.iter().enumerate() {
+                let started = Instant::now();
+
+                let relative = path.strip_prefix(hooks.root).unwrap_or(path);
+
+                // with hooks that retry or skip, the file is held while read, so a skipped file
+                // writes nothing
+                let holds = hooks.entries.recovers(hooks.run) && !hooks.transformed.contains_key(path);
 
-            for (path, size) in paths {
-                let mut buf: Vec<u8> = vec![0; *size as usize];
-                
-                {
-                    let mut file = File::open(path)?;
+                // a single lease per file covers its buffers, as a second could wait on the first
+                let chunk = (*size).min(BUDGET_CHUNK_LEN as u64);
+                let _lease = hooks.run.budget.as_ref().map(|budget| match holds {
+                    true => budget.acquire(*size + chunk),
+                    false => budget.acquire(chunk),
+                });
 
-                    file.read(&mut buf)?;
+                if hooks.entries.is_enabled(hooks.run) {
+                    hooks.entries.before(relative);
                 }
 
-                //write to asar...
-                
-                asar.write_all(&buf)?;
+                let held: Option<Vec<u8>> = match holds {
+                    true => {
+                        let content = hooks.entries.attempt(hooks.run, relative, || {
+                            let mut buf: Vec<u8> = vec![0; *size as usize];
+                            hooks.retry.open(path)?.read_exact(&mut buf)?;
+                            Ok(buf)
+                        })?;
+
+                        if content.is_none() {
+                            skipped.push(index);
+                            continue;
+                        }
+
+                        content
+                    }
+                    false => None,
+                };
+
+                let padding = align_up(offset, align) - offset;
+                io::copy(&mut io::repeat(0).take(padding), asar)?;
+                offset += padding + size;
+
+                if let Some(content) = hooks.transformed.get(path).or(held.as_ref()) {
+                    hooks.scanner.copy(content.as_slice(), asar, *size, relative, cancel)?;
+                } else if hooks.scanner.is_enabled() {
+                    hooks.scanner.copy(hooks.retry.open(path)?, asar, *size, relative, cancel)?;
+                } else if let Some(token) = cancel {
+                    cancel::copy_cancellable(hooks.retry.open(path)?, asar, *size, token)?;
+                } else if hooks.run.budget.is_some() {
+                    budget::stream(hooks.retry.open(path)?, asar, *size)?;
+                } else {
+                    let mut buf: Vec<u8> = vec![0; *size as usize];
+
+                    {
+                        let mut file = hooks.retry.open(path)?;
+
+                        file.read_exact(&mut buf)?;
+                    }
+
+                    //write to asar...
+
+                    asar.write_all(&buf)?;
+                }
+
+                recorder.file_written(path, *size, started);
+
+                hooks.entries.after(hooks.run, relative, *size);
+
+                trace::event!(TRACE, path = %path.display(), size = *size, "packed file");
             }
 
-            return Ok(());
+            return Ok(skipped);
         }
 
-        Err(Error::UnknownContentType(format!(
-            "Folder must be src_path, Asar archive file found"
-        )))
+        Err(Error::UnknownContentType(
+            "Folder must be src_path, Asar archive file found".to_string(),
+        ))
+    }
+
+
+    /// Returns the contents of the folder at the provided full path without cloning the header,
+    /// where an empty path refers to the home directory.
+    ///
+    /// Returns `None` if the path is not a folder, or if Content is not Content::Home.
+    pub fn folder_contents<P: AsRef<Path>>(&self, path: P) -> Option<&Map<String, Value>> {
+        let mut dir = match self {
+            Content::Home(dir) => dir,
+            _ => return None,
+        };
+
+        for name in path.as_ref().iter() {
+            dir = dir
+                .get(name.to_str()?)?
+                .get("files")?
+                .as_object()?;
+        }
+
+        Some(dir)
     }
 
 
@@ -234,7 +511,6 @@ impl Content {
     ///
     /// Returns the Content enum of the `path` if found, otherwise `None`.
     /// > All Content varients except for Content::List are valid to be returned.
-
     pub fn find<P>(&self, path: P) -> Option<Content>
     where
         P: AsRef<Path>,
@@ -246,9 +522,9 @@ impl Content {
         {
             match content {
                 Content::Home(dir) => {
-                    if let None = path.as_ref().file_stem() {
+                    if path.as_ref().file_stem().is_none() {
                         // path is home
-                        return Some(content.clone());
+                        Some(content.clone())
                         //return Some(Content::Home(dir.clone()));
                     } else {
                         // iterate through home directory
@@ -258,7 +534,7 @@ impl Content {
                                 // check if item is correct
                                 if let Value::Object(item) = object {
                                     return find_aux(
-                                        &lookahead(&name, &item).unwrap(),
+                                        &lookahead(name, item).ok()?,
                                         path,
                                         curr_path,
                                     );
@@ -266,13 +542,13 @@ impl Content {
                             }
                         }
 
-                        return None;
+                        None
                     }
                 }
 
                 Content::File(name, _, _) => {
                     if path.as_ref().eq(curr_path.join(name).as_path()) {
-                        return Some(content.clone());
+                        Some(content.clone())
                         //return Some(Content::File(*name, *offset, *size));
                     } else {
                         None
@@ -291,7 +567,7 @@ impl Content {
                         if path.as_ref().starts_with(curr_path.join(name)) {
                             if let Value::Object(item) = object {
                                 return find_aux(
-                                    &lookahead(&name, &item).unwrap(),
+                                    &lookahead(name, item).ok()?,
                                     path,
                                     curr_path.as_path(),
                                 );
@@ -310,11 +586,117 @@ impl Content {
     }
 }
 
+// Returns the object of the entry at the full path within a header.
+pub(crate) fn entry_mut<'a>(header: &'a mut Value, path: &Path) -> Option<&'a mut Map<String, Value>> {
+    let mut entry = header;
+
+    for name in path.iter() {
+        entry = entry.get_mut("files")?.get_mut(name.to_str()?)?;
+    }
+
+    entry.as_object_mut()
+}
+
+// The files of a regenerated header as `(full_file_path, previous_offset, offset, size)`.
+pub(crate) type Layout = Vec<(PathBuf, u64, u64, u64)>;
+
+/// Rounds `offset` up to the next multiple of `align` (see `header::align_up`).
+pub use crate::header::align_up;
+
+/// Inserts an entry (file object) into a header at the provided full path, creating
+/// any missing folders along the way.
+///
+/// The parameter `header` is the root header object, i.e. `{"files": {...}}`.
+///
+/// Returns (), otherwise Error if an entry already exists at the path, or a file already exists
+/// where a folder is expected.
+pub fn insert_path<P: AsRef<Path>>(
+    header: &mut Map<String, Value>,
+    path: P,
+    entry: Value,
+) -> Result<(), asar_error::Error> {
+    let path = path.as_ref();
+    let mut dir = header;
+
+    let names = path
+        .iter()
+        .map(|name| name.to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+
+    if let Some((file_name, folders)) = names.split_last() {
+        for name in folders {
+            let files = dir
+                .entry("files")
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            dir = match files
+                .as_object_mut()
+                .and_then(|files| files.entry(name.clone()).or_insert_with(|| json!({ "files": {} })).as_object_mut())
+            {
+                Some(folder) if !folder.contains_key("offset") => folder,
+                _ => {
+                    return Err(Error::ParseHeaderError(format!(
+                        "Expected folder at {} while inserting {}",
+                        name,
+                        path.display()
+                    )))
+                }
+            };
+        }
+
+        if let Some(files) = dir
+            .entry("files")
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+        {
+            if files.contains_key(file_name) {
+                return Err(Error::ParseHeaderError(format!(
+                    "Duplicate entry: {}",
+                    path.display()
+                )));
+            }
+
+            files.insert(file_name.clone(), entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns true if the header entry is stored outside of the archive body: a file left
+/// `unpacked` next to the archive, a `link` to another entry, or an entry of a registered custom
+/// kind (see `kinds::register`). Such entries have no Content.
+pub fn is_external(item: &Map<String, Value>) -> bool {
+    item.get("unpacked").and_then(Value::as_bool).unwrap_or(false)
+        || item.contains_key("link")
+        || kinds::find(item).is_some()
+}
+
+// Joins the path of an entry to the folder it is written to, returning Error if any of its names
+// is illegal on the current platform, e.g. `..` or a root, so nothing is written outside of it.
+pub(crate) fn checked_join(folder: &Path, path: &Path) -> Result<PathBuf, asar_error::Error> {
+    let mut joined = folder.to_path_buf();
+
+    for component in path.iter() {
+        let name = component.to_string_lossy();
+
+        if let Some(reason) = names::illegal_reason(&name, Platform::current()) {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Illegal file name {}: {}", path.display(), reason),
+            )));
+        }
+
+        joined.push(component);
+    }
+
+    Ok(joined)
+}
+
 /// Returns the content value based on the paramters given.
 /// Receives a name of type string slice and item of Serde Map,
 /// returning a named Content value after parsing the item.
-
-fn lookahead(
+pub(crate) fn lookahead(
     name: &str,
     item: &serde_json::Map<String, Value>,
 ) -> Result<Content, asar_error::Error> {
@@ -330,91 +712,93 @@ fn lookahead(
     }
 
     //check if "offset" & "size" are included:
-    match (item.get("offset"), item.get("size")) {
-        (Some(Value::String(offset)), Some(Value::Number(size))) => {
-            let size = size.as_u64();
-
-            if size.is_none() {
-                return Err(asar_error::Error::ParseHeaderError(format!(
-                    "size nan for file: {}",
-                    name
-                )));
-            }
-
-            let size = size.unwrap();
-
-            // check for max integer size
-            if size > MAX_SAFE_INTEGER {
-                return Err(asar_error::Error::ParseHeaderError(format!(
-                    "size of {} is greater than MAX_SAFE_INTEGER",
-                    name
-                )));
-            }
-
-            let offset = offset.parse::<u64>()?;
-
-            //Path::new(name).to_path_buf()
-
-            return Ok(Content::File(
-                PathBuf::new().join(name), //experimental
-                offset,
-                size,
-            ));
-        }
+    match header::file_entry::<io::Error>(name, item)? {
+        Some((offset, size)) => Ok(Content::File(
+            PathBuf::new().join(name), //experimental
+            offset,
+            size,
+        )),
 
-        _ => {
+        None => {
             //offset and size not found in lookahead, check for files
 
             if let Some(Value::Object(dir)) = item.get("files") {
-                return Ok(Content::Folder(PathBuf::new().join(name), dir.clone()));
+                Ok(Content::Folder(PathBuf::new().join(name), dir.clone()))
             } else {
-                return Err(asar_error::Error::ParseHeaderError(format!(
+                Err(asar_error::Error::ParseHeaderError(format!(
                     "Error parsing header for entity: {}",
                     name
-                )));
+                )))
             }
         }
     }
 }
 
-fn paths_to_vec_aux(
+/// Callbacks made by `Content::visit` for every file and folder, with their full path.
+///
+/// Every method continues the walk by default, so implementors only handle what they need.
+/// Returning `ControlFlow::Break` stops the walk, which then returns the break value.
+pub trait Visitor {
+    type Break;
+
+    /// Called for every folder, before its contents.
+    fn visit_folder(&mut self, _path: &Path) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for every file, with its offset (relative to the start of the body) and size.
+    fn visit_file(&mut self, _path: &Path, _offset: u64, _size: u64) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+fn visit_aux<B>(
     content: &Content,
     path: &Path,
-    vec: &mut Vec<PathBuf>,
-) -> Result<(), asar_error::Error> {
-    match &content {
+    visitor: &mut dyn Visitor<Break = B>,
+) -> Result<ControlFlow<B>, asar_error::Error> {
+    let dir = match content {
+        Content::Home(dir) => dir,
+
         Content::Folder(name, dir) => {
             let path = path.join(name);
 
-            vec.push(path.clone()); //add folder to vec
-
-            for (name, object) in dir.iter() {
-                if let Value::Object(content) = object {
-                    let next_content = lookahead(name, content)?;
-                    paths_to_vec_aux(&next_content, path.as_path(), vec)?;
-                } else {
-                    return Err(Error::UnknownContentType(
-                        "Uknown content type, expected Object".to_string(),
-                    )); //unecessary i think
-                }
+            if let ControlFlow::Break(value) = visitor.visit_folder(&path) {
+                return Ok(ControlFlow::Break(value));
             }
 
-            Ok(())
+            return visit_dir(dir, &path, visitor);
         }
 
-        Content::File(name, _, _) => {
-            let path = path.join(name);
+        Content::File(name, offset, size) => return Ok(visitor.visit_file(&path.join(name), *offset, *size)),
 
-            vec.push(path.clone());
-
-            Ok(())
+        Content::List(_) => {
+            return Err(asar_error::Error::UnknownContentType(
+                "Unexpected Content Type: Content::List can not be visited".to_string(),
+            ))
         }
+    };
 
-        _ => Err(asar_error::Error::UnknownContentType(
-            "Unexepcted Content Type".to_string(),
-        )),
-    }
-    //Ok(())
+    visit_dir(dir, path, visitor)
 }
 
-// TODO: Implement fold for Content
+// Visits every entry of a folder, whose full path is `path`.
+fn visit_dir<B>(
+    dir: &Map<String, Value>,
+    path: &Path,
+    visitor: &mut dyn Visitor<Break = B>,
+) -> Result<ControlFlow<B>, asar_error::Error> {
+    for (name, object) in dir.iter() {
+        if let Value::Object(item) = object {
+            if is_external(item) {
+                continue;
+            }
+
+            if let ControlFlow::Break(value) = visit_aux(&lookahead(name, item)?, path, visitor)? {
+                return Ok(ControlFlow::Break(value));
+            }
+        }
+    }
+
+    Ok(ControlFlow::Continue(()))
+}
@@ -0,0 +1,221 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+    time::Instant,
+};
+
+use positioned_io::{Cursor, Slice};
+use serde_json::{json, Value};
+
+use crate::{
+    asar::{self, Asar, Source},
+    asar_error::{self, Error},
+    cancel, content,
+    index::EntryRef,
+    layout::{self, LayoutOptions},
+    trace,
+    volume::ArchiveFile,
+    walk::WalkOptions,
+};
+
+// A file recorded by `Asar::copy_entry_from`, streamed from another archive when packing.
+//
+// - path: Full path of the file within the archive being packed
+//
+// - archive: Path of the Asar archive file holding the content
+//
+// - from: Where that archive file is read from (its volumes, or its decrypted plaintext)
+//
+// - source: Offset of the content within that archive file (its start included, and the offset of
+//   a nested archive within it)
+//
+// - offset: Offset of the file within the body of the archive being packed
+//
+// - size: Size of the file
+#[derive(Debug, Clone)]
+pub(crate) struct CopiedEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) archive: PathBuf,
+    pub(crate) from: Source,
+    pub(crate) source: u64,
+    pub(crate) offset: u64,
+    pub(crate) size: u64,
+}
+
+impl Asar {
+
+    /// Copies the file or folder at `src_path` within another opened Asar archive file to
+    /// `dest_path` within this opened directory, without extracting it.
+    ///
+    /// Only the header is updated: `pack` streams the bytes directly from the body of the other
+    /// archive into the new one, after the files of the directory. Folders are copied with
+    /// every file below them.
+    ///
+    /// ```no_run
+    /// use rust_asar::asar::Asar;
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let vendor = Asar::open("vendor.asar")?;
+    /// let mut app = Asar::open("app")?;
+    ///
+    /// app.copy_entry_from(&vendor, "node_modules/lodash", "node_modules/lodash")?;
+    /// app.pack("app.asar")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns (), otherwise Error if `src_path` is not within the other archive, or a file already
+    /// exists at a destination path.
+    pub fn copy_entry_from<S: AsRef<Path>, D: AsRef<Path>>(&mut self, other: &Asar, src_path: S, dest_path: D) -> Result<(), asar_error::Error> {
+        self.ensure_writable()?;
+
+        let src_path = src_path.as_ref();
+        let dest_path = dest_path.as_ref();
+
+        let files: Vec<(PathBuf, u64, u64)> = match other.stat(src_path) {
+            Some(EntryRef::File(offset, size)) => vec![(dest_path.to_path_buf(), offset, size)],
+            Some(EntryRef::Folder) => {
+                let options = WalkOptions {
+                    prefix: Some(src_path.to_path_buf()),
+                    ..WalkOptions::default()
+                };
+
+                other
+                    .walk_with(&options)?
+                    .into_iter()
+                    .filter_map(|entry| match entry.kind {
+                        EntryRef::File(offset, size) => {
+                            let relative = entry.path.strip_prefix(src_path).unwrap_or(&entry.path);
+                            Some((dest_path.join(relative), offset, size))
+                        }
+                        EntryRef::Folder => None,
+                    })
+                    .collect()
+            }
+            None => {
+                return Err(Error::UnknownContentType(format!(
+                    "Not found within {}: {}",
+                    other.src_path.display(),
+                    src_path.display()
+                )))
+            }
+        };
+
+        // copies are laid out after the current body, which is aligned first
+        let align = self.align();
+        let base = content::align_up(self.body_len(), align);
+
+        let header = match &mut self.header {
+            Some(Value::Object(header)) => header,
+            _ => {
+                return Err(Error::UnknownContentType(
+                    "Entries can only be copied into an opened directory".to_string(),
+                ))
+            }
+        };
+
+        for (path, _, _) in files.iter() {
+            if header_contains(header, path) {
+                return Err(Error::ParseHeaderError(format!("Duplicate entry: {}", path.display())));
+            }
+        }
+
+        let entries: Vec<(PathBuf, u64)> = files.iter().map(|(path, _, size)| (path.clone(), *size)).collect();
+        let options = LayoutOptions { align, ..LayoutOptions::default() };
+
+        for placement in layout::assign_offsets(&entries, &options) {
+            let offset = base + placement.offset.unwrap_or_default();
+
+            content::insert_path(header, &placement.path, json!({ "size": placement.size, "offset": offset.to_string() }))?;
+
+            self.copies.push(CopiedEntry {
+                path: placement.path,
+                archive: other.src_path.clone(),
+                from: other.source.outermost(),
+                source: other.base() + other.start + files[placement.index].1,
+                offset,
+                size: placement.size,
+            });
+        }
+
+        // the header grew, so the body starts later
+        let header_len = self.header.as_ref().map_or(Ok(0), asar::json_len)?;
+        self.start = content::align_up(header_len + 16, self.align());
+
+        Ok(())
+    }
+
+    // Returns the length of the body written by `pack`: the files of the directory, followed by
+    // every copied entry.
+    pub(crate) fn body_len(&self) -> u64 {
+        self.copies
+            .last()
+            .map_or(self.listed_len(), |copy| copy.offset + copy.size)
+    }
+
+    // Returns the length of the body holding the files of an opened directory, before any copies.
+    fn listed_len(&self) -> u64 {
+        match &self.content {
+            content::Content::List(list) => list
+                .iter()
+                .fold(0, |offset, (_, size)| content::align_up(offset, self.align()) + size),
+            _ => 0,
+        }
+    }
+
+    // Writes every copied entry to the archive being packed, after the files of the directory.
+    pub(crate) fn write_copies<W: Write>(&self, asar: &mut W, cancel: Option<&AtomicBool>) -> Result<(), asar_error::Error> {
+        let mut written = self.listed_len();
+        let mut archives: HashMap<&Path, ArchiveFile> = HashMap::new();
+
+        for copy in self.copies.iter() {
+            io::copy(&mut io::repeat(0).take(copy.offset - written), asar)?;
+
+            if !archives.contains_key(copy.archive.as_path()) {
+                archives.insert(&copy.archive, copy.from.open(&copy.archive)?);
+            }
+
+            let started = Instant::now();
+            let reader = Cursor::new(Slice::new(&archives[copy.archive.as_path()], copy.source, Some(copy.size)));
+
+            match cancel {
+                _ if self.options.scanner().is_enabled() => self.options.scanner().copy(reader, asar, copy.size, &copy.path, cancel)?,
+                Some(token) => cancel::copy_cancellable(reader, asar, copy.size, token)?,
+                None => {
+                    if io::copy(&mut reader.take(copy.size), asar)? != copy.size {
+                        return Err(Error::IoError(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!("{} is truncated within {}", copy.path.display(), copy.archive.display()),
+                        )));
+                    }
+                }
+            }
+
+            self.metrics.file_written(&copy.path, copy.size, started);
+            trace::event!(TRACE, path = %copy.path.display(), size = copy.size, "copied file");
+
+            written = copy.offset + copy.size;
+        }
+
+        Ok(())
+    }
+}
+
+// Returns true if anything exists at the full path within a header.
+fn header_contains(header: &serde_json::Map<String, Value>, path: &Path) -> bool {
+    let mut entry = header;
+
+    for name in path.iter() {
+        match name
+            .to_str()
+            .and_then(|name| entry.get("files")?.get(name)?.as_object())
+        {
+            Some(next) => entry = next,
+            None => return false,
+        }
+    }
+
+    true
+}
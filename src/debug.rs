@@ -0,0 +1,117 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::{asar::Asar, asar_error};
+
+/// The kind of a debug artifact found by `Asar::find_debug_artifacts`.
+///
+/// - SourceMap: A source map file (`.map`)
+///
+/// - Pdb: A Windows program database (`.pdb`)
+///
+/// - Dsym: A file within a macOS debug symbol bundle (`.dSYM`)
+///
+/// - SourceMappingUrl (url): A script or stylesheet referencing a source map through a
+///   `sourceMappingURL` comment, holding the referenced URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactKind {
+    SourceMap,
+    Pdb,
+    Dsym,
+    SourceMappingUrl(String),
+}
+
+/// A debug artifact within an archive, as listed by `Asar::find_debug_artifacts`.
+///
+/// - path: Full path of the file within the archive
+///
+/// - kind: The kind of artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugArtifact {
+    pub path: PathBuf,
+    pub kind: ArtifactKind,
+}
+
+/// Returns the kind of debug artifact the file at the provided path is, judging by its path alone,
+/// or `None` if it is not one (see `PackOptions::strip_debug_artifacts`).
+pub fn artifact_kind<P: AsRef<Path>>(path: P) -> Option<ArtifactKind> {
+    let path = path.as_ref();
+
+    let in_dsym = path.parent().is_some_and(|parent| {
+        parent
+            .iter()
+            .any(|name| Path::new(name).extension().is_some_and(|ext| ext.eq_ignore_ascii_case("dSYM")))
+    });
+
+    if in_dsym {
+        return Some(ArtifactKind::Dsym);
+    }
+
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "map" => Some(ArtifactKind::SourceMap),
+        "pdb" => Some(ArtifactKind::Pdb),
+        _ => None,
+    }
+}
+
+// Returns true if the file may hold a sourceMappingURL comment.
+fn is_script(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "js" | "mjs" | "cjs" | "css"))
+}
+
+// Returns the URL of the last sourceMappingURL comment (`//# sourceMappingURL=...`, its legacy
+// `//@` form, or `/*# ... */` in stylesheets) within the content, if any.
+fn source_mapping_url(content: &str) -> Option<String> {
+    content.lines().rev().find_map(|line| {
+        let line = line.trim();
+        let comment = line
+            .strip_prefix("//# ")
+            .or_else(|| line.strip_prefix("//@ "))
+            .or_else(|| line.strip_prefix("/*# ").map(|rest| rest.trim_end_matches("*/").trim_end()))?;
+
+        comment
+            .strip_prefix("sourceMappingURL=")
+            .map(|url| url.trim().to_string())
+    })
+}
+
+impl Asar {
+
+    /// Finds the debug artifacts within an opened Asar archive file, which are commonly stripped
+    /// from release builds: source maps, program databases, debug symbol bundles, and scripts or
+    /// stylesheets referencing a source map through a `sourceMappingURL` comment.
+    ///
+    /// Only scripts (`.js`, `.mjs`, `.cjs`) and stylesheets (`.css`) are read.
+    ///
+    /// Returns the artifacts found by path, then the scripts referencing a source map, each in
+    /// header order, otherwise Error if a file can not be read.
+    pub fn find_debug_artifacts(&self) -> Result<Vec<DebugArtifact>, asar_error::Error> {
+        let mut artifacts: Vec<DebugArtifact> = Vec::new();
+
+        for (path, _, _) in self.content.files_to_vec()? {
+            if let Some(kind) = artifact_kind(&path) {
+                artifacts.push(DebugArtifact { path, kind });
+            }
+        }
+
+        self.extract_each(is_script, |path, reader| {
+            let mut content: Vec<u8> = Vec::with_capacity(reader.limit() as usize);
+            reader.read_to_end(&mut content)?;
+
+            if let Some(url) = source_mapping_url(&String::from_utf8_lossy(&content)) {
+                artifacts.push(DebugArtifact {
+                    path: path.to_path_buf(),
+                    kind: ArtifactKind::SourceMappingUrl(url),
+                });
+            }
+
+            Ok(())
+        })?;
+
+        Ok(artifacts)
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use positioned_io::ReadAt;
+
+use crate::{asar::Asar, asar_error};
+
+/// Number of leading bytes read from each file to classify it.
+const SIGNATURE_LEN: u64 = 128;
+
+/// FileType enum classifies a file within an Asar archive by its leading bytes (magic number).
+///
+/// - Elf, Pe, MachO, Wasm: native or compiled binaries
+///
+/// - Png, Jpeg, Gif, Pdf, Zip, Gzip: common binary formats
+///
+/// - Script (interpreter): a text file starting with a shebang line, such as `#!/usr/bin/env node`
+///
+/// - Text: valid UTF-8 without null bytes
+///
+/// - Unknown: anything else, including empty files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileType {
+    Elf,
+    Pe,
+    MachO,
+    Wasm,
+    Png,
+    Jpeg,
+    Gif,
+    Pdf,
+    Zip,
+    Gzip,
+    Script(String),
+    Text,
+    Unknown,
+}
+
+impl FileType {
+
+    /// Classifies the provided leading bytes of a file.
+    pub fn from_bytes(bytes: &[u8]) -> FileType {
+        match bytes {
+            [0x7F, b'E', b'L', b'F', ..] => FileType::Elf,
+            [b'M', b'Z', ..] => FileType::Pe,
+            [0xFE, 0xED, 0xFA, 0xCE | 0xCF, ..]
+            | [0xCE | 0xCF, 0xFA, 0xED, 0xFE, ..]
+            | [0xCA, 0xFE, 0xBA, 0xBE, ..] => FileType::MachO,
+            [0x00, b'a', b's', b'm', ..] => FileType::Wasm,
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => FileType::Png,
+            [0xFF, 0xD8, 0xFF, ..] => FileType::Jpeg,
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => FileType::Gif,
+            [b'%', b'P', b'D', b'F', ..] => FileType::Pdf,
+            [b'P', b'K', 0x03, 0x04, ..] => FileType::Zip,
+            [0x1F, 0x8B, ..] => FileType::Gzip,
+            [b'#', b'!', rest @ ..] => {
+                let line = rest.split(|b| *b == b'\n').next().unwrap_or_default();
+                FileType::Script(String::from_utf8_lossy(line).trim().to_string())
+            }
+            [] => FileType::Unknown,
+            _ => {
+                if !bytes.contains(&0) && is_utf8_prefix(bytes) {
+                    FileType::Text
+                } else {
+                    FileType::Unknown
+                }
+            }
+        }
+    }
+
+    /// Returns true if the file is an executable binary or shared library (ELF, PE, Mach-O).
+    pub fn is_native_binary(&self) -> bool {
+        matches!(self, FileType::Elf | FileType::Pe | FileType::MachO)
+    }
+}
+
+// A truncated read may split a multi-byte character, so only the complete prefix must be valid.
+fn is_utf8_prefix(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+impl Asar {
+
+    /// Reads the first bytes of every file within an opened Asar archive file and classifies it by
+    /// its signature.
+    ///
+    /// Returns a vector of tuples `(full_file_path, file_type)` in archive order, otherwise Error.
+    /// > Useful for policy checks, e.g. `types.iter().any(|(_, t)| t.is_native_binary())`.
+    pub fn detect_types(&self) -> Result<Vec<(PathBuf, FileType)>, asar_error::Error> {
+        let file = self.archive_file()?;
+        let mut result: Vec<(PathBuf, FileType)> = Vec::new();
+
+        for (path, offset, size) in self.content.files_to_vec()? {
+            let mut buf: Vec<u8> = vec![0; size.min(SIGNATURE_LEN) as usize];
+            file.read_exact_at(self.start + offset, &mut buf)?;
+
+            result.push((path, FileType::from_bytes(&buf)));
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use crate::{asar::Asar, asar_error, hash};
+
+/// Files with identical content within an archive, as listed by `Asar::find_duplicates`.
+///
+/// - hash: Lowercase hex digest of the content, by the Hasher of the Asar (see `Asar::set_hasher`)
+///
+/// - size: Size in bytes of each file
+///
+/// - paths: Full paths of the files, sorted
+///
+/// - wasted: Bytes that would be saved by storing the content once. Files already sharing
+///   their offset (deduplicated by the packer) do not count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    pub hash: String,
+    pub size: u64,
+    pub paths: Vec<PathBuf>,
+    pub wasted: u64,
+}
+
+/// The duplicate files within an archive, as returned by `Asar::find_duplicates`.
+///
+/// - groups: Groups of identical files, most wasted bytes first
+///
+/// - wasted: Wasted bytes of every group combined
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub wasted: u64,
+}
+
+impl Asar {
+
+    /// Finds the files with identical content within an opened Asar archive file, without
+    /// repacking it.
+    ///
+    /// Only files sharing their size with another file are hashed, each streamed from the
+    /// archive. Empty files are ignored.
+    ///
+    /// Returns the DuplicateReport, otherwise Error if a directory is open or a file can not be read.
+    pub fn find_duplicates(&self) -> Result<DuplicateReport, asar_error::Error> {
+        let file = self.archive_file()?;
+        let mut by_size: BTreeMap<u64, Vec<(PathBuf, u64)>> = BTreeMap::new();
+
+        for (path, offset, size) in self.content.files_to_vec()? {
+            if size > 0 {
+                by_size.entry(size).or_default().push((path, offset));
+            }
+        }
+
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+
+        for (size, candidates) in by_size.into_iter().filter(|(_, files)| files.len() > 1) {
+            let mut by_hash: BTreeMap<String, Vec<(PathBuf, u64)>> = BTreeMap::new();
+
+            for (path, offset) in candidates {
+                let hash = hash::hex_digest(self.hasher(), self.entry_reader(&file, offset, size))?;
+                by_hash.entry(hash).or_default().push((path, offset));
+            }
+
+            for (hash, files) in by_hash.into_iter().filter(|(_, files)| files.len() > 1) {
+                let offsets: BTreeSet<u64> = files.iter().map(|(_, offset)| *offset).collect();
+                let mut paths: Vec<PathBuf> = files.into_iter().map(|(path, _)| path).collect();
+                paths.sort();
+
+                groups.push(DuplicateGroup {
+                    hash,
+                    size,
+                    paths,
+                    wasted: size * (offsets.len() as u64 - 1),
+                });
+            }
+        }
+
+        groups.sort_by(|a, b| b.wasted.cmp(&a.wasted).then_with(|| a.paths.cmp(&b.paths)));
+
+        Ok(DuplicateReport {
+            wasted: groups.iter().map(|group| group.wasted).sum(),
+            groups,
+        })
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use zeroize::Zeroize;
+
+use crate::{
+    asar::{Asar, Source},
+    asar_error::{self, Error},
+    header,
+    keys::KeyProvider,
+    pack::PackOptions,
+    temp::TempGuard,
+    volume::ArchiveFile,
+};
+
+/// Leading bytes of every encrypted archive (`.asar.enc`), followed by the format version.
+pub const MAGIC: &[u8; 8] = b"RASARENC";
+
+/// Version of the envelope format written by `encrypt_file`.
+pub const VERSION: u8 = 1;
+
+/// Bytes of plaintext within every frame but the last.
+pub const FRAME_SIZE: u32 = 64 * 1024;
+
+const SALT_LEN: usize = 16;
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+// Limits on values read from an envelope, so a crafted header can not exhaust memory.
+const MAX_FRAME_SIZE: u32 = 16 * 1024 * 1024;
+const MAX_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 64;
+
+// magic, version, memory, iterations, parallelism, salt, nonce prefix, frame size
+const HEADER_LEN: usize = 8 + 1 + 4 + 4 + 4 + SALT_LEN + NONCE_PREFIX_LEN + 4;
+
+/// Cost of the Argon2id key derivation of an encrypted archive, recorded within its envelope so it
+/// can be raised later without breaking older archives.
+///
+/// - memory_kib: Memory used in KiB
+///
+/// - iterations: Number of passes over the memory
+///
+/// - parallelism: Number of lanes
+///
+/// The default (19 MiB, 2 passes, 1 lane) follows the OWASP recommendation for Argon2id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+// The envelope header, authenticated as associated data of every frame.
+struct Header {
+    params: KdfParams,
+    salt: [u8; SALT_LEN],
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    frame_size: u32,
+}
+
+impl Header {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(HEADER_LEN);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.params.memory_kib.to_le_bytes());
+        bytes.extend_from_slice(&self.params.iterations.to_le_bytes());
+        bytes.extend_from_slice(&self.params.parallelism.to_le_bytes());
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce_prefix);
+        bytes.extend_from_slice(&self.frame_size.to_le_bytes());
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; HEADER_LEN]) -> Result<Header, asar_error::Error> {
+        if &bytes[..8] != MAGIC {
+            return Err(Error::ParseHeaderError("Not an encrypted Asar archive".to_string()));
+        }
+
+        if bytes[8] != VERSION {
+            return Err(Error::ParseHeaderError(format!("Unsupported envelope version: {}", bytes[8])));
+        }
+
+        let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        let salt_at = 21;
+        let nonce_at = salt_at + SALT_LEN;
+
+        let header = Header {
+            params: KdfParams {
+                memory_kib: u32_at(9),
+                iterations: u32_at(13),
+                parallelism: u32_at(17),
+            },
+            salt: bytes[salt_at..nonce_at].try_into().unwrap_or_default(),
+            nonce_prefix: bytes[nonce_at..nonce_at + NONCE_PREFIX_LEN].try_into().unwrap_or_default(),
+            frame_size: u32_at(nonce_at + NONCE_PREFIX_LEN),
+        };
+
+        if header.frame_size == 0 || header.frame_size > MAX_FRAME_SIZE {
+            return Err(Error::ParseHeaderError(format!("Invalid envelope frame size: {}", header.frame_size)));
+        }
+
+        if header.params.memory_kib > MAX_MEMORY_KIB {
+            return Err(Error::ParseHeaderError(format!(
+                "Envelope key derivation requires too much memory: {} KiB",
+                header.params.memory_kib
+            )));
+        }
+
+        if header.params.iterations > MAX_ITERATIONS {
+            return Err(Error::ParseHeaderError(format!(
+                "Envelope key derivation requires too many iterations: {}",
+                header.params.iterations
+            )));
+        }
+
+        if header.params.parallelism > MAX_PARALLELISM {
+            return Err(Error::ParseHeaderError(format!(
+                "Envelope key derivation requires too many lanes: {}",
+                header.params.parallelism
+            )));
+        }
+
+        Ok(header)
+    }
+
+    // Derives the key of the envelope from the passphrase.
+    fn cipher(&self, passphrase: &[u8]) -> Result<ChaCha20Poly1305, asar_error::Error> {
+        let params = Params::new(
+            self.params.memory_kib,
+            self.params.iterations,
+            self.params.parallelism,
+            Some(KEY_LEN),
+        )
+        .map_err(|err| invalid_input(format!("Invalid key derivation parameters: {}", err)))?;
+
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, &self.salt, &mut key)
+            .map_err(|err| invalid_input(format!("Failed to derive key: {}", err)))?;
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        key.zeroize();
+
+        Ok(cipher)
+    }
+
+    // The nonce of a frame: the random prefix, the frame counter, and whether the frame is the last,
+    // so frames can not be reordered, dropped, or truncated without failing authentication.
+    fn nonce(&self, counter: u32, last: bool) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&counter.to_be_bytes());
+        nonce[11] = last as u8;
+
+        nonce
+    }
+}
+
+fn invalid_input(message: String) -> Error {
+    Error::IoError(io::Error::new(io::ErrorKind::InvalidInput, message))
+}
+
+fn decrypt_failed() -> Error {
+    Error::IoError(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "Failed to decrypt archive: wrong passphrase, or the archive is corrupted or truncated",
+    ))
+}
+
+/// Returns true if the file at the provided path starts with the envelope MAGIC.
+pub fn is_encrypted<P: AsRef<Path>>(path: P) -> io::Result<bool> {
+    let mut magic = [0u8; 8];
+
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == MAGIC),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Encrypts the file at `source` (usually an Asar archive file) into an envelope at `destination`
+/// (`.asar.enc`), keyed by the passphrase through Argon2id with the provided KdfParams.
+///
+/// The content is encrypted with ChaCha20-Poly1305 in frames of `FRAME_SIZE` bytes, each
+/// authenticated along with the envelope header, so tampering, reordering, or truncation is
+/// detected. The destination is written to a temporary file first, so it is replaced only once
+/// encrypted completely.
+///
+/// Returns (), otherwise Error if the source can not be read or the destination written.
+pub fn encrypt_file<S, D>(source: S, destination: D, passphrase: &[u8], params: &KdfParams) -> Result<(), asar_error::Error>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    let destination = destination.as_ref();
+    let mut header = Header {
+        params: *params,
+        salt: [0; SALT_LEN],
+        nonce_prefix: [0; NONCE_PREFIX_LEN],
+        frame_size: FRAME_SIZE,
+    };
+
+    getrandom::fill(&mut header.salt).map_err(|err| Error::IoError(io::Error::other(err.to_string())))?;
+    getrandom::fill(&mut header.nonce_prefix).map_err(|err| Error::IoError(io::Error::other(err.to_string())))?;
+
+    let cipher = header.cipher(passphrase)?;
+    let aad = header.to_bytes();
+
+    let mut reader = BufReader::new(File::open(source)?);
+    let mut guard = TempGuard::new_for(destination, None)?;
+    let mut out = BufWriter::new(guard.file());
+
+    out.write_all(&aad)?;
+
+    let mut frame: Vec<u8> = Vec::with_capacity(FRAME_SIZE as usize);
+    let mut counter: u32 = 0;
+
+    loop {
+        frame.clear();
+        (&mut reader).take(FRAME_SIZE as u64).read_to_end(&mut frame)?;
+
+        // every frame but the last is full; the last (possibly empty) frame is always written
+        let last = frame.len() < FRAME_SIZE as usize;
+        let sealed = cipher
+            .encrypt(&Nonce::from(header.nonce(counter, last)), Payload { msg: &frame, aad: &aad })
+            .map_err(|_| invalid_input("Failed to encrypt frame".to_string()))?;
+
+        out.write_all(&sealed)?;
+
+        if last {
+            break;
+        }
+
+        counter = counter
+            .checked_add(1)
+            .ok_or_else(|| invalid_input("File is too large to encrypt".to_string()))?;
+    }
+
+    out.flush()?;
+    drop(out);
+    guard.persist(destination)?;
+
+    Ok(())
+}
+
+/// Encrypts the file at `source` into an envelope at `destination`, as `encrypt_file` does, with
+/// the passphrase of the destination returned by the KeyProvider.
+pub fn encrypt_file_with<S, D>(source: S, destination: D, keys: &dyn KeyProvider, params: &KdfParams) -> Result<(), asar_error::Error>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    let secret = keys.secret(destination.as_ref())?;
+
+    encrypt_file(source, destination, secret.expose(), params)
+}
+
+/// Decrypts the envelope at `source` (as written by `encrypt_file`) into `destination`.
+///
+/// Returns (), otherwise Error if the passphrase is wrong or the envelope was tampered with or
+/// truncated, in which case nothing is written to the destination.
+pub fn decrypt_file<S, D>(source: S, destination: D, passphrase: &[u8]) -> Result<(), asar_error::Error>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    let destination = destination.as_ref();
+    let mut guard = TempGuard::new_for(destination, None)?;
+
+    decrypt_to(source.as_ref(), BufWriter::new(guard.file()), passphrase)?;
+    guard.persist(destination)?;
+
+    Ok(())
+}
+
+/// Decrypts the envelope at `source` into `destination`, as `decrypt_file` does, with the
+/// passphrase of the source returned by the KeyProvider.
+pub fn decrypt_file_with<S, D>(source: S, destination: D, keys: &dyn KeyProvider) -> Result<(), asar_error::Error>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    let secret = keys.secret(source.as_ref())?;
+
+    decrypt_file(source, destination, secret.expose())
+}
+
+// Decrypts the envelope at the source, writing its plaintext to `out`.
+fn decrypt_to<W: Write>(source: &Path, mut out: W, passphrase: &[u8]) -> Result<(), asar_error::Error> {
+    let mut reader = BufReader::new(File::open(source)?);
+    let mut header_bytes = [0u8; HEADER_LEN];
+
+    reader
+        .read_exact(&mut header_bytes)
+        .map_err(|_| Error::ParseHeaderError("Not an encrypted Asar archive".to_string()))?;
+
+    let header = Header::from_bytes(&header_bytes)?;
+    let cipher = header.cipher(passphrase)?;
+    let sealed_size = header.frame_size as u64 + TAG_LEN as u64;
+
+    let mut sealed: Vec<u8> = Vec::with_capacity(sealed_size as usize);
+    let mut counter: u32 = 0;
+
+    loop {
+        sealed.clear();
+        (&mut reader).take(sealed_size).read_to_end(&mut sealed)?;
+
+        let last = (sealed.len() as u64) < sealed_size;
+        let frame = cipher
+            .decrypt(&Nonce::from(header.nonce(counter, last)), Payload { msg: &sealed, aad: &header_bytes })
+            .map_err(|_| decrypt_failed())?;
+
+        out.write_all(&frame)?;
+
+        if last {
+            break;
+        }
+
+        counter = counter.checked_add(1).ok_or_else(decrypt_failed)?;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
+
+impl Asar {
+
+    /// Opens an encrypted Asar archive file (`.asar.enc`, as written by `encrypt_file`) with the
+    /// passphrase, as `open` opens a plain one.
+    ///
+    /// The archive is decrypted into memory, never to disk, and its plaintext is shared by every
+    /// clone of the Asar.
+    ///
+    /// ```no_run
+    /// use rust_asar::{asar::Asar, envelope::{self, KdfParams}};
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// envelope::encrypt_file("resources.asar", "resources.asar.enc", b"passphrase", &KdfParams::default())?;
+    ///
+    /// let asar = Asar::open_encrypted("resources.asar.enc", b"passphrase")?;
+    /// let config = asar.get_file("config.json");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns instantiated struct, otherwise Error if the passphrase is wrong, or the envelope was
+    /// tampered with or does not hold an Asar archive.
+    pub fn open_encrypted<P: AsRef<Path>>(path: P, passphrase: &[u8]) -> Result<Asar, asar_error::Error> {
+        let mut plaintext: Vec<u8> = Vec::new();
+        decrypt_to(path.as_ref(), &mut plaintext, passphrase)?;
+
+        let plaintext: Arc<[u8]> = plaintext.into();
+        let (header, start) = header::read_header(&ArchiveFile::Memory(plaintext.clone()))
+            .map_err(|_| Error::ParseHeaderError("Failed to parse archive header, check format".to_string()))?;
+
+        Self::from_archive(path.as_ref(), header, start, Source::Decrypted(plaintext), &PackOptions::default())
+    }
+
+    /// Opens an encrypted Asar archive file, as `open_encrypted` does, with the passphrase of the
+    /// archive returned by the KeyProvider.
+    pub fn open_encrypted_with<P: AsRef<Path>>(path: P, keys: &dyn KeyProvider) -> Result<Asar, asar_error::Error> {
+        let secret = keys.secret(path.as_ref())?;
+
+        Self::open_encrypted(path, secret.expose())
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    fs,
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use serde_json::Value;
+use crate::{asar::Asar, asar_error, hash, index::EntryRef};
+
+/// Most hex characters of the digest kept within an ETag.
+const ETAG_LEN: usize = 32;
+
+impl Asar {
+
+    /// Returns a strong HTTP ETag (quoted, e.g. `"3f2a..."`) for the file at the provided full path
+    /// within an opened Asar archive file, so servers embedding the crate can answer conditional
+    /// GETs (`If-None-Match`) without reading the file.
+    ///
+    /// The tag is derived from the identity of the archive (its size, modification time, and header
+    /// size), and the offset, size, and `integrity` hash (if recorded) of the entry, so it is
+    /// stable across process restarts and changes whenever the archive is rewritten.
+    ///
+    /// Returns `None` if the path is not a file within the archive, otherwise Error if the archive
+    /// can not be read.
+    pub fn etag<P: AsRef<Path>>(&self, path: P) -> Result<Option<String>, asar_error::Error> {
+        let path = path.as_ref();
+
+        if self.src_path.is_dir() {
+            return Err(asar_error::Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        let Some(EntryRef::File(offset, size)) = self.stat(path) else {
+            return Ok(None);
+        };
+
+        let metadata = fs::metadata(&self.src_path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos());
+
+        let integrity = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| {
+                self.content
+                    .folder_contents(path.parent().unwrap_or(Path::new("")))?
+                    .get(name)?
+                    .pointer("/integrity/hash")
+                    .and_then(Value::as_str)
+            })
+            .unwrap_or("");
+
+        let identity = format!(
+            "{}\0{}\0{}\0{}\0{}\0{}\0{}",
+            metadata.len(),
+            modified,
+            self.start,
+            path.to_string_lossy(),
+            offset,
+            size,
+            integrity
+        );
+
+        let digest = hash::hex_digest(self.hasher(), identity.as_bytes())?;
+
+        Ok(Some(format!("\"{}\"", &digest[..digest.len().min(ETAG_LEN)])))
+    }
+}
@@ -0,0 +1,761 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    fs::{self, DirBuilder, File, FileTimes},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    budget::{self, MemoryBudget, BUDGET_CHUNK_LEN},
+    cancel,
+    hooks::{Hooks, RunContext},
+    index::EntryRef,
+    metrics::Operation,
+    names::{self, CollisionPolicy, NamePolicy, Platform},
+    retry::RetryPolicy,
+    throttle::{Throttle, Throttled},
+    trace,
+    transform::{TransformResult, Transformer},
+    volume::ArchiveFile,
+};
+
+/// How the modification and access times of extracted files and folders are set (see
+/// `ExtractOptions::timestamps`).
+///
+/// - Now: Left to the time of extraction, as set by the filesystem
+///
+/// - FromArchiveMeta: Set from the `mtime` (seconds since the Unix epoch) recorded within the
+///   custom metadata of each entry (see `Asar::set_entry_meta`); entries without one are left as `Now`
+///
+/// - Fixed: Set to the provided time, e.g. `SystemTime::UNIX_EPOCH` for reproducible extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampPolicy {
+    #[default]
+    Now,
+    FromArchiveMeta,
+    Fixed(SystemTime),
+}
+
+/// Permission bits masked out of extracted files and folders by default (see `ExtractOptions::umask`).
+pub const DEFAULT_UMASK: u32 = 0o022;
+
+// Ranks a file by its full path within the archive (see `ExtractOptions::priority`).
+type Priority = dyn Fn(&Path) -> u32 + Send + Sync;
+
+/// Options used by `Asar::extract_with` when an Asar archive file is extracted.
+///
+/// Built with chained calls starting from `ExtractOptions::new()`:
+///
+/// ```
+/// use rust_asar::{extract::ExtractOptions, transform::TransformResult};
+///
+/// let options = ExtractOptions::new().transform(|path, content| {
+///     if path.ends_with("config.json") {
+///         let config = String::from_utf8_lossy(&content).replace("/opt/app", "/usr/local/app");
+///         TransformResult::Content(config.into_bytes())
+///     } else {
+///         TransformResult::Content(content)
+///     }
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct ExtractOptions {
+    extensions: Option<Vec<String>>,
+    flatten: bool,
+    permissions: bool,
+    ownership: bool,
+    umask: Option<u32>,
+    timestamps: TimestampPolicy,
+    read_only: bool,
+    names: NamePolicy,
+    platform: Option<Platform>,
+    collisions: Option<CollisionPolicy>,
+    transformer: Option<Arc<Transformer>>,
+    hooks: Hooks,
+    retry: Option<RetryPolicy>,
+    priority: Option<Arc<Priority>>,
+    throttle: Option<Arc<Throttle>>,
+    budget: Option<MemoryBudget>,
+}
+
+impl ExtractOptions {
+
+    /// Returns the default options: every file is extracted as is.
+    pub fn new() -> ExtractOptions {
+        ExtractOptions::default()
+    }
+
+    /// Extracts only the files with one of the provided extensions (without the leading dot,
+    /// regardless of case), e.g. `&["js", "json", "html"]` for the code of an application.
+    ///
+    /// Only the folders holding extracted files are created.
+    pub fn extensions<S: AsRef<str>>(mut self, extensions: &[S]) -> ExtractOptions {
+        self.extensions = Some(
+            extensions
+                .iter()
+                .map(|ext| ext.as_ref().trim_start_matches('.').to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Writes every file directly into the destination folder, dropping the folders of the
+    /// archive, e.g. to feed every file to a scanner that does not care about structure.
+    ///
+    /// Files are written in path order; a file whose name was already taken is suffixed with a
+    /// counter before its extension (`index.js`, `index-1.js`, `index-2.js`, ...).
+    pub fn flatten(mut self, flatten: bool) -> ExtractOptions {
+        self.flatten = flatten;
+        self
+    }
+
+    /// Sets the mode of every extracted file and folder from the archive, rather than leaving
+    /// whatever `File::create` defaults to: `0o777` for executable files (marked `executable`
+    /// within their entries, as done by Electron's packer), `0o666` for other files, and `0o777`
+    /// for folders, each masked by the umask (see `umask`).
+    ///
+    /// Folders are set once every file is extracted. Only applies on Unix.
+    pub fn preserve_permissions(mut self, preserve: bool) -> ExtractOptions {
+        self.permissions = preserve;
+        self
+    }
+
+    /// Sets the permission bits masked out by `preserve_permissions`, `DEFAULT_UMASK` unless set,
+    /// e.g. `0o027` to keep extracted resources private to their group.
+    pub fn umask(mut self, umask: u32) -> ExtractOptions {
+        self.umask = Some(umask & 0o777);
+        self
+    }
+
+    /// Restores the owner and group of every extracted file and folder recorded as `uid` and
+    /// `gid` within its custom metadata (see `Asar::set_entry_meta`), on Unix.
+    ///
+    /// Ownership can only be changed when running privileged (e.g. as an installer), so this is
+    /// best-effort: failures to change it are ignored.
+    pub fn preserve_ownership(mut self, preserve: bool) -> ExtractOptions {
+        self.ownership = preserve;
+        self
+    }
+
+    /// Sets the modification and access times of every extracted file and folder, e.g. to a fixed
+    /// epoch so extracted trees hash the same on every machine.
+    ///
+    /// Folders are set once every file is extracted, where the platform allows opening them.
+    pub fn timestamps(mut self, policy: TimestampPolicy) -> ExtractOptions {
+        self.timestamps = policy;
+        self
+    }
+
+    /// Marks every extracted file read-only once written (after its permissions, see
+    /// `preserve_permissions`), so the extracted resources are treated as immutable. Folders are
+    /// left writable, so the tree can still be removed.
+    ///
+    /// Files marked read-only can not be extracted over; see `set_read_only` to un-mark them first.
+    pub fn read_only(mut self, read_only: bool) -> ExtractOptions {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Sets what happens to entries whose names are illegal on the target platform (see
+    /// `names::illegal_reason`), e.g. `CON` or `aux.js` on Windows. Every name is checked before
+    /// anything is written. Defaults to `NamePolicy::Error`.
+    pub fn illegal_names(mut self, policy: NamePolicy) -> ExtractOptions {
+        self.names = policy;
+        self
+    }
+
+    /// Sets the platform whose filename rules are checked, `Platform::current()` unless set,
+    /// e.g. `Platform::Windows` to check archives on Linux before they ship to Windows.
+    pub fn platform(mut self, platform: Platform) -> ExtractOptions {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Sets what happens to entries whose paths collide once case is folded (`Foo.js` and
+    /// `foo.js`), which would otherwise silently overwrite each other on a case-insensitive
+    /// filesystem. Every path is checked before anything is written.
+    ///
+    /// Unless set, collisions are an Error when the platform (see `platform`) is case-insensitive
+    /// (see `Platform::is_case_insensitive`), and are not checked otherwise.
+    pub fn case_collisions(mut self, policy: CollisionPolicy) -> ExtractOptions {
+        self.collisions = Some(policy);
+        self
+    }
+
+    /// Sets the transformer called on the content of every file before it is written out, e.g.
+    /// to decrypt, decompress, or patch absolute paths within configuration files.
+    ///
+    /// Files are transformed one at a time, each read whole into memory.
+    pub fn transform<F>(mut self, transformer: F) -> ExtractOptions
+    where
+        F: Fn(&Path, Vec<u8>) -> TransformResult + Send + Sync + 'static,
+    {
+        self.transformer = Some(Arc::new(transformer));
+        self
+    }
+
+    /// Sets the Hooks called for every file extracted, e.g. to retry or skip files that can not be
+    /// read or written. A skipped file is removed from the destination.
+    pub fn hooks(mut self, hooks: Hooks) -> ExtractOptions {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Sets how a file is read and written again after a transient IO error (see
+    /// `retry::is_transient`), e.g. on a network filesystem. Errors left once every retry failed go
+    /// to `Hooks::on_error`, aborting by default.
+    pub fn retry(mut self, policy: RetryPolicy) -> ExtractOptions {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Sets the priority of every file, by its full path within the archive: files with a higher
+    /// priority are written first, e.g. so an application can start launching from `package.json`
+    /// and its entry point while the rest of a large archive is still extracted. Folders are
+    /// created before any file, and files of equal priority keep their path order.
+    ///
+    /// ```
+    /// use rust_asar::extract::ExtractOptions;
+    ///
+    /// let options = ExtractOptions::new().priority(|path| match path.to_str() {
+    ///     Some("package.json") | Some("main.js") => 2,
+    ///     _ if path.starts_with("lib") => 1,
+    ///     _ => 0,
+    /// });
+    /// ```
+    pub fn priority<F>(mut self, priority: F) -> ExtractOptions
+    where
+        F: Fn(&Path) -> u32 + Send + Sync + 'static,
+    {
+        self.priority = Some(Arc::new(priority));
+        self
+    }
+
+    /// Limits writing extracted files to about `bytes_per_sec` bytes per second, e.g. so a
+    /// background update does not saturate the disk of the user.
+    ///
+    /// The limit is shared by every extraction using these options (and their clones).
+    pub fn throttle(mut self, bytes_per_sec: u64) -> ExtractOptions {
+        self.throttle = Some(Arc::new(Throttle::new(bytes_per_sec)));
+        self
+    }
+
+    /// Caps the buffers used to read and write extracted files (see MemoryBudget), streaming every
+    /// file rather than reading it whole, unless a transformer is set.
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> ExtractOptions {
+        self.budget = Some(budget);
+        self
+    }
+
+    // The context of a run extracting with these options, with its RetryPolicy, throttle, and budget.
+    pub(crate) fn run_context(&self) -> RunContext<'static> {
+        RunContext {
+            retry: self.retry,
+            throttle: self.throttle.clone(),
+            budget: self.budget.clone(),
+            ..RunContext::default()
+        }
+    }
+
+    // Returns true if folders are created at the destination.
+    fn creates_folders(&self) -> bool {
+        self.extensions.is_none() && !self.flatten
+    }
+
+    // Applies the mode and ownership of the entry (if enabled) to the extracted file or folder.
+    #[cfg(unix)]
+    fn restore(&self, out_path: &Path, entry: Option<&Map<String, Value>>, meta: Option<&Map<String, Value>>, is_dir: bool) -> io::Result<()> {
+        use std::os::unix::fs::{self as unix_fs, PermissionsExt};
+
+        if self.permissions {
+            let executable = is_dir || entry.and_then(|entry| entry.get("executable")).and_then(Value::as_bool) == Some(true);
+            let mode = if executable { 0o777 } else { 0o666 } & !self.umask.unwrap_or(DEFAULT_UMASK);
+
+            fs::set_permissions(out_path, fs::Permissions::from_mode(mode))?;
+        }
+
+        if self.ownership {
+            let id = |key: &str| meta?.get(key)?.as_u64().and_then(|id| u32::try_from(id).ok());
+
+            let (uid, gid) = (id("uid"), id("gid"));
+
+            if uid.is_some() || gid.is_some() {
+                unix_fs::chown(out_path, uid, gid).ok(); // requires privileges
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn restore(&self, _out_path: &Path, _entry: Option<&Map<String, Value>>, _meta: Option<&Map<String, Value>>, _is_dir: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    // Sets the times of the extracted file or folder as selected by the TimestampPolicy.
+    fn set_times(&self, out_path: &Path, meta: Option<&Map<String, Value>>, is_dir: bool) -> io::Result<()> {
+        let time = match self.timestamps {
+            TimestampPolicy::Now => return Ok(()),
+            TimestampPolicy::Fixed(time) => time,
+            TimestampPolicy::FromArchiveMeta => match meta.and_then(|meta| meta.get("mtime")?.as_u64()) {
+                Some(seconds) => SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+                None => return Ok(()),
+            },
+        };
+
+        let times = FileTimes::new().set_accessed(time).set_modified(time);
+
+        match File::options().write(!is_dir).read(is_dir).open(out_path) {
+            Ok(file) => file.set_times(times),
+            Err(_) if is_dir => Ok(()), // folders can not be opened on every platform
+            Err(err) => Err(err),
+        }
+    }
+
+    // Returns true if permissions, ownership, or times are restored, or files are marked read-only.
+    fn restores(&self) -> bool {
+        self.permissions || self.ownership || self.timestamps != TimestampPolicy::Now || self.read_only
+    }
+
+    // Returns the path the entry at the full path is extracted to (relative to the destination),
+    // escaping illegal names, `None` if it is skipped, otherwise why its name is illegal.
+    fn target_path(&self, path: &Path) -> Result<Option<PathBuf>, &'static str> {
+        let platform = self.platform.unwrap_or_else(Platform::current);
+        let mut target = PathBuf::new();
+
+        for component in path.iter() {
+            let name = component.to_string_lossy();
+
+            match (names::illegal_reason(&name, platform), self.names) {
+                (None, _) => target.push(component),
+                (Some(reason), NamePolicy::Error) => return Err(reason),
+                (Some(_), NamePolicy::Skip) => return Ok(None),
+                (Some(_), NamePolicy::RenameEscaped) => target.push(names::escape(&name, platform)),
+            }
+        }
+
+        Ok(Some(target))
+    }
+
+    // Detects the entries whose target paths collide once case is folded, renaming or dropping
+    // them as selected by the CollisionPolicy. Entries must be sorted by path.
+    fn resolve_collisions(&self, entries: &mut Vec<(&PathBuf, &EntryRef, PathBuf)>) -> Result<(), asar_error::Error> {
+        let policy = match (self.collisions, self.platform.unwrap_or_else(Platform::current)) {
+            (Some(policy), _) => policy,
+            (None, platform) if platform.is_case_insensitive() => CollisionPolicy::Error,
+            _ => return Ok(()),
+        };
+
+        let mut seen: HashMap<String, bool> = HashMap::new(); // folded target -> is a folder
+        let mut moved: Vec<(PathBuf, Option<PathBuf>)> = Vec::new(); // folders renamed or dropped
+        let mut collisions: Vec<String> = Vec::new();
+        let mut resolved = Vec::with_capacity(entries.len());
+
+        for (path, entry, mut target) in entries.drain(..) {
+            // paths below a renamed or dropped folder follow it
+            if let Some((from, to)) = moved.iter().find(|(from, _)| target.starts_with(from)) {
+                match to {
+                    Some(to) => target = to.join(target.strip_prefix(from).unwrap_or(&target)),
+                    None => continue,
+                }
+            }
+
+            let is_dir = *entry == EntryRef::Folder;
+            let mut key = fold(&target);
+
+            // folders colliding with folders are merged
+            let collides = seen.get(&key).is_some_and(|&was_dir| !(was_dir && is_dir));
+
+            match policy {
+                _ if !collides => {}
+                CollisionPolicy::Error => {
+                    collisions.push(path.display().to_string());
+                    continue;
+                }
+                CollisionPolicy::KeepFirst => {
+                    if is_dir {
+                        moved.push((target, None));
+                    }
+
+                    continue;
+                }
+                CollisionPolicy::SuffixRename => {
+                    let name = target.file_name().unwrap_or_default().to_os_string();
+                    let renamed = (1..)
+                        .map(|counter| target.with_file_name(suffixed(&name, counter)))
+                        .find(|candidate| !seen.contains_key(&fold(candidate)))
+                        .unwrap_or_default();
+
+                    if is_dir {
+                        moved.push((target, Some(renamed.clone())));
+                    }
+
+                    target = renamed;
+                    key = fold(&target);
+                }
+            }
+
+            seen.insert(key, is_dir);
+            resolved.push((path, entry, target));
+        }
+
+        if !collisions.is_empty() {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} path(s) collide with another once case is folded\n  {}",
+                    collisions.len(),
+                    collisions.join("\n  ")
+                ),
+            )));
+        }
+
+        *entries = resolved;
+
+        Ok(())
+    }
+
+    // Returns true if the file at the provided full path is extracted.
+    pub(crate) fn includes(&self, path: &Path) -> bool {
+        match &self.extensions {
+            Some(extensions) => path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.iter().any(|allowed| allowed.eq_ignore_ascii_case(ext))),
+            None => true,
+        }
+    }
+}
+
+// Returns the file name of the path, suffixed with a counter before its extension if it was
+// already taken, and marks it taken.
+fn flat_name(path: &Path, taken: &mut HashSet<OsString>) -> OsString {
+    let name = path.file_name().unwrap_or(path.as_os_str()).to_os_string();
+
+    let mut candidate = name.clone();
+    let mut counter = 0;
+
+    while taken.contains(&candidate) {
+        counter += 1;
+        candidate = suffixed(&name, counter);
+    }
+
+    taken.insert(candidate.clone());
+    candidate
+}
+
+// Returns the name suffixed with the counter before its extension (`index-1.js`).
+fn suffixed(name: &OsStr, counter: usize) -> OsString {
+    let stem = Path::new(name).file_stem().unwrap_or(name).to_string_lossy().into_owned();
+
+    match Path::new(name).extension() {
+        Some(ext) => format!("{}-{}.{}", stem, counter, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, counter),
+    }
+    .into()
+}
+
+// Returns the path with every component folded to lower case, as compared by case-insensitive
+// filesystems.
+fn fold(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// Marks every file below the provided folder (or the provided file itself) read-only, or
+/// un-marks them, e.g. to update resources extracted with `ExtractOptions::read_only`.
+///
+/// On Unix, un-marking only makes files writable by their owner.
+///
+/// Returns (), otherwise Error if a file can not be read or its permissions changed.
+pub fn set_read_only<P: AsRef<Path>>(path: P, read_only: bool) -> Result<(), asar_error::Error> {
+    let path = path.as_ref();
+
+    if !fs::metadata(path)?.is_dir() {
+        return Ok(mark_file(path, read_only)?);
+    }
+
+    for entry in fs::read_dir(path)? {
+        set_read_only(entry?.path(), read_only)?;
+    }
+
+    Ok(())
+}
+
+// Creates the folder and its missing parents, recording those created, parents first.
+fn create_dir(path: &Path, created: &mut Vec<PathBuf>) -> io::Result<()> {
+    let missing: Vec<&Path> = path
+        .ancestors()
+        .take_while(|folder| !folder.as_os_str().is_empty() && !folder.exists())
+        .collect();
+
+    DirBuilder::new().recursive(true).create(path)?;
+    created.extend(missing.into_iter().rev().map(Path::to_path_buf));
+
+    Ok(())
+}
+
+// Marks the file read-only, or writable by its owner.
+#[cfg(unix)]
+fn mark_file(path: &Path, read_only: bool) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+
+    match read_only {
+        true => permissions.set_readonly(true),
+        false => permissions.set_mode(permissions.mode() | 0o200),
+    }
+
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::permissions_set_readonly_false)]
+fn mark_file(path: &Path, read_only: bool) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(read_only);
+
+    fs::set_permissions(path, permissions)
+}
+
+impl Asar {
+
+    /// Extracts an opened Asar archive file to the provided destination folder, as does `extract`,
+    /// applying the provided ExtractOptions to every file.
+    ///
+    /// Files left out by the extensions or skipped by the transformer are not written; their
+    /// folders are still created, unless extensions are set or files are flattened.
+    ///
+    /// Returns (), otherwise Error if a directory is open or a file can not be read or written.
+    pub fn extract_with<P: AsRef<Path>>(&self, destination: P, options: &ExtractOptions) -> Result<(), asar_error::Error> {
+        self.extract_run(destination, options, &options.run_context())
+    }
+
+    // extract_with within the provided run, e.g. gathering its report or checking its cancel token
+    // (between files and every 64 KiB within them). Once cancelled, every file and folder created
+    // so far is removed.
+    pub(crate) fn extract_run<P: AsRef<Path>>(&self, destination: P, options: &ExtractOptions, run: &RunContext) -> Result<(), asar_error::Error> {
+        let _span = trace::span!(DEBUG, "extract", path = %self.src_path.display());
+
+        if self.src_path.is_dir() {
+            return Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        let destination = destination.as_ref();
+        let file = self.archive_file()?;
+
+        let mut sorted: Vec<(&PathBuf, &EntryRef)> = self.index.iter().collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0)); // folders before their contents
+
+        // every name is checked before anything is written, so extraction never fails halfway
+        let mut entries: Vec<(&PathBuf, &EntryRef, PathBuf)> = Vec::with_capacity(sorted.len());
+        let mut illegal: Vec<String> = Vec::new();
+
+        for (path, entry) in sorted {
+            match options.target_path(path) {
+                Ok(Some(target)) => entries.push((path, entry, target)),
+                Ok(None) => {
+                    trace::event!(DEBUG, path = %path.display(), "skipped illegal name");
+                }
+                Err(reason) => illegal.push(format!("{}: {}", path.display(), reason)),
+            }
+        }
+
+        if !illegal.is_empty() {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} illegal file name(s)\n  {}", illegal.len(), illegal.join("\n  ")),
+            )));
+        }
+
+        options.resolve_collisions(&mut entries)?;
+
+        if let Some(priority) = &options.priority {
+            // stable, so folders stay before their contents, and files of equal priority in order
+            entries.sort_by_cached_key(|(path, entry, _)| match entry {
+                EntryRef::File(..) => Reverse(priority(path)),
+                EntryRef::Folder => Reverse(u32::MAX),
+            });
+        }
+
+        let mut created: Vec<PathBuf> = Vec::new();
+        let result = self.extract_entries(&file, destination, &entries, options, run, &mut created);
+
+        if let Err(Error::Cancelled) = result {
+            // remove children before their folders
+            for path in created.iter().rev() {
+                if path.is_dir() {
+                    fs::remove_dir(path).ok();
+                } else {
+                    fs::remove_file(path).ok();
+                }
+            }
+        }
+
+        result
+    }
+
+    // Writes the checked entries `(full_path, entry, target_path)` to the destination, recording
+    // every file and folder created that did not exist before.
+    fn extract_entries(
+        &self,
+        file: &ArchiveFile,
+        destination: &Path,
+        entries: &[(&PathBuf, &EntryRef, PathBuf)],
+        options: &ExtractOptions,
+        run: &RunContext,
+        created: &mut Vec<PathBuf>,
+    ) -> Result<(), asar_error::Error> {
+        let hooks = &options.hooks;
+        let started = Instant::now();
+        let (mut files, mut bytes) = (0, 0);
+
+        create_dir(destination, created)?;
+
+        let mut taken: HashSet<OsString> = HashSet::new();
+        let mut folders: Vec<(&PathBuf, PathBuf)> = Vec::new();
+
+        for (path, entry, target) in entries.iter() {
+            if run.cancel.is_some_and(|token| token.load(Ordering::Relaxed)) {
+                return Err(Error::Cancelled);
+            }
+
+            match entry {
+                EntryRef::Folder if options.creates_folders() => {
+                    let out_path = destination.join(target);
+
+                    create_dir(&out_path, created)?;
+                    folders.push((path, out_path));
+                }
+                EntryRef::Folder => {}
+                EntryRef::File(..) if !options.includes(path) => {}
+                EntryRef::File(offset, size) => {
+                    // only files written out take a flat name, so folders and filtered files do not shift them
+                    let out_path = if options.flatten {
+                        destination.join(flat_name(target, &mut taken))
+                    } else {
+                        destination.join(target)
+                    };
+
+                    hooks.before(path);
+
+                    if !out_path.exists() {
+                        created.push(out_path.clone());
+                    }
+
+                    let written = match hooks.attempt(run, path, || self.extract_file(file, path, (*offset, *size), &out_path, options, run))? {
+                        Some(Some(written)) => written,
+                        Some(None) => continue, // skipped by the transformer
+                        None => {
+                            // skipped by the on_error hook, leaving no partial file behind
+                            if out_path.is_file() {
+                                fs::remove_file(&out_path)?;
+                            }
+
+                            continue;
+                        }
+                    };
+
+                    (files, bytes) = (files + 1, bytes + written);
+
+                    if options.restores() {
+                        options.set_times(&out_path, self.entry_meta(path), false)?; // before a read-only mode
+                        options.restore(&out_path, self.entry_fields(path), self.entry_meta(path), false)?;
+
+                        if options.read_only {
+                            mark_file(&out_path, true)?;
+                        }
+                    }
+
+                    hooks.after(run, path, written);
+
+                    trace::event!(TRACE, path = %out_path.display(), size = written, "extracted file");
+                }
+            }
+        }
+
+        // folders are restored last, as a restricted mode could prevent writing their files, and
+        // writing them changes their times
+        if options.restores() {
+            for (path, out_path) in folders.iter().rev() {
+                options.restore(out_path, self.entry_fields(path), self.entry_meta(path), true)?;
+                options.set_times(out_path, self.entry_meta(path), true)?;
+            }
+        }
+
+        self.metrics.operation(Operation::Extract, files, bytes, started);
+
+        Ok(())
+    }
+
+    // Reads the file `(offset, size)` at the provided full path, transforms it, and writes it to
+    // `out_path`. Returns the number of bytes written, or `None` if the transformer skipped it.
+    fn extract_file(
+        &self,
+        file: &ArchiveFile,
+        path: &Path,
+        (offset, size): (u64, u64),
+        out_path: &Path,
+        options: &ExtractOptions,
+        run: &RunContext,
+    ) -> Result<Option<u64>, asar_error::Error> {
+        let read_started = Instant::now();
+
+        let Some(transformer) = &options.transformer else {
+            let _lease = run.budget.as_ref().map(|budget| budget.acquire(size.min(BUDGET_CHUNK_LEN as u64)));
+
+            if let Some(parent) = out_path.parent() {
+                DirBuilder::new().recursive(true).create(parent)?;
+            }
+
+            let mut out = Throttled::new(File::create(out_path)?, run.throttle.as_deref());
+
+            match run.cancel {
+                Some(token) => cancel::copy_cancellable(self.entry_reader(file, offset, size), &mut out, size, token)?,
+                None => budget::stream(self.entry_reader(file, offset, size), &mut out, size)?,
+            }
+
+            self.metrics.file_read(path, size, read_started);
+
+            return Ok(Some(size));
+        };
+
+        // the whole file is held, for the transformer
+        let _lease = run.budget.as_ref().map(|budget| budget.acquire(size));
+
+        let mut content: Vec<u8> = vec![0; size as usize];
+        self.entry_reader(file, offset, size).read_exact(&mut content)?;
+        self.metrics.file_read(path, size, read_started);
+
+        match transformer(path, content) {
+            TransformResult::Content(transformed) => content = transformed,
+            TransformResult::Skip => return Ok(None),
+        }
+
+        if let Some(parent) = out_path.parent() {
+            DirBuilder::new().recursive(true).create(parent)?;
+        }
+
+        Throttled::new(File::create(out_path)?, run.throttle.as_deref()).write_all(&content)?;
+
+        Ok(Some(content.len() as u64))
+    }
+
+    // Returns the header entry of the file or folder at the provided full path.
+    pub(crate) fn entry_fields(&self, path: &Path) -> Option<&Map<String, Value>> {
+        self.content
+            .folder_contents(path.parent()?)?
+            .get(path.file_name()?.to_str()?)?
+            .as_object()
+    }
+}
@@ -0,0 +1,248 @@
+// C interface (feature `ffi`), built as a shared library with
+// `cargo rustc --release --lib --features ffi --crate-type cdylib`. The matching header is generated within OUT_DIR
+// by the build script, and the tests check that include/rust_asar.h (the committed copy) matches it.
+//
+// Every function returning a pointer returns NULL on failure, and every function returning an
+// int returns 0 on success and -1 on failure; `asar_last_error` then describes the failure. A
+// panic is caught before it unwinds into C, and reported as a failure.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    ptr,
+};
+
+use crate::asar::Asar;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An Asar archive file opened by `asar_open`, freed by `asar_close`.
+pub struct AsarArchive {
+    asar: Asar,
+}
+
+fn set_last_error(err: impl ToString) {
+    let message = CString::new(err.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+// Runs the body of an exported function, returning `failed` and setting the last error if it panics.
+fn guard<T>(failed: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+
+        set_last_error(format!("panicked: {}", message));
+        failed
+    })
+}
+
+// Converts a C string argument to a Path, setting the last error if it is NULL or not UTF-8.
+unsafe fn path_arg<'a>(path: *const c_char) -> Option<&'a Path> {
+    if path.is_null() {
+        set_last_error("path is NULL");
+        return None;
+    }
+
+    match CStr::from_ptr(path).to_str() {
+        Ok(path) => Some(Path::new(path)),
+        Err(err) => {
+            set_last_error(err);
+            None
+        }
+    }
+}
+
+// Converts an archive argument to a reference, setting the last error if it is NULL.
+unsafe fn archive_arg<'a>(archive: *const AsarArchive) -> Option<&'a AsarArchive> {
+    if archive.is_null() {
+        set_last_error("archive is NULL");
+    }
+
+    archive.as_ref()
+}
+
+/// Returns a description of the last failure on the calling thread, or NULL if nothing failed.
+///
+/// The string is owned by the library and valid until the next call on the same thread.
+#[no_mangle]
+pub extern "C" fn asar_last_error() -> *const c_char {
+    guard(ptr::null(), || {
+        LAST_ERROR.with(|last| match &*last.borrow() {
+            Some(message) => message.as_ptr(),
+            None => ptr::null(),
+        })
+    })
+}
+
+/// Opens the Asar archive file at `path` (a null-terminated UTF-8 string).
+///
+/// Returns the archive, to be freed with `asar_close`, otherwise NULL.
+///
+/// # Safety
+///
+/// `path` must be NULL or a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn asar_open(path: *const c_char) -> *mut AsarArchive {
+    guard(ptr::null_mut(), || {
+        let Some(path) = path_arg(path) else {
+            return ptr::null_mut();
+        };
+
+        if path.is_dir() {
+            set_last_error("path must be an Asar archive file");
+            return ptr::null_mut();
+        }
+
+        match Asar::open(path) {
+            Ok(asar) => Box::into_raw(Box::new(AsarArchive { asar })),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Frees an archive returned by `asar_open`. Passing NULL does nothing.
+///
+/// # Safety
+///
+/// `archive` must be NULL or returned by `asar_open`, and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn asar_close(archive: *mut AsarArchive) {
+    guard((), || {
+        if !archive.is_null() {
+            drop(Box::from_raw(archive));
+        }
+    })
+}
+
+/// Lists the full path of every file and folder within the archive, sorted, storing the number
+/// of paths in `len`.
+///
+/// Returns an array of null-terminated strings, to be freed with `asar_list_free`, otherwise NULL.
+///
+/// # Safety
+///
+/// `archive` must be returned by `asar_open`, and `len` must point to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn asar_list(archive: *const AsarArchive, len: *mut usize) -> *mut *mut c_char {
+    guard(ptr::null_mut(), || {
+        let Some(archive) = archive_arg(archive) else {
+            return ptr::null_mut();
+        };
+
+        let mut paths: Vec<String> = archive
+            .asar
+            .index()
+            .keys()
+            .filter(|path| !path.as_os_str().is_empty())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+
+        let list: Box<[*mut c_char]> = paths
+            .into_iter()
+            .map(|path| CString::new(path).unwrap_or_default().into_raw())
+            .collect();
+
+        *len = list.len();
+        Box::into_raw(list) as *mut *mut c_char
+    })
+}
+
+/// Frees an array returned by `asar_list`, along with its strings.
+///
+/// # Safety
+///
+/// `list` must be NULL or returned by `asar_list` with the same `len`, and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn asar_list_free(list: *mut *mut c_char, len: usize) {
+    guard((), || {
+        if list.is_null() {
+            return;
+        }
+
+        let list = Box::from_raw(ptr::slice_from_raw_parts_mut(list, len));
+
+        for path in list.iter() {
+            drop(CString::from_raw(*path));
+        }
+    })
+}
+
+/// Reads the file at `path` within the archive into memory, storing its size in `len`.
+///
+/// Returns the content, to be freed with `asar_bytes_free`, otherwise NULL.
+///
+/// # Safety
+///
+/// `archive` must be returned by `asar_open`, `path` must be a valid null-terminated string,
+/// and `len` must point to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn asar_read_file(archive: *const AsarArchive, path: *const c_char, len: *mut usize) -> *mut u8 {
+    guard(ptr::null_mut(), || {
+        let (Some(archive), Some(path)) = (archive_arg(archive), path_arg(path)) else {
+            return ptr::null_mut();
+        };
+
+        match archive.asar.get_file(path) {
+            Some(content) => {
+                let content = content.into_boxed_slice();
+
+                *len = content.len();
+                Box::into_raw(content) as *mut u8
+            }
+            None => {
+                set_last_error(format!("File not found in archive: {}", path.display()));
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Frees content returned by `asar_read_file`.
+///
+/// # Safety
+///
+/// `bytes` must be NULL or returned by `asar_read_file` with the same `len`, and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn asar_bytes_free(bytes: *mut u8, len: usize) {
+    guard((), || {
+        if !bytes.is_null() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(bytes, len)));
+        }
+    })
+}
+
+/// Extracts the archive to the folder at `destination`, creating it if needed.
+///
+/// Returns 0, otherwise -1.
+///
+/// # Safety
+///
+/// `archive` must be returned by `asar_open`, and `destination` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn asar_extract(archive: *const AsarArchive, destination: *const c_char) -> c_int {
+    guard(-1, || {
+        let (Some(archive), Some(destination)) = (archive_arg(archive), path_arg(destination)) else {
+            return -1;
+        };
+
+        match archive.asar.extract(destination) {
+            Ok(()) => 0,
+            Err(err) => {
+                set_last_error(err);
+                -1
+            }
+        }
+    })
+}
@@ -0,0 +1,224 @@
+use std::{
+    fmt, mem,
+    io::{self, Read},
+    sync::{Arc, RwLock},
+};
+
+use crate::asar::Asar;
+
+/// A hash algorithm, used by `Asar::find_duplicates`, `Asar::etag`, `Asar::export_cas`, pack
+/// verification, and integrity (see `PackOptions::hash_algorithm`).
+///
+/// Implement it to use a custom algorithm, optionally registering it by name with `register`.
+///
+/// ```
+/// use rust_asar::hash::{self, Hasher, HashState};
+///
+/// struct Length;
+/// struct LengthState(u64);
+///
+/// impl Hasher for Length {
+///     fn name(&self) -> &str {
+///         "LENGTH"
+///     }
+///
+///     fn start(&self) -> Box<dyn HashState> {
+///         Box::new(LengthState(0))
+///     }
+/// }
+///
+/// impl HashState for LengthState {
+///     fn update(&mut self, bytes: &[u8]) {
+///         self.0 += bytes.len() as u64;
+///     }
+///
+///     fn finish(self: Box<Self>) -> Vec<u8> {
+///         self.0.to_be_bytes().to_vec()
+///     }
+/// }
+///
+/// hash::register(std::sync::Arc::new(Length));
+/// assert_eq!(hash::find("length").unwrap().name(), "LENGTH");
+/// ```
+pub trait Hasher: Send + Sync {
+
+    /// Returns the name of the algorithm, as recorded within headers and manifests (e.g. `SHA256`).
+    fn name(&self) -> &str;
+
+    /// Starts hashing a new input.
+    fn start(&self) -> Box<dyn HashState>;
+}
+
+/// The state of a Hasher while an input is hashed, as returned by `Hasher::start`.
+pub trait HashState {
+
+    /// Hashes the next bytes of the input.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the digest of every byte hashed.
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+/// SHA-256, as used by Electron for integrity and by `sha256sum`. The default Hasher.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha256;
+
+impl Hasher for Sha256 {
+    fn name(&self) -> &str {
+        "SHA256"
+    }
+
+    fn start(&self) -> Box<dyn HashState> {
+        Box::new(<sha2::Sha256 as sha2::Digest>::new())
+    }
+}
+
+impl HashState for sha2::Sha256 {
+    fn update(&mut self, bytes: &[u8]) {
+        sha2::Digest::update(self, bytes);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        sha2::Digest::finalize(*self).to_vec()
+    }
+}
+
+/// BLAKE3, a cryptographic hash several times faster than SHA-256 (enabled by the `blake3` feature).
+#[cfg(feature = "blake3")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake3;
+
+#[cfg(feature = "blake3")]
+impl Hasher for Blake3 {
+    fn name(&self) -> &str {
+        "BLAKE3"
+    }
+
+    fn start(&self) -> Box<dyn HashState> {
+        Box::new(blake3::Hasher::new())
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl HashState for blake3::Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        blake3::Hasher::update(self, bytes);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.finalize().as_bytes().to_vec()
+    }
+}
+
+/// XXH64, a fast non-cryptographic hash, only suited to detecting accidental changes (enabled by
+/// the `xxhash` feature). Digests are big-endian.
+#[cfg(feature = "xxhash")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Xxh64;
+
+#[cfg(feature = "xxhash")]
+impl Hasher for Xxh64 {
+    fn name(&self) -> &str {
+        "XXH64"
+    }
+
+    fn start(&self) -> Box<dyn HashState> {
+        Box::new(xxhash_rust::xxh64::Xxh64::new(0))
+    }
+}
+
+#[cfg(feature = "xxhash")]
+impl HashState for xxhash_rust::xxh64::Xxh64 {
+    fn update(&mut self, bytes: &[u8]) {
+        xxhash_rust::xxh64::Xxh64::update(self, bytes);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.digest().to_be_bytes().to_vec()
+    }
+}
+
+// Algorithms registered with `register`, searched after the built-in ones.
+static REGISTRY: RwLock<Vec<Arc<dyn Hasher>>> = RwLock::new(Vec::new());
+
+/// Registers a custom algorithm, so `find` returns it by name. An algorithm registered later
+/// under the same name replaces the earlier one; built-in algorithms can not be replaced.
+pub fn register(hasher: Arc<dyn Hasher>) {
+    let mut registry = REGISTRY.write().unwrap_or_else(|err| err.into_inner());
+
+    registry.retain(|registered| !registered.name().eq_ignore_ascii_case(hasher.name()));
+    registry.push(hasher);
+}
+
+/// Returns the built-in or registered algorithm with the provided name (in any case), e.g. the
+/// `algorithm` of an integrity field or a manifest.
+pub fn find(name: &str) -> Option<Arc<dyn Hasher>> {
+    let builtin: Vec<Arc<dyn Hasher>> = vec![
+        Arc::new(Sha256),
+        #[cfg(feature = "blake3")]
+        Arc::new(Blake3),
+        #[cfg(feature = "xxhash")]
+        Arc::new(Xxh64),
+    ];
+
+    let registry = REGISTRY.read().unwrap_or_else(|err| err.into_inner());
+
+    builtin
+        .into_iter()
+        .chain(registry.iter().cloned())
+        .find(|hasher| hasher.name().eq_ignore_ascii_case(name))
+}
+
+/// Returns the lowercase hex digest of everything read from the provided reader.
+pub fn hex_digest<R: Read>(hasher: &dyn Hasher, mut reader: R) -> io::Result<String> {
+    let mut state = hasher.start();
+    let mut buf: Vec<u8> = vec![0; 64 * 1024];
+
+    loop {
+        let read = reader.read(&mut buf)?;
+
+        if read == 0 {
+            break;
+        }
+
+        state.update(&buf[..read]);
+    }
+
+    Ok(hex(&state.finish()))
+}
+
+/// Returns the lowercase hex encoding of the bytes.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// The Hasher of an Asar or PackOptions, SHA256 by default.
+#[derive(Clone)]
+pub(crate) struct HashHook(pub(crate) Arc<dyn Hasher>);
+
+impl Default for HashHook {
+    fn default() -> Self {
+        HashHook(Arc::new(Sha256))
+    }
+}
+
+impl fmt::Debug for HashHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HashHook").field(&self.0.name()).finish()
+    }
+}
+
+impl Asar {
+
+    /// Sets the Hasher used by `find_duplicates`, `etag`, `export_cas`, and pack verification of
+    /// this Asar. An opened directory uses the Hasher of its PackOptions (see
+    /// `PackOptions::hash_algorithm`).
+    pub fn set_hasher(&mut self, hasher: Arc<dyn Hasher>) {
+        self.options = mem::take(&mut self.options).hash_algorithm(hasher);
+    }
+
+    /// Returns the Hasher of this Asar, SHA256 unless set otherwise.
+    pub fn hasher(&self) -> &dyn Hasher {
+        self.options.hasher().0.as_ref()
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{asar::Asar, asar_error, cas};
+
+/// A lowercase hex SHA256 digest of the content of a file, as returned by `cas::sha256_hex`.
+pub type Digest = String;
+
+/// Parses a hash list, one SHA256 digest per line, as written by `sha256sum` (anything after the
+/// digest, such as a file name, is ignored). Blank lines and lines starting with `#` are skipped,
+/// and digests are lowercased.
+///
+/// ```
+/// use rust_asar::hashlist;
+///
+/// let hashes = hashlist::parse_hash_list("# compromised release\nE3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855  index.js\n");
+/// assert!(hashes.contains("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+/// ```
+pub fn parse_hash_list(list: &str) -> HashSet<Digest> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+/// Reads and parses the hash list file at the provided path (see `parse_hash_list`).
+pub fn read_hash_list<P: AsRef<Path>>(path: P) -> Result<HashSet<Digest>, asar_error::Error> {
+    Ok(parse_hash_list(&fs::read_to_string(path)?))
+}
+
+impl Asar {
+
+    /// Hashes every file of an opened Asar archive file, streaming each from the archive, and
+    /// reports those whose digest is within the provided set, e.g. the hashes of the files of a
+    /// compromised dependency release.
+    ///
+    /// Returns the matching files with their digest, in header order, otherwise Error if a file can
+    /// not be read.
+    pub fn match_hashes(&self, hashes: &HashSet<Digest>) -> Result<Vec<(PathBuf, Digest)>, asar_error::Error> {
+        let mut matches: Vec<(PathBuf, Digest)> = Vec::new();
+
+        if hashes.is_empty() {
+            return Ok(matches);
+        }
+
+        self.extract_each(|_| true, |path, reader| {
+            let digest = cas::sha256_hex(reader)?;
+
+            if hashes.contains(&digest) {
+                matches.push((path.to_path_buf(), digest));
+            }
+
+            Ok(())
+        })?;
+
+        Ok(matches)
+    }
+}
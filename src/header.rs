@@ -0,0 +1,304 @@
+// Header parsing and offset math, built with `no_std + alloc` so Asar headers can be inspected
+// without the standard library. Everything here reads through the ByteSource trait.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use serde_json::{Map, Value};
+
+/// Offset of the header size (pickle payload size, excluding the first 8 bytes) within the prefix.
+pub const HEADER_SIZE_OFFSET: u64 = 4;
+
+/// Offset of the JSON length within the prefix.
+pub const JSON_LEN_OFFSET: u64 = 12;
+
+/// Offset at which the JSON value begins, right after the 16 byte prefix.
+pub const JSON_OFFSET: u64 = 16;
+
+/// The maximum size of a file within an asar archive.
+pub const MAX_SAFE_INTEGER: u64 = 9007199254740991; //for compatability with Electron's Asar library
+
+/// A source of bytes readable at arbitrary offsets, such as an Asar archive file or a buffer
+/// holding (at least) its header.
+pub trait ByteSource {
+    type Error;
+
+    /// Fills `buf` with the bytes starting at `pos`, failing if fewer are available.
+    fn read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+/// The Error of a buffer used as a ByteSource: the header extends past the end of the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds;
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Header extends past the end of the buffer")
+    }
+}
+
+impl ByteSource for [u8] {
+    type Error = OutOfBounds;
+
+    fn read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let end = pos.checked_add(buf.len() as u64).ok_or(OutOfBounds)?;
+
+        if end > self.len() as u64 {
+            return Err(OutOfBounds);
+        }
+
+        buf.copy_from_slice(&self[pos as usize..end as usize]);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl ByteSource for std::fs::File {
+    type Error = std::io::Error;
+
+    fn read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        positioned_io::ReadAt::read_exact_at(self, pos, buf)
+    }
+}
+
+/// Errors found while parsing a header, generic over the Error of the ByteSource.
+///
+/// - Source (err): The ByteSource failed
+///
+/// - Json (err): The JSON value could not be parsed
+///
+/// - SizeMismatch (header_size, json_len): The header size can not hold the JSON value
+///
+/// - Malformed (reason): An entry of the JSON value is invalid
+///
+/// - Unsupported (format): The prefix is of an unknown format
+#[derive(Debug)]
+pub enum HeaderError<E> {
+    Source(E),
+    Json(serde_json::Error),
+    SizeMismatch(u64, u32),
+    Malformed(String),
+    Unsupported(ArchiveFormat),
+}
+
+impl<E: fmt::Display> fmt::Display for HeaderError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Source(err) => write!(f, "{}", err),
+            Self::Json(err) => write!(f, "{}", err),
+            Self::SizeMismatch(start, json_len) => write!(
+                f,
+                "Header size {} is smaller than JSON length {}",
+                start, json_len
+            ),
+            Self::Malformed(reason) => write!(f, "{}", reason),
+            Self::Unsupported(format) => write!(f, "Unsupported archive format: {}", format),
+        }
+    }
+}
+
+impl<E> From<serde_json::Error> for HeaderError<E> {
+    fn from(err: serde_json::Error) -> Self {
+        HeaderError::Json(err)
+    }
+}
+
+/// The framing of the 16 byte prefix of an Asar archive file, a Chromium pickle holding the header
+/// size followed by a pickle holding the JSON length and value.
+///
+/// - Electron: The JSON value is padded to 4 bytes, exactly as written by `@electron/asar`
+///
+/// - Compact: The JSON value is not padded (as written by rust_asar without alignment)
+///
+/// - Padded (slack): `slack` bytes lie between the JSON value and the body, beyond any pickle
+///   padding (as written by aligning packers)
+///
+/// - Unknown (prefix): The prefix does not follow the pickle framing, holding the four raw `u32` values
+///
+/// Every known variant is read the same way: the body starts at the header size. Unknown variants
+/// are rejected by `read_header` rather than guessed at, since their offsets can not be trusted;
+/// new variants are only accepted once added here with a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Electron,
+    Compact,
+    Padded(u64),
+    Unknown([u32; 4]),
+}
+
+impl ArchiveFormat {
+
+    /// Detects the format of the provided 16 byte prefix of an Asar archive file.
+    pub fn from_prefix(prefix: &[u8; 16]) -> ArchiveFormat {
+        let fields: [u32; 4] = core::array::from_fn(|i| {
+            u32::from_le_bytes([prefix[i * 4], prefix[i * 4 + 1], prefix[i * 4 + 2], prefix[i * 4 + 3]])
+        });
+        let [size_len, header_size, payload_size, json_len] = fields;
+
+        // the first pickle holds a single u32, the second is the rest of the header
+        if size_len != 4 || header_size.checked_sub(4) != Some(payload_size) {
+            return ArchiveFormat::Unknown(fields);
+        }
+
+        let slack = match (header_size as u64).checked_sub(8 + json_len as u64) {
+            Some(slack) => slack,
+            None => return ArchiveFormat::Unknown(fields),
+        };
+
+        let pickle_padding = align_up(json_len as u64, 4) - json_len as u64;
+
+        match slack {
+            0 if pickle_padding != 0 => ArchiveFormat::Compact,
+            slack if slack == pickle_padding => ArchiveFormat::Electron,
+            slack => ArchiveFormat::Padded(slack),
+        }
+    }
+
+    /// Returns true for every variant except Unknown.
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, ArchiveFormat::Unknown(_))
+    }
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Electron => write!(f, "Electron"),
+            Self::Compact => write!(f, "compact"),
+            Self::Padded(slack) => write!(f, "padded ({} bytes)", slack),
+            Self::Unknown(fields) => write!(f, "unknown (prefix {:?})", fields),
+        }
+    }
+}
+
+/// Reads the 16 byte prefix of an Asar archive file from the provided ByteSource and returns its format.
+pub fn read_format<S: ByteSource + ?Sized>(source: &S) -> Result<ArchiveFormat, HeaderError<S::Error>> {
+    let mut prefix = [0_u8; 16];
+    source.read_exact_at(0, &mut prefix).map_err(HeaderError::Source)?;
+
+    Ok(ArchiveFormat::from_prefix(&prefix))
+}
+
+/// Returns a tuple of the header of an Asar archive file as `serde_json::Value`, and the start
+/// offset (at which file content begins) as `u64`, read from the provided ByteSource
+/// (e.g. a `&[u8]` holding the beginning of the archive).
+///
+/// The start offset is read from the header size, so any padding after the JSON value is skipped.
+/// Trailing null bytes counted within the JSON length are ignored.
+///
+/// Archives of an unknown format (see `ArchiveFormat`) are rejected with `HeaderError::Unsupported`.
+pub fn read_header<S: ByteSource + ?Sized>(source: &S) -> Result<(Value, u64), HeaderError<S::Error>> {
+    let (json, start) = read_header_json(source)?;
+
+    Ok((serde_json::from_slice(&json)?, start))
+}
+
+/// Returns the raw JSON of the header of an Asar archive file, without its trailing null bytes,
+/// and the start offset, as does `read_header` without parsing it (e.g. to parse it as a stream).
+pub fn read_header_json<S: ByteSource + ?Sized>(source: &S) -> Result<(Vec<u8>, u64), HeaderError<S::Error>> {
+    let mut prefix = [0_u8; 16];
+    source.read_exact_at(0, &mut prefix).map_err(HeaderError::Source)?;
+
+    let field = |offset: u64| {
+        let offset = offset as usize;
+        u32::from_le_bytes([prefix[offset], prefix[offset + 1], prefix[offset + 2], prefix[offset + 3]])
+    };
+
+    let json_len = field(JSON_LEN_OFFSET);
+    let start = field(HEADER_SIZE_OFFSET) as u64 + 8; // 8 bytes prior to header must be included
+
+    if start < JSON_OFFSET + json_len as u64 {
+        return Err(HeaderError::SizeMismatch(start, json_len));
+    }
+
+    if let format @ ArchiveFormat::Unknown(_) = ArchiveFormat::from_prefix(&prefix) {
+        return Err(HeaderError::Unsupported(format));
+    }
+
+    let mut json_u8: Vec<u8> = vec![0; json_len as usize];
+    source.read_exact_at(JSON_OFFSET, &mut json_u8).map_err(HeaderError::Source)?;
+
+    while json_u8.last() == Some(&0) {
+        json_u8.pop();
+    }
+
+    Ok((json_u8, start))
+}
+
+/// Rounds `offset` up to the next multiple of `align` (an `align` of 0 or 1 leaves it unchanged).
+pub fn align_up(offset: u64, align: u64) -> u64 {
+    if align <= 1 {
+        offset
+    } else {
+        offset.div_ceil(align) * align
+    }
+}
+
+/// Returns the `(offset, size)` of a file entry of a header, `None` if the entry is not a file
+/// (e.g. a folder, a link, or an unpacked file).
+pub fn file_entry<E>(name: &str, item: &Map<String, Value>) -> Result<Option<(u64, u64)>, HeaderError<E>> {
+    match (item.get("offset"), item.get("size")) {
+        (Some(Value::String(offset)), Some(Value::Number(size))) => {
+            let size = size
+                .as_u64()
+                .ok_or_else(|| HeaderError::Malformed(format!("size nan for file: {}", name)))?;
+
+            // check for max integer size
+            if size > MAX_SAFE_INTEGER {
+                return Err(HeaderError::Malformed(format!(
+                    "size of {} is greater than MAX_SAFE_INTEGER",
+                    name
+                )));
+            }
+
+            let offset = offset
+                .parse::<u64>()
+                .map_err(|err| HeaderError::Malformed(format!("ParseIntError: {}", err)))?;
+
+            Ok(Some((offset, size)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns every file of a header as `(full_file_path, offset, size)`, with paths joined by `/`,
+/// in header order.
+pub fn files<E>(header: &Value) -> Result<Vec<(String, u64, u64)>, HeaderError<E>> {
+    let mut files: Vec<(String, u64, u64)> = Vec::new();
+
+    match header.get("files") {
+        Some(Value::Object(dir)) => files_aux(dir, "", &mut files)?,
+        _ => {
+            return Err(HeaderError::Malformed(
+                "'files' not found in Home directory".to_string(),
+            ))
+        }
+    }
+
+    Ok(files)
+}
+
+fn files_aux<E>(dir: &Map<String, Value>, path: &str, files: &mut Vec<(String, u64, u64)>) -> Result<(), HeaderError<E>> {
+    for (name, value) in dir.iter() {
+        let full_path = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", path, name)
+        };
+
+        if let Value::Object(item) = value {
+            if let Some(Value::Object(folder)) = item.get("files") {
+                files_aux(folder, &full_path, files)?;
+            } else if let Some((offset, size)) = file_entry(name, item)? {
+                files.push((full_path, offset, size));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,324 @@
+use std::{
+    collections::HashMap,
+    fmt, mem,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    background::Tally,
+    budget::MemoryBudget,
+    content::{self, Content},
+    outcome::OperationReport,
+    retry::{self, RetryPolicy},
+    throttle::Throttle,
+};
+
+/// What happens to an entry once packing or extracting it failed, as decided by `Hooks::on_error`.
+///
+/// - Abort: The operation stops, returning the Error (as it does without an `on_error` hook)
+///
+/// - Skip: The entry is left out and the operation continues: an extracted file is removed, and a
+///   packed file is left out of the header, the files following it moved back within the body
+///
+/// - Retry: The entry is read again from its start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorDecision {
+    Abort,
+    Skip,
+    Retry,
+}
+
+/// A hook called with the full path of an entry before it is packed or extracted.
+pub type BeforeEntry = dyn Fn(&Path) + Send + Sync;
+
+/// A hook called with the full path of an entry and its size in bytes once it is packed or extracted.
+pub type AfterEntry = dyn Fn(&Path, u64) + Send + Sync;
+
+/// A hook called with the full path of an entry that failed, the Error, and the number of attempts
+/// made so far (starting at 1), deciding what happens next.
+pub type OnError = dyn Fn(&Path, &asar_error::Error, u32) -> ErrorDecision + Send + Sync;
+
+/// Callbacks made for every file by `pack` (see `PackOptions::hooks`) and `extract_with` (see
+/// `ExtractOptions::hooks`), e.g. to log progress, retry flaky reads, or skip unreadable files,
+/// without re-implementing their loops.
+///
+/// Only reading a file is retried or skipped: the source file when packing, or the file within the
+/// archive along with writing it to the destination when extracting. Errors writing the archive
+/// being packed, and cancellation, always abort.
+///
+/// With an `on_error` hook (or a RetryPolicy, or while gathering an OperationReport), `pack` holds
+/// every file in memory while it is read, and writes the header of the archive after its body, so
+/// skipped files are left out of both.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use rust_asar::{asar::Asar, hooks::{ErrorDecision, Hooks}, pack::PackOptions};
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let hooks = Hooks::new()
+///     .after_entry(|path, size| println!("packed {} ({} bytes)", path.display(), size))
+///     .on_error(|path, err, attempts| match attempts {
+///         1..=2 => ErrorDecision::Retry,
+///         _ => {
+///             eprintln!("skipped {}: {}", path.display(), err);
+///             ErrorDecision::Skip
+///         }
+///     });
+///
+/// Asar::open_with("app", &PackOptions::new().hooks(hooks))?.pack("app.asar")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Hooks {
+    before: Option<Arc<BeforeEntry>>,
+    after: Option<Arc<AfterEntry>>,
+    on_error: Option<Arc<OnError>>,
+}
+
+// The state of a single pack or extract run, passed alongside its Hooks (which only hold the
+// callbacks set by their user):
+// - report: Report gathering the outcome of every file (see `Asar::extract_report`)
+// - retry: How reads failing with transient errors are retried (see `PackOptions::retry`)
+// - tally: Files and bytes written so far (see `Asar::extract_background`)
+// - cancel: Token stopping the run once set
+// - throttle: Limit on the rate at which files are written (see `PackOptions::throttle`)
+// - budget: Cap on the buffers of the files being read (see `PackOptions::memory_budget`)
+#[derive(Clone, Default)]
+pub(crate) struct RunContext<'a> {
+    pub(crate) report: Option<Arc<Mutex<OperationReport>>>,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) tally: Option<Arc<Tally>>,
+    pub(crate) cancel: Option<&'a AtomicBool>,
+    pub(crate) throttle: Option<Arc<Throttle>>,
+    pub(crate) budget: Option<MemoryBudget>,
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("before_entry", &self.before.is_some())
+            .field("after_entry", &self.after.is_some())
+            .field("on_error", &self.on_error.is_some())
+            .finish()
+    }
+}
+
+impl Hooks {
+
+    /// Returns hooks doing nothing, every failure aborting.
+    pub fn new() -> Hooks {
+        Hooks::default()
+    }
+
+    /// Sets the hook called before every entry.
+    pub fn before_entry<F>(mut self, hook: F) -> Hooks
+    where
+        F: Fn(&Path) + Send + Sync + 'static,
+    {
+        self.before = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the hook called after every entry packed or extracted (not after skipped entries).
+    pub fn after_entry<F>(mut self, hook: F) -> Hooks
+    where
+        F: Fn(&Path, u64) + Send + Sync + 'static,
+    {
+        self.after = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the hook deciding what happens once an entry failed, called again after every failed
+    /// retry. Without it, the first failure aborts.
+    pub fn on_error<F>(mut self, hook: F) -> Hooks
+    where
+        F: Fn(&Path, &asar_error::Error, u32) -> ErrorDecision + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(hook));
+        self
+    }
+
+    // Returns true if any hook is set, or the run is reported, retried, tallied, or cancellable (a
+    // throttle or budget alone does not).
+    pub(crate) fn is_enabled(&self, run: &RunContext) -> bool {
+        self.before.is_some() || self.after.is_some() || self.on_error.is_some() || run.report.is_some()
+            || run.retry.is_some()
+            || run.tally.is_some()
+            || run.cancel.is_some()
+    }
+
+    // Returns true if reading a file may be retried or skipped, so a packed file is held while it
+    // is read, and the header written after the body (see `Asar::relayout`).
+    pub(crate) fn recovers(&self, run: &RunContext) -> bool {
+        self.on_error.is_some() || run.report.is_some() || run.retry.is_some()
+    }
+
+    pub(crate) fn before(&self, path: &Path) {
+        if let Some(hook) = &self.before {
+            hook(path);
+        }
+    }
+
+    pub(crate) fn after(&self, run: &RunContext, path: &Path, size: u64) {
+        if let Some(tally) = &run.tally {
+            tally.add(size);
+        }
+
+        if let Some(report) = &run.report {
+            report.lock().unwrap_or_else(|err| err.into_inner()).succeeded.push(path.to_path_buf());
+        }
+
+        if let Some(hook) = &self.after {
+            hook(path, size);
+        }
+    }
+
+    // Runs the operation on the entry until it succeeds or the on_error hook stops retrying, first
+    // retrying transient errors as set by the RetryPolicy of the run. Returns `None` if the entry is
+    // skipped, or Error::Cancelled once the cancel token is set. While a report is gathered, a
+    // failed entry is skipped rather than aborting, and its Error recorded.
+    pub(crate) fn attempt<T, F>(&self, run: &RunContext, path: &Path, mut op: F) -> Result<Option<T>, asar_error::Error>
+    where
+        F: FnMut() -> Result<T, asar_error::Error>,
+    {
+        let mut attempts: u32 = 0;
+        let mut retries: u32 = 0;
+
+        loop {
+            if run.cancel.is_some_and(|token| token.load(Ordering::Relaxed)) {
+                return Err(Error::Cancelled);
+            }
+
+            attempts += 1;
+
+            let err = match op() {
+                Ok(value) => return Ok(Some(value)),
+                Err(err @ (Error::Cancelled | Error::TimedOut)) => return Err(err),
+                Err(err) => err,
+            };
+
+            if let Some(policy) = run.retry.filter(|policy| retries < policy.attempts && retry::is_transient(&err)) {
+                policy.wait(retries);
+                retries += 1;
+                continue;
+            }
+
+            let decision = match &self.on_error {
+                Some(hook) => hook(path, &err, attempts),
+                None => ErrorDecision::Abort,
+            };
+
+            let report = match (decision, &run.report) {
+                (ErrorDecision::Retry, _) => continue,
+                (ErrorDecision::Abort, None) => return Err(err),
+                (ErrorDecision::Skip, None) => return Ok(None),
+                (_, Some(report)) => report,
+            };
+
+            report.lock().unwrap_or_else(|err| err.into_inner()).failed.push((path.to_path_buf(), err));
+
+            return Ok(None);
+        }
+    }
+}
+
+impl Asar {
+
+    // Removes the files at the provided (ascending) indices of the list from the header, once the
+    // hooks skipped them while packing, moving every following file, and every copied entry, back
+    // as they were written. The start is kept, as the header only shrinks and is padded up to it.
+    pub(crate) fn relayout(&mut self, skipped: &[usize]) {
+        let align = self.align();
+
+        let (Some(header), Content::List(list)) = (&mut self.header, &mut self.content) else {
+            return;
+        };
+
+        // the new offset of every file by its full path within the archive, `None` once skipped,
+        // and by its previous offset for the hard links stored in place of it
+        let mut moved: HashMap<PathBuf, Option<u64>> = HashMap::with_capacity(list.len());
+        let mut shared: HashMap<u64, Option<u64>> = HashMap::new();
+        let mut kept: Vec<(PathBuf, u64)> = Vec::with_capacity(list.len());
+        let (mut previous, mut offset) = (0, 0);
+
+        for (index, (path, size)) in mem::take(list).into_iter().enumerate() {
+            let was = content::align_up(previous, align);
+            previous = was + size;
+
+            let placed = match skipped.binary_search(&index) {
+                Ok(_) => None,
+                Err(_) => {
+                    let placed = content::align_up(offset, align);
+                    offset = placed + size;
+                    Some(placed)
+                }
+            };
+
+            // empty files may share an offset, where a kept one wins
+            let link = shared.entry(was).or_insert(placed);
+            *link = link.or(placed);
+
+            moved.insert(path.strip_prefix(&self.src_path).unwrap_or(&path).to_path_buf(), placed);
+
+            if placed.is_some() {
+                kept.push((path, size));
+            }
+        }
+
+        // copied entries follow the files, aligned, so they all move back by the same amount
+        let shift = content::align_up(previous, align) - content::align_up(offset, align);
+
+        for copy in self.copies.iter_mut() {
+            copy.offset -= shift;
+            moved.insert(copy.path.clone(), Some(copy.offset));
+        }
+
+        if let Some(Value::Object(files)) = header.get_mut("files") {
+            move_offsets(files, Path::new(""), &moved, &shared);
+        }
+
+        *list = kept;
+    }
+}
+
+// Moves every file of the folder at the full path `dir_path` stored within the body to its new
+// offset (see `Asar::relayout`), removing those left out.
+fn move_offsets(
+    dir: &mut Map<String, Value>,
+    dir_path: &Path,
+    moved: &HashMap<PathBuf, Option<u64>>,
+    shared: &HashMap<u64, Option<u64>>,
+) {
+    dir.retain(|name, entry| {
+        let path = dir_path.join(name);
+
+        if let Some(Value::Object(files)) = entry.get_mut("files") {
+            move_offsets(files, &path, moved, shared);
+            return true;
+        }
+
+        let Some(item) = entry.as_object_mut().filter(|item| !content::is_external(item)) else {
+            return true;
+        };
+
+        let offset = item.get("offset").and_then(Value::as_str).and_then(|offset| offset.parse::<u64>().ok());
+
+        match moved.get(&path).or_else(|| offset.and_then(|offset| shared.get(&offset))) {
+            Some(Some(offset)) => {
+                item.insert("offset".to_string(), Value::String(offset.to_string()));
+                true
+            }
+            Some(None) => false,
+            None => true,
+        }
+    });
+}
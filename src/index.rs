@@ -0,0 +1,382 @@
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    content::{self, Content},
+    header::{self, ByteSource},
+    kinds,
+};
+
+/// A lightweight reference to an entry within an Asar archive file, as stored in its path index.
+///
+/// `File   (offset, size)` -> `File   (u64, u64)`
+///
+/// `Folder`                -> the archive root (empty path) or any folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryRef {
+    File(u64, u64),
+    Folder,
+}
+
+/// Maps the full path of every file and folder within an Asar archive file to its EntryRef.
+pub type PathIndex = HashMap<PathBuf, EntryRef>;
+
+/// Builds the path index of the provided Content, walking the header once.
+///
+/// Only Content::Home is indexed; other varients return an empty index.
+///
+/// Returns the index, otherwise the first Error found while parsing the header.
+pub fn build_index(content: &Content) -> Result<PathIndex, asar_error::Error> {
+    let mut errors: Vec<(PathBuf, asar_error::Error)> = Vec::new();
+    let index = build_index_lossy(content, &mut errors);
+
+    match errors.into_iter().next() {
+        Some((_, err)) => Err(err),
+        None => Ok(index),
+    }
+}
+
+/// Builds the path index of the provided Content, as does `build_index`, skipping every entry
+/// that can not be parsed.
+///
+/// Each skipped entry is pushed to `errors` as `(full_path, error)`.
+pub fn build_index_lossy(content: &Content, errors: &mut Vec<(PathBuf, asar_error::Error)>) -> PathIndex {
+    let mut index = PathIndex::new();
+
+    if let Content::Home(dir) = content {
+        index.insert(PathBuf::new(), EntryRef::Folder);
+        index_dir(dir, Path::new(""), &mut index, errors);
+    }
+
+    index
+}
+
+fn index_dir(
+    dir: &Map<String, Value>,
+    path: &Path,
+    index: &mut PathIndex,
+    errors: &mut Vec<(PathBuf, asar_error::Error)>,
+) {
+    for (name, value) in dir.iter() {
+        let full_path = path.join(name);
+
+        let item = match value {
+            Value::Object(item) => item,
+            _ => continue,
+        };
+
+        if let Some(kind) = kinds::find(item) {
+            if let Err(err) = kind.parse(&full_path, item) {
+                errors.push((full_path, err));
+            }
+            continue;
+        }
+
+        if content::is_external(item) {
+            continue;
+        }
+
+        // as does `content::lookahead`, without cloning every folder
+        match header::file_entry::<io::Error>(name, item) {
+            Ok(Some((offset, size))) => {
+                index.insert(full_path, EntryRef::File(offset, size));
+            }
+            Ok(None) => match item.get("files") {
+                Some(Value::Object(dir)) => {
+                    index_dir(dir, &full_path, index, errors);
+                    index.insert(full_path, EntryRef::Folder);
+                }
+                _ => errors.push((full_path, entity_error(name))),
+            },
+            Err(err) => errors.push((full_path, err.into())),
+        }
+    }
+}
+
+// The Error of an entry that is neither a file nor a folder.
+fn entity_error(name: &str) -> asar_error::Error {
+    Error::ParseHeaderError(format!("Error parsing header for entity: {}", name))
+}
+
+/// Builds the path index of an Asar archive file straight from the JSON of its header, as does
+/// `build_index` from its Content, without building the header as a `serde_json::Value` first.
+///
+/// Entries are indexed as they are parsed, so no tree of the header of a large archive (hundreds of
+/// thousands of entries) is built, only its JSON bytes are held, e.g. for services that only look
+/// files up. The `integrity` of every file is skipped, and custom entry kinds (see
+/// `kinds::EntryKind`) are given their entries without it.
+///
+/// This is opt-in: `Asar::open` still builds the header as a `serde_json::Value`, which its Content
+/// is read from, and indexes that.
+///
+/// Returns the index, otherwise the first Error found while parsing the header.
+pub fn index_from_json(json: &[u8]) -> Result<PathIndex, asar_error::Error> {
+    let mut stream = Stream { entries: vec![(PathBuf::new(), EntryRef::Folder)], error: None };
+    let mut deserializer = serde_json::Deserializer::from_slice(json);
+
+    let parsed = RootSeed(&mut stream)
+        .deserialize(&mut deserializer)
+        .and_then(|found| deserializer.end().map(|_| found));
+
+    match (parsed, stream.error) {
+        (_, Some(err)) => Err(err),
+        (Err(err), None) => Err(err.into()),
+        (Ok(false), None) => Err(Error::ParseHeaderError(
+            "'files' not found in Home directory".to_string(),
+        )),
+        (Ok(true), None) => Ok(stream.entries.into_iter().collect()),
+    }
+}
+
+/// Reads the header of an Asar archive file from the provided ByteSource (e.g. a `File`) and
+/// builds its path index with `index_from_json`.
+///
+/// Returns the index and the start offset (at which file content begins), otherwise Error.
+pub fn read_index<S>(source: &S) -> Result<(PathIndex, u64), asar_error::Error>
+where
+    S: ByteSource + ?Sized,
+    S::Error: Into<asar_error::Error>,
+{
+    let (json, start) = header::read_header_json(source).map_err(asar_error::Error::from)?;
+
+    Ok((index_from_json(&json)?, start))
+}
+
+// The state of `index_from_json`: every entry indexed so far, and the first Error of an entry.
+struct Stream {
+    entries: Vec<(PathBuf, EntryRef)>,
+    error: Option<asar_error::Error>,
+}
+
+impl Stream {
+
+    // Records the Error of an entry, returning the error aborting the parse.
+    fn fail<E: de::Error>(&mut self, err: asar_error::Error) -> E {
+        let message = err.to_string();
+        self.error.get_or_insert(err);
+
+        E::custom(message)
+    }
+}
+
+// The root of the header, whose `files` are indexed. Yields true if they were found.
+struct RootSeed<'a>(&'a mut Stream);
+
+impl<'de> DeserializeSeed<'de> for RootSeed<'_> {
+    type Value = bool;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RootSeed<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an Asar archive header")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<bool, A::Error> {
+        let mut found = false;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "files" => found = map.next_value_seed(DirSeed { stream: self.0, path: PathBuf::new() })?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+// The `files` of a folder at `path`, every entry of which is indexed. Yields true if they are an
+// object (anything else is skipped).
+struct DirSeed<'a> {
+    stream: &'a mut Stream,
+    path: PathBuf,
+}
+
+impl<'de> DeserializeSeed<'de> for DirSeed<'_> {
+    type Value = bool;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DirSeed<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the files of a folder")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<bool, A::Error> {
+        while let Some(name) = map.next_key::<String>()? {
+            let path = self.path.join(&name);
+            map.next_value_seed(EntrySeed { stream: self.stream, path, name })?;
+        }
+
+        Ok(true)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<bool, A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(false)
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<bool, E> {
+        Ok(false)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<bool, E> {
+        Ok(false)
+    }
+}
+
+// A file or folder named `name` at `path`, indexed as does `index_dir` (entries that are not
+// objects are skipped).
+struct EntrySeed<'a> {
+    stream: &'a mut Stream,
+    path: PathBuf,
+    name: String,
+}
+
+impl<'de> DeserializeSeed<'de> for EntrySeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntrySeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a header entry")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        // the entries of `files` are indexed as they are parsed, and dropped again unless this
+        // turns out to be a folder
+        let start = self.stream.entries.len();
+        let mut fields: Map<String, Value> = Map::new();
+        let mut folder: Option<bool> = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "files" => {
+                    let path = self.path.clone();
+                    folder = Some(map.next_value_seed(DirSeed { stream: self.stream, path })?);
+                }
+                "integrity" => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+                _ => {
+                    fields.insert(key, map.next_value::<Value>()?);
+                }
+            }
+        }
+
+        // custom kinds and external entries are never folders, and `files` marks a builtin entry
+        let kind = folder.is_none().then(|| kinds::find(&fields)).flatten();
+        let external = fields.get("unpacked").and_then(Value::as_bool).unwrap_or(false) || fields.contains_key("link");
+
+        if kind.is_some() || external {
+            self.stream.entries.truncate(start);
+
+            return match kind.map(|kind| kind.parse(&self.path, &fields)) {
+                Some(Err(err)) => Err(self.stream.fail(err)),
+                _ => Ok(()),
+            };
+        }
+
+        match header::file_entry::<io::Error>(&self.name, &fields) {
+            Ok(Some((offset, size))) => {
+                self.stream.entries.truncate(start);
+                self.stream.entries.push((self.path, EntryRef::File(offset, size)));
+                Ok(())
+            }
+            Ok(None) if folder == Some(true) => {
+                self.stream.entries.push((self.path, EntryRef::Folder));
+                Ok(())
+            }
+            Ok(None) => Err(self.stream.fail(entity_error(&self.name))),
+            Err(err) => Err(self.stream.fail(err.into())),
+        }
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(())
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+impl Asar {
+
+    /// Returns the path index of an opened Asar archive file, built when the archive was opened.
+    ///
+    /// The index is empty if a directory is opened.
+    pub fn index(&self) -> &PathIndex {
+        &self.index
+    }
+
+    /// Returns the EntryRef of the provided path in constant time, or `None` if it does not exist
+    /// within an opened Asar archive file.
+    pub fn stat<P: AsRef<Path>>(&self, path: P) -> Option<EntryRef> {
+        self.index.get(path.as_ref()).copied()
+    }
+}
@@ -0,0 +1,131 @@
+use std::cmp::Reverse;
+
+use serde_json::Value;
+
+use crate::{asar::Asar, asar_error};
+
+/// How strongly a piece of evidence pins down the Electron version.
+///
+/// - Low: A layout only shipped by a range of versions
+///
+/// - Medium: A declared requirement, such as a semver range within `package.json`
+///
+/// - High: An exact version recorded within the archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+/// A guess of the Electron version an archive was packaged for, as returned by `guess_electron_version`.
+///
+/// - version: The version (e.g. `28.1.0`), or the range of versions (e.g. `<7`) it was found to be within
+///
+/// - confidence: How strongly the evidence pins down the version
+///
+/// - evidence: Where the version was found (e.g. `package.json devDependencies.electron`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionGuess {
+    pub version: String,
+    pub confidence: Confidence,
+    pub evidence: String,
+}
+
+// Returns the version within a semver requirement, e.g. `28.1.0` from `^28.1.0`.
+fn requirement_version(requirement: &str) -> Option<String> {
+    let version = requirement
+        .trim()
+        .trim_start_matches(['^', '~', '>', '<', '=', 'v', ' '])
+        .split_whitespace()
+        .next()?;
+
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+// Reads and parses the JSON file at the provided full path, if any.
+fn read_json(asar: &Asar, path: &str) -> Option<Value> {
+    serde_json::from_slice(&asar.get_file(path)?).ok()
+}
+
+/// Inspects an opened Asar archive file for every hint of the Electron version it was packaged
+/// for, from the most to the least confident:
+///
+/// - `electronVersion` of the electron-builder configuration (`build` within `package.json`, see `Asar::package_json`)
+/// - `version` of a bundled `node_modules/electron/package.json`
+/// - `electron` within the `devDependencies`, `dependencies`, or `engines` of `package.json`
+/// - Layouts only shipped by a range of versions: Electron's own `electron.asar` (before 7),
+///   `electron-prebuilt` (before 1.3), and `@electron/remote` (10 and later)
+///
+/// Returns every guess found, sorted by descending confidence, otherwise Error if a directory is open.
+pub fn electron_version_hints(asar: &Asar) -> Result<Vec<VersionGuess>, asar_error::Error> {
+    if asar.src_path.is_dir() {
+        return Err(asar_error::Error::UnknownContentType(
+            "Asar archive file must be src_path".to_string(),
+        ));
+    }
+
+    let mut guesses: Vec<VersionGuess> = Vec::new();
+    let mut guess = |version: String, confidence: Confidence, evidence: &str| {
+        guesses.push(VersionGuess {
+            version,
+            confidence,
+            evidence: evidence.to_string(),
+        })
+    };
+
+    if let Some(manifest) = asar.package_json() {
+        if let Some(version) = manifest.pointer("/build/electronVersion").and_then(Value::as_str) {
+            guess(version.to_string(), Confidence::High, "package.json build.electronVersion");
+        }
+
+        for field in ["devDependencies", "dependencies", "engines"] {
+            let requirement = manifest.get(field).and_then(|deps| deps.get("electron")).and_then(Value::as_str);
+
+            if let Some(version) = requirement.and_then(requirement_version) {
+                guess(version, Confidence::Medium, &format!("package.json {}.electron", field));
+            }
+        }
+    }
+
+    if let Some(version) = read_json(asar, "node_modules/electron/package.json")
+        .and_then(|manifest| manifest.get("version")?.as_str().map(str::to_string))
+    {
+        guess(version, Confidence::High, "node_modules/electron/package.json");
+    }
+
+    if asar.is_file("browser/init.js") && asar.is_file("renderer/init.js") {
+        guess("<7".to_string(), Confidence::Low, "electron.asar layout");
+    }
+
+    if asar.is_dir("node_modules/electron-prebuilt") {
+        guess("<1.3".to_string(), Confidence::Low, "node_modules/electron-prebuilt");
+    }
+
+    if asar.is_dir("node_modules/@electron/remote") {
+        guess(">=10".to_string(), Confidence::Low, "node_modules/@electron/remote");
+    }
+
+    guesses.sort_by_key(|guess| Reverse(guess.confidence)); // stable, keeping the order above
+
+    Ok(guesses)
+}
+
+/// Returns the most confident guess of the Electron version an opened Asar archive file was
+/// packaged for (see `electron_version_hints`), or `None` if nothing hints at it.
+///
+/// ```no_run
+/// use rust_asar::{asar::Asar, inspect};
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// if let Some(guess) = inspect::guess_electron_version(&Asar::open("app.asar")?)? {
+///     println!("Electron {} ({:?}, from {})", guess.version, guess.confidence, guess.evidence);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn guess_electron_version(asar: &Asar) -> Result<Option<VersionGuess>, asar_error::Error> {
+    Ok(electron_version_hints(asar)?.into_iter().next())
+}
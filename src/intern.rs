@@ -0,0 +1,207 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    mem,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error,
+    header::ByteSource,
+    index::{self, EntryRef, PathIndex},
+};
+
+/// A set of interned file and folder names, shared by every CompactIndex built with it, so a
+/// name such as `index.js` or `package.json` is stored once however many archives hold it.
+///
+/// Clones share the same set.
+#[derive(Debug, Clone, Default)]
+pub struct NameInterner {
+    names: Arc<Mutex<HashSet<Arc<str>>>>,
+}
+
+impl NameInterner {
+
+    /// Returns an empty set of names.
+    pub fn new() -> NameInterner {
+        NameInterner::default()
+    }
+
+    /// Returns the interned copy of the name, interning it first if it is new.
+    pub fn intern(&self, name: &str) -> Arc<str> {
+        let mut names = self.names.lock().unwrap_or_else(|err| err.into_inner());
+
+        match names.get(name) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned: Arc<str> = Arc::from(name);
+                names.insert(interned.clone());
+
+                interned
+            }
+        }
+    }
+
+    /// Returns the number of distinct names interned.
+    pub fn len(&self) -> usize {
+        self.names.lock().unwrap_or_else(|err| err.into_inner()).len()
+    }
+
+    /// Returns true if no name is interned.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// A file or folder of a CompactIndex: its interned name, its EntryRef, and its children sorted by
+// name (as indices into the nodes).
+#[derive(Debug)]
+struct Node {
+    name: Arc<str>,
+    entry: EntryRef,
+    children: Vec<u32>,
+}
+
+/// A path index (see `index::PathIndex`) storing every file and folder once, as a tree of
+/// interned names (see NameInterner) rather than a full path per entry, e.g. for catalogs keeping
+/// the indices of many large archives in memory.
+///
+/// ```no_run
+/// use rust_asar::{asar::Asar, intern::NameInterner};
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let names = NameInterner::new();
+/// let indices = vec![
+///     Asar::open("app-1.0.asar")?.compact_index(&names),
+///     Asar::open("app-1.1.asar")?.compact_index(&names), // shares the names of 1.0
+/// ];
+///
+/// assert!(indices[1].get("package.json").is_some());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CompactIndex {
+    nodes: Vec<Node>,
+}
+
+impl CompactIndex {
+
+    /// Builds the CompactIndex of a path index, interning its names within the provided set.
+    pub fn new(index: &PathIndex, names: &NameInterner) -> CompactIndex {
+        let mut nodes: Vec<Node> = vec![Node { name: names.intern(""), entry: EntryRef::Folder, children: Vec::new() }];
+        let mut lookup: HashMap<(u32, Arc<str>), u32> = HashMap::with_capacity(index.len());
+
+        for (path, entry) in index.iter() {
+            let mut node = 0;
+
+            for name in components(path) {
+                let name = names.intern(&name);
+
+                node = *lookup.entry((node, name.clone())).or_insert_with(|| {
+                    // a folder until its own entry is set
+                    let child = nodes.len() as u32;
+                    nodes.push(Node { name, entry: EntryRef::Folder, children: Vec::new() });
+                    nodes[node as usize].children.push(child);
+
+                    child
+                });
+            }
+
+            nodes[node as usize].entry = *entry;
+        }
+
+        // sorted by name, for lookups by binary search
+        for i in 0..nodes.len() {
+            let mut children = mem::take(&mut nodes[i].children);
+            children.sort_by(|a, b| nodes[*a as usize].name.cmp(&nodes[*b as usize].name));
+            nodes[i].children = children;
+        }
+
+        CompactIndex { nodes }
+    }
+
+    // Returns the child of the node with the provided name.
+    fn child(&self, node: u32, name: &str) -> Option<u32> {
+        let children = &self.nodes[node as usize].children;
+
+        children
+            .binary_search_by(|child| (*self.nodes[*child as usize].name).cmp(name))
+            .ok()
+            .map(|found| children[found])
+    }
+
+    /// Returns the EntryRef of the provided full path, or `None` if it does not exist.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<EntryRef> {
+        let mut node = 0;
+
+        for name in components(path.as_ref()) {
+            node = self.child(node, &name)?;
+        }
+
+        Some(self.nodes[node as usize].entry)
+    }
+
+    /// Returns the number of files and folders, the root included, as does `PathIndex::len`.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the index holds no file or folder besides the root.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() <= 1
+    }
+
+    /// Returns the full path and EntryRef of every file and folder, as a PathIndex.
+    pub fn to_index(&self) -> PathIndex {
+        let mut index = PathIndex::with_capacity(self.nodes.len());
+        let mut pending: Vec<(u32, PathBuf)> = vec![(0, PathBuf::new())];
+
+        while let Some((node, path)) = pending.pop() {
+            let node = &self.nodes[node as usize];
+
+            for child in node.children.iter() {
+                pending.push((*child, path.join(&*self.nodes[*child as usize].name)));
+            }
+
+            index.insert(path, node.entry);
+        }
+
+        index
+    }
+}
+
+// Returns the names of the components of a full path within an archive.
+fn components(path: &Path) -> impl Iterator<Item = Cow<'_, str>> {
+    path.components().filter_map(|component| match component {
+        Component::Normal(name) => Some(name.to_string_lossy()),
+        _ => None,
+    })
+}
+
+/// Reads the header of an Asar archive file from the provided ByteSource as a stream (see
+/// `index::read_index`) and builds its CompactIndex.
+///
+/// Returns the index and the start offset (at which file content begins), otherwise Error.
+pub fn read_compact_index<S>(source: &S, names: &NameInterner) -> Result<(CompactIndex, u64), asar_error::Error>
+where
+    S: ByteSource + ?Sized,
+    S::Error: Into<asar_error::Error>,
+{
+    let (index, start) = index::read_index(source)?;
+
+    Ok((CompactIndex::new(&index, names), start))
+}
+
+impl Asar {
+
+    /// Builds the CompactIndex of an opened Asar archive file, interning its names within the
+    /// provided set (shared with the indices of other archives to store every name once).
+    ///
+    /// The index is empty if a directory is opened.
+    pub fn compact_index(&self, names: &NameInterner) -> CompactIndex {
+        CompactIndex::new(&self.index, names)
+    }
+}
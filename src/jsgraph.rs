@@ -0,0 +1,318 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt,
+    path::{Component, Path, PathBuf},
+    sync::OnceLock,
+};
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    index::EntryRef,
+    package::PACKAGE_JSON,
+};
+
+/// Extensions tried, in order, when a specifier omits one (as done by Node).
+const EXTENSIONS: [&str; 5] = ["js", "mjs", "cjs", "json", "node"];
+
+/// Modules provided by Node and Electron rather than the archive.
+const BUILTINS: [&str; 40] = [
+    "assert", "async_hooks", "buffer", "child_process", "cluster", "console", "constants", "crypto",
+    "dgram", "diagnostics_channel", "dns", "domain", "electron", "events", "fs", "http", "http2",
+    "https", "inspector", "module", "net", "os", "path", "perf_hooks", "process", "punycode",
+    "querystring", "readline", "repl", "stream", "string_decoder", "timers", "tls", "tty", "url",
+    "util", "v8", "vm", "worker_threads", "zlib",
+];
+
+// Matches `require('x')`, `import('x')`, `import ... from 'x'`, `import 'x'`, and `export ... from 'x'`.
+fn specifier_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r#"(?:\brequire|\bimport)\s*\(\s*['"]([^'"\n]+)['"]\s*\)|\b(?:import|export)\s+(?:[\w*${}\s,]+?\s+from\s+)?['"]([^'"\n]+)['"]"#,
+        )
+        .expect("valid specifier pattern")
+    })
+}
+
+/// Returns every module specifier required or imported by the provided script, in order of appearance.
+///
+/// Specifiers are found with a regular expression rather than a parser, so computed requires
+/// (e.g. `require(name)`) are missed, and specifiers within comments or strings are included.
+///
+/// ```
+/// use rust_asar::jsgraph;
+///
+/// let script = "const fs = require('fs');\nimport { a } from './a.js';\nexport * from \"./b\";";
+/// assert_eq!(jsgraph::specifiers(script), ["fs", "./a.js", "./b"]);
+/// ```
+pub fn specifiers(script: &str) -> Vec<String> {
+    specifier_pattern()
+        .captures_iter(script)
+        .filter_map(|captures| captures.get(1).or_else(|| captures.get(2)))
+        .map(|specifier| specifier.as_str().to_string())
+        .collect()
+}
+
+// Returns true if the specifier names a module of Node or Electron.
+fn is_builtin(specifier: &str) -> bool {
+    if specifier.starts_with("node:") {
+        return true;
+    }
+
+    let name = specifier.split('/').next().unwrap_or(specifier);
+    BUILTINS.contains(&name)
+}
+
+// Joins a specifier onto a folder, resolving `.` and `..` within the archive.
+fn join(folder: &Path, specifier: &str) -> PathBuf {
+    let mut path = folder.to_path_buf();
+
+    for component in Path::new(specifier).components() {
+        match component {
+            Component::Normal(name) => path.push(name),
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::RootDir => path = PathBuf::new(),
+            _ => {}
+        }
+    }
+
+    path
+}
+
+/// The files of an archive reachable from its entry points by following `require` and `import`
+/// specifiers, as returned by `Asar::reachability`.
+///
+/// - entries: Full paths of the scripts the walk started from
+///
+/// - reachable: Full paths of every file reached, including the `package.json` of every package
+///   resolved through its folder
+///
+/// - unreachable: `(full_file_path, size)` of every other file, sorted by path
+///
+/// - unresolved: `(full_file_path, specifier)` of every specifier that matched no file of the
+///   archive (other than the modules of Node and Electron)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Reachability {
+    pub entries: Vec<PathBuf>,
+    pub reachable: BTreeSet<PathBuf>,
+    pub unreachable: Vec<(PathBuf, u64)>,
+    pub unresolved: Vec<(PathBuf, String)>,
+}
+
+impl Reachability {
+
+    /// Returns the combined size in bytes of every unreachable file.
+    pub fn unreachable_size(&self) -> u64 {
+        self.unreachable.iter().map(|(_, size)| size).sum()
+    }
+}
+
+impl fmt::Display for Reachability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} reachable file(s), {} unreachable file(s) ({} bytes)",
+            self.reachable.len(),
+            self.unreachable.len(),
+            self.unreachable_size()
+        )?;
+
+        for (path, size) in self.unreachable.iter() {
+            writeln!(f, "  unreachable {:>10}  {}", size, path.display())?;
+        }
+
+        for (path, specifier) in self.unresolved.iter() {
+            writeln!(f, "  unresolved  {} in {}", specifier, path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Asar {
+
+    /// Walks the scripts of an opened Asar archive file from the main entry of the packaged
+    /// application (see `Asar::main_entry`), reporting which files are referenced, to suggest
+    /// dead files or bloat that could be removed.
+    ///
+    /// Files only loaded dynamically (e.g. HTML pages, assets, or computed requires) are reported
+    /// unreachable, so the report is a list of suggestions to review, not of files safe to delete.
+    ///
+    /// Returns the Reachability, otherwise Error if there is no main entry or a script can not be read.
+    pub fn reachability(&self) -> Result<Reachability, asar_error::Error> {
+        let main = self.main_entry().ok_or_else(|| {
+            Error::UnknownContentType("No package.json declaring a main entry".to_string())
+        })?;
+
+        let mut entries = vec![main];
+        entries.extend(self.package_json_path());
+
+        self.reachability_from(&entries)
+    }
+
+    /// Walks the scripts of an opened Asar archive file from the provided entry points, as does
+    /// `reachability`. Specifiers are resolved as done by Node: relative paths are tried as files
+    /// (with and without an extension) and folders (through the `main` of their `package.json`,
+    /// or `index`), and package names within every `node_modules` folder up to the root.
+    ///
+    /// Returns the Reachability, otherwise Error if an entry point is not a file or a script can not be read.
+    pub fn reachability_from<P: AsRef<Path>>(&self, entries: &[P]) -> Result<Reachability, asar_error::Error> {
+        let mut report = Reachability::default();
+        let mut queue: VecDeque<PathBuf> = VecDeque::new();
+
+        for entry in entries {
+            let entry = entry.as_ref().to_path_buf();
+
+            if !self.is_file(&entry) {
+                return Err(Error::UnknownContentType(format!(
+                    "Entry point not found within archive: {}",
+                    entry.display()
+                )));
+            }
+
+            if report.reachable.insert(entry.clone()) {
+                queue.push_back(entry.clone());
+            }
+
+            report.entries.push(entry);
+        }
+
+        while let Some(script) = queue.pop_front() {
+            if !matches!(script.extension().and_then(|ext| ext.to_str()), Some("js" | "mjs" | "cjs")) {
+                continue;
+            }
+
+            let content = self.get_file(&script).ok_or_else(|| {
+                Error::UnknownContentType(format!("{} can not be read", script.display()))
+            })?;
+
+            for specifier in specifiers(&String::from_utf8_lossy(&content)) {
+                if is_builtin(&specifier) {
+                    continue;
+                }
+
+                let mut found: Vec<PathBuf> = Vec::new();
+
+                match self.resolve(&script, &specifier, &mut found) {
+                    Some(resolved) => found.push(resolved),
+                    None => {
+                        report.unresolved.push((script.clone(), specifier));
+                        continue;
+                    }
+                }
+
+                for path in found {
+                    if report.reachable.insert(path.clone()) {
+                        queue.push_back(path);
+                    }
+                }
+            }
+        }
+
+        let sizes: BTreeMap<&PathBuf, u64> = self
+            .index
+            .iter()
+            .filter_map(|(path, entry)| match entry {
+                EntryRef::File(_, size) => Some((path, *size)),
+                EntryRef::Folder => None,
+            })
+            .collect();
+
+        report.unreachable = sizes
+            .into_iter()
+            .filter(|(path, _)| !report.reachable.contains(*path))
+            .map(|(path, size)| (path.clone(), size))
+            .collect();
+
+        Ok(report)
+    }
+
+    // Resolves a specifier imported by the script, pushing every package.json read along the way.
+    fn resolve(&self, script: &Path, specifier: &str, manifests: &mut Vec<PathBuf>) -> Option<PathBuf> {
+        let folder = script.parent().unwrap_or(Path::new(""));
+
+        if specifier.starts_with('.') || specifier.starts_with('/') {
+            return self.resolve_path(&join(folder, specifier), manifests);
+        }
+
+        // bare specifier, looked up within every node_modules folder up to the root
+        let mut current = Some(folder);
+
+        while let Some(dir) = current {
+            if dir.file_name().is_none_or(|name| name != "node_modules") {
+                let candidate = join(&dir.join("node_modules"), specifier);
+
+                if let Some(resolved) = self.resolve_path(&candidate, manifests) {
+                    return Some(resolved);
+                }
+            }
+
+            current = dir.parent();
+        }
+
+        None
+    }
+
+    // Resolves a path as a file, a file without its extension, or a folder.
+    fn resolve_path(&self, path: &Path, manifests: &mut Vec<PathBuf>) -> Option<PathBuf> {
+        if self.is_file(path) {
+            return Some(path.to_path_buf());
+        }
+
+        let with_extension = |path: &Path| {
+            EXTENSIONS.iter().find_map(|ext| {
+                let mut candidate = path.as_os_str().to_os_string();
+                candidate.push(".");
+                candidate.push(ext);
+
+                let candidate = PathBuf::from(candidate);
+                self.is_file(&candidate).then_some(candidate)
+            })
+        };
+
+        if let Some(found) = with_extension(path) {
+            return Some(found);
+        }
+
+        if !self.is_dir(path) {
+            return None;
+        }
+
+        let manifest_path = path.join(PACKAGE_JSON);
+
+        if self.is_file(&manifest_path) {
+            manifests.push(manifest_path.clone());
+
+            let main = self
+                .get_file(&manifest_path)
+                .and_then(|content| serde_json::from_slice::<Value>(&content).ok())
+                .and_then(|manifest| manifest.get("main")?.as_str().map(str::to_string));
+
+            if let Some(main) = main {
+                let main = join(path, &main);
+
+                if let Some(found) = self.resolve_file(&main, &with_extension) {
+                    return Some(found);
+                }
+            }
+        }
+
+        with_extension(&path.join("index"))
+    }
+
+    // Resolves the main of a package, as a file, a file without its extension, or a folder index.
+    fn resolve_file(&self, path: &Path, with_extension: &dyn Fn(&Path) -> Option<PathBuf>) -> Option<PathBuf> {
+        if self.is_file(path) {
+            return Some(path.to_path_buf());
+        }
+
+        with_extension(path).or_else(|| with_extension(&path.join("index")))
+    }
+}
@@ -0,0 +1,173 @@
+use std::{env, fmt, io, path::Path};
+
+use zeroize::Zeroize;
+
+use crate::asar_error::{self, Error};
+
+/// A key or passphrase, zeroed in memory once dropped and never printed by Debug.
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+
+    /// Wraps the bytes of a key or passphrase.
+    pub fn new<B: Into<Vec<u8>>>(bytes: B) -> Secret {
+        Secret(bytes.into())
+    }
+
+    /// Returns the bytes of the secret.
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret(..)")
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(secret: &str) -> Self {
+        Secret::new(secret)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(secret: String) -> Self {
+        Secret::new(secret)
+    }
+}
+
+impl From<Vec<u8>> for Secret {
+    fn from(secret: Vec<u8>) -> Self {
+        Secret::new(secret)
+    }
+}
+
+/// A source of the passphrase of encrypted archives (see `envelope::encrypt_file_with` and
+/// `Asar::open_encrypted_with`), so applications can plug in their secret management rather than
+/// passing raw key bytes around.
+///
+/// Implemented by StaticKey, EnvKey, KeyringKey (with the `keyring` feature), and by every closure
+/// `Fn(&Path) -> Result<Secret, Error>`, e.g. to call a KMS:
+///
+/// ```no_run
+/// use rust_asar::{asar::Asar, keys::Secret};
+///
+/// # fn fetch_from_vault(name: &str) -> Result<String, rust_asar::asar_error::Error> { unimplemented!() }
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let vault = |_archive: &std::path::Path| fetch_from_vault("resources").map(Secret::from);
+///
+/// let asar = Asar::open_encrypted_with("resources.asar.enc", &vault)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait KeyProvider: Send + Sync {
+
+    /// Returns the passphrase of the encrypted archive at the provided path (the archive written
+    /// when encrypting, or read when decrypting), e.g. to pick a key per archive.
+    fn secret(&self, archive: &Path) -> Result<Secret, asar_error::Error>;
+}
+
+impl<F> KeyProvider for F
+where
+    F: Fn(&Path) -> Result<Secret, asar_error::Error> + Send + Sync,
+{
+    fn secret(&self, archive: &Path) -> Result<Secret, asar_error::Error> {
+        self(archive)
+    }
+}
+
+/// A KeyProvider returning the same passphrase for every archive.
+#[derive(Debug)]
+pub struct StaticKey(Secret);
+
+impl StaticKey {
+
+    /// Creates a StaticKey holding the passphrase.
+    pub fn new<S: Into<Secret>>(secret: S) -> StaticKey {
+        StaticKey(secret.into())
+    }
+}
+
+impl KeyProvider for StaticKey {
+    fn secret(&self, _archive: &Path) -> Result<Secret, asar_error::Error> {
+        Ok(Secret::new(self.0.expose()))
+    }
+}
+
+/// A KeyProvider reading the passphrase from an environment variable whenever it is needed, e.g.
+/// a secret injected by CI.
+#[derive(Debug, Clone)]
+pub struct EnvKey {
+    var: String,
+}
+
+impl EnvKey {
+
+    /// Creates an EnvKey reading the environment variable with the provided name.
+    pub fn new<S: Into<String>>(var: S) -> EnvKey {
+        EnvKey { var: var.into() }
+    }
+}
+
+impl KeyProvider for EnvKey {
+    fn secret(&self, _archive: &Path) -> Result<Secret, asar_error::Error> {
+        match env::var_os(&self.var) {
+            Some(value) if !value.is_empty() => Ok(Secret::new(value.into_encoded_bytes())),
+            _ => Err(Error::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Environment variable {} is not set", self.var),
+            ))),
+        }
+    }
+}
+
+/// A KeyProvider reading the passphrase from the keyring of the OS (enabled by the `keyring`
+/// feature), stored as a generic password under a service and an account.
+///
+/// The keyring is read through the `keyring` crate, so no credential is cached by this crate and
+/// no external tool is run: the macOS Keychain, the Windows Credential Manager, or the Secret
+/// Service (e.g. GNOME Keyring or KWallet) on Linux. The password is returned as a Secret, zeroed
+/// once dropped.
+#[cfg(feature = "keyring")]
+#[derive(Debug, Clone)]
+pub struct KeyringKey {
+    service: String,
+    account: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringKey {
+
+    /// Creates a KeyringKey reading the password of the account within the service.
+    pub fn new<S: Into<String>, A: Into<String>>(service: S, account: A) -> KeyringKey {
+        KeyringKey {
+            service: service.into(),
+            account: account.into(),
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl KeyProvider for KeyringKey {
+    fn secret(&self, _archive: &Path) -> Result<Secret, asar_error::Error> {
+        let secret = keyring::Entry::new(&self.service, &self.account)
+            .and_then(|entry| entry.get_secret())
+            .map_err(|err| match err {
+                keyring::Error::NoEntry => Error::IoError(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No password in the keyring for service {}, account {}", self.service, self.account),
+                )),
+                err => Error::IoError(io::Error::other(format!("Failed to read the keyring: {}", err))),
+            })?;
+
+        Ok(Secret::new(secret))
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    content::{self, Content},
+};
+
+/// A custom kind of header entry, e.g. a URL fetched at runtime or a file generated on install,
+/// stored within the header only (it has no content within the body). Register it with `register`
+/// so its entries are routed to it rather than rejected as malformed.
+///
+/// Only entries that are neither files (`offset` and `size`), folders (`files`), unpacked files,
+/// nor links are offered to `matches`.
+///
+/// ```
+/// use std::path::Path;
+/// use serde_json::{Map, Value};
+/// use rust_asar::{asar_error::Error, kinds::{self, EntryKind}};
+///
+/// struct Remote;
+///
+/// impl EntryKind for Remote {
+///     fn name(&self) -> &str {
+///         "remote"
+///     }
+///
+///     fn matches(&self, entry: &Map<String, Value>) -> bool {
+///         entry.contains_key("url")
+///     }
+///
+///     fn extract(&self, _path: &Path, entry: &Map<String, Value>, destination: &Path) -> Result<(), Error> {
+///         Ok(std::fs::write(destination.with_extension("url"), entry["url"].to_string())?)
+///     }
+/// }
+///
+/// kinds::register(std::sync::Arc::new(Remote));
+/// ```
+pub trait EntryKind: Send + Sync {
+
+    /// Returns the name of the kind, used to replace or unregister it.
+    fn name(&self) -> &str;
+
+    /// Returns true if the header entry is of this kind.
+    fn matches(&self, entry: &Map<String, Value>) -> bool;
+
+    /// Validates an entry of this kind at the provided full path when an archive is opened, failing
+    /// the open if it returns Error. Accepts every entry by default.
+    fn parse(&self, _path: &Path, _entry: &Map<String, Value>) -> Result<(), asar_error::Error> {
+        Ok(())
+    }
+
+    /// Extracts an entry of this kind at the provided full path to `destination` (the path the
+    /// entry would be extracted to) when `Asar::extract` is called. Does nothing by default.
+    fn extract(&self, _path: &Path, _entry: &Map<String, Value>, _destination: &Path) -> Result<(), asar_error::Error> {
+        Ok(())
+    }
+
+    /// Called for every file and folder found while a directory is packed, with its path relative
+    /// to the directory and its full path on disk. Returning an entry (which this kind must match)
+    /// packs it in place of the file or folder. Returns `None` by default.
+    fn pack(&self, _path: &Path, _source: &Path) -> Result<Option<Map<String, Value>>, asar_error::Error> {
+        Ok(None)
+    }
+}
+
+// An entry of a custom kind `(full_path, entry, kind)`.
+type CustomEntry = (PathBuf, Map<String, Value>, Arc<dyn EntryKind>);
+
+// Kinds registered with `register`, offered every entry in order of registration.
+static REGISTRY: RwLock<Vec<Arc<dyn EntryKind>>> = RwLock::new(Vec::new());
+
+/// Registers a custom kind of header entry. A kind registered later under the same name replaces
+/// the earlier one.
+pub fn register(kind: Arc<dyn EntryKind>) {
+    let mut registry = REGISTRY.write().unwrap_or_else(|err| err.into_inner());
+
+    registry.retain(|registered| registered.name() != kind.name());
+    registry.push(kind);
+}
+
+/// Unregisters the custom kind with the provided name, returning true if it was registered.
+pub fn unregister(name: &str) -> bool {
+    let mut registry = REGISTRY.write().unwrap_or_else(|err| err.into_inner());
+    let len = registry.len();
+
+    registry.retain(|registered| registered.name() != name);
+    registry.len() != len
+}
+
+/// Returns the registered kind of the header entry, if it is an entry of a custom kind.
+pub fn find(entry: &Map<String, Value>) -> Option<Arc<dyn EntryKind>> {
+    let builtin = entry.contains_key("files")
+        || entry.contains_key("offset")
+        || entry.contains_key("link")
+        || entry.get("unpacked").and_then(Value::as_bool).unwrap_or(false);
+
+    if builtin {
+        return None;
+    }
+
+    let registry = REGISTRY.read().unwrap_or_else(|err| err.into_inner());
+
+    registry.iter().find(|kind| kind.matches(entry)).cloned()
+}
+
+// Returns the entry of a custom kind to pack in place of the file or folder at `source`, if any
+// registered kind claims it.
+pub(crate) fn pack_entry(path: &Path, source: &Path) -> Result<Option<Map<String, Value>>, asar_error::Error> {
+    let registry: Vec<Arc<dyn EntryKind>> = REGISTRY.read().unwrap_or_else(|err| err.into_inner()).clone();
+
+    for kind in registry.iter() {
+        if let Some(entry) = kind.pack(path, source)? {
+            if !kind.matches(&entry) || find(&entry).is_none() {
+                return Err(Error::ParseHeaderError(format!(
+                    "Entry kind {} packed {} as an entry it does not match",
+                    kind.name(),
+                    path.display()
+                )));
+            }
+
+            return Ok(Some(entry));
+        }
+    }
+
+    Ok(None)
+}
+
+// Pushes every entry of a custom kind within the folder at `path`.
+fn custom_dir(dir: &Map<String, Value>, path: &Path, found: &mut Vec<CustomEntry>) {
+    for (name, value) in dir.iter() {
+        let Value::Object(entry) = value else {
+            continue;
+        };
+
+        if let Some(Value::Object(folder)) = entry.get("files") {
+            custom_dir(folder, &path.join(name), found);
+        } else if let Some(kind) = find(entry) {
+            found.push((path.join(name), entry.clone(), kind));
+        }
+    }
+}
+
+impl Asar {
+
+    /// Returns the full path and kind name of every entry of a registered custom kind within an
+    /// opened Asar archive file (see `kinds::register`), in header order.
+    pub fn custom_entries(&self) -> Vec<(PathBuf, String)> {
+        self.custom()
+            .into_iter()
+            .map(|(path, _, kind)| (path, kind.name().to_string()))
+            .collect()
+    }
+
+    // Every entry of a custom kind, in header order.
+    fn custom(&self) -> Vec<CustomEntry> {
+        let mut found = Vec::new();
+
+        if let Content::Home(dir) = &self.content {
+            custom_dir(dir, Path::new(""), &mut found);
+        }
+
+        found
+    }
+
+    // Calls the extract handler of every entry of a custom kind, extracted below `destination`.
+    pub(crate) fn extract_custom(&self, destination: &Path) -> Result<(), asar_error::Error> {
+        for (path, entry, kind) in self.custom() {
+            kind.extract(&path, &entry, &content::checked_join(destination, &path)?)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use crate::content;
+
+/// Options used by `assign_offsets` to lay out the files of an archive body.
+///
+/// - align: Alignment of the offset of every file (0 or 1 for none, see `PackOptions::align`)
+///
+/// - sort: Orders files by full path; otherwise they keep the order they were provided in
+///
+/// - order: Ordering hints, full paths of files or folders placed first, in the order listed
+///   (e.g. files read at startup, so they are contiguous)
+///
+/// - unpacked: Full paths of files or folders left out of the body, to be shipped next to the archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayoutOptions {
+    pub align: u64,
+    pub sort: bool,
+    pub order: Vec<PathBuf>,
+    pub unpacked: Vec<PathBuf>,
+}
+
+impl LayoutOptions {
+
+    /// Returns true if the file at the provided full path is left out of the body.
+    pub fn is_unpacked<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.unpacked.iter().any(|prefix| path.as_ref().starts_with(prefix))
+    }
+
+    // Returns the position of the first ordering hint matching the path, after every hint if none does.
+    fn rank(&self, path: &Path) -> usize {
+        self.order
+            .iter()
+            .position(|hint| path.starts_with(hint))
+            .unwrap_or(self.order.len())
+    }
+}
+
+/// The place of a file within an archive body, as returned by `assign_offsets`.
+///
+/// - index: Position of the file within the entries provided to `assign_offsets`
+///
+/// - path: Full path of the file
+///
+/// - offset: Offset of the file within the body, `None` if it is unpacked
+///
+/// - size: Size of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub index: usize,
+    pub path: PathBuf,
+    pub offset: Option<u64>,
+    pub size: u64,
+}
+
+/// Assigns the offset of every file `(full_file_path, size)` within an archive body, as done by
+/// every writer (packing a directory, repacking, and copying entries between archives), so the
+/// same files and options always produce the same layout regardless of filesystem order.
+///
+/// Files are ordered by ordering hint, then by path if sorted (otherwise keeping their order),
+/// and laid out back to back, each aligned. Unpacked files are given no offset.
+///
+/// ```
+/// use std::path::PathBuf;
+/// use rust_asar::layout::{self, LayoutOptions};
+///
+/// let entries = vec![(PathBuf::from("b.js"), 10), (PathBuf::from("a.js"), 5), (PathBuf::from("main.js"), 3)];
+/// let options = LayoutOptions { align: 8, sort: true, order: vec![PathBuf::from("main.js")], ..LayoutOptions::default() };
+///
+/// let offsets: Vec<_> = layout::assign_offsets(&entries, &options).into_iter().map(|p| (p.path, p.offset)).collect();
+/// assert_eq!(offsets, [("main.js".into(), Some(0)), ("a.js".into(), Some(8)), ("b.js".into(), Some(16))]);
+/// ```
+///
+/// Returns the placement of every file, in body order with unpacked files last.
+pub fn assign_offsets(entries: &[(PathBuf, u64)], options: &LayoutOptions) -> Vec<Placement> {
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+
+    // stable, so files without a hint keep their order unless sorted
+    order.sort_by(|&a, &b| {
+        let (path_a, path_b) = (&entries[a].0, &entries[b].0);
+
+        options
+            .is_unpacked(path_a)
+            .cmp(&options.is_unpacked(path_b))
+            .then_with(|| options.rank(path_a).cmp(&options.rank(path_b)))
+            .then_with(|| if options.sort { path_a.cmp(path_b) } else { a.cmp(&b) })
+    });
+
+    let mut offset: u64 = 0;
+
+    order
+        .into_iter()
+        .map(|index| {
+            let (path, size) = &entries[index];
+
+            let offset = if options.is_unpacked(path) {
+                None
+            } else {
+                let placed = content::align_up(offset, options.align);
+                offset = placed + size;
+                Some(placed)
+            };
+
+            Placement {
+                index,
+                path: path.clone(),
+                offset,
+                size: *size,
+            }
+        })
+        .collect()
+}
@@ -1,24 +1,164 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
 
+
+
+#[cfg(feature = "std")]
 pub mod asar;
+#[cfg(feature = "std")]
 pub mod asar_error;
+#[cfg(feature = "std")]
+pub mod background;
+#[cfg(feature = "std")]
+pub mod budget;
+#[cfg(feature = "std")]
+pub mod cancel;
+#[cfg(feature = "std")]
+pub mod cas;
+#[cfg(feature = "catalog")]
+pub mod catalog;
+#[cfg(feature = "std")]
+pub mod chunk;
+#[cfg(feature = "std")]
+pub mod compat;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(feature = "std")]
 pub mod content;
-
-
-
-#[cfg(test)]
+#[cfg(feature = "std")]
+mod copy;
+#[cfg(feature = "std")]
+pub mod debug;
+#[cfg(feature = "std")]
+pub mod detect;
+#[cfg(feature = "std")]
+pub mod duplicates;
+#[cfg(feature = "encryption")]
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod etag;
+#[cfg(feature = "std")]
+pub mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod header;
+#[cfg(feature = "std")]
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod hashlist;
+#[cfg(feature = "std")]
+pub mod hooks;
+#[cfg(feature = "std")]
+pub mod index;
+#[cfg(feature = "std")]
+pub mod inspect;
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "jsgraph")]
+pub mod jsgraph;
+#[cfg(feature = "encryption")]
+pub mod keys;
+#[cfg(feature = "std")]
+pub mod kinds;
+#[cfg(feature = "std")]
+pub mod layout;
+#[cfg(feature = "std")]
+pub mod links;
+#[cfg(feature = "std")]
+pub mod lint;
+#[cfg(feature = "std")]
+pub mod locked;
+#[cfg(feature = "std")]
+pub mod memory;
+#[cfg(feature = "std")]
+pub mod meta;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod mime;
+#[cfg(feature = "std")]
+pub mod mode;
+#[cfg(feature = "std")]
+pub mod names;
+#[cfg(feature = "std")]
+pub mod nested;
+#[cfg(feature = "std")]
+pub mod outcome;
+#[cfg(feature = "std")]
+pub mod pack;
+#[cfg(feature = "std")]
+pub mod package;
+#[cfg(feature = "std")]
+pub mod prune;
+#[cfg(feature = "std")]
+pub mod reader;
+#[cfg(feature = "std")]
+pub mod recover;
+#[cfg(feature = "object-store")]
+pub mod remote;
+#[cfg(feature = "std")]
+mod repack;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod retry;
+#[cfg(feature = "std")]
+pub mod roundtrip;
+#[cfg(feature = "std")]
+pub mod salvage;
+#[cfg(feature = "std")]
+pub mod sbom;
+#[cfg(feature = "std")]
+pub mod scan;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "std")]
+pub mod set;
+#[cfg(feature = "std")]
+pub mod sink;
+#[cfg(feature = "std")]
+pub mod split;
+#[cfg(feature = "std")]
+pub mod stubs;
+#[cfg(feature = "std")]
+pub mod temp;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "std")]
+mod throttle;
+#[cfg(feature = "std")]
+pub mod timeout;
+#[cfg(feature = "std")]
+mod trace;
+#[cfg(feature = "std")]
+pub mod transform;
+#[cfg(feature = "std")]
+pub mod typed;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "std")]
+pub mod volume;
+#[cfg(feature = "std")]
+pub mod walk;
+
+
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use std::{
         fs::File,
-        io::{BufReader, Read},
-        path::Path,
+        io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
     };
 
     use byteorder::LittleEndian;
     use positioned_io::{ReadAt, ReadBytesExt};
     use serde_json::{Value};
 
-    use crate::{asar::Asar, content::Content, asar_error};
+    use crate::{asar::Asar, cas, chunk::{self, ChunkOptions}, compat::{Check, Corpus}, header, recover, content::{Content, Visitor}, asar_error, debug::ArtifactKind, detect::FileType, extract::{self, ExtractOptions, TimestampPolicy}, hash::{self, HashState, Hasher}, hashlist, index::EntryRef, inspect::{self, Confidence}, layout::{self, LayoutOptions}, lint::{Rule, RuleSet, Severity}, locked::{self, LockPolicy, LockRetry}, metrics::Counters, mime::MimeTable, mode::OpenMode, names::{self, CollisionPolicy, NamePolicy, Platform}, pack::{PackOptions, Packer, PolicyDecision, StreamPacker}, prune::Manifest, report::{GroupBy, ReportOptions}, roundtrip, salvage::EntryStatus, scan::ScanResult, search::{self, SearchOptions}, set::AsarSet, sink::EntrySink, split::{self, SplitRule}, stubs, temp::TempGuard, transform::TransformResult, typed::{AsarPacker, AsarReader}, verify::Verification, walk::WalkOptions};
 
     #[test]
     fn test_header_1() {
@@ -78,16 +218,16 @@ mod tests {
 
         if let Content::File(name, offset, size) = content {
             assert_eq!(name, Path::new("test1.txt").to_path_buf());
-            assert_eq!(offset, 30023 as u64); //uncertain if offset remains static at all times.
-            assert_eq!(size, 21 as u64);
+            assert_eq!(offset, 30023_u64); //uncertain if offset remains static at all times.
+            assert_eq!(size, 21_u64);
         }
 
         let content = dummy.find(Path::new("folder1/test_image.jpg")).unwrap();
 
         if let Content::File(name, offset, size) = content {
             assert_eq!(name, Path::new("test_image.jpg").to_path_buf());
-            assert_eq!(offset, 55 as u64);
-            assert_eq!(size, 29968 as u64);
+            assert_eq!(offset, 55_u64);
+            assert_eq!(size, 29968_u64);
         }
 
         let content = dummy.find(Path::new("folder1")).unwrap();
@@ -125,7 +265,7 @@ mod tests {
 
         let mut file = File::open("test_header.json").unwrap();
         let mut buf: Vec<u8> = vec![0; file.metadata().unwrap().len() as usize];
-        file.read(&mut buf).unwrap();
+        file.read_exact(&mut buf).unwrap();
 
         assert_eq!(value, serde_json::from_slice::<Value>(&buf).unwrap());
         assert_eq!(start, 796);
@@ -157,7 +297,7 @@ mod tests {
         { // test get_file()
             let mut file = File::open("test_folder/folder1/test_image.jpg").unwrap();
             let mut buf: Vec<u8> = vec![0; file.metadata().unwrap().len() as usize];
-            file.read(&mut buf).unwrap();
+            file.read_exact(&mut buf).unwrap();
 
             assert_eq!(asar.get_file(Path::new("folder1/test_image.jpg")).unwrap(), buf);
             assert!(asar.get_file("folder1").is_none());
@@ -173,14 +313,14 @@ mod tests {
             let test = {
                 let mut f = File::open(Path::new("test_extract").join(file)).unwrap();
                 let mut buf: Vec<u8> = vec![0; f.metadata().unwrap().len() as usize];
-                f.read(&mut buf).unwrap();
+                f.read_exact(&mut buf).unwrap();
                 buf
             };
 
             let correct = {
                 let mut f = File::open(Path::new("test_folder").join(file)).unwrap();
                 let mut buf: Vec<u8> = vec![0; f.metadata().unwrap().len() as usize];
-                f.read(&mut buf).unwrap();
+                f.read_exact(&mut buf).unwrap();
                 buf
             };
 
@@ -197,11 +337,11 @@ mod tests {
 
         assert!(asar.is_ok());
 
-        let _ = asar.unwrap().pack("test_pack.asar").unwrap();
+        asar.unwrap().pack("test_pack.asar").unwrap();
 
         let asar = Asar::open("test_pack.asar")?;
 
-        let _ = asar.extract("test_generated_folder")?;
+        asar.extract("test_generated_folder")?;
 
         {
             let mut file = File::open("test_folder/test1.txt")?;
@@ -238,7 +378,2886 @@ mod tests {
 
         Ok(())
     }
-}
 
-// TODO:
-// - change path args to AsRef<Path> from &Path
+    #[test]
+    fn test_search() -> Result<(), asar_error::Error> { // tests searching file contents inside archive
+        let asar = Asar::open("test_asar.asar")?;
+
+        let matches = asar.search("hello", &SearchOptions::default())?;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, Path::new("folder1/script.py").to_path_buf());
+        assert_eq!(matches[0].line, 3);
+        assert_eq!(matches[0].offset, 20); // of the match, not of its line
+        assert_eq!(matches[0].text, "print(\"hello world\")");
+
+        let options = SearchOptions { case_insensitive: true, ..Default::default() };
+        assert_eq!(asar.search("THIS IS", &options)?.len(), 2);
+        assert!(asar.search("THIS IS", &SearchOptions::default())?.is_empty());
+
+        let options = SearchOptions { max_matches: Some(1), ..Default::default() };
+        assert_eq!(asar.search("is", &options)?.len(), 1);
+        let options = SearchOptions { max_matches: Some(0), ..Default::default() };
+        assert!(asar.search("is", &options)?.is_empty());
+
+        let options = SearchOptions { regex: true, ..Default::default() };
+        if cfg!(feature = "regex") {
+            assert_eq!(asar.search("^print\\(.*\\)$", &options)?.len(), 1);
+        } else {
+            assert!(asar.search("^print", &options).is_err());
+        }
+
+        // a line longer than MAX_LINE_LEN is searched in pieces, each keeping the line number
+        let long = [vec![b'a'; search::MAX_LINE_LEN + 10], b"needle\nnext needle\n".to_vec()].concat();
+        let mut packer = Packer::new();
+        packer.add_reader("long.txt", Box::new(std::io::Cursor::new(long.clone())), long.len() as u64)?;
+        packer.write("test_search.asar")?;
+        let matches = Asar::open("test_search.asar")?.search("needle", &SearchOptions::default())?;
+        assert_eq!(matches.iter().map(|m| (m.line, m.offset)).collect::<Vec<_>>(), vec![(1, search::MAX_LINE_LEN as u64 + 10), (2, search::MAX_LINE_LEN as u64 + 22)]);
+        assert_eq!(matches[0].text, "aaaaaaaaaaneedle");
+
+        std::fs::remove_file("test_search.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_types() -> Result<(), asar_error::Error> { // tests classifying files by signature
+        let asar = Asar::open("test_asar.asar")?;
+        let types = asar.detect_types()?;
+
+        assert!(types.contains(&(Path::new("folder1/test_image.jpg").to_path_buf(), FileType::Jpeg)));
+        assert!(types.contains(&(Path::new("folder1/script.py").to_path_buf(), FileType::Text)));
+        assert!(types.contains(&(Path::new("test1.txt").to_path_buf(), FileType::Text)));
+        assert!(!types.iter().any(|(_, t)| t.is_native_binary()));
+
+        assert_eq!(FileType::from_bytes(b"\x7fELF\x02\x01"), FileType::Elf);
+        assert_eq!(FileType::from_bytes(b"#!/usr/bin/env node\nconsole.log(1)"), FileType::Script("/usr/bin/env node".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_policy() -> Result<(), asar_error::Error> { // tests policy enforcement while opening a directory
+        let options = PackOptions::new().policy(|path, _| {
+            if path.extension().is_some_and(|ext| ext == "py") {
+                PolicyDecision::Skip
+            } else {
+                PolicyDecision::Allow
+            }
+        });
+
+        let list = Asar::open_with("test_folder", &options)?.content;
+        if let Content::List(files) = list {
+            assert_eq!(files.len(), 3);
+            assert!(!files.iter().any(|(path, _)| path.ends_with("script.py")));
+        }
+
+        let options = PackOptions::new().policy(|_, size| {
+            if size > 1000 {
+                PolicyDecision::Reject("over 1000 bytes".to_string())
+            } else {
+                PolicyDecision::Allow
+            }
+        });
+
+        match Asar::open_with("test_folder", &options) {
+            Err(asar_error::Error::PolicyViolation(violations)) => {
+                assert_eq!(violations, vec![(Path::new("folder1/test_image.jpg").to_path_buf(), "over 1000 bytes".to_string())]);
+            }
+            _ => panic!("expected policy violation"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cas() -> Result<(), asar_error::Error> { // tests exporting to and packing from a content-addressable store
+        let asar = Asar::open("test_asar.asar")?;
+        let manifest = asar.export_cas("test_cas")?;
+
+        let files = manifest["files"].as_object().unwrap();
+        assert_eq!(files.len(), 3);
+
+        let hash = files["test1.txt"].as_str().unwrap();
+        assert_eq!(hash, cas::sha256_hex(File::open("test_folder/test1.txt")?)?);
+
+        let mut buf: Vec<u8> = Vec::new();
+        File::open(cas::object_path("test_cas", hash))?.read_to_end(&mut buf)?;
+        assert_eq!(buf, asar.get_file("test1.txt").unwrap());
+
+        let written: Value = serde_json::from_reader(File::open("test_cas/manifest.json")?)?;
+        assert_eq!(written, manifest);
+
+        // reconstruct archive from the store
+        Asar::pack_from_manifest(&written, "test_cas", "test_cas.asar")?;
+
+        let rebuilt = Asar::open("test_cas.asar")?;
+        assert_eq!(rebuilt.list()?, asar.list()?);
+        assert_eq!(rebuilt.get_file("folder1/test_image.jpg"), asar.get_file("folder1/test_image.jpg"));
+
+        // hashes that are not digests are rejected before any path is built from them
+        for hash in ["../../../Cargo.toml", "é", &hash.to_uppercase(), &hash[..62]] {
+            let crafted = serde_json::json!({ "algorithm": "SHA256", "files": { "test1.txt": hash } });
+            let err = Asar::pack_from_manifest(&crafted, "test_cas", "test_cas.asar").unwrap_err();
+            assert!(err.to_string().contains("Expected SHA256 hex digest"), "{}", err);
+        }
+
+        assert_eq!(cas::object_path("test_cas", "é1"), Path::new("test_cas/objects/é1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_lines_chunks() -> Result<(), asar_error::Error> { // tests streaming files from archive
+        let asar = Asar::open("test_asar.asar")?;
+
+        let lines = asar.read_lines("folder1/script.py")?.collect::<Result<Vec<String>, _>>()?;
+        assert_eq!(lines, vec!["import test", "", "print(\"hello world\")", "", "# this is a comment"]);
+
+        let chunks = asar.read_chunks("folder1/test_image.jpg", 1000)?.collect::<Result<Vec<Vec<u8>>, _>>()?;
+        assert_eq!(chunks.len(), 30);
+        assert_eq!(chunks[29].len(), 968);
+        assert_eq!(chunks.concat(), asar.get_file("folder1/test_image.jpg").unwrap());
+
+        assert!(asar.read_lines("folder1").is_err());
+        assert!(asar.read_chunks("missing.txt", 10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_body_reader() -> Result<(), asar_error::Error> { // tests reading the archive body as a whole
+        let asar = Asar::open("test_asar.asar")?;
+        let mut body = asar.body_reader()?;
+
+        let mut buf: Vec<u8> = Vec::new();
+        body.read_to_end(&mut buf)?;
+        assert_eq!(buf.len(), 30044);
+
+        body.seek(SeekFrom::Start(30023))?;
+        let mut line = String::new();
+        body.read_line(&mut line)?;
+        assert_eq!(line, "This is a test file.\n");
+
+        body.seek(SeekFrom::End(-21))?;
+        let mut buf: Vec<u8> = Vec::new();
+        body.read_to_end(&mut buf)?;
+        assert_eq!(buf, asar.get_file("test1.txt").unwrap());
+
+        assert!(Asar::open("test_folder")?.body_reader().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_packer_reserve() -> Result<(), asar_error::Error> { // tests writing reserved slots after packing
+        let bundle = b"console.log(\"bundled\");\n";
+
+        let mut packer = Packer::new();
+        packer.add_file("test1.txt", "test_folder/test1.txt")?;
+        packer.reserve("dist/bundle.js", bundle.len() as u64)?;
+        packer.add_file("dist/script.py", "test_folder/folder1/script.py")?;
+        assert!(packer.reserve("test1.txt", 1).is_err());
+
+        // a file can not replace a folder added before it, nor a folder a file
+        assert!(packer.reserve("dist", 4).is_err());
+        assert!(packer.add_file("test1.txt/nested.txt", "test_folder/test1.txt").is_err());
+        assert!(packer.header()["files"]["dist"]["files"].get("bundle.js").is_some());
+
+        let archive = packer.write("test_packer.asar")?;
+
+        let mut slot = archive.slot("dist/bundle.js")?;
+        slot.write_all(&bundle[..10])?;
+        slot.write_all(&bundle[10..])?;
+        assert!(slot.write_all(b"!").is_err());
+        slot.finish()?;
+
+        assert!(archive.slot("test1.txt").is_err());
+
+        let asar = Asar::open("test_packer.asar")?;
+        assert_eq!(asar.get_file("dist/bundle.js").unwrap(), bundle);
+        assert_eq!(asar.get_file("test1.txt").unwrap(), b"This is a test file.\n");
+        assert_eq!(asar.get_file("dist/script.py"), Asar::open("test_asar.asar")?.get_file("folder1/script.py"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_stream() -> Result<(), asar_error::Error> { // tests packing from readers
+        let entries: Vec<(PathBuf, Box<dyn Read>, u64)> = vec![
+            (PathBuf::from("a.txt"), Box::new(&b"first"[..]), 5),
+            (PathBuf::from("nested/b.txt"), Box::new(File::open("test_folder/test1.txt")?), 21),
+        ];
+
+        Asar::pack_stream(entries, "test_stream.asar")?;
+
+        let asar = Asar::open("test_stream.asar")?;
+        assert_eq!(asar.get_file("a.txt").unwrap(), b"first");
+        assert_eq!(asar.get_file("nested/b.txt").unwrap(), b"This is a test file.\n");
+
+        let short: Vec<(PathBuf, Box<dyn Read>, u64)> = vec![(PathBuf::from("a.txt"), Box::new(&b"abc"[..]), 5)];
+        assert!(Asar::pack_stream(short, "test_stream_short.asar").is_err());
+
+        let long: Vec<(PathBuf, Box<dyn Read>, u64)> = vec![(PathBuf::from("a.txt"), Box::new(&b"abcdef"[..]), 5)];
+        assert!(Asar::pack_stream(long, "test_stream_long.asar").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_packer() -> Result<(), asar_error::Error> { // tests two-pass packing with unknown sizes
+        for capacity in [0, 4096] {
+            let dest = format!("test_stream_packer_{}.asar", capacity);
+            let mut packer = StreamPacker::create(&dest, capacity)?;
+
+            assert_eq!(packer.add("a.txt", &b"generated"[..])?, 9);
+            packer.add("folder1/test_image.jpg", File::open("test_folder/folder1/test_image.jpg")?)?;
+            assert!(packer.add("a.txt", &b"replaced"[..]).is_err());
+            assert!(packer.add("a.txt/nested.txt", &b"nested"[..]).is_err());
+            assert_eq!(packer.add("b.txt", &b"after"[..])?, 5); // written where the rejected files were not
+
+            let start = packer.finish()?;
+
+            let asar = Asar::open(&dest)?;
+            assert_eq!(asar.start, start);
+            assert_eq!(asar.get_file("a.txt").unwrap(), b"generated");
+            assert_eq!(asar.get_file("b.txt").unwrap(), b"after");
+            assert_eq!(asar.get_file("folder1/test_image.jpg"), Asar::open("test_asar.asar")?.get_file("folder1/test_image.jpg"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stat() -> Result<(), asar_error::Error> { // tests constant-time lookups within the path index
+        let asar = Asar::open("test_asar.asar")?;
+
+        assert_eq!(asar.stat("folder1/test_image.jpg"), Some(EntryRef::File(55, 29968)));
+        assert_eq!(asar.stat("folder1"), Some(EntryRef::Folder));
+        assert_eq!(asar.stat(""), Some(EntryRef::Folder)); // the root
+        assert_eq!(asar.stat("missing.txt"), None);
+        assert_eq!(asar.stat("folder1/missing.txt"), None);
+
+        // entries stored outside of the body are not indexed
+        let header = serde_json::json!({ "files": {
+            "index.js": { "offset": "0", "size": 4 },
+            "native.node": { "size": 8, "unpacked": true },
+            "current": { "link": "index.js" }
+        } });
+
+        let mut bytes: Vec<u8> = Vec::new();
+        Asar::write_header(&mut bytes, &header)?;
+        bytes.extend(b"main");
+        std::fs::write("test_stat.asar", &bytes)?;
+
+        let asar = Asar::open("test_stat.asar")?;
+        assert_eq!(asar.stat("index.js"), Some(EntryRef::File(0, 4)));
+        assert_eq!(asar.stat("native.node"), None);
+        assert_eq!(asar.stat("current"), None);
+        assert_eq!(asar.index().len(), 2);
+
+        // nor is an opened directory
+        assert_eq!(Asar::open("test_folder")?.stat("test1.txt"), None);
+
+        std::fs::remove_file("test_stat.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_exists() -> Result<(), asar_error::Error> { // tests existence checks
+        let asar = Asar::open("test_asar.asar")?;
+
+        assert!(asar.contains("folder1") && asar.is_dir("folder1") && !asar.is_file("folder1"));
+        assert!(asar.contains("folder1/script.py") && asar.is_file("folder1/script.py") && !asar.is_dir("folder1/script.py"));
+        assert!(asar.contains("") && asar.is_dir(""));
+        assert!(!asar.contains("folder1/missing.py") && !asar.is_file("missing") && !asar.is_dir("missing"));
+
+        assert_eq!(asar.index().len(), 5);
+        assert_eq!(asar.stat("test1.txt"), Some(EntryRef::File(30023, 21)));
+        assert_eq!(asar.stat("folder1/"), Some(EntryRef::Folder));
+        assert_eq!(asar.stat("folder1/script.py/x"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_dir() -> Result<(), asar_error::Error> { // tests listing immediate children of a folder
+        let asar = Asar::open("test_asar.asar")?;
+
+        let root = asar.read_dir("")?;
+        assert_eq!(root.iter().map(|e| e.name.as_str()).collect::<Vec<&str>>(), vec!["folder1", "test1.txt"]);
+        assert!(root[0].is_dir() && root[1].is_file());
+        assert_eq!(root[1].size(), 21);
+
+        let folder = asar.read_dir("folder1")?;
+        assert_eq!(folder.len(), 2);
+        assert_eq!(folder[0].path, Path::new("folder1/script.py").to_path_buf());
+        assert_eq!(folder[1].kind, EntryRef::File(55, 29968));
+
+        assert!(asar.read_dir("test1.txt").is_err());
+        assert!(asar.read_dir("missing").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk() -> Result<(), asar_error::Error> { // tests limited traversal
+        let asar = Asar::open("test_asar.asar")?;
+
+        let all = asar.walk()?;
+        let paths = all.iter().map(|e| e.path.clone()).collect::<Vec<PathBuf>>();
+        assert_eq!(paths, asar.content.paths_to_vec()?);
+
+        let options = WalkOptions { max_depth: Some(1), ..Default::default() };
+        assert_eq!(asar.walk_with(&options)?.len(), 2);
+
+        let options = WalkOptions { prefix: Some(PathBuf::from("folder1")), ..Default::default() };
+        let subtree = asar.walk_with(&options)?;
+        assert_eq!(subtree.len(), 2);
+        assert!(subtree.iter().all(|e| e.path.starts_with("folder1") && e.is_file()));
+
+        let options = WalkOptions { prefix: Some(PathBuf::from("test1.txt")), ..Default::default() };
+        assert!(asar.walk_with(&options).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_preallocate() -> Result<(), asar_error::Error> { // tests reserving the destination archive before packing
+        let file = File::create("test_preallocate.asar")?;
+        crate::asar::preallocate(&file, 4096)?;
+        assert_eq!(file.metadata()?.len(), 4096);
+
+        // the reserved length is exactly what is written, even over a longer existing destination
+        std::fs::write("test_preallocate.asar", vec![0xff; 1 << 16])?;
+
+        let dir = Asar::open("test_folder")?;
+        dir.pack("test_preallocate.asar")?;
+        assert_eq!(std::fs::metadata("test_preallocate.asar")?.len(), dir.packed_size_estimate()?);
+
+        let asar = Asar::open("test_preallocate.asar")?;
+        assert_eq!(asar.get_file("test1.txt"), Some(std::fs::read("test_folder/test1.txt")?));
+
+        std::fs::remove_file("test_preallocate.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_packed_size_estimate() -> Result<(), asar_error::Error> { // tests estimating the packed size
+        let asar = Asar::open("test_asar.asar")?;
+        assert_eq!(asar.packed_size_estimate()?, File::open("test_asar.asar")?.metadata()?.len());
+
+        let dir = Asar::open("test_folder")?;
+        let estimate = dir.packed_size_estimate()?;
+        dir.pack("test_estimate.asar")?;
+        assert_eq!(estimate, File::open("test_estimate.asar")?.metadata()?.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_align() -> Result<(), asar_error::Error> { // tests aligned file bodies
+        let dir = Asar::open_with("test_folder", &PackOptions::new().align(4096))?;
+        assert_eq!(dir.start % 4096, 0);
+
+        let estimate = dir.packed_size_estimate()?;
+        dir.pack("test_align.asar")?;
+        assert_eq!(estimate, File::open("test_align.asar")?.metadata()?.len());
+
+        let asar = Asar::open("test_align.asar")?;
+        assert_eq!(asar.start % 4096, 0);
+
+        for (path, offset, _) in asar.content.files_to_vec()? {
+            assert_eq!(offset % 4096, 0);
+
+            let mut buf: Vec<u8> = Vec::new();
+            File::open(Path::new("test_folder").join(&path))?.read_to_end(&mut buf)?;
+            assert_eq!(asar.get_file(&path).unwrap(), buf);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_padded_archive() -> Result<(), asar_error::Error> { // tests reading archives with padding and gaps
+        let header: Value = serde_json::from_str(r#"{"files":{"a.txt":{"size":3,"offset":"5"},"b":{"files":{"c.txt":{"size":2,"offset":"64"}}}}}"#)?;
+
+        // header padded with null bytes counted in the JSON length, then padding before the body
+        let mut json_u8 = serde_json::to_vec(&header)?;
+        json_u8.resize(json_u8.len() + 3, 0);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        let start = (16 + json_u8.len() + 29) as u32;
+        bytes.extend_from_slice(&4_u32.to_le_bytes());
+        bytes.extend_from_slice(&(start - 8).to_le_bytes());
+        bytes.extend_from_slice(&(start - 12).to_le_bytes());
+        bytes.extend_from_slice(&(json_u8.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&json_u8);
+        bytes.resize(start as usize, 0xAA);
+
+        let mut body = vec![0xBB_u8; 66];
+        body[5..8].copy_from_slice(b"abc");
+        body[64..66].copy_from_slice(b"de");
+        bytes.extend_from_slice(&body);
+
+        std::fs::write("test_padded.asar", &bytes)?;
+
+        let asar = Asar::open("test_padded.asar")?;
+        assert_eq!(asar.start, start as u64);
+        assert_eq!(asar.get_file("a.txt").unwrap(), b"abc");
+        assert_eq!(asar.get_file("b/c.txt").unwrap(), b"de");
+
+        asar.extract("test_padded_extract")?;
+        assert_eq!(std::fs::read("test_padded_extract/b/c.txt")?, b"de");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_salvage() -> Result<(), asar_error::Error> { // tests recovering a truncated archive
+        let mut bytes = std::fs::read("test_asar.asar")?;
+        bytes.truncate(796 + 55 + 100); // ends within folder1/test_image.jpg
+        std::fs::write("test_truncated.asar", &bytes)?;
+
+        let (asar, report) = Asar::open_salvage("test_truncated.asar")?;
+        assert!(!report.is_intact());
+        assert_eq!(report.entries, vec![
+            (PathBuf::from("folder1/script.py"), EntryStatus::Intact),
+            (PathBuf::from("folder1/test_image.jpg"), EntryStatus::Truncated(100)),
+            (PathBuf::from("test1.txt"), EntryStatus::Missing),
+        ]);
+        assert!(asar.get_file("folder1/script.py").is_some());
+        assert!(asar.get_file("test1.txt").is_none());
+
+        let extracted = asar.extract_salvage("test_salvage")?;
+        assert_eq!(extracted, report);
+        assert_eq!(std::fs::read("test_salvage/folder1/script.py")?, std::fs::read("test_folder/folder1/script.py")?);
+        assert_eq!(std::fs::read("test_salvage/folder1/test_image.jpg")?.len(), 100);
+        assert!(!Path::new("test_salvage/test1.txt").exists());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing() -> Result<(), asar_error::Error> { // tests the spans and events emitted with the tracing feature
+        use std::sync::{Arc, Mutex, atomic::{AtomicU64, Ordering}};
+        use tracing::{Event, Metadata, field::{Field, Visit}, span::{Attributes, Id, Record}};
+
+        // records the name of every span and the message of every event
+        #[derive(Clone, Default)]
+        struct Recorder {
+            spans: Arc<Mutex<Vec<&'static str>>>,
+            events: Arc<Mutex<Vec<String>>>,
+            next: Arc<AtomicU64>,
+        }
+
+        struct Message<'a>(&'a mut String);
+
+        impl Visit for Message<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    *self.0 = format!("{:?}", value);
+                }
+            }
+        }
+
+        impl tracing::Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                self.spans.lock().unwrap().push(span.metadata().name());
+                Id::from_u64(self.next.fetch_add(1, Ordering::Relaxed) + 1)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut message = String::new();
+                event.record(&mut Message(&mut message));
+                self.events.lock().unwrap().push(message);
+            }
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        let recorder = Recorder::default();
+
+        tracing::subscriber::with_default(recorder.clone(), || -> Result<(), asar_error::Error> {
+            Asar::open("test_folder")?.pack("test_tracing.asar")?;
+            Asar::open("test_tracing.asar")?.extract("test_tracing")
+        })?;
+
+        assert_eq!(*recorder.spans.lock().unwrap(), ["open", "pack", "open", "extract"]);
+
+        let events = recorder.events.lock().unwrap();
+        let count = |message: &str| events.iter().filter(|event| *event == message).count();
+        let files = Asar::open("test_tracing.asar")?.content.files_to_vec()?.len();
+
+        assert_eq!(count("packed file"), files);
+        assert_eq!(count("packed archive"), 1);
+        assert_eq!(count("extracted file"), files);
+
+        std::fs::remove_file("test_tracing.asar")?;
+        std::fs::remove_dir_all("test_tracing")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_body() -> Result<(), asar_error::Error> { // tests carving files from an archive without header
+        let mut bytes = std::fs::read("test_asar.asar")?;
+        bytes[..796].fill(0); // destroy header
+        bytes.extend_from_slice(b"\0#!/usr/bin/env node\nconsole.log(1)\n");
+        std::fs::write("test_headerless.asar", &bytes)?;
+
+        let carves = recover::scan_body(&File::open("test_headerless.asar")?)?;
+
+        assert_eq!(carves.len(), 2);
+        assert_eq!((carves[0].offset, carves[0].len, &carves[0].kind), (796 + 55, 29968, &FileType::Jpeg));
+        assert_eq!(carves[1].kind, FileType::Script("/usr/bin/env node".to_string()));
+        assert_eq!(carves[1].offset + carves[1].len, bytes.len() as u64);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_metrics() -> Result<(), asar_error::Error> { // tests counting files and bytes read
+        let counters = Arc::new(Counters::default());
+        let mut asar = Asar::open("test_asar.asar")?;
+        asar.set_metrics(counters.clone());
+
+        asar.get_file("test1.txt");
+        assert_eq!((counters.files_read(), counters.bytes_read()), (1, 21));
+
+        asar.extract("test_metrics")?;
+        assert_eq!((counters.files_read(), counters.bytes_read()), (4, 21 + 30044));
+        assert_eq!(counters.files_written(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel() -> Result<(), asar_error::Error> { // tests cancelling extraction and packing
+        let asar = Asar::open("test_asar.asar")?;
+        std::fs::remove_dir_all("test_cancel").ok(); // left over from a previous run
+        std::fs::remove_file("test_cancel.asar").ok();
+
+        assert!(matches!(asar.extract_with_cancel("test_cancel", &AtomicBool::new(true)), Err(asar_error::Error::Cancelled)));
+        assert!(!Path::new("test_cancel").exists());
+
+        asar.extract_with_cancel("test_cancel", &AtomicBool::new(false))?;
+        assert_eq!(std::fs::read("test_cancel/folder1/test_image.jpg")?, std::fs::read("test_folder/folder1/test_image.jpg")?);
+
+        let folder = Asar::open("test_folder")?;
+
+        assert!(matches!(folder.pack_with_cancel("test_cancel.asar", &AtomicBool::new(true)), Err(asar_error::Error::Cancelled)));
+        assert!(!Path::new("test_cancel.asar").exists());
+
+        folder.pack_with_cancel("test_cancel.asar", &AtomicBool::new(false))?;
+        assert_eq!(Asar::open("test_cancel.asar")?.get_file("test1.txt"), Some(std::fs::read("test_folder/test1.txt")?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout() -> Result<(), asar_error::Error> { // tests operations completing within a deadline
+        let asar = Asar::open("test_asar.asar")?;
+
+        assert_eq!(asar.get_file_with_timeout("folder1/script.py", Duration::from_secs(10))?, std::fs::read("test_folder/folder1/script.py")?);
+        assert!(asar.get_file_with_timeout("missing.txt", Duration::from_secs(10)).is_err());
+
+        asar.extract_with_timeout("test_timeout", Duration::from_secs(10))?;
+        assert_eq!(std::fs::read("test_timeout/test1.txt")?, std::fs::read("test_folder/test1.txt")?);
+
+        // on timeout, the cancelled extraction has removed its output once this returns
+        match asar.extract_with_timeout("test_timeout/zero", Duration::ZERO) {
+            Err(asar_error::Error::TimedOut) => assert!(!Path::new("test_timeout/zero/test1.txt").exists()),
+            result => result?,
+        }
+
+        std::fs::remove_dir_all("test_timeout")?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_round_trip_property() { // tests round trips of random trees (requires the test-util feature)
+        use proptest::prelude::*;
+        use crate::test_util;
+
+        test_util::assert_round_trips("test_folder");
+
+        proptest!(ProptestConfig::with_cases(16), |(tree in test_util::tree(8, 3, 256))| {
+            let dir = std::env::temp_dir().join(format!("rust_asar-property-{}", std::process::id()));
+            std::fs::remove_dir_all(&dir).ok();
+
+            test_util::write_tree(&tree, &dir)?;
+            test_util::assert_round_trips(&dir);
+            prop_assert_eq!(test_util::read_tree(&dir)?, tree);
+
+            std::fs::remove_dir_all(&dir)?;
+        });
+
+        proptest!(ProptestConfig::with_cases(16), |(header in test_util::header(8, 3, 256))| {
+            prop_assert!(Content::new_json(header).and_then(|content| content.files_to_vec()).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_corpus() -> Result<(), asar_error::Error> { // tests conformance checks of a reference corpus
+        std::fs::create_dir_all("test_corpus/v1")?;
+        std::fs::copy("test_asar.asar", "test_corpus/v1/reference.asar")?;
+
+        let mut bytes = std::fs::read("test_asar.asar")?;
+        bytes.truncate(796 + 55 + 100);
+        std::fs::write("test_corpus/truncated.asar", &bytes)?;
+
+        let corpus = Corpus::load("test_corpus")?;
+        assert_eq!(corpus.archives(), [PathBuf::from("test_corpus/truncated.asar"), PathBuf::from("test_corpus/v1/reference.asar")]);
+
+        let results = corpus.check();
+        assert_eq!(results[0].passed, vec![Check::Parse]);
+        assert_eq!(results[0].failed[0].0, Check::Extract);
+        assert!(results[1].is_conformant());
+        assert_eq!(results[1].passed, vec![Check::Parse, Check::Extract, Check::Repack]);
+
+        assert!(Corpus::load("test_folder").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_core() -> Result<(), asar_error::Error> { // tests parsing a header from a buffer
+        let bytes = std::fs::read("test_asar.asar")?;
+
+        let (value, start) = header::read_header(&bytes[..796])?;
+        assert_eq!(start, 796);
+        assert_eq!(header::files::<std::io::Error>(&value)?, vec![
+            ("folder1/script.py".to_string(), 0, 55),
+            ("folder1/test_image.jpg".to_string(), 55, 29968),
+            ("test1.txt".to_string(), 30023, 21),
+        ]);
+
+        assert!(matches!(header::read_header(&bytes[..100]), Err(header::HeaderError::Source(header::OutOfBounds))));
+        assert_eq!(header::align_up(55, 16), 64);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn test_ffi() { // tests the C interface (requires the ffi feature)
+        use std::ffi::{CStr, CString};
+        use crate::ffi::*;
+
+        unsafe {
+            let path = CString::new("test_asar.asar").unwrap();
+            let archive = asar_open(path.as_ptr());
+            assert!(!archive.is_null());
+
+            let mut len: usize = 0;
+            let list = asar_list(archive, &mut len);
+            assert_eq!(len, 4);
+            assert_eq!(CStr::from_ptr(*list).to_str().unwrap(), "folder1");
+            asar_list_free(list, len);
+
+            let file = CString::new("test1.txt").unwrap();
+            let bytes = asar_read_file(archive, file.as_ptr(), &mut len);
+            assert_eq!(std::slice::from_raw_parts(bytes, len), b"This is a test file.\n");
+            asar_bytes_free(bytes, len);
+
+            let missing = CString::new("missing.txt").unwrap();
+            assert!(asar_read_file(archive, missing.as_ptr(), &mut len).is_null());
+            assert!(CStr::from_ptr(asar_last_error()).to_str().unwrap().contains("missing.txt"));
+
+            let destination = CString::new("test_ffi").unwrap();
+            assert_eq!(asar_extract(archive, destination.as_ptr()), 0);
+            assert!(Path::new("test_ffi/folder1/script.py").exists());
+
+            asar_close(archive);
+            assert!(asar_open(std::ptr::null()).is_null());
+        }
+
+        assert_eq!(
+            include_str!(concat!(env!("OUT_DIR"), "/rust_asar.h")),
+            include_str!("../include/rust_asar.h"),
+            "include/rust_asar.h is out of date, copy it from {}",
+            env!("OUT_DIR")
+        );
+    }
+
+    #[test]
+    fn test_archive_format() -> Result<(), asar_error::Error> { // tests detecting and rejecting prefix variants
+        use header::ArchiveFormat;
+
+        let prefix = |fields: [u32; 4]| -> [u8; 16] {
+            let mut prefix = [0_u8; 16];
+            for (i, field) in fields.iter().enumerate() {
+                prefix[i * 4..i * 4 + 4].copy_from_slice(&field.to_le_bytes());
+            }
+            prefix
+        };
+
+        assert_eq!(ArchiveFormat::from_prefix(&prefix([4, 788, 784, 778])), ArchiveFormat::Electron);
+        assert_eq!(ArchiveFormat::from_prefix(&prefix([4, 786, 782, 778])), ArchiveFormat::Compact);
+        assert_eq!(ArchiveFormat::from_prefix(&prefix([4, 808, 804, 778])), ArchiveFormat::Padded(22));
+        assert_eq!(ArchiveFormat::from_prefix(&prefix([8, 788, 784, 778])), ArchiveFormat::Unknown([8, 788, 784, 778]));
+        assert!(!ArchiveFormat::from_prefix(&prefix([4, 788, 700, 778])).is_supported());
+
+        assert_eq!(Asar::open("test_asar.asar")?.format()?, ArchiveFormat::Electron);
+        assert!(Asar::open("test_folder")?.format().is_err());
+
+        let mut bytes = std::fs::read("test_asar.asar")?;
+        bytes[8..12].copy_from_slice(&0_u32.to_le_bytes());
+        assert!(matches!(header::read_header(&bytes[..]), Err(header::HeaderError::Unsupported(ArchiveFormat::Unknown(_)))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_asar_set() -> Result<(), asar_error::Error> { // tests lookups across archives in priority order
+        let overlay: Vec<(PathBuf, Box<dyn Read>, u64)> = vec![
+            (PathBuf::from("test1.txt"), Box::new(&b"overlay"[..]), 7),
+            (PathBuf::from("locale/fr.json"), Box::new(&b"{}"[..]), 2),
+        ];
+        Asar::pack_stream(overlay, "test_set_overlay.asar")?;
+
+        let set = AsarSet::open(["test_set_overlay.asar", "test_asar.asar"])?;
+        assert_eq!(set.archives().len(), 2);
+        assert_eq!(set.get_file("test1.txt").unwrap(), b"overlay");
+        assert_eq!(set.get_file("folder1/script.py"), Asar::open("test_asar.asar")?.get_file("folder1/script.py"));
+        assert_eq!(set.find("locale").map(|(_, entry)| entry), Some(EntryRef::Folder));
+        assert!(set.get_file("missing.txt").is_none());
+
+        let paths: Vec<PathBuf> = set.walk()?.into_iter().map(|entry| entry.path).collect();
+        assert_eq!(paths, ["folder1", "folder1/script.py", "folder1/test_image.jpg", "locale", "locale/fr.json", "test1.txt"].map(PathBuf::from));
+
+        // a file hides a folder of the same path in archives of lower priority
+        let shadow: Vec<(PathBuf, Box<dyn Read>, u64)> = vec![(PathBuf::from("folder1"), Box::new(&b"!"[..]), 1)];
+        Asar::pack_stream(shadow, "test_set_shadow.asar")?;
+
+        let set = AsarSet::open(["test_set_shadow.asar", "test_asar.asar"])?;
+        assert!(set.find("folder1/script.py").is_none());
+        assert_eq!(set.walk()?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() -> Result<(), asar_error::Error> { // tests splitting a tree into per-locale archives
+        std::fs::remove_dir_all("test_split_src").ok();
+        std::fs::remove_dir_all("test_split").ok();
+
+        for (path, content) in [("app.js", "app"), ("locales/index.json", "[]"), ("locales/fr/strings.json", "fr"), ("locales/de/strings.json", "de")] {
+            let path = Path::new("test_split_src").join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, content)?;
+        }
+
+        let manifest = split::by_top_level_dirs("test_split_src", &[SplitRule::EachChild("locales".into())], "test_split")?;
+
+        let names: Vec<&str> = manifest.archives.iter().map(|archive| archive.name.as_str()).collect();
+        assert_eq!(names, ["core.asar", "locales-de.asar", "locales-fr.asar"]);
+        assert_eq!(manifest.archives[0].files, 2);
+        assert_eq!(manifest.archives[2].prefix, Some(PathBuf::from("locales/fr")));
+        assert!(Path::new("test_split/manifest.json").exists());
+
+        let fr = Asar::open("test_split/locales-fr.asar")?;
+        assert_eq!(fr.get_file("locales/fr/strings.json").unwrap(), b"fr");
+        assert!(!fr.contains("app.js"));
+
+        let set = AsarSet::open(["test_split/core.asar", "test_split/locales-de.asar", "test_split/locales-fr.asar"])?;
+        assert_eq!(set.get_file("locales/de/strings.json").unwrap(), b"de");
+        assert_eq!(set.get_file("locales/index.json").unwrap(), b"[]");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_report() -> Result<(), asar_error::Error> { // tests grouping file sizes against a budget
+        let asar = Asar::open("test_asar.asar")?;
+
+        let report = asar.size_report(&ReportOptions::default())?;
+        assert_eq!((report.files, report.size), (3, 30044));
+        assert_eq!(report.groups[0].name, "folder1");
+        assert_eq!((report.groups[0].files, report.groups[0].size), (2, 30023));
+        assert_eq!(report.groups[1].name, ".");
+        assert_eq!(report.largest[0], (PathBuf::from("folder1/test_image.jpg"), 29968));
+        assert!(!report.exceeds_threshold());
+
+        let options = ReportOptions { group_by: GroupBy::Extension, top_n: Some(1), threshold: Some(30000) };
+        let report = asar.size_report(&options)?;
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].name, "jpg");
+        assert_eq!(report.largest.len(), 1);
+        assert!(report.exceeds_threshold());
+
+        let report = Asar::open("test_folder")?.size_report(&ReportOptions::default())?;
+        assert_eq!(report.files, 4);
+        assert!(report.groups.iter().any(|group| group.name == "folder1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_duplicates() -> Result<(), asar_error::Error> { // tests reporting identical files and wasted bytes
+        let entries: Vec<(PathBuf, Box<dyn Read>, u64)> = vec![
+            (PathBuf::from("a.txt"), Box::new(&b"same"[..]), 4),
+            (PathBuf::from("b/c.txt"), Box::new(&b"same"[..]), 4),
+            (PathBuf::from("b/d.txt"), Box::new(&b"diff"[..]), 4),
+            (PathBuf::from("e.txt"), Box::new(&b""[..]), 0),
+            (PathBuf::from("f.txt"), Box::new(&b""[..]), 0),
+        ];
+        Asar::pack_stream(entries, "test_duplicates.asar")?;
+
+        let report = Asar::open("test_duplicates.asar")?.find_duplicates()?;
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].paths, [PathBuf::from("a.txt"), PathBuf::from("b/c.txt")]);
+        assert_eq!(report.groups[0].hash, cas::sha256_hex(&b"same"[..])?);
+        assert_eq!((report.groups[0].wasted, report.wasted), (4, 4));
+
+        assert!(Asar::open("test_asar.asar")?.find_duplicates()?.groups.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_to_memory() -> Result<(), asar_error::Error> { // tests reading many files without touching the filesystem
+        let asar = Asar::open("test_asar.asar")?;
+
+        let files = asar.extract_to_memory(|path| path.starts_with("folder1"))?;
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[Path::new("folder1/script.py")], asar.get_file("folder1/script.py").unwrap());
+        assert_eq!(files[Path::new("folder1/test_image.jpg")].len(), 29968);
+
+        let mut visited: Vec<(PathBuf, u64)> = Vec::new();
+        asar.extract_each(|_| true, |path, reader| {
+            visited.push((path.to_path_buf(), reader.limit()));
+            Ok(())
+        })?;
+        assert_eq!(visited.len(), 3);
+        assert!(visited.contains(&(PathBuf::from("test1.txt"), 21)));
+
+        let stopped = asar.extract_each(|_| true, |_, _| Err(asar_error::Error::Cancelled));
+        assert!(matches!(stopped, Err(asar_error::Error::Cancelled)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_visit() -> Result<(), asar_error::Error> { // tests walking Content once with early termination
+        use std::ops::ControlFlow;
+
+        struct FindLarge { visited: usize, limit: u64 }
+
+        impl Visitor for FindLarge {
+            type Break = PathBuf;
+
+            fn visit_folder(&mut self, _path: &Path) -> ControlFlow<PathBuf> {
+                self.visited += 1;
+                ControlFlow::Continue(())
+            }
+
+            fn visit_file(&mut self, path: &Path, _offset: u64, size: u64) -> ControlFlow<PathBuf> {
+                self.visited += 1;
+                if size > self.limit {
+                    return ControlFlow::Break(path.to_path_buf());
+                }
+                ControlFlow::Continue(())
+            }
+        }
+
+        let asar = Asar::open("test_asar.asar")?;
+
+        let mut visitor = FindLarge { visited: 0, limit: 1000 };
+        assert_eq!(asar.content.visit(&mut visitor)?, ControlFlow::Break(PathBuf::from("folder1/test_image.jpg")));
+        assert_eq!(visitor.visited, 3); // stopped before test1.txt
+
+        let mut visitor = FindLarge { visited: 0, limit: u64::MAX };
+        assert_eq!(asar.content.visit(&mut visitor)?, ControlFlow::Continue(()));
+        assert_eq!(visitor.visited, asar.content.paths_to_vec()?.len());
+
+        let folder = asar.content.find("folder1").unwrap();
+        let mut visitor = FindLarge { visited: 0, limit: 0 };
+        assert_eq!(folder.visit(&mut visitor)?, ControlFlow::Break(PathBuf::from("folder1/script.py")));
+
+        assert!(Content::new_list(Vec::new()).visit(&mut visitor).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_entry_from() -> Result<(), asar_error::Error> { // tests packing entries streamed from another archive
+        let source = Asar::open("test_asar.asar")?;
+        let mut asar = Asar::open_with("test_folder", &PackOptions::new().align(64))?;
+
+        asar.copy_entry_from(&source, "folder1", "vendor/folder1")?;
+        asar.copy_entry_from(&source, "test1.txt", "copied.txt")?;
+        assert!(asar.copy_entry_from(&source, "test1.txt", "another.txt").is_err());
+        assert!(asar.copy_entry_from(&source, "missing.txt", "missing.txt").is_err());
+        assert!(Asar::open("test_asar.asar")?.copy_entry_from(&source, "test1.txt", "copied.txt").is_err());
+
+        asar.pack("test_copy.asar")?;
+        assert_eq!(std::fs::metadata("test_copy.asar")?.len(), asar.packed_size_estimate()?);
+
+        let packed = Asar::open("test_copy.asar")?;
+        assert_eq!(packed.get_file("vendor/folder1/test_image.jpg"), source.get_file("folder1/test_image.jpg"));
+        assert_eq!(packed.get_file("vendor/folder1/script.py"), source.get_file("folder1/script.py"));
+        assert_eq!(packed.get_file("copied.txt").unwrap(), b"This is a test file.\n");
+        assert_eq!(packed.get_file("another.txt").unwrap(), std::fs::read("test_folder/another.txt")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repack() -> Result<(), asar_error::Error> { // tests packing an opened archive into a canonical archive
+        let header: Value = serde_json::from_str(r#"{"files":{"a.txt":{"size":3,"offset":"5","executable":true},"b":{"files":{"c.txt":{"size":2,"offset":"64"}}}}}"#)?;
+        let json_u8 = serde_json::to_vec(&header)?;
+
+        // header followed by padding, and a body with gaps between files
+        let mut bytes: Vec<u8> = Vec::new();
+        let start = (16 + json_u8.len() + 32) as u32;
+        bytes.extend_from_slice(&4_u32.to_le_bytes());
+        bytes.extend_from_slice(&(start - 8).to_le_bytes());
+        bytes.extend_from_slice(&(start - 12).to_le_bytes());
+        bytes.extend_from_slice(&(json_u8.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&json_u8);
+        bytes.resize(start as usize, 0);
+
+        let mut body = vec![0xBB_u8; 66];
+        body[5..8].copy_from_slice(b"abc");
+        body[64..66].copy_from_slice(b"de");
+        bytes.extend_from_slice(&body);
+
+        std::fs::write("test_repack_src.asar", &bytes)?;
+
+        let source = Asar::open("test_repack_src.asar")?;
+        source.pack("test_repack.asar")?;
+
+        let repacked = Asar::open("test_repack.asar")?;
+        assert_eq!(repacked.list()?, source.list()?);
+        assert_eq!(repacked.get_file("a.txt").unwrap(), b"abc");
+        assert_eq!(repacked.get_file("b/c.txt").unwrap(), b"de");
+        assert_eq!(repacked.content.find("a.txt"), Some(Content::File(PathBuf::from("a.txt"), 0, 3)));
+        assert_eq!(repacked.content.find("b/c.txt"), Some(Content::File(PathBuf::from("c.txt"), 3, 2)));
+        assert_eq!(repacked.content.folder_contents("").unwrap()["a.txt"]["executable"], true);
+        assert_eq!(std::fs::metadata("test_repack.asar")?.len(), repacked.start + 5);
+
+        assert!(source.pack("test_repack_src.asar").is_err());
+        assert_eq!(std::fs::read("test_repack_src.asar")?, bytes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_header_value() -> Result<(), asar_error::Error> { // tests regenerating a header from Content
+        let asar = Asar::open("test_asar.asar")?;
+
+        let header = asar.content.to_header_value()?;
+        assert_eq!(Content::new_json(header.clone())?.paths_to_vec()?, asar.content.paths_to_vec()?);
+        assert_eq!(header["files"]["folder1"]["files"]["script.py"]["offset"], "0");
+        assert!(header["files"]["folder1"]["files"]["script.py"].get("integrity").is_some());
+        assert_eq!(header["files"]["test1.txt"]["offset"], "30023");
+
+        // a mutated Content is written out with recomputed offsets
+        let folder = asar.content.find("folder1").unwrap();
+        let header = folder.to_header_value()?;
+        assert_eq!(header["files"]["folder1"]["files"]["test_image.jpg"]["offset"], "55");
+        assert_eq!(header["files"]["folder1"]["files"]["test_image.jpg"]["size"], 29968);
+
+        let file = asar.content.find("test1.txt").unwrap();
+        assert_eq!(file.to_header_value()?, serde_json::json!({ "files": { "test1.txt": { "size": 21, "offset": "0" } } }));
+
+        assert!(Content::new_list(Vec::new()).to_header_value().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_assign_offsets() -> Result<(), asar_error::Error> { // tests deterministic layouts shared by every writer
+        let entries = vec![(PathBuf::from("b/z.js"), 3), (PathBuf::from("a.js"), 5), (PathBuf::from("b/y.js"), 2), (PathBuf::from("native/x.node"), 7)];
+
+        let options = LayoutOptions { align: 4, sort: true, order: vec![PathBuf::from("b")], unpacked: vec![PathBuf::from("native")] };
+        let placements: Vec<(usize, Option<u64>)> = layout::assign_offsets(&entries, &options).into_iter().map(|p| (p.index, p.offset)).collect();
+        assert_eq!(placements, [(2, Some(0)), (0, Some(4)), (1, Some(8)), (3, None)]);
+        assert!(options.is_unpacked("native/x.node"));
+
+        let placements: Vec<usize> = layout::assign_offsets(&entries, &LayoutOptions::default()).into_iter().map(|p| p.index).collect();
+        assert_eq!(placements, [0, 1, 2, 3]);
+
+        // directories are laid out sorted by path, after any ordering hint
+        let asar = Asar::open_with("test_folder", &PackOptions::new().order(["test1.txt"]))?;
+        asar.pack("test_layout.asar")?;
+
+        let packed = Asar::open("test_layout.asar")?;
+        let mut files = packed.content.files_to_vec()?;
+        files.sort_by_key(|(_, offset, _)| *offset);
+
+        let order: Vec<PathBuf> = files.into_iter().map(|(path, _, _)| path).collect();
+        assert_eq!(order, ["test1.txt", "another.txt", "folder1/script.py", "folder1/test_image.jpg"].map(PathBuf::from));
+        assert_eq!(packed.get_file("folder1/script.py"), Asar::open("test_asar.asar")?.get_file("folder1/script.py"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_meta() -> Result<(), asar_error::Error> { // tests custom metadata preserved through packing and repacking
+        let mut asar = Asar::open("test_folder")?;
+        asar.set_entry_meta("test1.txt", "license", serde_json::json!("MIT"))?;
+        asar.set_entry_meta("folder1", "build", serde_json::json!({ "stamp": 42 }))?;
+        assert!(asar.set_entry_meta("missing.txt", "license", serde_json::json!("MIT")).is_err());
+        assert!(asar.set_entry_meta("", "license", serde_json::json!("MIT")).is_err());
+        assert_eq!(asar.entry_meta("test1.txt").unwrap()["license"], "MIT");
+        asar.pack("test_meta.asar")?;
+
+        let mut packed = Asar::open("test_meta.asar")?;
+        assert_eq!(packed.entry_meta("test1.txt").unwrap()["license"], "MIT");
+        assert_eq!(packed.entry_meta("folder1").unwrap()["build"]["stamp"], 42);
+        assert!(packed.entry_meta("another.txt").is_none());
+        assert_eq!(packed.get_file("test1.txt").unwrap(), b"This is a test file.\n");
+
+        packed.set_entry_meta("folder1/script.py", "hash", serde_json::json!("abc"))?;
+        packed.pack("test_meta_repack.asar")?;
+
+        let repacked = Asar::open("test_meta_repack.asar")?;
+        assert_eq!(repacked.entry_meta("test1.txt").unwrap()["license"], "MIT");
+        assert_eq!(repacked.entry_meta("folder1/script.py").unwrap()["hash"], "abc");
+        assert_eq!(repacked.get_file("folder1/script.py"), packed.get_file("folder1/script.py"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sbom() -> Result<(), asar_error::Error> { // tests collecting license files and package manifests
+        let entries: Vec<(PathBuf, Box<dyn Read>, u64)> = [
+            ("package.json", r#"{"name":"app","version":"2.0.0","license":"UNLICENSED"}"#),
+            ("node_modules/foo/package.json", r#"{"name":"foo","version":"1.0.0","license":"MIT"}"#),
+            ("node_modules/foo/LICENSE.md", "MIT License"),
+            ("node_modules/foo/lib/package.json", r#"{"type":"module"}"#),
+            ("node_modules/bar/package.json", r#"{"name":"bar","license":{"type":"ISC"}}"#),
+            ("node_modules/bar/copying", "ISC"),
+            ("main.js", ""),
+        ]
+        .into_iter()
+        .map(|(path, content)| (PathBuf::from(path), Box::new(content.as_bytes()) as Box<dyn Read>, content.len() as u64))
+        .collect();
+        Asar::pack_stream(entries, "test_sbom.asar")?;
+
+        let asar = Asar::open("test_sbom.asar")?;
+        assert_eq!(asar.collect_license_files().len(), 6);
+
+        let sbom = asar.sbom()?;
+        let names: Vec<&str> = sbom.components.iter().map(|component| component.name.as_str()).collect();
+        assert_eq!(names, ["app", "bar", "foo"]);
+        assert_eq!(sbom.components[0].path, PathBuf::from(""));
+        assert_eq!(sbom.components[1].license.as_deref(), Some("ISC"));
+        assert_eq!(sbom.components[1].license_files, [PathBuf::from("node_modules/bar/copying")]);
+        assert_eq!(sbom.components[2].version.as_deref(), Some("1.0.0"));
+
+        let bom = sbom.to_cyclonedx();
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["components"][2]["licenses"][0]["expression"], "MIT");
+        assert!(bom["components"][1].get("version").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_artifacts() -> Result<(), asar_error::Error> { // tests finding and stripping debug artifacts
+        std::fs::remove_dir_all("test_debug_src").ok();
+
+        for (path, content) in [
+            ("main.js", "console.log(1);\n//# sourceMappingURL=main.js.map\n"),
+            ("main.js.map", "{}"),
+            ("style.css", "a{}\n/*# sourceMappingURL=style.css.map */"),
+            ("clean.js", "console.log(2);"),
+            ("native/addon.pdb", ""),
+            ("native/addon.node.dSYM/Contents/Info.plist", ""),
+        ] {
+            let path = Path::new("test_debug_src").join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, content)?;
+        }
+
+        Asar::open("test_debug_src")?.pack("test_debug.asar")?;
+
+        let artifacts = Asar::open("test_debug.asar")?.find_debug_artifacts()?;
+        let kinds: Vec<(PathBuf, ArtifactKind)> = artifacts.into_iter().map(|artifact| (artifact.path, artifact.kind)).collect();
+        assert_eq!(kinds.len(), 5);
+        assert!(kinds.contains(&(PathBuf::from("main.js.map"), ArtifactKind::SourceMap)));
+        assert!(kinds.contains(&(PathBuf::from("native/addon.pdb"), ArtifactKind::Pdb)));
+        assert!(kinds.contains(&(PathBuf::from("native/addon.node.dSYM/Contents/Info.plist"), ArtifactKind::Dsym)));
+        assert!(kinds.contains(&(PathBuf::from("main.js"), ArtifactKind::SourceMappingUrl("main.js.map".to_string()))));
+        assert!(kinds.contains(&(PathBuf::from("style.css"), ArtifactKind::SourceMappingUrl("style.css.map".to_string()))));
+
+        Asar::open_with("test_debug_src", &PackOptions::new().strip_debug_artifacts())?.pack("test_debug.asar")?;
+
+        let stripped = Asar::open("test_debug.asar")?;
+        assert!(stripped.is_file("main.js") && stripped.is_file("clean.js"));
+        assert!(!stripped.contains("main.js.map") && !stripped.contains("native/addon.pdb"));
+        assert_eq!(stripped.find_debug_artifacts()?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan() -> Result<(), asar_error::Error> { // tests blocking secrets while packing
+        std::fs::remove_dir_all("test_scan_src").ok();
+        std::fs::remove_file("test_scan.asar").ok();
+        std::fs::create_dir_all("test_scan_src/config")?;
+        std::fs::write("test_scan_src/main.js", "console.log(1);")?;
+        std::fs::write("test_scan_src/config/keys.js", "const API_KEY = 'secret';")?;
+
+        let options = PackOptions::new().scan(|path, chunk| {
+            if path.file_name().is_some_and(|name| name == ".env") {
+                ScanResult::Block("environment file".to_string())
+            } else if chunk.windows(7).any(|window| window == b"API_KEY") {
+                ScanResult::Block("API key".to_string())
+            } else {
+                ScanResult::Clean
+            }
+        });
+
+        match Asar::open_with("test_scan_src", &options)?.pack("test_scan.asar") {
+            Err(asar_error::Error::PolicyViolation(violations)) => {
+                assert_eq!(violations, [(PathBuf::from("config/keys.js"), "API key".to_string())]);
+            }
+            other => panic!("expected a policy violation, got {:?}", other),
+        }
+        assert!(!Path::new("test_scan.asar").exists());
+
+        std::fs::remove_file("test_scan_src/config/keys.js")?;
+        std::fs::write("test_scan_src/.env", "")?;
+
+        match Asar::open_with("test_scan_src", &options)?.pack("test_scan.asar") {
+            Err(asar_error::Error::PolicyViolation(violations)) => assert_eq!(violations[0].0, PathBuf::from(".env")),
+            other => panic!("expected a policy violation, got {:?}", other),
+        }
+
+        std::fs::remove_file("test_scan_src/.env")?;
+        Asar::open_with("test_scan_src", &options)?.pack("test_scan.asar")?;
+        assert_eq!(Asar::open("test_scan.asar")?.get_file("main.js").unwrap(), b"console.log(1);");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_transform() -> Result<(), asar_error::Error> { // tests transforming files while packing
+        let options = PackOptions::new().align(8).transform(|path, content| {
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("txt") => {
+                    let mut banner = b"// banner\n".to_vec();
+                    banner.extend(content);
+                    TransformResult::Content(banner)
+                }
+                Some("jpg") => TransformResult::Skip,
+                _ => TransformResult::Content(content),
+            }
+        });
+
+        let asar = Asar::open_with("test_folder", &options)?;
+        asar.pack("test_transform.asar")?;
+
+        let packed = Asar::open("test_transform.asar")?;
+        assert_eq!(packed.get_file("test1.txt").unwrap(), b"// banner\nThis is a test file.\n");
+        assert_eq!(packed.get_file("folder1/script.py").unwrap(), std::fs::read("test_folder/folder1/script.py")?);
+        assert!(packed.is_file("another.txt") && !packed.contains("folder1/test_image.jpg"));
+
+        let sizes: Vec<(PathBuf, u64)> = packed.content.files_to_vec()?.into_iter().map(|(path, _, size)| (path, size)).collect();
+        assert!(sizes.contains(&(PathBuf::from("test1.txt"), 31)));
+
+        // headers generated without opening are left untransformed
+        let (_, list) = Asar::gen_header_from_dir_with("test_folder", &options)?;
+        assert_eq!(list.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_transform() -> Result<(), asar_error::Error> { // tests transforming files while extracting
+        std::fs::remove_dir_all("test_extract_transform").ok();
+
+        let options = ExtractOptions::new().transform(|path, content| {
+            if path.extension().is_some_and(|ext| ext == "jpg") {
+                TransformResult::Skip
+            } else if path == Path::new("test1.txt") {
+                TransformResult::Content(String::from_utf8_lossy(&content).to_uppercase().into_bytes())
+            } else {
+                TransformResult::Content(content)
+            }
+        });
+
+        Asar::open("test_asar.asar")?.extract_with("test_extract_transform", &options)?;
+
+        assert_eq!(std::fs::read("test_extract_transform/test1.txt")?, b"THIS IS A TEST FILE.\n");
+        assert_eq!(std::fs::read("test_extract_transform/folder1/script.py")?, std::fs::read("test_folder/folder1/script.py")?);
+        assert!(!Path::new("test_extract_transform/folder1/test_image.jpg").exists());
+
+        // a truncated archive fails rather than extracting a short file, with or without a transformer
+        let bytes = std::fs::read("test_asar.asar")?;
+        std::fs::write("test_extract_short.asar", &bytes[..bytes.len() - 5])?;
+
+        let short = Asar::open("test_extract_short.asar")?;
+        assert!(short.extract_with("test_extract_short", &ExtractOptions::new()).is_err());
+        assert!(short.extract_with("test_extract_short", &options).is_err());
+
+        std::fs::remove_dir_all("test_extract_short")?;
+        std::fs::remove_file("test_extract_short.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_extensions() -> Result<(), asar_error::Error> { // tests extracting files by extension
+        std::fs::remove_dir_all("test_extract_extensions").ok();
+
+        Asar::open("test_asar.asar")?.extract_with("test_extract_extensions", &ExtractOptions::new().extensions(&["TXT", ".py"]))?;
+
+        assert!(Path::new("test_extract_extensions/test1.txt").is_file());
+        assert!(Path::new("test_extract_extensions/folder1/script.py").is_file());
+        assert!(!Path::new("test_extract_extensions/folder1/test_image.jpg").exists());
+
+        std::fs::remove_dir_all("test_extract_extensions")?;
+        Asar::open("test_asar.asar")?.extract_with("test_extract_extensions", &ExtractOptions::new().extensions(&["txt"]))?;
+        assert!(!Path::new("test_extract_extensions/folder1").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_flatten() -> Result<(), asar_error::Error> { // tests extracting files into a single folder
+        std::fs::remove_dir_all("test_flatten_src").ok();
+        std::fs::remove_dir_all("test_flatten").ok();
+
+        for path in ["a/index.js", "b/index.js", "c/index.js", "index-1.js", "README", "x/a", "x/b.txt"] {
+            let path = Path::new("test_flatten_src").join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(&path, path.to_string_lossy().as_bytes())?;
+        }
+
+        Asar::open("test_flatten_src")?.pack("test_flatten.asar")?;
+        Asar::open("test_flatten.asar")?.extract_with("test_flatten", &ExtractOptions::new().flatten(true))?;
+
+        let mut names: Vec<String> = std::fs::read_dir("test_flatten")?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<_, _>>()?;
+        names.sort();
+
+        // folders take no name, so the file `x/a` keeps its own
+        assert_eq!(names, ["README", "a", "b.txt", "index-1-1.js", "index-1.js", "index-2.js", "index.js"]);
+        assert_eq!(std::fs::read("test_flatten/index.js")?, b"test_flatten_src/a/index.js");
+        assert_eq!(std::fs::read("test_flatten/index-2.js")?, b"test_flatten_src/c/index.js");
+        assert_eq!(std::fs::read("test_flatten/a")?, b"test_flatten_src/x/a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_to_sink() -> Result<(), asar_error::Error> { // tests extracting to a caller-provided sink
+        #[derive(Default)]
+        struct Events(Vec<String>, Vec<u8>);
+
+        impl EntrySink for Events {
+            fn create_dir(&mut self, path: &Path) -> Result<(), asar_error::Error> {
+                self.0.push(format!("dir {}", path.display()));
+                Ok(())
+            }
+
+            fn begin_file(&mut self, path: &Path, size: u64) -> Result<(), asar_error::Error> {
+                self.0.push(format!("file {} {}", path.display(), size));
+                self.1.clear();
+                Ok(())
+            }
+
+            fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), asar_error::Error> {
+                self.1.extend_from_slice(chunk);
+                Ok(())
+            }
+
+            fn end_file(&mut self) -> Result<(), asar_error::Error> {
+                self.0.push(format!("end {}", self.1.len()));
+                Ok(())
+            }
+        }
+
+        let mut events = Events::default();
+        Asar::open("test_asar.asar")?.extract_to_sink(&mut events)?;
+
+        assert_eq!(events.0, [
+            "dir folder1",
+            "file folder1/script.py 55", "end 55",
+            "file folder1/test_image.jpg 29968", "end 29968",
+            "file test1.txt 21", "end 21",
+        ]);
+        assert_eq!(events.1, b"This is a test file.\n");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "object-store")]
+    #[test]
+    fn test_object_store() -> Result<(), asar_error::Error> { // tests packing to and reading from object storage
+        use std::sync::Arc;
+        use object_store::{memory::InMemory, path::Path as ObjectPath};
+        use crate::remote::RemoteAsar;
+
+        let store = Arc::new(InMemory::new());
+        let location = ObjectPath::from("releases/test.asar");
+
+        Asar::open("test_folder")?.pack_to_store(store.clone(), &location)?;
+
+        let remote = RemoteAsar::open(store.clone(), location)?;
+        assert_eq!(remote.read_file("test1.txt")?, std::fs::read("test_folder/test1.txt")?);
+        assert_eq!(remote.read_file("folder1/test_image.jpg")?, std::fs::read("test_folder/folder1/test_image.jpg")?);
+        assert!(remote.read_file("folder1").is_err());
+        assert_eq!(remote.list()?.len(), 5);
+
+        // repacking an archive streams it from its body
+        let repacked = ObjectPath::from("releases/repacked.asar");
+        Asar::open("test_asar.asar")?.pack_to_store(store.clone(), &repacked)?;
+        assert_eq!(RemoteAsar::open(store, repacked)?.read_file("folder1/script.py")?, std::fs::read("test_folder/folder1/script.py")?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "catalog")]
+    #[test]
+    fn test_catalog() -> Result<(), asar_error::Error> { // tests querying a catalog of archives
+        use crate::catalog::{AsarCatalog, CatalogMatch};
+
+        Asar::open("test_folder")?.pack("test_catalog.asar")?;
+
+        let catalog = AsarCatalog::temporary()?;
+        assert_eq!(catalog.add("test_asar.asar")?, 3);
+        assert_eq!(catalog.add("test_catalog.asar")?, 4);
+        assert_eq!(catalog.add("test_catalog.asar")?, 4); // replaced, not duplicated
+
+        assert_eq!(catalog.archives()?, [PathBuf::from("test_asar.asar"), PathBuf::from("test_catalog.asar")]);
+        assert_eq!(catalog.archives_with_path("test1.txt")?, [PathBuf::from("test_asar.asar"), PathBuf::from("test_catalog.asar")]);
+        assert_eq!(catalog.archives_with_path("another.txt")?, [PathBuf::from("test_catalog.asar")]);
+        assert!(catalog.archives_with_path("test1")?.is_empty());
+
+        let hash = cas::sha256_hex(std::fs::File::open("test_folder/folder1/script.py")?)?;
+        assert_eq!(catalog.archives_with_hash(&hash)?, [
+            CatalogMatch { archive: PathBuf::from("test_asar.asar"), path: PathBuf::from("folder1/script.py") },
+            CatalogMatch { archive: PathBuf::from("test_catalog.asar"), path: PathBuf::from("folder1/script.py") },
+        ]);
+
+        assert!(catalog.remove("test_asar.asar")?);
+        assert!(catalog.files("test_asar.asar")?.is_none());
+        assert_eq!(catalog.archives_with_hash(&hash)?.len(), 1);
+        assert_eq!(catalog.files("test_catalog.asar")?.unwrap().len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_match_hashes() -> Result<(), asar_error::Error> { // tests matching files against a hash list
+        let digest = cas::sha256_hex(std::fs::File::open("test_folder/test1.txt")?)?;
+        let list = format!("# known bad\n\n{}  test1.txt\n{}\n", digest.to_uppercase(), "0".repeat(64));
+
+        let hashes = hashlist::parse_hash_list(&list);
+        assert_eq!(hashes.len(), 2);
+
+        let asar = Asar::open("test_asar.asar")?;
+        assert_eq!(asar.match_hashes(&hashes)?, [(PathBuf::from("test1.txt"), digest)]);
+        assert!(asar.match_hashes(&Default::default())?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_guess_electron_version() -> Result<(), asar_error::Error> { // tests guessing the packaged Electron version
+        std::fs::remove_dir_all("test_inspect_src").ok();
+
+        for (path, content) in [
+            ("package.json", r#"{"name": "app", "devDependencies": {"electron": "^28.1.0"}, "build": {"electronVersion": "28.1.3"}}"#),
+            ("node_modules/@electron/remote/index.js", ""),
+        ] {
+            let path = Path::new("test_inspect_src").join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, content)?;
+        }
+
+        Asar::open("test_inspect_src")?.pack("test_inspect.asar")?;
+        let asar = Asar::open("test_inspect.asar")?;
+
+        let hints = inspect::electron_version_hints(&asar)?;
+        let versions: Vec<(&str, Confidence)> = hints.iter().map(|hint| (hint.version.as_str(), hint.confidence)).collect();
+        assert_eq!(versions, [("28.1.3", Confidence::High), ("28.1.0", Confidence::Medium), (">=10", Confidence::Low)]);
+
+        assert_eq!(inspect::guess_electron_version(&asar)?.unwrap().evidence, "package.json build.electronVersion");
+        assert!(inspect::guess_electron_version(&Asar::open("test_asar.asar")?)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_package_json() -> Result<(), asar_error::Error> { // tests reading the manifest of the packaged application
+        std::fs::remove_dir_all("test_package_src").ok();
+
+        for (path, content) in [
+            ("app/package.json", r#"{"name": "demo", "version": "1.2.3", "main": "./dist/../main.js"}"#),
+            ("app/node_modules/dep/package.json", r#"{"name": "dep"}"#),
+            ("app/main.js", ""),
+        ] {
+            let path = Path::new("test_package_src").join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, content)?;
+        }
+
+        Asar::open("test_package_src")?.pack("test_package.asar")?;
+        let asar = Asar::open("test_package.asar")?;
+
+        assert_eq!(asar.package_json_path(), Some(PathBuf::from("app/package.json")));
+        assert_eq!(asar.app_name().as_deref(), Some("demo"));
+        assert_eq!(asar.app_version().as_deref(), Some("1.2.3"));
+        assert_eq!(asar.main_entry(), Some(PathBuf::from("app/main.js")));
+        assert!(asar.is_file(asar.main_entry().unwrap()));
+
+        assert!(Asar::open("test_asar.asar")?.package_json().is_none());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "jsgraph")]
+    #[test]
+    fn test_reachability() -> Result<(), asar_error::Error> { // tests walking the scripts reachable from main
+        std::fs::remove_dir_all("test_jsgraph_src").ok();
+
+        for (path, content) in [
+            ("package.json", r#"{"name": "app", "main": "src/main.js"}"#),
+            ("src/main.js", "const { app } = require('electron');\nconst util = require('./util');\nimport dep from 'dep';\nrequire('./missing');"),
+            ("src/util.js", "module.exports = require('../config.json');"),
+            ("src/unused.js", "require('fs');"),
+            ("config.json", "{}"),
+            ("node_modules/dep/package.json", r#"{"name": "dep", "main": "lib"}"#),
+            ("node_modules/dep/lib/index.js", "export * from './helpers.mjs';"),
+            ("node_modules/dep/lib/helpers.mjs", ""),
+            ("node_modules/dep/test/spec.js", ""),
+            ("locales/fr.pak", "bonjour"),
+        ] {
+            let path = Path::new("test_jsgraph_src").join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, content)?;
+        }
+
+        Asar::open("test_jsgraph_src")?.pack("test_jsgraph.asar")?;
+        let report = Asar::open("test_jsgraph.asar")?.reachability()?;
+
+        let reachable: Vec<&str> = report.reachable.iter().map(|path| path.to_str().unwrap()).collect();
+        assert_eq!(reachable, [
+            "config.json",
+            "node_modules/dep/lib/helpers.mjs",
+            "node_modules/dep/lib/index.js",
+            "node_modules/dep/package.json",
+            "package.json",
+            "src/main.js",
+            "src/util.js",
+        ]);
+
+        let unreachable: Vec<&str> = report.unreachable.iter().map(|(path, _)| path.to_str().unwrap()).collect();
+        assert_eq!(unreachable, ["locales/fr.pak", "node_modules/dep/test/spec.js", "src/unused.js"]);
+        assert_eq!(report.unreachable_size(), 7 + 14);
+        assert_eq!(report.unresolved, [(PathBuf::from("src/main.js"), "./missing".to_string())]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune() -> Result<(), asar_error::Error> { // tests repacking only the entries of a manifest
+        let asar = Asar::open("test_asar.asar")?;
+
+        let keep = Manifest::parse("# allowlist\nfolder1/script.py\n\n/test1.txt\n");
+        assert!(keep.keeps("test1.txt") && !keep.keeps("folder1/test_image.jpg"));
+
+        let report = asar.prune(&keep, "test_prune.asar")?;
+        assert_eq!(report.removed, [(PathBuf::from("folder1/test_image.jpg"), 29968)]);
+        assert!(report.bytes_saved >= 29968);
+
+        let pruned = Asar::open("test_prune.asar")?;
+        assert_eq!(pruned.get_file("test1.txt").unwrap(), b"This is a test file.\n");
+        assert_eq!(pruned.get_file("folder1/script.py").unwrap(), asar.get_file("folder1/script.py").unwrap());
+        assert!(!pruned.contains("folder1/test_image.jpg"));
+
+        // folders left empty are removed, kept folders keep everything below them
+        asar.prune(&Manifest::new(["test1.txt"]), "test_prune.asar")?;
+        assert!(!Asar::open("test_prune.asar")?.contains("folder1"));
+
+        asar.prune(&Manifest::new(["folder1"]), "test_prune.asar")?;
+        assert_eq!(Asar::open("test_prune.asar")?.content.files_to_vec()?.len(), 2);
+
+        assert!(asar.prune(&keep, "test_asar.asar").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_stubs() -> Result<(), asar_error::Error> { // tests extracting file stubs with a manifest of sizes
+        std::fs::remove_dir_all("test_stubs").ok();
+
+        let asar = Asar::open("test_asar.asar")?;
+        let manifest = asar.extract_stubs("test_stubs", 0)?;
+
+        assert_eq!(std::fs::metadata("test_stubs/folder1/test_image.jpg")?.len(), 0);
+        assert_eq!(manifest["files"]["folder1/test_image.jpg"]["size"], 29968);
+
+        let written: serde_json::Value = serde_json::from_slice(&std::fs::read(Path::new("test_stubs").join(stubs::STUBS_MANIFEST))?)?;
+        assert_eq!(written, manifest);
+
+        asar.extract_stubs("test_stubs", 4)?;
+        assert_eq!(std::fs::read("test_stubs/test1.txt")?, b"This");
+        assert_eq!(std::fs::metadata("test_stubs/folder1/test_image.jpg")?.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_peek() -> Result<(), asar_error::Error> { // tests reading the first bytes of a file
+        let asar = Asar::open("test_asar.asar")?;
+
+        assert_eq!(asar.peek("test1.txt", 4).unwrap(), b"This");
+        assert_eq!(asar.peek("test1.txt", 1000).unwrap(), asar.get_file("test1.txt").unwrap());
+        assert_eq!(asar.peek("folder1/test_image.jpg", 3).unwrap(), [0xFF, 0xD8, 0xFF]);
+        assert!(asar.peek("test1.txt", 0).unwrap().is_empty());
+        assert!(asar.peek("folder1", 4).is_none() && asar.peek("missing.txt", 4).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_range() -> Result<(), asar_error::Error> { // tests reading byte ranges and tails of files
+        let asar = Asar::open("test_asar.asar")?;
+
+        assert_eq!(asar.read_range("test1.txt", 5..7)?, b"is");
+        assert_eq!(asar.read_range("test1.txt", 0..21)?, asar.get_file("test1.txt").unwrap());
+        assert!(asar.read_range("test1.txt", 21..21)?.is_empty());
+        assert_eq!(asar.read_range("folder1/test_image.jpg", 29966..29968)?, [0xFF, 0xD9]);
+
+        assert!(asar.read_range("test1.txt", 20..22).is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = asar.read_range("test1.txt", 7..5);
+        assert!(reversed.is_err());
+        assert!(asar.read_range("folder1", 0..1).is_err());
+
+        assert_eq!(asar.read_tail("test1.txt", 6)?, b"file.\n");
+        assert_eq!(asar.read_tail("test1.txt", 100)?.len(), 21);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_type() -> Result<(), asar_error::Error> { // tests mapping files to content types
+        let asar = Asar::open("test_asar.asar")?;
+
+        assert_eq!(asar.content_type("folder1/test_image.jpg"), Some("image/jpeg"));
+        assert_eq!(asar.content_type("test1.txt"), Some("text/plain"));
+        assert_eq!(asar.content_type("folder1/script.py"), Some("application/octet-stream"));
+        assert_eq!(asar.content_type("folder1"), None);
+        assert_eq!(asar.content_type("missing.txt"), None);
+
+        let table = MimeTable::new().with(".PY", "text/x-python");
+        assert_eq!(asar.content_type_with("folder1/script.py", &table), Some("text/x-python"));
+        assert_eq!(asar.content_type_with("test1.txt", &table), Some("text/plain"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_etag() -> Result<(), asar_error::Error> { // tests stable per-entry ETags
+        let asar = Asar::open("test_asar.asar")?;
+
+        let tag = asar.etag("test1.txt")?.unwrap();
+        assert!(tag.starts_with('"') && tag.ends_with('"'));
+        assert_eq!(Asar::open("test_asar.asar")?.etag("test1.txt")?, Some(tag.clone()));
+        assert_ne!(asar.etag("folder1/script.py")?, Some(tag));
+
+        assert_eq!(asar.etag("folder1")?, None);
+        assert_eq!(asar.etag("missing.txt")?, None);
+        assert!(Asar::open("test_folder")?.etag("test1.txt").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_mode() -> Result<(), asar_error::Error> { // tests that read-only archives can not be packed or modified
+        let mut asar = Asar::open_mode("test_asar.asar", OpenMode::ReadOnly)?;
+
+        assert_eq!(asar.mode(), OpenMode::ReadOnly);
+        assert_eq!(asar.get_file("test1.txt").unwrap(), b"This is a test file.\n");
+
+        assert!(asar.pack("test_open_mode.asar").is_err());
+        assert!(!Path::new("test_open_mode.asar").exists());
+        assert!(asar.set_entry_meta("test1.txt", "license", serde_json::json!("MIT")).is_err());
+        assert!(asar.prune(&Manifest::new(["test1.txt"]), "test_open_mode.asar").is_err());
+
+        let mut dir = Asar::open_mode("test_folder", OpenMode::ReadOnly)?;
+        assert!(dir.copy_entry_from(&asar, "test1.txt", "copied.txt").is_err());
+        assert!(dir.pack("test_open_mode.asar").is_err());
+
+        assert_eq!(Asar::open("test_asar.asar")?.mode(), OpenMode::ReadWrite);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed() -> Result<(), asar_error::Error> { // tests the AsarReader and AsarPacker types
+        let reader = AsarReader::open("test_asar.asar")?;
+        assert_eq!(reader.get_file("test1.txt").unwrap(), b"This is a test file.\n");
+
+        assert!(AsarReader::open("test_folder").is_err());
+        assert!(AsarPacker::open("test_asar.asar").is_err());
+        assert!(AsarReader::try_from(Asar::open("test_folder")?).is_err());
+        assert!(AsarPacker::try_from(Asar::open("test_asar.asar")?).is_err());
+
+        let mut packer = AsarPacker::open("test_folder")?;
+        packer.copy_entry_from(&reader, "folder1/script.py", "copied.py")?;
+        packer.pack("test_typed.asar")?;
+        assert_eq!(std::fs::metadata("test_typed.asar")?.len(), packer.packed_size_estimate()?);
+
+        let mut repacker = AsarReader::open("test_typed.asar")?.into_packer()?;
+        repacker.set_entry_meta("copied.py", "license", serde_json::json!("MIT"))?;
+        repacker.pack("test_typed_repacked.asar")?;
+
+        let repacked = AsarReader::open("test_typed_repacked.asar")?;
+        assert_eq!(repacked.get_file("copied.py"), reader.get_file("folder1/script.py"));
+        assert_eq!(repacked.entry_meta("copied.py").unwrap()["license"], "MIT");
+        assert_eq!(Asar::from(repacked).list()?.len(), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_header_streamed() -> Result<(), asar_error::Error> { // tests that streamed headers match their buffered bytes
+        let (header, _) = Asar::gen_header_from_dir("test_folder")?;
+
+        let mut written: Vec<u8> = Vec::new();
+        let start = Asar::write_header(&mut written, &header)?;
+        assert_eq!(written, Asar::header_bytes(&header, 0)?);
+        assert_eq!(start, written.len() as u64);
+
+        let asar = Asar::open_with("test_folder", &PackOptions::new().align(4096))?;
+        asar.pack("test_write_header.asar")?;
+
+        let bytes = std::fs::read("test_write_header.asar")?;
+        assert_eq!(asar.start, 4096);
+        let header = asar.header.as_ref().unwrap();
+        let padding = asar.start - 16 - serde_json::to_vec(header)?.len() as u64;
+        assert_eq!(&bytes[..asar.start as usize], Asar::header_bytes(header, padding)?);
+        assert_eq!(Asar::open("test_write_header.asar")?.get_file("test1.txt").unwrap(), b"This is a test file.\n");
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedupe_hard_links() -> Result<(), asar_error::Error> { // tests storing hard-linked files once
+        let _ = std::fs::remove_dir_all("test_links_src");
+        std::fs::create_dir_all("test_links_src/node_modules/.pnpm/lodash")?;
+        std::fs::create_dir_all("test_links_src/node_modules/lodash")?;
+        std::fs::write("test_links_src/node_modules/.pnpm/lodash/index.js", vec![b'x'; 1000])?;
+        std::fs::hard_link("test_links_src/node_modules/.pnpm/lodash/index.js", "test_links_src/node_modules/lodash/index.js")?;
+        std::fs::write("test_links_src/main.js", b"require('lodash');")?;
+
+        Asar::open("test_links_src")?.pack("test_links_full.asar")?;
+
+        let asar = Asar::open_with("test_links_src", &PackOptions::new().dedupe_hard_links())?;
+        let groups = asar.link_groups();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].paths, [PathBuf::from("node_modules/.pnpm/lodash/index.js"), PathBuf::from("node_modules/lodash/index.js")]);
+        assert_eq!((groups[0].size, groups[0].saved), (1000, 1000));
+
+        asar.pack("test_links.asar")?;
+        assert_eq!(std::fs::metadata("test_links.asar")?.len(), asar.packed_size_estimate()?);
+        assert!(std::fs::metadata("test_links.asar")?.len() + 1000 <= std::fs::metadata("test_links_full.asar")?.len());
+
+        let packed = Asar::open("test_links.asar")?;
+        assert_eq!(packed.get_file("node_modules/lodash/index.js").unwrap(), vec![b'x'; 1000]);
+        assert_eq!(packed.get_file("node_modules/.pnpm/lodash/index.js").unwrap(), vec![b'x'; 1000]);
+        assert_eq!(packed.get_file("main.js").unwrap(), b"require('lodash');");
+        assert_eq!(packed.link_groups(), groups);
+        assert!(Asar::open("test_links_full.asar")?.link_groups().is_empty());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_pnpm() -> Result<(), asar_error::Error> { // tests packing a pnpm symlinked node_modules
+        use std::os::unix::fs::symlink;
+
+        let _ = std::fs::remove_dir_all("test_pnpm_src");
+        let store = "test_pnpm_src/node_modules/.pnpm/lodash@4.17.21/node_modules/lodash";
+        std::fs::create_dir_all(store)?;
+        std::fs::write(format!("{}/index.js", store), vec![b'x'; 500])?;
+        symlink(".pnpm/lodash@4.17.21/node_modules/lodash", "test_pnpm_src/node_modules/lodash")?;
+        symlink("../../..", format!("{}/cycle", store))?; // back to node_modules
+        symlink("missing.js", "test_pnpm_src/dangling.js")?;
+
+        assert!(Asar::open("test_pnpm_src").is_err());
+
+        let asar = Asar::open_with("test_pnpm_src", &PackOptions::new().resolve_pnpm())?;
+        asar.pack("test_pnpm.asar")?;
+
+        let packed = Asar::open("test_pnpm.asar")?;
+        assert_eq!(packed.get_file("node_modules/lodash/index.js").unwrap(), vec![b'x'; 500]);
+        assert_eq!(packed.get_file(format!("{}/index.js", &store[14..])).unwrap(), vec![b'x'; 500]);
+        assert!(!packed.contains("dangling.js"));
+        assert!(!packed.contains(format!("{}/cycle", &store[14..])));
+        assert_eq!(packed.link_groups().len(), 1);
+        assert!(std::fs::metadata("test_pnpm.asar")?.len() < packed.start + 1000);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "ignore")]
+    #[test]
+    fn test_asarignore() -> Result<(), asar_error::Error> { // tests leaving out files matched by ignore files
+        let _ = std::fs::remove_dir_all("test_ignore_src");
+        std::fs::create_dir_all("test_ignore_src/tests")?;
+        std::fs::create_dir_all("test_ignore_src/dist")?;
+        std::fs::write("test_ignore_src/main.js", b"main")?;
+        std::fs::write("test_ignore_src/notes.md", b"notes")?;
+        std::fs::write("test_ignore_src/tests/main.test.js", b"test")?;
+        std::fs::write("test_ignore_src/dist/bundle.js", b"bundle")?;
+        std::fs::write("test_ignore_src/.asarignore", b"# dev files\n*.md\ntests/\n!dist/\n")?;
+        std::fs::write("test_ignore_src/.gitignore", b"dist/\n")?;
+
+        let paths = |options: &PackOptions| -> Result<Vec<String>, asar_error::Error> {
+            let (header, _) = Asar::gen_header_from_dir_with("test_ignore_src", options)?;
+            Ok(header::files::<std::io::Error>(&header).unwrap().into_iter().map(|(path, _, _)| path).collect())
+        };
+
+        assert_eq!(paths(&PackOptions::new().asarignore())?, [".gitignore", "dist/bundle.js", "main.js"]);
+        assert_eq!(paths(&PackOptions::new().gitignore())?, [".asarignore", "main.js", "notes.md", "tests/main.test.js"]);
+        assert_eq!(paths(&PackOptions::new().asarignore().gitignore())?, ["dist/bundle.js", "main.js"]);
+        assert_eq!(paths(&PackOptions::new())?.len(), 6);
+
+        std::fs::write("test_ignore_src/.asarignore", b"{a\n")?;
+        assert!(matches!(paths(&PackOptions::new().asarignore()), Err(asar_error::Error::InvalidPattern(_))));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_pack_config() -> Result<(), asar_error::Error> { // tests packing as described by a config file
+        use crate::config::PackConfig;
+
+        let _ = std::fs::remove_dir_all("test_config");
+        std::fs::create_dir_all("test_config/app/native")?;
+        std::fs::create_dir_all("test_config/app/test")?;
+        std::fs::write("test_config/app/main.js", b"main();")?;
+        std::fs::write("test_config/app/README.md", b"readme")?;
+        std::fs::write("test_config/app/test/main.test.js", b"test")?;
+        std::fs::write("test_config/app/native/addon.node", b"\x7fELF")?;
+        std::fs::write(
+            "test_config/asar.toml",
+            "source = \"app\"\ndestination = \"out/app.asar\"\nexclude = [\"*.md\", \"/test/\"]\nunpack = [\"*.node\"]\nintegrity = true\n\n[[transform]]\ninclude = [\"*.js\"]\nprepend = \"/* banner */\"\n",
+        )?;
+
+        let config = PackConfig::from_file("test_config/asar.toml")?;
+        assert_eq!(config.source(), Path::new("test_config/app"));
+        assert_eq!(config.destination(), Path::new("test_config/out/app.asar"));
+
+        std::fs::create_dir_all("test_config/out")?;
+        config.pack()?;
+
+        let asar = Asar::open("test_config/out/app.asar")?;
+        assert_eq!(asar.list()?, ["main.js", "native"]);
+        assert_eq!(asar.get_file("main.js").unwrap(), b"/* banner */main();");
+        assert_eq!(std::fs::read("test_config/out/app.asar.unpacked/native/addon.node")?, b"\x7fELF");
+
+        let entry = &asar.content.folder_contents("").unwrap()["main.js"];
+        assert_eq!(entry["integrity"]["hash"], cas::sha256_hex(&b"/* banner */main();"[..])?);
+        assert_eq!(asar.content.folder_contents("native").unwrap()["addon.node"]["unpacked"], true);
+
+        assert!(PackConfig::from_toml("source = \"app\"").is_err());
+        assert!(PackConfig::from_toml("source = \"app\"\ndestination = \"app.asar\"\ncompress = true").is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn test_pack_config_profiles() -> Result<(), asar_error::Error> { // tests variables and profiles of pack configs
+        use crate::config::PackConfig;
+
+        std::env::set_var("RUST_ASAR_TEST_CHANNEL", "beta");
+        std::env::remove_var("RUST_ASAR_TEST_UNSET");
+
+        let config = "source = \"${RUST_ASAR_TEST_UNSET:-app}\"\ndestination = \"dist/${RUST_ASAR_TEST_CHANNEL}/app.asar\"\nexclude = [\"*.md\"]\n\n[profile.release]\nexclude = [\"*.md\", \"*.map\"]\nintegrity = true\n\n[profile.debug]\nintegrity = false\n";
+
+        let base = PackConfig::from_toml(config)?;
+        assert_eq!(base.source(), Path::new("app"));
+        assert_eq!(base.destination(), Path::new("dist/beta/app.asar"));
+        assert_eq!((base.exclude.len(), base.integrity), (1, false));
+
+        let release = PackConfig::from_toml_profile(config, "release")?;
+        assert_eq!((release.exclude.len(), release.integrity), (2, true));
+        assert_eq!(release.destination(), base.destination());
+
+        assert!(!PackConfig::from_toml_profile(config, "debug")?.integrity);
+        assert!(PackConfig::from_toml_profile(config, "staging").is_err());
+        assert!(PackConfig::from_toml("source = \"${RUST_ASAR_TEST_UNSET}\"\ndestination = \"a.asar\"").is_err());
+        assert!(PackConfig::from_toml("source = \"${RUST_ASAR_TEST_CHANNEL\"\ndestination = \"a.asar\"").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_verify() -> Result<(), asar_error::Error> { // tests verification of packed archives
+        let options = PackOptions::new().verify(Verification::Full);
+        let mut asar = Asar::open_with("test_folder", &options)?;
+        asar.copy_entry_from(&Asar::open("test_asar.asar")?, "test1.txt", "copied.txt")?;
+        asar.pack("test_verify.asar")?;
+
+        Asar::open_with("test_folder", &PackOptions::new().verify(Verification::Sample(2)))?.pack("test_verify_sample.asar")?;
+
+        let mut repacked = Asar::open("test_verify.asar")?;
+        repacked.options = repacked.options.clone().verify(Verification::Full);
+        repacked.pack("test_verify_repacked.asar")?;
+        assert_eq!(Asar::open("test_verify_repacked.asar")?.get_file("copied.txt").unwrap(), b"This is a test file.\n");
+
+        // corrupts the last byte of the body
+        let mut bytes = std::fs::read("test_verify.asar")?;
+        *bytes.last_mut().unwrap() ^= 0xff;
+        std::fs::write("test_verify.asar", &bytes)?;
+
+        let err = asar.verify_packed(Path::new("test_verify.asar")).unwrap_err();
+        assert!(err.to_string().contains("1 file(s) differ"));
+
+        bytes.push(0);
+        std::fs::write("test_verify.asar", &bytes)?;
+        assert!(asar.verify_packed(Path::new("test_verify.asar")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip_check() -> Result<(), asar_error::Error> { // tests comparing folders with their packed and extracted copies
+        let dir = Path::new("test_roundtrip_src");
+        std::fs::remove_dir_all(dir).ok();
+        std::fs::create_dir_all(dir.join("nested"))?;
+        std::fs::write(dir.join("a.txt"), "a")?;
+        std::fs::write(dir.join("nested/b.txt"), "bb")?;
+
+        let report = roundtrip::roundtrip_check(dir)?;
+        assert!(report.is_identical());
+        assert_eq!(report.files, 2);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::set_permissions(dir.join("a.txt"), std::fs::Permissions::from_mode(0o755))?;
+
+            let report = roundtrip::roundtrip_check(dir)?;
+            assert_eq!(report.permissions, vec![PathBuf::from("a.txt")]);
+            assert!(report.changed.is_empty() && report.missing.is_empty() && report.extra.is_empty());
+        }
+
+        assert!(roundtrip::roundtrip_check("test_asar.asar").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_temp_guard() -> Result<(), asar_error::Error> { // tests that packing replaces archives only once complete
+        std::fs::remove_dir_all("test_temp").ok();
+        std::fs::create_dir_all("test_temp/tmp")?;
+
+        let destination = Path::new("test_temp/app.asar");
+        Asar::open("test_folder")?.pack(destination)?;
+        let original = std::fs::read(destination)?;
+
+        let blocked = PackOptions::new().scan(|_, _| ScanResult::Block("blocked".to_string()));
+        assert!(Asar::open_with("test_folder", &blocked)?.pack(destination).is_err());
+        assert_eq!(std::fs::read(destination)?, original);
+
+        Asar::open_with("test_folder", &PackOptions::new().temp_dir("test_temp/tmp"))?.pack("test_temp/copy.asar")?;
+        assert_eq!(std::fs::read("test_temp/copy.asar")?, original);
+
+        let guard = TempGuard::new_for(destination, None)?;
+        let temp = guard.path().to_path_buf();
+        assert!(temp.parent().is_some_and(|parent| parent.ends_with("test_temp")));
+        drop(guard);
+        assert!(!temp.exists());
+
+        assert_eq!(std::fs::read_dir("test_temp")?.count(), 3); // app.asar, copy.asar, and tmp only
+        assert_eq!(std::fs::read_dir("test_temp/tmp")?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_retry() -> Result<(), asar_error::Error> { // tests retrying files locked by other processes
+        let retry = LockRetry { attempts: 3, backoff: std::time::Duration::from_millis(1) };
+        let mut calls = 0;
+
+        let result = retry.run(|| {
+            calls += 1;
+
+            match calls {
+                1 | 2 => Err(std::io::Error::from(std::io::ErrorKind::WouldBlock)),
+                _ => Ok(calls),
+            }
+        });
+        assert_eq!(result?, 3);
+
+        calls = 0;
+        let locked = retry.run(|| -> std::io::Result<()> {
+            calls += 1;
+            Err(std::io::Error::from(std::io::ErrorKind::WouldBlock))
+        });
+        assert!(locked.is_err_and(|err| locked::is_locked(&err)));
+        assert_eq!(calls, 4);
+
+        calls = 0;
+        assert!(retry.run(|| -> std::io::Result<()> { calls += 1; Err(std::io::Error::from(std::io::ErrorKind::NotFound)) }).is_err());
+        assert_eq!(calls, 1);
+
+        let options = PackOptions::new().retry_locked(2, std::time::Duration::from_millis(1)).on_locked(LockPolicy::SkipLocked);
+        let asar = Asar::open_with("test_folder", &options)?;
+        assert!(asar.skipped_locked().is_empty());
+        assert_eq!(asar.header, Asar::open("test_folder")?.header);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_permissions() -> Result<(), asar_error::Error> { // tests restoring modes and ownership of extracted files
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let owner = std::fs::metadata("test_folder/test1.txt")?;
+
+        let mut asar = Asar::open("test_folder")?;
+        crate::content::entry_mut(asar.header.as_mut().unwrap(), Path::new("test1.txt")).unwrap().insert("executable".to_string(), serde_json::json!(true));
+        asar.set_entry_meta("another.txt", "uid", serde_json::json!(owner.uid()))?;
+        asar.set_entry_meta("another.txt", "gid", serde_json::json!(owner.gid()))?;
+        asar.pack("test_permissions.asar")?;
+
+        std::fs::remove_dir_all("test_permissions").ok();
+        let options = ExtractOptions::new().preserve_permissions(true).umask(0o027).preserve_ownership(true);
+        Asar::open("test_permissions.asar")?.extract_with("test_permissions", &options)?;
+
+        let mode = |path: &str| std::fs::metadata(Path::new("test_permissions").join(path)).map(|metadata| metadata.permissions().mode() & 0o777);
+        assert_eq!(mode("test1.txt")?, 0o750);
+        assert_eq!(mode("another.txt")?, 0o640);
+        assert_eq!(mode("folder1")?, 0o750);
+        assert_eq!(std::fs::metadata("test_permissions/another.txt")?.uid(), owner.uid());
+
+        std::fs::remove_dir_all("test_permissions").ok();
+        Asar::open("test_permissions.asar")?.extract_with("test_permissions", &ExtractOptions::new().preserve_permissions(true))?;
+        assert_eq!(mode("test1.txt")?, 0o755);
+        assert_eq!(mode("folder1/script.py")?, 0o644);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_timestamps() -> Result<(), asar_error::Error> { // tests setting times of extracted files
+        use std::time::{Duration, SystemTime};
+
+        let mut asar = Asar::open("test_folder")?;
+        asar.set_entry_meta("test1.txt", "mtime", serde_json::json!(1_000_000_000))?;
+        asar.pack("test_timestamps.asar")?;
+
+        let modified = |path: &str| std::fs::metadata(Path::new("test_timestamps").join(path)).and_then(|metadata| metadata.modified());
+
+        std::fs::remove_dir_all("test_timestamps").ok();
+        let fixed = ExtractOptions::new().timestamps(TimestampPolicy::Fixed(SystemTime::UNIX_EPOCH));
+        Asar::open("test_timestamps.asar")?.extract_with("test_timestamps", &fixed)?;
+        assert_eq!(modified("test1.txt")?, SystemTime::UNIX_EPOCH);
+        assert_eq!(modified("folder1/script.py")?, SystemTime::UNIX_EPOCH);
+        #[cfg(unix)]
+        assert_eq!(modified("folder1")?, SystemTime::UNIX_EPOCH);
+
+        std::fs::remove_dir_all("test_timestamps").ok();
+        let recorded = ExtractOptions::new().timestamps(TimestampPolicy::FromArchiveMeta);
+        Asar::open("test_timestamps.asar")?.extract_with("test_timestamps", &recorded)?;
+        assert_eq!(modified("test1.txt")?, SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000));
+        assert!(modified("another.txt")? > SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_read_only() -> Result<(), asar_error::Error> { // tests marking extracted files read-only
+        let readonly = |path: &str| std::fs::metadata(Path::new("test_read_only").join(path)).map(|metadata| metadata.permissions().readonly());
+
+        if Path::new("test_read_only").exists() {
+            extract::set_read_only("test_read_only", false)?;
+            std::fs::remove_dir_all("test_read_only")?;
+        }
+
+        let options = ExtractOptions::new().preserve_permissions(true).read_only(true);
+        Asar::open("test_asar.asar")?.extract_with("test_read_only", &options)?;
+        assert!(readonly("test1.txt")? && readonly("folder1/script.py")?);
+        assert!(!readonly("folder1")?);
+
+        extract::set_read_only("test_read_only", false)?;
+        assert!(!readonly("test1.txt")? && !readonly("folder1/script.py")?);
+
+        Asar::open("test_asar.asar")?.extract("test_read_only")?; // writable again
+        extract::set_read_only("test_read_only/test1.txt", true)?;
+        assert!(readonly("test1.txt")? && !readonly("folder1/script.py")?);
+        extract::set_read_only("test_read_only", false)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_illegal_names() -> Result<(), asar_error::Error> { // tests extracting names illegal on the target platform
+        let mut packer = Packer::new();
+        for name in ["aux.js", "a:b.txt", "CON", "ok.txt", "dir./x.txt"] {
+            packer.add_reader(name, Box::new(&b"x"[..]), 1)?;
+        }
+        packer.write("test_names.asar")?;
+
+        let asar = Asar::open("test_names.asar")?;
+        let windows = ExtractOptions::new().platform(Platform::Windows);
+
+        std::fs::remove_dir_all("test_names").ok();
+        let err = asar.extract_with("test_names", &windows).unwrap_err();
+        assert!(err.to_string().starts_with("5 illegal file name(s)"));
+        assert!(!Path::new("test_names").exists());
+
+        asar.extract_with("test_names", &windows.clone().illegal_names(NamePolicy::Skip))?;
+        assert_eq!(std::fs::read_dir("test_names")?.count(), 1);
+
+        std::fs::remove_dir_all("test_names").ok();
+        asar.extract_with("test_names", &windows.illegal_names(NamePolicy::RenameEscaped))?;
+        for name in ["au%78.js", "a%3Ab.txt", "CO%4E", "ok.txt", "dir%2E/x.txt"] {
+            assert!(Path::new("test_names").join(name).is_file(), "{} was not extracted", name);
+        }
+
+        for name in ["aux.js", "a:b.txt", "CON", "con.tar.gz", "50%", "dir.", "..", "a%3A"] {
+            let escaped = names::escape(name, Platform::Windows);
+            assert_eq!(names::illegal_reason(&escaped, Platform::Windows), None);
+            assert_eq!(names::unescape(&escaped), name);
+        }
+        assert_eq!(names::illegal_reason("aux.js", Platform::Unix), None);
+
+        let mut packer = Packer::new();
+        packer.add_reader("../escaped.txt", Box::new(&b"x"[..]), 1)?;
+        packer.write("test_names.asar")?;
+        let escaping = Asar::open("test_names.asar")?;
+        assert!(escaping.extract_with("test_names", &ExtractOptions::new()).is_err());
+        assert!(escaping.extract("test_names").is_err());
+        assert!(escaping.content.asar_to_dir("test_names", &File::open("test_names.asar")?, escaping.start).is_err());
+        assert!(!Path::new("escaped.txt").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_case_collisions() -> Result<(), asar_error::Error> { // tests entries colliding on case-insensitive filesystems
+        let mut packer = Packer::new();
+        for name in ["Data", "Foo.js", "foo.js", "Lib/a.js", "lib/A.js", "lib/b.js", "data/x.txt"] {
+            packer.add_reader(name, Box::new(&b"x"[..]), 1)?;
+        }
+        packer.write("test_collisions.asar")?;
+
+        let asar = Asar::open("test_collisions.asar")?;
+        let macos = ExtractOptions::new().platform(Platform::MacOs);
+        let extracted = |names: &[&str]| names.iter().all(|name| Path::new("test_collisions").join(name).is_file());
+
+        std::fs::remove_dir_all("test_collisions").ok();
+        let err = asar.extract_with("test_collisions", &macos).unwrap_err();
+        assert!(err.to_string().starts_with("3 path(s) collide"));
+        assert!(!Path::new("test_collisions").exists());
+
+        asar.extract_with("test_collisions", &macos.clone().case_collisions(CollisionPolicy::KeepFirst))?;
+        assert!(extracted(&["Data", "Foo.js", "Lib/a.js", "lib/b.js"]));
+        assert!(!Path::new("test_collisions/foo.js").exists() && !Path::new("test_collisions/data").exists());
+
+        std::fs::remove_dir_all("test_collisions").ok();
+        asar.extract_with("test_collisions", &macos.case_collisions(CollisionPolicy::SuffixRename))?;
+        assert!(extracted(&["Data", "Foo.js", "foo-1.js", "Lib/a.js", "lib/A-1.js", "lib/b.js", "data-1/x.txt"]));
+
+        std::fs::remove_dir_all("test_collisions").ok();
+        asar.extract_with("test_collisions", &ExtractOptions::new().platform(Platform::Unix))?;
+        assert!(extracted(&["Data", "Foo.js", "foo.js", "lib/A.js", "data/x.txt"]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint() -> Result<(), asar_error::Error> { // tests every lint rule and severity overrides
+        let mut packer = Packer::new();
+        packer.add_reader("b.js", Box::new(&b"bbbb"[..]), 4)?;
+        packer.add_reader("a.js.map", Box::new(&b"{}"[..]), 2)?;
+        packer.add_reader("lib.so", Box::new(&b"\x7FELF"[..]), 4)?;
+        packer.write("test_lint.asar")?;
+
+        let asar = Asar::open("test_lint.asar")?;
+        let findings = asar.lint(&RuleSet::new().max_file_size(3))?;
+        let found = |rule: Rule| -> Vec<String> {
+            findings
+                .iter()
+                .filter(|finding| finding.rule == rule)
+                .filter_map(|finding| finding.path.as_ref().map(|path| path.display().to_string()))
+                .collect()
+        };
+
+        assert_eq!(found(Rule::LargeFile), vec!["b.js", "lib.so"]);
+        assert_eq!(found(Rule::SourceMap), vec!["a.js.map"]);
+        assert_eq!(found(Rule::NativeBinary), vec!["lib.so"]);
+        assert_eq!(found(Rule::UnorderedOffsets), vec!["b.js"]);
+        assert_eq!(found(Rule::MissingIntegrity).len(), 3);
+        assert!(findings.iter().all(|finding| finding.severity == finding.rule.default_severity()));
+
+        let rules = RuleSet::empty().severity(Rule::SourceMap, Severity::Error);
+        let findings = asar.lint(&rules)?;
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].to_string(), "error[source-map] a.js.map: debug artifact packed; strip it from release builds");
+
+        Asar::open_with("test_folder", &PackOptions::new().integrity())?.pack("test_lint_integrity.asar")?;
+        let findings = Asar::open("test_lint_integrity.asar")?.lint(&RuleSet::new().disable(Rule::LargeFile))?;
+        assert!(findings.iter().all(|finding| finding.rule != Rule::MissingIntegrity && finding.rule != Rule::UnorderedOffsets));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_kind() -> Result<(), asar_error::Error> { // tests stable error kinds and JSON offsets
+        let not_found = Asar::open("test_missing.asar").unwrap_err();
+        assert_eq!(not_found.kind(), "not-found");
+        assert_eq!(not_found.offset(), None);
+
+        let json: asar_error::Error = serde_json::from_str::<serde_json::Value>(r#"{"files": {]"#).unwrap_err().into();
+        assert_eq!(json.kind(), "invalid-json");
+        assert_eq!(json.offset(), Some(11));
+
+        assert_eq!(asar_error::Error::PolicyViolation(Vec::new()).kind(), "policy-violation");
+        assert_eq!(asar_error::Error::TimedOut.kind(), "timed-out");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hasher() -> Result<(), asar_error::Error> { // tests custom hash algorithms across integrity, duplicates, and exports
+        struct Sum;
+        struct SumState(u64);
+
+        impl Hasher for Sum {
+            fn name(&self) -> &str {
+                "SUM8"
+            }
+
+            fn start(&self) -> Box<dyn HashState> {
+                Box::new(SumState(0))
+            }
+        }
+
+        impl HashState for SumState {
+            fn update(&mut self, bytes: &[u8]) {
+                self.0 = bytes.iter().fold(self.0, |sum, byte| sum + *byte as u64);
+            }
+
+            fn finish(self: Box<Self>) -> Vec<u8> {
+                self.0.to_be_bytes().to_vec()
+            }
+        }
+
+        assert_eq!(hash::hex_digest(&hash::Sha256, &b"same"[..])?, cas::sha256_hex(&b"same"[..])?);
+        assert!(hash::find("sha256").is_some() && hash::find("sum8").is_none());
+
+        hash::register(Arc::new(Sum));
+        assert_eq!(hash::find("sum8").map(|hasher| hasher.name().to_string()), Some("SUM8".to_string()));
+
+        let integrity = cas::integrity_with(&Sum, &b"ab"[..])?;
+        assert_eq!(integrity["algorithm"], "SUM8");
+        assert_eq!(integrity["hash"], "00000000000000c3");
+
+        let options = PackOptions::new().integrity().hash_algorithm(Arc::new(Sum));
+        let asar = Asar::open_with("test_folder", &options)?;
+        assert_eq!(asar.hasher().name(), "SUM8");
+        asar.pack("test_hasher.asar")?;
+
+        let mut packed = Asar::open("test_hasher.asar")?;
+        let header = packed.content.to_header_value()?;
+        assert_eq!(header.pointer("/files/folder1/files/script.py/integrity/algorithm"), Some(&serde_json::json!("SUM8")));
+
+        let sha256 = packed.etag("folder1/script.py")?;
+        packed.set_hasher(Arc::new(Sum));
+        assert_ne!(packed.etag("folder1/script.py")?, sha256);
+
+        std::fs::remove_dir_all("test_hasher_cas").ok();
+        let manifest = packed.export_cas("test_hasher_cas")?;
+        assert_eq!(manifest["algorithm"], "SUM8");
+        Asar::pack_from_manifest(&manifest, "test_hasher_cas", "test_hasher_rebuilt.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chunk() -> Result<(), asar_error::Error> { // tests content-defined chunks surviving shifted bytes
+        let mut state: u64 = 1;
+        let data: Vec<u8> = (0..256 * 1024)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect();
+        let mut edited = data.clone();
+        edited.splice(100_000..100_000, b"inserted bytes".iter().copied());
+
+        let options = ChunkOptions::default();
+        let old = chunk::chunk(data.as_slice(), &options, &hash::Sha256)?;
+        let new = chunk::chunk(edited.as_slice(), &options, &hash::Sha256)?;
+
+        assert_eq!(old, chunk::chunk(data.as_slice(), &options, &hash::Sha256)?);
+        assert_eq!(new.iter().map(|chunk| chunk.size).sum::<u64>(), edited.len() as u64);
+        assert!(new.windows(2).all(|pair| pair[0].offset + pair[0].size == pair[1].offset));
+        assert!(new.iter().all(|chunk| chunk.size <= options.max_size as u64));
+
+        let delta = chunk::delta(&old, &new);
+        assert!(old.len() > data.len() / options.avg_size / 2);
+        assert!(delta.added_size() < 4 * options.avg_size as u64);
+        assert_eq!(delta.reused + delta.added_size(), edited.len() as u64);
+
+        let invalid = ChunkOptions { min_size: 0, ..options };
+        assert!(chunk::chunk(data.as_slice(), &invalid, &hash::Sha256).is_err());
+
+        let asar = Asar::open("test_asar.asar")?;
+        let chunks = asar.chunk_file("folder1/test_image.jpg", &options)?;
+        assert_eq!(Some(chunks.iter().map(|chunk| chunk.size).sum::<u64>()), asar.get_file("folder1/test_image.jpg").map(|file| file.len() as u64));
+        assert!(asar.chunk_file("folder1", &options).is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_archive() -> Result<(), asar_error::Error> { // tests the encrypted envelope and opening it transparently
+        use crate::envelope::{self, KdfParams};
+
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+        envelope::encrypt_file("test_asar.asar", "test_envelope.asar.enc", b"correct horse", &params)?;
+
+        let sealed = std::fs::read("test_envelope.asar.enc")?;
+        assert!(envelope::is_encrypted("test_envelope.asar.enc")? && !envelope::is_encrypted("test_asar.asar")?);
+        assert!(Asar::open("test_envelope.asar.enc").is_err());
+
+        let asar = Asar::open_encrypted("test_envelope.asar.enc", b"correct horse")?;
+        let plain = Asar::open("test_asar.asar")?;
+        assert_eq!(asar.list()?, plain.list()?);
+        assert_eq!(asar.get_file("folder1/test_image.jpg"), plain.get_file("folder1/test_image.jpg"));
+
+        assert_eq!(asar.src_path, Path::new("test_envelope.asar.enc"));
+        asar.pack("test_envelope_repacked.asar")?;
+        assert_eq!(Asar::open("test_envelope_repacked.asar")?.get_file("folder1/test_image.jpg"), plain.get_file("folder1/test_image.jpg"));
+        std::fs::remove_file("test_envelope_repacked.asar")?;
+
+        let mut costly = sealed.clone();
+        costly[13..17].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write("test_envelope_tampered.asar.enc", &costly)?;
+        assert_eq!(Asar::open_encrypted("test_envelope_tampered.asar.enc", b"correct horse").unwrap_err().kind(), "invalid-header");
+
+        let err = Asar::open_encrypted("test_envelope.asar.enc", b"wrong horse").unwrap_err();
+        assert_eq!(err.kind(), "invalid-data");
+
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        std::fs::write("test_envelope_tampered.asar.enc", &tampered)?;
+        assert!(Asar::open_encrypted("test_envelope_tampered.asar.enc", b"correct horse").is_err());
+
+        std::fs::write("test_envelope_tampered.asar.enc", &sealed[..sealed.len() - 20])?;
+        assert!(envelope::decrypt_file("test_envelope_tampered.asar.enc", "test_envelope.asar", b"correct horse").is_err());
+        assert!(!Path::new("test_envelope.asar").exists());
+
+        envelope::decrypt_file("test_envelope.asar.enc", "test_envelope.asar", b"correct horse")?;
+        assert_eq!(std::fs::read("test_envelope.asar")?, std::fs::read("test_asar.asar")?);
+        std::fs::remove_file("test_envelope.asar")?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_key_providers() -> Result<(), asar_error::Error> { // tests opening encrypted archives through key providers
+        use crate::{envelope::{self, KdfParams}, keys::{EnvKey, KeyProvider, Secret, StaticKey}};
+
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 };
+        let key = StaticKey::new("correct horse");
+        assert_eq!(format!("{:?}", key), "StaticKey(Secret(..))");
+
+        envelope::encrypt_file_with("test_asar.asar", "test_keys.asar.enc", &key, &params)?;
+        let expected = Asar::open("test_asar.asar")?.list()?;
+        assert_eq!(Asar::open_encrypted_with("test_keys.asar.enc", &key)?.list()?, expected);
+
+        std::env::set_var("RUST_ASAR_TEST_KEYS_PASSPHRASE", "correct horse");
+        assert_eq!(Asar::open_encrypted_with("test_keys.asar.enc", &EnvKey::new("RUST_ASAR_TEST_KEYS_PASSPHRASE"))?.list()?, expected);
+
+        let unset = EnvKey::new("RUST_ASAR_TEST_KEYS_UNSET").secret(Path::new("test_keys.asar.enc")).unwrap_err();
+        assert_eq!(unset.kind(), "not-found");
+
+        let callback = |archive: &Path| -> Result<Secret, asar_error::Error> {
+            assert_eq!(archive, Path::new("test_keys.asar.enc"));
+            Ok(Secret::from("correct horse"))
+        };
+        assert_eq!(Asar::open_encrypted_with("test_keys.asar.enc", &callback)?.list()?, expected);
+
+        let wrong = |_: &Path| Ok(Secret::from("wrong horse"));
+        assert!(Asar::open_encrypted_with("test_keys.asar.enc", &wrong).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_volumes() -> Result<(), asar_error::Error> { // tests packing into volumes and reading them as one archive
+        use crate::volume;
+
+        let plain = Asar::open("test_asar.asar")?;
+        Asar::open_with("test_asar.asar", &PackOptions::new().split_size(4096))?.pack("test_volumes.asar")?;
+
+        let volumes = volume::volumes("test_volumes.asar")?;
+        assert!(volumes.len() > 2 && !Path::new("test_volumes.asar").exists());
+        assert!(volumes[1..].iter().all(|path| std::fs::metadata(path).map(|m| m.len() <= 4096).unwrap_or(false)));
+
+        let asar = Asar::open("test_volumes.asar")?;
+        assert_eq!(asar.volumes(), volumes.iter().map(PathBuf::as_path).collect::<Vec<&Path>>());
+        assert_eq!(asar.list()?, plain.list()?);
+
+        for path in plain.list()?.iter().filter(|path| plain.is_file(path)) {
+            assert_eq!(asar.get_file(path), plain.get_file(path));
+
+            let mut read: Vec<u8> = Vec::new();
+            asar.open_entry(path)?.read_to_end(&mut read)?;
+            assert_eq!(Some(read), plain.get_file(path));
+        }
+
+        // repacking the volumes into a single file matches repacking the original
+        Asar::open("test_volumes.asar.001")?.pack("test_volumes_joined.asar")?;
+        plain.pack("test_volumes_single.asar")?;
+        assert!(std::fs::read("test_volumes_joined.asar")? == std::fs::read("test_volumes_single.asar")?);
+
+        // fewer volumes replace the earlier ones, a missing volume fails to open
+        Asar::open_with("test_asar.asar", &PackOptions::new().split_size(1 << 20))?.pack("test_volumes.asar")?;
+        assert_eq!(volume::volumes("test_volumes.asar")?.len(), 1);
+        assert!(!volume::volume_path("test_volumes.asar", 2).exists());
+
+        Asar::open_with("test_asar.asar", &PackOptions::new().split_size(4096))?.pack("test_volumes.asar")?;
+        std::fs::remove_file(volume::volume_path("test_volumes.asar", 2))?;
+        assert_eq!(Asar::open("test_volumes.asar").unwrap_err().kind(), "not-found");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_archives() -> Result<(), asar_error::Error> { // tests reading archives nested within archives in place
+        use crate::nested;
+
+        let mut inner = Packer::new();
+        inner.add_reader("index.js", Box::new(&b"module.exports = 1;"[..]), 19)?;
+        inner.add_file("deep.asar", "test_asar.asar")?;
+        inner.write("test_nested_inner.asar")?;
+
+        let mut outer = Packer::new();
+        outer.add_reader("main.js", Box::new(&b"main"[..]), 4)?;
+        outer.add_file("plugins/inner.asar", "test_nested_inner.asar")?;
+        outer.add_reader("plugins/fake.asar", Box::new(&b"not an archive"[..]), 14)?;
+        outer.write("test_nested.asar")?;
+
+        let asar = Asar::open("test_nested.asar")?;
+        assert_eq!(asar.nested_archives()?, vec![PathBuf::from("plugins/inner.asar")]);
+        assert!(!asar.is_nested_archive("plugins/fake.asar") && !asar.is_nested_archive("main.js"));
+
+        let plain = Asar::open("test_asar.asar")?;
+        let image = plain.get_file("folder1/test_image.jpg");
+        assert_eq!(asar.read_nested("plugins/inner.asar!/index.js")?, b"module.exports = 1;");
+        assert_eq!(Some(asar.read_nested("plugins/inner.asar!/deep.asar!/folder1/test_image.jpg")?), image);
+        assert_eq!(Some(nested::read("test_nested.asar!/plugins/inner.asar!/deep.asar!/folder1/test_image.jpg")?), image);
+
+        let (deep, path) = nested::open("test_nested.asar!/plugins/inner.asar!/deep.asar!/folder1/script.py")?;
+        assert_eq!(deep.list()?, plain.list()?);
+        assert_eq!(deep.get_file(&path), plain.get_file(&path));
+        assert!(nested::open("test_nested.asar").is_err() && asar.open_nested("plugins/fake.asar").is_err());
+
+        let listed = asar.list_nested()?;
+        assert!(listed.contains(&"plugins/inner.asar!/deep.asar!/folder1/test_image.jpg".to_string()));
+        assert_eq!(asar.find_nested("image")?, vec!["plugins/inner.asar!/deep.asar!/folder1/test_image.jpg"]);
+
+        // a nested archive extracts and repacks as any other
+        std::fs::remove_dir_all("test_nested").ok();
+        deep.extract("test_nested")?;
+        assert_eq!(Some(std::fs::read("test_nested/folder1/test_image.jpg")?), image);
+        deep.pack("test_nested_repacked.asar")?;
+        assert_eq!(Asar::open("test_nested_repacked.asar")?.get_file("folder1/test_image.jpg"), image);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_entry_kinds() -> Result<(), asar_error::Error> { // tests routing custom header entries to a registered kind
+        use crate::kinds::{self, EntryKind};
+        use serde_json::{json, Map, Value};
+
+        struct Remote;
+
+        impl EntryKind for Remote {
+            fn name(&self) -> &str {
+                "test-remote"
+            }
+
+            fn matches(&self, entry: &Map<String, Value>) -> bool {
+                entry.contains_key("test_remote_url")
+            }
+
+            fn parse(&self, path: &Path, entry: &Map<String, Value>) -> Result<(), asar_error::Error> {
+                match entry["test_remote_url"].as_str() {
+                    Some(url) if url.starts_with("https://") => Ok(()),
+                    _ => Err(asar_error::Error::ParseHeaderError(format!("{} is not an https URL", path.display()))),
+                }
+            }
+
+            fn extract(&self, _path: &Path, entry: &Map<String, Value>, destination: &Path) -> Result<(), asar_error::Error> {
+                Ok(std::fs::write(destination, entry["test_remote_url"].as_str().unwrap_or_default())?)
+            }
+
+            fn pack(&self, path: &Path, source: &Path) -> Result<Option<Map<String, Value>>, asar_error::Error> {
+                if path.extension().is_none_or(|extension| extension != "remote") {
+                    return Ok(None);
+                }
+
+                let mut entry = Map::new();
+                entry.insert("test_remote_url".to_string(), Value::String(std::fs::read_to_string(source)?));
+                Ok(Some(entry))
+            }
+        }
+
+        let header = json!({ "files": { "model.bin": { "test_remote_url": "https://example.com/model.bin" } } });
+        std::fs::write("test_kinds.asar", Asar::header_bytes(&header, 0)?)?;
+        assert!(Asar::open("test_kinds.asar").is_err()); // unknown entries are malformed
+
+        kinds::register(Arc::new(Remote));
+
+        std::fs::remove_dir_all("test_kinds").ok();
+        std::fs::create_dir_all("test_kinds/src/assets")?;
+        std::fs::write("test_kinds/src/index.js", "main")?;
+        std::fs::write("test_kinds/src/assets/model.remote", "https://example.com/model.bin")?;
+
+        Asar::open("test_kinds/src")?.pack("test_kinds.asar")?;
+        let asar = Asar::open("test_kinds.asar")?;
+        assert_eq!(asar.custom_entries(), vec![(PathBuf::from("assets/model.remote"), "test-remote".to_string())]);
+        assert_eq!(asar.get_file("index.js"), Some(b"main".to_vec()));
+        assert!(!asar.is_file("assets/model.remote"));
+
+        asar.extract("test_kinds/out")?;
+        assert_eq!(std::fs::read_to_string("test_kinds/out/assets/model.remote")?, "https://example.com/model.bin");
+
+        let header = json!({ "files": { "model.bin": { "test_remote_url": "ftp://example.com/model.bin" } } });
+        std::fs::write("test_kinds.asar", Asar::header_bytes(&header, 0)?)?;
+        let err = Asar::open("test_kinds.asar").unwrap_err();
+        assert!(err.to_string().contains("model.bin is not an https URL"));
+
+        assert!(kinds::unregister("test-remote") && !kinds::unregister("test-remote"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lifecycle_hooks() -> Result<(), asar_error::Error> { // tests before/after hooks and retrying or skipping failed entries
+        use crate::{extract::ExtractOptions, hooks::{ErrorDecision, Hooks}, pack::PackOptions};
+        use std::sync::Mutex;
+
+        std::fs::remove_dir_all("test_hooks").ok();
+        std::fs::create_dir_all("test_hooks/src/lib")?;
+        std::fs::write("test_hooks/src/index.js", "main")?;
+        std::fs::write("test_hooks/src/lib/gone.js", "gone")?;
+        std::fs::write("test_hooks/src/lib/util.js", "util")?;
+        std::fs::write("test_hooks/src/lib/empty.js", "")?;
+        std::fs::write("test_hooks/src/lib/none.js", "")?;
+
+        let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let (before, after, failed) = (events.clone(), events.clone(), events.clone());
+
+        let hooks = Hooks::new()
+            .before_entry(move |path| before.lock().unwrap().push(format!("before {}", path.display())))
+            .after_entry(move |path, size| after.lock().unwrap().push(format!("after {} {}", path.display(), size)))
+            .on_error(move |path, _, attempts| {
+                failed.lock().unwrap().push(format!("error {} {}", path.display(), attempts));
+                match attempts {
+                    1 => ErrorDecision::Retry,
+                    _ => ErrorDecision::Skip,
+                }
+            });
+
+        let asar = Asar::open_with("test_hooks/src", &PackOptions::new().hooks(hooks.clone()))?;
+        std::fs::remove_file("test_hooks/src/lib/gone.js")?; // fails to read while packing
+        std::fs::remove_file("test_hooks/src/lib/none.js")?; // shares its offset with empty.js
+        asar.pack("test_hooks.asar")?;
+
+        // skipped files are left out of the header, written after the body, and the files following them moved back
+        let packed = Asar::open("test_hooks.asar")?;
+        assert_eq!(packed.get_file("index.js"), Some(b"main".to_vec()));
+        assert!(packed.stat("lib/gone.js").is_none());
+        assert!(packed.stat("lib/none.js").is_none());
+        assert_eq!(packed.get_file("lib/empty.js"), Some(Vec::new()));
+        assert_eq!(packed.stat("lib/util.js"), Some(EntryRef::File(4, 4)));
+        assert_eq!(packed.get_file("lib/util.js"), Some(b"util".to_vec()));
+        assert_eq!(
+            std::mem::take(&mut *events.lock().unwrap()),
+            vec![
+                "before index.js", "after index.js 4", "before lib/empty.js", "after lib/empty.js 0",
+                "before lib/gone.js", "error lib/gone.js 1", "error lib/gone.js 2",
+                "before lib/none.js", "error lib/none.js 1", "error lib/none.js 2",
+                "before lib/util.js", "after lib/util.js 4",
+            ]
+        );
+
+        // a folder in place of a file fails every write, which is skipped
+        std::fs::create_dir_all("test_hooks/out/index.js/blocked")?;
+        packed.extract_with("test_hooks/out", &ExtractOptions::new().hooks(hooks))?;
+        assert!(std::fs::metadata("test_hooks/out/index.js")?.is_dir());
+        assert_eq!(std::fs::read("test_hooks/out/lib/util.js")?, b"util");
+        assert_eq!(
+            std::mem::take(&mut *events.lock().unwrap()),
+            vec![
+                "before index.js", "error index.js 1", "error index.js 2", "before lib/empty.js", "after lib/empty.js 0",
+                "before lib/util.js", "after lib/util.js 4",
+            ]
+        );
+
+        // without an on_error hook, the first failure aborts
+        assert!(packed.extract_with("test_hooks/out", &ExtractOptions::new().hooks(Hooks::new())).is_err());
+
+        std::fs::remove_dir_all("test_hooks")?;
+        std::fs::remove_file("test_hooks.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_operation_report() -> Result<(), asar_error::Error> { // tests continuing past failed files and reporting them
+        use crate::{extract::ExtractOptions, hooks::{ErrorDecision, Hooks}};
+
+        std::fs::remove_dir_all("test_report").ok();
+        std::fs::create_dir_all("test_report/src/lib")?;
+        std::fs::write("test_report/src/index.js", "main")?;
+        std::fs::write("test_report/src/lib/gone.js", "gone")?;
+        std::fs::write("test_report/src/lib/util.js", "util")?;
+
+        let asar = Asar::open("test_report/src")?;
+        std::fs::remove_file("test_report/src/lib/gone.js")?;
+        assert!(asar.pack("test_report.asar").is_err()); // a single failed file aborts
+
+        let report = asar.pack_report("test_report.asar")?;
+        assert!(!report.is_complete());
+        assert_eq!(report.succeeded, vec![PathBuf::from("index.js"), PathBuf::from("lib/util.js")]);
+        assert_eq!(report.failed_paths(), vec![Path::new("lib/gone.js")]);
+        assert_eq!(report.failed[0].1.kind(), "not-found");
+
+        let packed = Asar::open("test_report.asar")?;
+        assert_eq!(packed.get_file("lib/util.js"), Some(b"util".to_vec()));
+        assert!(packed.stat("lib/gone.js").is_none());
+
+        // a folder in place of a file fails to be written, the other files are still extracted
+        std::fs::create_dir_all("test_report/out/index.js/blocked")?;
+        let report = packed.extract_report("test_report/out", &ExtractOptions::new())?;
+        assert_eq!(report.failed_paths(), vec![Path::new("index.js")]);
+        assert_eq!(report.succeeded.len(), 1);
+        assert_eq!(std::fs::read("test_report/out/lib/util.js")?, b"util");
+
+        // once nothing fails, along with hooks of its own, the report is complete
+        std::fs::remove_dir_all("test_report/out")?;
+        let hooks = Hooks::new().on_error(|_, _, _| ErrorDecision::Retry);
+        let report = packed.extract_report("test_report/out", &ExtractOptions::new().hooks(hooks))?;
+        assert!(report.is_complete());
+        assert_eq!(report.succeeded.len(), 2);
+
+        std::fs::remove_dir_all("test_report")?;
+        std::fs::remove_file("test_report.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retry_policy() -> Result<(), asar_error::Error> { // tests retrying transient IO errors with exponential backoff
+        use crate::{hooks::{ErrorDecision, Hooks, RunContext}, retry::{self, RetryPolicy}};
+        use std::{io, sync::atomic::{AtomicU32, Ordering}, time::Duration};
+
+        let policy = RetryPolicy::new().attempts(4).backoff(Duration::from_millis(10), Duration::from_millis(50)).jitter(2.0);
+        assert_eq!(policy.jitter, 1.0);
+        assert_eq!(
+            (0..4).map(|retry| policy.delay(retry)).collect::<Vec<Duration>>(),
+            vec![Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(40), Duration::from_millis(50)]
+        );
+
+        assert!(retry::is_transient(&io::Error::from(io::ErrorKind::TimedOut).into()));
+        assert!(!retry::is_transient(&io::Error::from(io::ErrorKind::NotFound).into()));
+        assert!(!retry::is_transient(&asar_error::Error::Cancelled));
+
+        let errors = Arc::new(AtomicU32::new(0));
+        let counted = errors.clone();
+        let hooks = Hooks::new().on_error(move |_, _, _| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            ErrorDecision::Skip
+        });
+        let run = RunContext {
+            retry: Some(RetryPolicy::new().attempts(2).backoff(Duration::ZERO, Duration::ZERO)),
+            ..RunContext::default()
+        };
+
+        // transient errors are retried without calling on_error
+        let mut calls = 0;
+        let read = hooks.attempt(&run, Path::new("index.js"), || {
+            calls += 1;
+            match calls {
+                1..=2 => Err(io::Error::from(io::ErrorKind::Interrupted).into()),
+                _ => Ok(calls),
+            }
+        })?;
+        assert_eq!((read, errors.load(Ordering::SeqCst)), (Some(3), 0));
+
+        // once every retry failed, or for other errors, on_error decides
+        let read: Option<()> = hooks.attempt(&run, Path::new("index.js"), || Err(io::Error::from(io::ErrorKind::TimedOut).into()))?;
+        assert_eq!((read, errors.load(Ordering::SeqCst)), (None, 1));
+        let read: Option<()> = hooks.attempt(&run, Path::new("index.js"), || Err(io::Error::from(io::ErrorKind::NotFound).into()))?;
+        assert_eq!((read, errors.load(Ordering::SeqCst)), (None, 2));
+
+        // retries apply while packing and extracting
+        std::fs::remove_dir_all("test_retry").ok();
+        std::fs::create_dir_all("test_retry/src")?;
+        std::fs::write("test_retry/src/index.js", "main")?;
+
+        Asar::open_with("test_retry/src", &PackOptions::new().retry(RetryPolicy::new()))?.pack("test_retry.asar")?;
+        Asar::open("test_retry.asar")?.extract_with("test_retry/out", &ExtractOptions::new().retry(RetryPolicy::new()))?;
+        assert_eq!(std::fs::read("test_retry/out/index.js")?, b"main");
+
+        std::fs::remove_dir_all("test_retry")?;
+        std::fs::remove_file("test_retry.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_priority() -> Result<(), asar_error::Error> { // tests writing files with a higher priority first
+        use crate::hooks::Hooks;
+        use std::sync::Mutex;
+
+        let mut packer = Packer::new();
+        packer.add_reader("assets/logo.png", Box::new(&b"png"[..]), 3)?;
+        packer.add_reader("lib/util.js", Box::new(&b"util"[..]), 4)?;
+        packer.add_reader("main.js", Box::new(&b"main"[..]), 4)?;
+        packer.add_reader("package.json", Box::new(&b"{}"[..]), 2)?;
+        packer.write("test_priority.asar")?;
+
+        let order: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+        let before = order.clone();
+
+        let options = ExtractOptions::new()
+            .priority(|path| match path.to_str() {
+                Some("package.json") | Some("main.js") => 2,
+                _ if path.starts_with("lib") => 1,
+                _ => 0,
+            })
+            .hooks(Hooks::new().before_entry(move |path| before.lock().unwrap().push(path.to_path_buf())));
+
+        std::fs::remove_dir_all("test_priority").ok();
+        Asar::open("test_priority.asar")?.extract_with("test_priority", &options)?;
+
+        let order: Vec<String> = order.lock().unwrap().iter().map(|path| path.display().to_string()).collect();
+        assert_eq!(order, vec!["main.js", "package.json", "lib/util.js", "assets/logo.png"]);
+        assert_eq!(std::fs::read("test_priority/assets/logo.png")?, b"png");
+
+        std::fs::remove_dir_all("test_priority")?;
+        std::fs::remove_file("test_priority.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_background() -> Result<(), asar_error::Error> { // tests polling, waiting on, and cancelling a background extraction
+        let mut packer = Packer::new();
+        packer.add_reader("index.js", Box::new(&b"main"[..]), 4)?;
+        packer.add_reader("lib/util.js", Box::new(&b"util"[..]), 4)?;
+        packer.add_reader("styles/app.css", Box::new(&b"body"[..]), 4)?;
+        packer.write("test_background.asar")?;
+
+        let asar = Asar::open("test_background.asar")?;
+        std::fs::remove_dir_all("test_background").ok();
+
+        let handle = asar.extract_background("test_background/all", &ExtractOptions::new().extensions(&["js"]));
+        assert_eq!((handle.progress().total_files, handle.progress().total_bytes), (2, 8));
+
+        while !handle.is_finished() {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let progress = handle.progress();
+        assert_eq!((progress.files, progress.bytes, progress.fraction()), (2, 8, 1.0));
+        handle.wait()?;
+        assert_eq!(std::fs::read("test_background/all/lib/util.js")?, b"util");
+
+        // cancelled before the first file, nothing is written
+        let handle = asar.extract_background("test_background/cancelled", &ExtractOptions::new().priority(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            0
+        }));
+        handle.cancel();
+        assert!(matches!(handle.wait(), Err(asar_error::Error::Cancelled)));
+        assert!(!Path::new("test_background/cancelled/index.js").exists());
+
+        // errors found up front are returned by wait
+        assert!(Asar::open("test_background/all")?.extract_background("test_background/dir", &ExtractOptions::new()).wait().is_err());
+
+        std::fs::remove_dir_all("test_background")?;
+        std::fs::remove_file("test_background.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_throttle() -> Result<(), asar_error::Error> { // tests pacing writes while packing and extracting
+        use std::time::{Duration, Instant};
+
+        std::fs::remove_dir_all("test_throttle").ok();
+        std::fs::create_dir_all("test_throttle/src")?;
+        std::fs::write("test_throttle/src/data.bin", vec![7u8; 64 * 1024])?;
+
+        // 64 KiB at 256 KiB per second, less a burst of 25.6 KiB, takes at least 150 ms
+        let started = Instant::now();
+        Asar::open_with("test_throttle/src", &PackOptions::new().throttle(256 * 1024))?.pack("test_throttle.asar")?;
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        let asar = Asar::open("test_throttle.asar")?;
+        assert_eq!(asar.get_file("data.bin"), Some(vec![7u8; 64 * 1024]));
+
+        let started = Instant::now();
+        asar.extract_with("test_throttle/out", &ExtractOptions::new().throttle(256 * 1024))?;
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(std::fs::read("test_throttle/out/data.bin")?, vec![7u8; 64 * 1024]);
+
+        std::fs::remove_dir_all("test_throttle")?;
+        std::fs::remove_file("test_throttle.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_budget() -> Result<(), asar_error::Error> { // tests capping the buffers of pack and extract
+        use crate::budget::MemoryBudget;
+        use std::time::Duration;
+
+        std::fs::remove_dir_all("test_budget").ok();
+        std::fs::create_dir_all("test_budget/src")?;
+        std::fs::write("test_budget/src/large.bin", vec![1u8; 200 * 1024])?;
+        std::fs::write("test_budget/src/small.js", "main")?;
+
+        let budget = MemoryBudget::new(16 * 1024);
+        Asar::open_with("test_budget/src", &PackOptions::new().memory_budget(budget.clone()))?.pack("test_budget.asar")?;
+
+        let asar = Asar::open("test_budget.asar")?;
+        assert_eq!(asar.get_file("large.bin"), Some(vec![1u8; 200 * 1024]));
+
+        // extractions sharing the budget wait for room, rather than exceeding it
+        let options = ExtractOptions::new().memory_budget(budget.clone());
+        let held = budget.acquire(budget.limit());
+        let handles: Vec<_> = (0..3).map(|i| asar.extract_background(format!("test_budget/out{}", i), &options)).collect();
+
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(handles.iter().all(|handle| handle.progress().files == 0));
+        drop(held);
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            handle.wait()?;
+            assert_eq!(std::fs::read(format!("test_budget/out{}/large.bin", i))?, vec![1u8; 200 * 1024]);
+        }
+
+        assert_eq!(budget.in_use(), 0);
+
+        // a file held whole for a transformer leases the whole budget while larger than it
+        let options = options.transform(|_, content| TransformResult::Content(content));
+        asar.extract_with("test_budget/transformed", &options)?;
+        assert_eq!(std::fs::read("test_budget/transformed/small.js")?, b"main");
+
+        std::fs::remove_dir_all("test_budget")?;
+        std::fs::remove_file("test_budget.asar")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_index() -> Result<(), asar_error::Error> { // tests building the path index without a header Value
+        use crate::index::{self, build_index, EntryRef};
+        use serde_json::json;
+
+        let file = std::fs::File::open("test_asar.asar")?;
+        let (index, start) = index::read_index(&file)?;
+        let asar = Asar::open("test_asar.asar")?;
+        assert_eq!(start, asar.start);
+        assert_eq!(&index, asar.index());
+
+        let header = json!({ "volumes": [1], "files": {
+            "index.js": { "offset": "0", "size": 4, "integrity": { "algorithm": "SHA256", "blocks": ["00"] } },
+            "native.node": { "size": 8, "unpacked": true },
+            "current": { "link": "index.js" },
+            "lib": { "files": { "util.js": { "size": 2, "offset": "4" }, "empty": { "files": {} } } },
+            "odd": { "offset": "6", "size": 1, "files": { "hidden.js": { "offset": "0", "size": 1 } } },
+            "note": "not an entry"
+        } });
+
+        let streamed = index::index_from_json(serde_json::to_string(&header)?.as_bytes())?;
+        assert_eq!(streamed, build_index(&Content::new_json(header)?)?);
+        assert_eq!(streamed.get(Path::new("lib/util.js")), Some(&EntryRef::File(4, 2)));
+        assert_eq!(streamed.get(Path::new("lib/empty")), Some(&EntryRef::Folder));
+        assert!(!streamed.contains_key(Path::new("odd/hidden.js")));
+
+        let err = index::index_from_json(br#"{ "files": { "broken": { "size": 1 } } }"#).unwrap_err();
+        assert_eq!(err.to_string(), "Error parsing header for entity: broken");
+        assert!(index::index_from_json(br#"{ "volumes": [] }"#).is_err());
+        assert!(index::index_from_json(br#"{ "files": {} } trailing"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_index() -> Result<(), asar_error::Error> { // tests indexing archives as trees of interned names
+        use crate::{index::EntryRef, intern::{self, NameInterner}};
+
+        let names = NameInterner::new();
+        let asar = Asar::open("test_asar.asar")?;
+        let compact = asar.compact_index(&names);
+
+        assert_eq!(compact.len(), asar.index().len());
+        assert_eq!(&compact.to_index(), asar.index());
+        assert!(asar.index().iter().all(|(path, entry)| compact.get(path) == Some(*entry)));
+        assert_eq!(compact.get(""), Some(EntryRef::Folder));
+        assert_eq!(compact.get("missing/file.js"), None);
+
+        // names shared between archives are stored once
+        for version in ["1.0", "1.1"] {
+            let mut packer = Packer::new();
+            packer.add_reader("package.json", Box::new(&b"{}"[..]), 2)?;
+            packer.add_reader("lib/index.js", Box::new(version.as_bytes()), 3)?;
+            packer.write(format!("test_intern_{}.asar", version))?;
+        }
+
+        let names = NameInterner::new();
+        let first = Asar::open("test_intern_1.0.asar")?.compact_index(&names);
+        let (second, _) = intern::read_compact_index(&std::fs::File::open("test_intern_1.1.asar")?, &names)?;
+        assert_eq!(names.len(), 4); // "", "package.json", "lib", and "index.js"
+        assert!(Arc::ptr_eq(&names.intern("lib"), &names.intern("lib")));
+        assert!(matches!(first.get("lib/index.js"), Some(EntryRef::File(_, 3))));
+        assert!(matches!(second.get("lib/index.js"), Some(EntryRef::File(_, 3))));
+        assert!(second.get("lib").is_some_and(|entry| entry == EntryRef::Folder));
+
+        std::fs::remove_file("test_intern_1.0.asar")?;
+        std::fs::remove_file("test_intern_1.1.asar")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{asar::Asar, content::Content, transform::Transformed};
+
+/// Files stored once within an archive body and shared by several paths, as returned by
+/// `Asar::link_groups`, e.g. hard links deduplicated by `PackOptions::dedupe_hard_links`, or
+/// packages linked by pnpm (see `PackOptions::resolve_pnpm`).
+///
+/// - paths: Full paths of the files, sorted
+///
+/// - size: Size in bytes of the shared content
+///
+/// - saved: Bytes saved by storing the content once rather than for every path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkGroup {
+    pub paths: Vec<PathBuf>,
+    pub size: u64,
+    pub saved: u64,
+}
+
+// Identity of a file on disk: its device and inode, shared by hard links and by every symlink
+// resolving to it. `None` unless it is a hard link, or `symlinks` is set.
+#[cfg(unix)]
+fn identity(path: &Path, symlinks: bool) -> io::Result<Option<(u64, u64)>> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+
+    Ok((symlinks || metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino())))
+}
+
+// Identity of a file on disk: its canonical path, shared by every symlink resolving to it.
+// Hard links are not detected on other platforms.
+#[cfg(not(unix))]
+fn identity(path: &Path, symlinks: bool) -> io::Result<Option<PathBuf>> {
+    symlinks.then(|| std::fs::canonicalize(path)).transpose()
+}
+
+// Returns the index of the file whose content is stored in place of each file found while
+// generating a header, `None` for files stored themselves. Hard links (and, if `symlinks` is
+// set, every path resolving to the same file through symlinks) are stored once, under the
+// smallest of their paths. Transformed files are always stored themselves, as their content
+// depends on their path.
+pub(crate) fn shared_leaders(found: &[(PathBuf, u64)], transformed: Option<&Transformed>, symlinks: bool) -> io::Result<Vec<Option<usize>>> {
+    let mut groups = BTreeMap::new();
+
+    for (index, (path, _)) in found.iter().enumerate() {
+        if transformed.is_some_and(|transformed| transformed.contains_key(path)) {
+            continue;
+        }
+
+        if let Some(identity) = identity(path, symlinks)? {
+            groups.entry(identity).or_insert_with(Vec::new).push(index);
+        }
+    }
+
+    let mut leaders: Vec<Option<usize>> = vec![None; found.len()];
+
+    for indices in groups.into_values() {
+        let leader = indices.iter().copied().min_by(|&a, &b| found[a].0.cmp(&found[b].0));
+
+        for index in indices {
+            leaders[index] = leader.filter(|&leader| leader != index);
+        }
+    }
+
+    Ok(leaders)
+}
+
+// Returns true if the symlink within the folder resolves to a file, or to a folder that is not
+// the folder itself or one above it (which would recurse forever).
+pub(crate) fn follows_symlink(folder: &Path, link: &Path) -> io::Result<bool> {
+    let target = match fs::canonicalize(link) {
+        Ok(target) => target,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    Ok(!target.is_dir() || !fs::canonicalize(folder)?.starts_with(target))
+}
+
+// Collects the full path of every file stored within the body by its (offset, size).
+fn collect_stored(dir: &Map<String, Value>, parent: &Path, stored: &mut BTreeMap<(u64, u64), Vec<PathBuf>>) {
+    for (name, entry) in dir.iter() {
+        let path = parent.join(name);
+
+        if let Some(Value::Object(files)) = entry.get("files") {
+            collect_stored(files, &path, stored);
+            continue;
+        }
+
+        let offset = entry
+            .get("offset")
+            .and_then(Value::as_str)
+            .and_then(|offset| offset.parse::<u64>().ok());
+        let size = entry.get("size").and_then(Value::as_u64);
+
+        if let (Some(offset), Some(size)) = (offset, size) {
+            stored.entry((offset, size)).or_default().push(path);
+        }
+    }
+}
+
+impl Asar {
+
+    /// Returns every group of files sharing their content within the body of an opened Asar
+    /// archive file, or within the header generated for an opened directory, largest savings first.
+    ///
+    /// Empty files are never grouped, as they take no space.
+    pub fn link_groups(&self) -> Vec<LinkGroup> {
+        let dir = match (&self.header, &self.content) {
+            (Some(header), _) => header.get("files").and_then(Value::as_object),
+            (None, Content::Home(dir)) => Some(dir),
+            _ => None,
+        };
+
+        let mut stored: BTreeMap<(u64, u64), Vec<PathBuf>> = BTreeMap::new();
+
+        if let Some(dir) = dir {
+            collect_stored(dir, Path::new(""), &mut stored);
+        }
+
+        let mut groups: Vec<LinkGroup> = stored
+            .into_iter()
+            .filter(|((_, size), paths)| *size > 0 && paths.len() > 1)
+            .map(|((_, size), mut paths)| {
+                paths.sort();
+
+                LinkGroup {
+                    saved: size * (paths.len() as u64 - 1),
+                    paths,
+                    size,
+                }
+            })
+            .collect();
+
+        groups.sort_by_key(|group| Reverse(group.saved));
+        groups
+    }
+}
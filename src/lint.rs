@@ -0,0 +1,301 @@
+use std::{fmt, path::PathBuf};
+
+use crate::{asar::Asar, asar_error, debug};
+
+/// Default size in bytes above which `Rule::LargeFile` reports a file (10 MiB).
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// How serious a Finding is, ordered from least to most serious.
+///
+/// - Info: Worth knowing, but usually intended
+///
+/// - Warning: Likely a mistake within the build
+///
+/// - Error: Must be fixed before the archive is shipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+
+    /// Returns the severity with the provided name (`info`, `warning`, or `error`), if any.
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name {
+            "info" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A check of `Asar::lint`, named in kebab case (e.g. `large-file`).
+///
+/// - LargeFile: A file is larger than the maximum size of the RuleSet
+///
+/// - SourceMap: A debug artifact (source map, program database, or debug symbols) is packed
+///
+/// - NativeBinary: A native executable or shared library (ELF, PE, Mach-O) is packed, which can
+///   not be loaded from within an archive unless it is unpacked
+///
+/// - UnorderedOffsets: Files are not stored in header order, as a non-deterministic packer writes
+///   them, so packing the same folder twice yields different archives
+///
+/// - MissingIntegrity: A file has no `integrity` field, so Electron can not validate it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    LargeFile,
+    SourceMap,
+    NativeBinary,
+    UnorderedOffsets,
+    MissingIntegrity,
+}
+
+impl Rule {
+
+    /// Every rule, in the order findings are reported.
+    pub const ALL: [Rule; 5] = [
+        Rule::LargeFile,
+        Rule::SourceMap,
+        Rule::NativeBinary,
+        Rule::UnorderedOffsets,
+        Rule::MissingIntegrity,
+    ];
+
+    /// Returns the kebab case name of the rule.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::LargeFile => "large-file",
+            Rule::SourceMap => "source-map",
+            Rule::NativeBinary => "native-binary",
+            Rule::UnorderedOffsets => "unordered-offsets",
+            Rule::MissingIntegrity => "missing-integrity",
+        }
+    }
+
+    /// Returns the rule with the provided kebab case name, if any.
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+
+    /// Returns the severity of the rule within `RuleSet::new`.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Rule::LargeFile | Rule::SourceMap | Rule::UnorderedOffsets => Severity::Warning,
+            Rule::NativeBinary | Rule::MissingIntegrity => Severity::Info,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The rules checked by `Asar::lint`, each with the severity of its findings.
+///
+/// ```no_run
+/// use rust_asar::{asar::Asar, lint::{Rule, RuleSet, Severity}};
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let rules = RuleSet::new()
+///     .max_file_size(50 * 1024 * 1024)
+///     .severity(Rule::SourceMap, Severity::Error)
+///     .disable(Rule::MissingIntegrity);
+///
+/// for finding in Asar::open("app.asar")?.lint(&rules)? {
+///     println!("{}", finding);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: Vec<(Rule, Severity)>,
+    max_file_size: u64,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::new()
+    }
+}
+
+impl RuleSet {
+
+    /// Creates a RuleSet checking every rule at its default severity, with files larger than
+    /// `DEFAULT_MAX_FILE_SIZE` reported.
+    pub fn new() -> RuleSet {
+        RuleSet {
+            rules: Rule::ALL.into_iter().map(|rule| (rule, rule.default_severity())).collect(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    /// Creates a RuleSet checking no rule, to enable single rules with `severity`.
+    pub fn empty() -> RuleSet {
+        RuleSet {
+            rules: Vec::new(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+
+    /// Sets the size in bytes above which `Rule::LargeFile` reports a file.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    /// Checks the rule, reporting its findings at the provided severity.
+    pub fn severity(mut self, rule: Rule, severity: Severity) -> Self {
+        match self.rules.iter_mut().find(|(r, _)| *r == rule) {
+            Some(entry) => entry.1 = severity,
+            None => {
+                self.rules.push((rule, severity));
+                self.rules.sort();
+            }
+        }
+        self
+    }
+
+    /// Stops checking the rule.
+    pub fn disable(mut self, rule: Rule) -> Self {
+        self.rules.retain(|(r, _)| *r != rule);
+        self
+    }
+
+    /// Returns the severity of the rule, or `None` if it is not checked.
+    pub fn severity_of(&self, rule: Rule) -> Option<Severity> {
+        self.rules.iter().find(|(r, _)| *r == rule).map(|(_, severity)| *severity)
+    }
+}
+
+/// A problem found by `Asar::lint`.
+///
+/// - rule: The rule reporting the problem
+///
+/// - severity: The severity of the rule within the RuleSet
+///
+/// - path: Full path of the file within the archive, or `None` for the archive as a whole
+///
+/// - message: Describes the problem and how to fix it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.severity, self.rule)?;
+
+        if let Some(path) = self.path.as_ref() {
+            write!(f, " {}", path.display())?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Asar {
+
+    /// Checks an opened Asar archive file against every rule of the RuleSet.
+    ///
+    /// Only the leading bytes of each file are read, and only if `Rule::NativeBinary` is checked.
+    ///
+    /// Returns the findings grouped by rule (in `Rule::ALL` order), each in header order,
+    /// otherwise Error if the archive can not be read.
+    pub fn lint(&self, rules: &RuleSet) -> Result<Vec<Finding>, asar_error::Error> {
+        let files = self.content.files_to_vec()?;
+        let mut findings: Vec<Finding> = Vec::new();
+
+        let mut report = |rule: Rule, path: Option<PathBuf>, message: String| {
+            if let Some(severity) = rules.severity_of(rule) {
+                findings.push(Finding { rule, severity, path, message });
+            }
+        };
+
+        if rules.severity_of(Rule::LargeFile).is_some() {
+            for (path, _, size) in files.iter().filter(|(_, _, size)| *size > rules.max_file_size) {
+                report(
+                    Rule::LargeFile,
+                    Some(path.clone()),
+                    format!("{} bytes exceed the maximum of {} bytes", size, rules.max_file_size),
+                );
+            }
+        }
+
+        if rules.severity_of(Rule::SourceMap).is_some() {
+            for path in files.iter().map(|(path, _, _)| path) {
+                if debug::artifact_kind(path).is_some() {
+                    report(
+                        Rule::SourceMap,
+                        Some(path.clone()),
+                        "debug artifact packed; strip it from release builds".to_string(),
+                    );
+                }
+            }
+        }
+
+        if rules.severity_of(Rule::NativeBinary).is_some() {
+            for (path, kind) in self.detect_types()? {
+                if kind.is_native_binary() {
+                    report(
+                        Rule::NativeBinary,
+                        Some(path),
+                        format!("{:?} binary packed; unpack it so it can be loaded", kind),
+                    );
+                }
+            }
+        }
+
+        if rules.severity_of(Rule::UnorderedOffsets).is_some() {
+            // a deterministic packer writes every file right after the previous one in the header
+            let mut end = 0;
+
+            for (path, offset, size) in files.iter() {
+                if *offset < end {
+                    report(
+                        Rule::UnorderedOffsets,
+                        Some(path.clone()),
+                        format!("stored at offset {} before the end of the previous file ({})", offset, end),
+                    );
+                    break;
+                }
+
+                end = offset + size;
+            }
+        }
+
+        if rules.severity_of(Rule::MissingIntegrity).is_some() {
+            for (path, _, _) in files.iter() {
+                if !self.entry_fields(path).is_some_and(|fields| fields.contains_key("integrity")) {
+                    report(
+                        Rule::MissingIntegrity,
+                        Some(path.clone()),
+                        "no integrity field; pack with integrity recorded".to_string(),
+                    );
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+}
+
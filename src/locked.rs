@@ -0,0 +1,94 @@
+use std::{
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use crate::asar::Asar;
+
+/// What packing does with a file still locked by another process once every retry failed
+/// (see `PackOptions::on_locked`).
+///
+/// - Fail: Packing fails with the error of the locked file
+///
+/// - SkipLocked: The file is left out of the archive and listed by `Asar::skipped_locked`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LockPolicy {
+    #[default]
+    Fail,
+    SkipLocked,
+}
+
+/// How often a file locked by another process is opened again while packing, before giving up
+/// (see `PackOptions::retry_locked`).
+///
+/// - attempts: Number of retries after the first attempt
+///
+/// - backoff: Delay before the first retry, doubled before every following retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockRetry {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+impl Default for LockRetry {
+    fn default() -> Self {
+        LockRetry {
+            attempts: 3,
+            backoff: Duration::from_millis(50),
+        }
+    }
+}
+
+impl LockRetry {
+
+    /// Calls the operation until it succeeds, fails with an error other than a lock (see
+    /// `is_locked`), or every retry is used.
+    ///
+    /// Returns the result of the last call.
+    pub fn run<T, F: FnMut() -> io::Result<T>>(&self, mut operation: F) -> io::Result<T> {
+        let mut backoff = self.backoff;
+
+        for _ in 0..self.attempts {
+            match operation() {
+                Err(err) if is_locked(&err) => {
+                    thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+
+        operation()
+    }
+
+    /// Opens the file at the provided path, retrying while it is locked.
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
+        self.run(|| File::open(path.as_ref()))
+    }
+}
+
+/// Returns true if the error was caused by another process holding the file: a sharing or lock
+/// violation on Windows (e.g. a file opened by an antivirus or an editor), or a non-blocking lock.
+pub fn is_locked(err: &io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    if cfg!(windows) && matches!(err.raw_os_error(), Some(32) | Some(33)) {
+        return true;
+    }
+
+    err.kind() == io::ErrorKind::WouldBlock
+}
+
+impl Asar {
+
+    /// Returns the full paths of the files left out of an opened directory because they were still
+    /// locked by another process once every retry failed, with `LockPolicy::SkipLocked`.
+    ///
+    /// Files are checked while the header is generated; a file locked later, while packing, is
+    /// retried and otherwise fails the pack, as its header entry is already written.
+    pub fn skipped_locked(&self) -> &[PathBuf] {
+        &self.skipped
+    }
+}
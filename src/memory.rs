@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Take},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use positioned_io::{Cursor, Slice};
+
+use crate::{asar::Asar, asar_error, volume::ArchiveFile};
+
+/// A reader over the content of a single file within an Asar archive file, as provided to the
+/// visitor of `Asar::extract_each`.
+pub type EntryReader<'a> = Take<Cursor<Slice<&'a ArchiveFile>>>;
+
+impl Asar {
+
+    /// Reads every file of an opened Asar archive file for which `filter` returns true into memory,
+    /// keyed by full path, without touching the filesystem (e.g. for test harnesses).
+    ///
+    /// ```no_run
+    /// use rust_asar::asar::Asar;
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let scripts = Asar::open("app.asar")?.extract_to_memory(|path| path.extension().is_some_and(|ext| ext == "js"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns the files, otherwise Error if a directory is open or a file can not be read.
+    pub fn extract_to_memory<F>(&self, filter: F) -> Result<HashMap<PathBuf, Vec<u8>>, asar_error::Error>
+    where
+        F: FnMut(&Path) -> bool,
+    {
+        let mut files: HashMap<PathBuf, Vec<u8>> = HashMap::new();
+
+        self.extract_each(filter, |path, reader| {
+            let mut content: Vec<u8> = Vec::with_capacity(reader.limit() as usize);
+            reader.read_to_end(&mut content)?;
+
+            files.insert(path.to_path_buf(), content);
+            Ok(())
+        })?;
+
+        Ok(files)
+    }
+
+    /// Streams every file of an opened Asar archive file for which `filter` returns true to
+    /// `visitor`, in header order, along with a reader over its content. Files are never held in
+    /// memory at once, so many (or large) files can be consumed in a single pass.
+    ///
+    /// The reader provides exactly the size of the file; the visitor does not need to read all of it.
+    ///
+    /// Returns (), otherwise the first Error found or returned by the visitor.
+    pub fn extract_each<F, V>(&self, mut filter: F, mut visitor: V) -> Result<(), asar_error::Error>
+    where
+        F: FnMut(&Path) -> bool,
+        V: FnMut(&Path, &mut EntryReader<'_>) -> Result<(), asar_error::Error>,
+    {
+        let file = self.archive_file()?;
+
+        for (path, offset, size) in self.content.files_to_vec()? {
+            if !filter(&path) {
+                continue;
+            }
+
+            let started = Instant::now();
+            let mut reader = self.entry_reader(&file, offset, size).take(size);
+
+            visitor(&path, &mut reader)?;
+            self.metrics.file_read(&path, size, started);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    asar::{self, Asar},
+    asar_error::{self, Error},
+    content::{self, Content},
+};
+
+/// Key of the entry field holding custom metadata within a header, namespaced so it never
+/// clashes with fields of the Asar format. Readers that do not know it ignore it.
+pub const META_KEY: &str = "x-meta";
+
+impl Asar {
+
+    /// Attaches custom metadata (e.g. a build stamp, source hash, or license tag) to the file or
+    /// folder at the provided full path, stored as `key: value` under `META_KEY` within its entry.
+    ///
+    /// Metadata is kept in memory: it is written by `pack`, whether packing an opened directory or
+    /// repacking an opened Asar archive file, and preserved by later repacks.
+    ///
+    /// ```no_run
+    /// use rust_asar::asar::Asar;
+    /// use serde_json::json;
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let mut asar = Asar::open("app")?;
+    ///
+    /// asar.set_entry_meta("main.js", "license", json!("MIT"))?;
+    /// asar.pack("app.asar")?;
+    ///
+    /// assert_eq!(Asar::open("app.asar")?.entry_meta("main.js").unwrap()["license"], "MIT");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns (), otherwise Error if nothing exists at the path (or it is the archive root).
+    pub fn set_entry_meta<P: AsRef<Path>, K: Into<String>>(&mut self, path: P, key: K, value: Value) -> Result<(), asar_error::Error> {
+        self.ensure_writable()?;
+
+        let path = path.as_ref();
+
+        let entry = self.entry_object_mut(path).ok_or_else(|| {
+            Error::UnknownContentType(format!("Not found within archive: {}", path.display()))
+        })?;
+
+        match entry
+            .entry(META_KEY)
+            .or_insert_with(|| Value::Object(Map::new()))
+        {
+            Value::Object(meta) => {
+                meta.insert(key.into(), value);
+            }
+            _ => {
+                return Err(Error::ParseHeaderError(format!(
+                    "Expected object for {} of {}",
+                    META_KEY,
+                    path.display()
+                )))
+            }
+        }
+
+        // the header of an opened directory grew, so its body starts later
+        if let Some(header) = &self.header {
+            let header_len = asar::json_len(header)?;
+            self.start = content::align_up(header_len + 16, self.align());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the custom metadata of the file or folder at the provided full path (see
+    /// `set_entry_meta`), or `None` if it has none or does not exist.
+    pub fn entry_meta<P: AsRef<Path>>(&self, path: P) -> Option<&Map<String, Value>> {
+        let mut names = path.as_ref().iter();
+
+        let mut entry = match (&self.header, &self.content) {
+            (Some(header), _) => header.get("files")?.get(names.next()?.to_str()?)?,
+            (None, Content::Home(dir)) => dir.get(names.next()?.to_str()?)?,
+            _ => return None,
+        };
+
+        for name in names {
+            entry = entry.get("files")?.get(name.to_str()?)?;
+        }
+
+        entry.get(META_KEY)?.as_object()
+    }
+
+    // Returns the entry at the full path within the header of an opened directory, or within the
+    // Content of an opened Asar archive file.
+    fn entry_object_mut(&mut self, path: &Path) -> Option<&mut Map<String, Value>> {
+        if path.as_os_str().is_empty() {
+            return None;
+        }
+
+        if let Some(header) = &mut self.header {
+            return content::entry_mut(header, path);
+        }
+
+        let mut names = path.iter();
+
+        let mut entry = match &mut self.content {
+            Content::Home(dir) => dir.get_mut(names.next()?.to_str()?)?,
+            _ => return None,
+        };
+
+        for name in names {
+            entry = entry.get_mut("files")?.get_mut(name.to_str()?)?;
+        }
+
+        entry.as_object_mut()
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::asar::Asar;
+
+/// An archive operation reported to `Metrics::operation` once it completes.
+///
+/// - Extract: `Asar::extract` of an opened Asar archive file
+///
+/// - Pack: `Asar::pack` of an opened directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Extract,
+    Pack,
+}
+
+/// Callbacks recording the throughput and latency of archive operations, set on an opened Asar
+/// with `Asar::set_metrics`.
+///
+/// Every method does nothing by default, so implementors only record what they need
+/// (e.g. by forwarding to a metrics registry). Callbacks are made on the calling thread
+/// and should return quickly.
+pub trait Metrics: Send + Sync {
+
+    /// Called after a file is read out of an Asar archive file (by `get_file` or `extract`),
+    /// with its full path, its size, and the time spent reading it.
+    fn file_read(&self, _path: &Path, _bytes: u64, _elapsed: Duration) {}
+
+    /// Called after a file is written into an Asar archive file (by `pack`),
+    /// with the path of its source file, its size, and the time spent writing it.
+    fn file_written(&self, _path: &Path, _bytes: u64, _elapsed: Duration) {}
+
+    /// Called after a whole operation succeeds, with the number of files and bytes processed,
+    /// and its total duration.
+    fn operation(&self, _operation: Operation, _files: u64, _bytes: u64, _elapsed: Duration) {}
+}
+
+/// A Metrics implementation keeping running totals in atomic counters, for applications that only
+/// poll totals (e.g. to report throughput).
+#[derive(Debug, Default)]
+pub struct Counters {
+    files_read: AtomicU64,
+    bytes_read: AtomicU64,
+    files_written: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl Counters {
+
+    /// Returns the number of files read so far.
+    pub fn files_read(&self) -> u64 {
+        self.files_read.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of files written so far.
+    pub fn files_written(&self) -> u64 {
+        self.files_written.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+}
+
+impl Metrics for Counters {
+    fn file_read(&self, _path: &Path, bytes: u64, _elapsed: Duration) {
+        self.files_read.fetch_add(1, Ordering::Relaxed);
+        self.bytes_read.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn file_written(&self, _path: &Path, bytes: u64, _elapsed: Duration) {
+        self.files_written.fetch_add(1, Ordering::Relaxed);
+        self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+// The Metrics set on an Asar, if any.
+#[derive(Clone, Default)]
+pub(crate) struct Recorder(Option<Arc<dyn Metrics>>);
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Recorder").field(&self.0.is_some()).finish()
+    }
+}
+
+impl Recorder {
+    pub(crate) fn file_read(&self, path: &Path, bytes: u64, started: Instant) {
+        if let Some(metrics) = &self.0 {
+            metrics.file_read(path, bytes, started.elapsed());
+        }
+    }
+
+    pub(crate) fn file_written(&self, path: &Path, bytes: u64, started: Instant) {
+        if let Some(metrics) = &self.0 {
+            metrics.file_written(path, bytes, started.elapsed());
+        }
+    }
+
+    pub(crate) fn operation(&self, operation: Operation, files: u64, bytes: u64, started: Instant) {
+        if let Some(metrics) = &self.0 {
+            metrics.operation(operation, files, bytes, started.elapsed());
+        }
+    }
+}
+
+impl Asar {
+
+    /// Sets the Metrics called by `get_file`, `extract`, and `pack` of this Asar.
+    ///
+    /// The same Metrics may be shared between several archives.
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+        self.metrics = Recorder(Some(metrics));
+    }
+}
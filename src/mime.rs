@@ -0,0 +1,122 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::asar::Asar;
+
+/// Content type of files whose extension is unknown.
+pub const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// Returns the MIME type of the provided file extension (without the leading dot, regardless of
+/// case) from the embedded table of formats commonly bundled by web and Electron applications,
+/// or `None` if it is unknown.
+pub fn from_extension(extension: &str) -> Option<&'static str> {
+    let content_type = match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" | "cjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "webmanifest" => "application/manifest+json",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "bmp" => "image/bmp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        _ => return None,
+    };
+
+    Some(content_type)
+}
+
+/// A table of content types by extension used by `Asar::content_type_with`, overriding or
+/// extending the embedded table (see `from_extension`).
+///
+/// ```
+/// use rust_asar::mime::MimeTable;
+///
+/// let table = MimeTable::new().with("js", "application/javascript").with("pak", "application/x-chrome-pak");
+///
+/// assert_eq!(table.lookup("main.js"), "application/javascript");
+/// assert_eq!(table.lookup("locales/fr.pak"), "application/x-chrome-pak");
+/// assert_eq!(table.lookup("index.html"), "text/html");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MimeTable {
+    overrides: HashMap<String, String>,
+}
+
+impl MimeTable {
+
+    /// Returns a table holding only the embedded types.
+    pub fn new() -> MimeTable {
+        MimeTable::default()
+    }
+
+    /// Maps the provided extension (without the leading dot, regardless of case) to a content type.
+    pub fn with<E: AsRef<str>, T: Into<String>>(mut self, extension: E, content_type: T) -> MimeTable {
+        let extension = extension.as_ref().trim_start_matches('.').to_ascii_lowercase();
+
+        self.overrides.insert(extension, content_type.into());
+        self
+    }
+
+    /// Returns the content type of the file at the provided path by its extension,
+    /// `DEFAULT_CONTENT_TYPE` if it is unknown.
+    pub fn lookup<P: AsRef<Path>>(&self, path: P) -> &str {
+        let Some(extension) = path.as_ref().extension().and_then(|ext| ext.to_str()) else {
+            return DEFAULT_CONTENT_TYPE;
+        };
+
+        self.overrides
+            .get(&extension.to_ascii_lowercase())
+            .map(String::as_str)
+            .or_else(|| from_extension(extension))
+            .unwrap_or(DEFAULT_CONTENT_TYPE)
+    }
+}
+
+impl Asar {
+
+    /// Returns the content type of the file at the provided full path by its extension (see
+    /// `mime::from_extension`), `mime::DEFAULT_CONTENT_TYPE` if it is unknown, or `None` if the
+    /// path is not a file within the archive.
+    ///
+    /// Files are not read; see `Asar::detect_types` to classify files by their content.
+    pub fn content_type<P: AsRef<Path>>(&self, path: P) -> Option<&'static str> {
+        let path = path.as_ref();
+
+        self.is_file(path).then(|| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(from_extension)
+                .unwrap_or(DEFAULT_CONTENT_TYPE)
+        })
+    }
+
+    /// Returns the content type of the file at the provided full path, as does `content_type`,
+    /// looked up within the provided table.
+    pub fn content_type_with<'a, P: AsRef<Path>>(&self, path: P, table: &'a MimeTable) -> Option<&'a str> {
+        self.is_file(&path).then(|| table.lookup(path))
+    }
+}
@@ -0,0 +1,61 @@
+use std::{io, path::Path};
+
+use crate::{asar::Asar, asar_error, pack::PackOptions};
+
+/// How an Asar archive file or directory is opened (see `Asar::open_mode`).
+///
+/// - ReadOnly: Only reading and extraction are allowed; packing (including `prune` and
+///   `pack_to_store`) and mutation (`set_entry_meta`, `copy_entry_from`) return Error
+///
+/// - ReadWrite: Every operation is allowed, as when opened with `Asar::open`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenMode {
+    ReadOnly,
+    #[default]
+    ReadWrite,
+}
+
+impl Asar {
+
+    /// Opens either an Asar archive file or a directory, as does `open`, in the provided OpenMode.
+    ///
+    /// Opening in `OpenMode::ReadOnly` gives services serving production bundles a guarantee they
+    /// can not accidentally pack or modify them through this Asar. The check is made at runtime;
+    /// public fields (e.g. `content`) can still be changed directly.
+    ///
+    /// ```no_run
+    /// use rust_asar::{asar::Asar, mode::OpenMode};
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let asar = Asar::open_mode("app.asar", OpenMode::ReadOnly)?;
+    ///
+    /// let manifest = asar.get_file("package.json");
+    /// assert!(asar.pack("copy.asar").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns instantiated struct, otherwise Error.
+    pub fn open_mode<P: AsRef<Path>>(src_path: P, mode: OpenMode) -> Result<Asar, asar_error::Error> {
+        let mut asar = Self::open_with(src_path, &PackOptions::default())?;
+        asar.mode = mode;
+
+        Ok(asar)
+    }
+
+    /// Returns the OpenMode the Asar was opened in.
+    pub fn mode(&self) -> OpenMode {
+        self.mode
+    }
+
+    // Returns PermissionDenied if opened read-only.
+    pub(crate) fn ensure_writable(&self) -> Result<(), asar_error::Error> {
+        match self.mode {
+            OpenMode::ReadWrite => Ok(()),
+            OpenMode::ReadOnly => Err(asar_error::Error::IoError(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is opened read-only", self.src_path.display()),
+            ))),
+        }
+    }
+}
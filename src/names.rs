@@ -0,0 +1,188 @@
+/// The platform whose filename rules are checked on extract (see `ExtractOptions::platform`).
+///
+/// - Windows: Reserved device names (`CON`, `aux.js`, `COM1`, ...), the characters
+///   `< > : " / \ | ? *` and control characters, and trailing dots or spaces are illegal
+///
+/// - MacOs: Only `/` and NUL are illegal, as on Unix, while names differing only by case collide
+///
+/// - Unix: Only `/` and NUL are illegal
+///
+/// Empty names, `.`, and `..` are illegal on every platform, as they would escape the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Windows,
+    MacOs,
+    Unix,
+}
+
+impl Platform {
+
+    /// Returns the platform this crate was built for.
+    pub fn current() -> Platform {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Unix
+        }
+    }
+
+    /// Returns true if the default filesystems of the platform are case-insensitive (NTFS, APFS),
+    /// so names differing only by case collide.
+    pub fn is_case_insensitive(&self) -> bool {
+        matches!(self, Platform::Windows | Platform::MacOs)
+    }
+}
+
+/// What extraction does with entries whose paths collide once case is folded, e.g. `Foo.js` and
+/// `foo.js` on a case-insensitive filesystem (see `ExtractOptions::case_collisions`). Folders
+/// colliding with folders are merged, as the filesystem would.
+///
+/// - Error: Nothing is extracted; Error lists every colliding entry
+///
+/// - SuffixRename: Every entry after the first is suffixed with a counter before its extension
+///   (`foo-1.js`), as are the paths below a renamed folder
+///
+/// - KeepFirst: Every entry after the first (in path order) is left out, with everything below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    Error,
+    SuffixRename,
+    KeepFirst,
+}
+
+/// What extraction does with an entry whose name is illegal on the target platform (see
+/// `ExtractOptions::illegal_names`).
+///
+/// - Error: Nothing is extracted; Error lists every illegal entry
+///
+/// - RenameEscaped: The name is escaped (see `escape`), which `unescape` reverses
+///
+/// - Skip: The entry (and everything below a folder) is left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NamePolicy {
+    #[default]
+    Error,
+    RenameEscaped,
+    Skip,
+}
+
+const RESERVED: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Returns why the file or folder name is illegal on the platform, or `None` if it is legal.
+pub fn illegal_reason(name: &str, platform: Platform) -> Option<&'static str> {
+    if name.is_empty() {
+        return Some("empty name");
+    }
+
+    if name == "." || name == ".." {
+        return Some("relative path component");
+    }
+
+    if name.chars().any(|c| is_illegal_char(c, platform)) {
+        return Some(match platform {
+            Platform::Windows => "character not allowed on Windows",
+            Platform::MacOs | Platform::Unix => "path separator or NUL within name",
+        });
+    }
+
+    if platform == Platform::Windows {
+        if is_reserved(name) {
+            return Some("reserved device name on Windows");
+        }
+
+        if name.ends_with(['.', ' ']) {
+            return Some("trailing dot or space on Windows");
+        }
+    }
+
+    None
+}
+
+/// Escapes the name so it is legal on the platform: illegal characters (and `%` itself) are
+/// replaced by `%XX` (the hex of their bytes), as is the last character of a reserved device name
+/// (`CON` -> `CO%4E`, `aux.js` -> `au%78.js`) and a trailing dot or space.
+///
+/// `unescape(&escape(name, platform)) == name` for every non-empty name.
+pub fn escape(name: &str, platform: Platform) -> String {
+    if name == "." || name == ".." {
+        return name.replace('.', "%2E");
+    }
+
+    let mut escaped = String::with_capacity(name.len());
+
+    for c in name.chars() {
+        if c == '%' || is_illegal_char(c, platform) {
+            let mut bytes = [0; 4];
+
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+
+    if platform == Platform::Windows {
+        if is_reserved(&escaped) {
+            let last = escaped.find('.').unwrap_or(escaped.len()) - 1;
+            escape_at(&mut escaped, last);
+        }
+
+        if escaped.ends_with(['.', ' ']) {
+            let last = escaped.len() - 1;
+            escape_at(&mut escaped, last);
+        }
+    }
+
+    escaped
+}
+
+/// Reverses `escape`, decoding every `%XX` within the name.
+pub fn unescape(name: &str) -> String {
+    let mut bytes: Vec<u8> = Vec::with_capacity(name.len());
+    let mut rest = name.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = match tail {
+            [high, low, ..] if byte == b'%' => std::str::from_utf8(&[*high, *low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn is_illegal_char(c: char, platform: Platform) -> bool {
+    c == '/' || c == '\0' || (platform == Platform::Windows && (c < ' ' || "<>:\"\\|?*".contains(c)))
+}
+
+// Returns true if the name, before its first dot, is a reserved device name (in any case).
+fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name);
+
+    RESERVED.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+// Replaces the ASCII character at the byte index by its escape.
+fn escape_at(name: &mut String, index: usize) {
+    let escaped = format!("%{:02X}", name.as_bytes()[index]);
+    name.replace_range(index..index + 1, &escaped);
+}
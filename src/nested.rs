@@ -0,0 +1,180 @@
+use std::{
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asar::{Asar, Source},
+    asar_error::{self, Error},
+    content::Content,
+    header,
+    index::{build_index, EntryRef},
+};
+
+/// Separator between an Asar archive and a path within it, e.g.
+/// `outer.asar!/plugins/inner.asar!/index.js` for `index.js` within the archive `plugins/inner.asar`,
+/// itself within `outer.asar`.
+pub const SEPARATOR: &str = "!/";
+
+/// Opens the innermost archive named by a path such as `outer.asar!/plugins/inner.asar!/index.js`
+/// (see SEPARATOR): the archive file before the first separator, then every nested archive in turn.
+///
+/// Returns the innermost archive along with the path within it (`index.js`), otherwise Error if the
+/// path has no separator or an archive can not be opened.
+pub fn open<P: AsRef<str>>(path: P) -> Result<(Asar, PathBuf), asar_error::Error> {
+    let path = path.as_ref();
+
+    let Some((archive, rest)) = path.split_once(SEPARATOR) else {
+        return Err(Error::IoError(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} does not name a path within an archive (archive{}path)", path, SEPARATOR),
+        )));
+    };
+
+    Asar::open(archive)?.resolve_nested(rest)
+}
+
+/// Reads a file named by a path such as `outer.asar!/plugins/inner.asar!/index.js` (see `open`).
+///
+/// Returns the content of the file, otherwise Error if an archive can not be opened or the file
+/// is not found.
+pub fn read<P: AsRef<str>>(path: P) -> Result<Vec<u8>, asar_error::Error> {
+    let (asar, path) = open(path)?;
+    let mut result: Vec<u8> = Vec::new();
+
+    asar.open_entry(path)?.read_to_end(&mut result)?;
+
+    Ok(result)
+}
+
+// Returns true if the path ends with an `.asar` extension, in any case.
+fn has_asar_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("asar"))
+}
+
+impl Asar {
+
+    /// Opens the archive stored as the file at the provided full path within an opened Asar archive
+    /// file, e.g. a plugin shipped as `plugins/inner.asar`. The nested archive is read in place
+    /// from the outer archive, never extracted, and can be nested in turn.
+    ///
+    /// Returns the nested archive, otherwise Error if the path is not a file within the archive or
+    /// the file is not an Asar archive.
+    pub fn open_nested<P: AsRef<Path>>(&self, path: P) -> Result<Asar, asar_error::Error> {
+        let path = path.as_ref();
+
+        let Some(EntryRef::File(offset, size)) = self.stat(path).filter(|_| !self.src_path.is_dir()) else {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File not found in archive: {}", path.display()),
+            )));
+        };
+
+        // the region is read from the outermost archive, however deeply nested
+        let mut nested = self.clone();
+        nested.source = Source::Nested(Box::new(self.source.outermost()), self.base() + self.start + offset, size);
+
+        let (header, start) = header::read_header(&nested.archive_file()?).map_err(|_| {
+            Error::ParseHeaderError(format!("{} is not an Asar archive file", path.display()))
+        })?;
+
+        nested.content = Content::new_json(header)?;
+        nested.index = build_index(&nested.content)?;
+        nested.start = start;
+        nested.header = None;
+        nested.copies = Vec::new();
+
+        Ok(nested)
+    }
+
+    /// Returns true if the provided full path is a file within an opened Asar archive file holding
+    /// an Asar archive itself (named `*.asar`, with a valid header), as opened by `open_nested`.
+    pub fn is_nested_archive<P: AsRef<Path>>(&self, path: P) -> bool {
+        has_asar_extension(path.as_ref()) && self.open_nested(path).is_ok()
+    }
+
+    /// Returns the full paths of the archives nested directly within an opened Asar archive file
+    /// (see `is_nested_archive`), in header order.
+    pub fn nested_archives(&self) -> Result<Vec<PathBuf>, asar_error::Error> {
+        Ok(self
+            .content
+            .files_to_vec()?
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .filter(|path| self.is_nested_archive(path))
+            .collect())
+    }
+
+    /// Lists every path within an opened Asar archive file, as does `list`, followed for every
+    /// nested archive by the paths within it, joined by SEPARATOR (e.g.
+    /// `plugins/inner.asar!/index.js`), at any depth.
+    pub fn list_nested(&self) -> Result<Vec<String>, asar_error::Error> {
+        let mut paths: Vec<String> = Vec::new();
+
+        for path in self.list()? {
+            let nested = match has_asar_extension(Path::new(&path)) {
+                true => self.open_nested(&path).ok(),
+                false => None,
+            };
+
+            paths.push(path.clone());
+
+            if let Some(nested) = nested {
+                for inner in nested.list_nested()? {
+                    paths.push(format!("{}{}{}", path, SEPARATOR, inner));
+                }
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Returns every path (see `list_nested`) whose file or folder name contains the provided
+    /// pattern, within an opened Asar archive file or any archive nested within it.
+    pub fn find_nested(&self, pat: &str) -> Result<Vec<String>, asar_error::Error> {
+        Ok(self
+            .list_nested()?
+            .into_iter()
+            .filter(|path| {
+                let name = path.rsplit(SEPARATOR).next().unwrap_or(path);
+                Path::new(name).file_name().is_some_and(|name| name.to_string_lossy().contains(pat))
+            })
+            .collect())
+    }
+
+    /// Reads a file within an opened Asar archive file or an archive nested within it, given a path
+    /// such as `plugins/inner.asar!/index.js` (see SEPARATOR).
+    ///
+    /// Returns the content of the file, otherwise Error if a nested archive can not be opened or
+    /// the file is not found.
+    pub fn read_nested<P: AsRef<str>>(&self, path: P) -> Result<Vec<u8>, asar_error::Error> {
+        let (asar, path) = self.resolve_nested(path.as_ref())?;
+        let mut result: Vec<u8> = Vec::new();
+
+        asar.open_entry(path)?.read_to_end(&mut result)?;
+
+        Ok(result)
+    }
+
+    // Opens every nested archive named by the path (relative to this archive) but the last segment,
+    // returning the innermost archive and the path within it.
+    fn resolve_nested(&self, path: &str) -> Result<(Asar, PathBuf), asar_error::Error> {
+        let mut segments: Vec<&str> = path.split(SEPARATOR).collect();
+        let last = PathBuf::from(segments.pop().unwrap_or_default());
+        let mut asar = self.clone();
+
+        for segment in segments {
+            asar = asar.open_nested(segment)?;
+        }
+
+        Ok((asar, last))
+    }
+
+    // Returns the offset of this archive within the outermost archive file, 0 unless nested.
+    pub(crate) fn base(&self) -> u64 {
+        match self.source {
+            Source::Nested(_, base, _) => base,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    mem,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    extract::ExtractOptions,
+    hooks::RunContext,
+};
+
+/// The outcome of every file of an extraction or packing that continued past failed files (see
+/// `Asar::extract_report` and `Asar::pack_report`).
+///
+/// - succeeded: Full paths of the files extracted or packed, in order
+///
+/// - failed: Full paths of the files that failed, in order, each with its Error (after any retries
+///   decided by `Hooks::on_error`)
+#[derive(Debug, Default)]
+pub struct OperationReport {
+    pub succeeded: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, Error)>,
+}
+
+impl OperationReport {
+
+    /// Returns true if no file failed.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Returns the full paths of the files that failed.
+    pub fn failed_paths(&self) -> Vec<&Path> {
+        self.failed.iter().map(|(path, _)| path.as_path()).collect()
+    }
+}
+
+// Starts gathering a report within the context of a run, returning the report shared with it.
+fn gather(run: &mut RunContext) -> Arc<Mutex<OperationReport>> {
+    let report = Arc::new(Mutex::new(OperationReport::default()));
+    run.report = Some(report.clone());
+
+    report
+}
+
+// Takes the report gathered by `gather`.
+fn take(report: &Mutex<OperationReport>) -> OperationReport {
+    mem::take(&mut *report.lock().unwrap_or_else(|err| err.into_inner()))
+}
+
+impl Asar {
+
+    /// Extracts an opened Asar archive file to the provided destination folder, as does
+    /// `extract_with`, continuing past files that can not be read or written, e.g. so a single
+    /// unreadable file does not abort the extraction of a large archive.
+    ///
+    /// Failed files are not left at the destination. `Hooks::on_error` (see `ExtractOptions::hooks`)
+    /// is still called, so failed files can be retried first.
+    ///
+    /// Returns the OperationReport, otherwise Error if the extraction itself fails (e.g. a folder
+    /// can not be created, or it is cancelled).
+    pub fn extract_report<P: AsRef<Path>>(&self, destination: P, options: &ExtractOptions) -> Result<OperationReport, asar_error::Error> {
+        let mut run = options.run_context();
+        let report = gather(&mut run);
+
+        self.extract_run(destination, options, &run)?;
+
+        Ok(take(&report))
+    }
+
+    /// Packs an opened directory to the provided destination Asar archive file, as does `pack`,
+    /// continuing past files that can not be read. They are left out of the archive, as its header
+    /// is written after its body (see `Hooks`).
+    ///
+    /// `Hooks::on_error` (see `PackOptions::hooks`) is still called, so failed files can be retried
+    /// first. Repacking an archive reports no files.
+    ///
+    /// Returns the OperationReport, otherwise Error if writing the archive fails.
+    pub fn pack_report<P: AsRef<Path>>(&self, destination: P) -> Result<OperationReport, asar_error::Error> {
+        let mut run = self.options.run_context();
+        let report = gather(&mut run);
+
+        self.pack_run(destination, &run)?;
+
+        Ok(take(&report))
+    }
+}
@@ -0,0 +1,957 @@
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+#[cfg(feature = "ignore")]
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use positioned_io::{ReadAt, WriteAt};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    asar::{self, Asar},
+    asar_error::{self, Error},
+    budget::MemoryBudget,
+    content, debug,
+    hash::{HashHook, Hasher},
+    hooks::{Hooks, RunContext},
+    layout::LayoutOptions,
+    locked::{LockPolicy, LockRetry},
+    retry::RetryPolicy,
+    scan::{ScanHook, ScanResult},
+    throttle::Throttle,
+    trace,
+    transform::{Transformed, TransformResult, Transformer},
+    verify::Verification,
+};
+
+/// Name of the ignore file read by `PackOptions::asarignore`.
+#[cfg(feature = "ignore")]
+pub const ASARIGNORE: &str = ".asarignore";
+
+/// The decision returned by a pack policy for a single file.
+///
+/// - Allow: The file is packed
+///
+/// - Skip: The file is silently left out of the archive
+///
+/// - Reject (reason): The file violates the policy, failing the pack once every file has been checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    Skip,
+    Reject(String),
+}
+
+/// A pack policy, called with the path of each file relative to the packed directory and its size.
+pub type Policy = dyn Fn(&Path, u64) -> PolicyDecision + Send + Sync;
+
+/// Options used when a directory is opened to be packed into an Asar archive file.
+///
+/// Built with chained calls starting from `PackOptions::new()`:
+///
+/// ```
+/// use rust_asar::pack::{PackOptions, PolicyDecision};
+///
+/// let options = PackOptions::new().policy(|path, size| {
+///     if size > 10_000_000 {
+///         PolicyDecision::Reject(format!("{} bytes exceeds the size limit", size))
+///     } else if path.extension().is_some_and(|ext| ext == "map") {
+///         PolicyDecision::Skip
+///     } else {
+///         PolicyDecision::Allow
+///     }
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct PackOptions {
+    policy: Option<Arc<Policy>>,
+    layout: LayoutOptions,
+    strip_debug: bool,
+    hard_links: bool,
+    pnpm: bool,
+    #[cfg(feature = "ignore")]
+    ignore_files: Vec<&'static str>,
+    #[cfg(feature = "ignore")]
+    exclude: Vec<String>,
+    #[cfg(feature = "ignore")]
+    unpack: Vec<String>,
+    #[cfg(feature = "ignore")]
+    ignore: Option<Arc<Gitignore>>,
+    #[cfg(feature = "ignore")]
+    unpacked: Option<Arc<Gitignore>>,
+    integrity: bool,
+    verification: Verification,
+    temp_dir: Option<PathBuf>,
+    split_size: Option<u64>,
+    lock_retry: LockRetry,
+    lock_policy: LockPolicy,
+    hasher: HashHook,
+    scanner: ScanHook,
+    transformer: Option<Arc<Transformer>>,
+    hooks: Hooks,
+    retry: Option<RetryPolicy>,
+    throttle: Option<Arc<Throttle>>,
+    budget: Option<MemoryBudget>,
+}
+
+impl fmt::Debug for PackOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("PackOptions");
+
+        debug
+            .field("policy", &self.policy.is_some())
+            .field("layout", &self.layout)
+            .field("strip_debug", &self.strip_debug)
+            .field("hard_links", &self.hard_links)
+            .field("pnpm", &self.pnpm);
+
+        #[cfg(feature = "ignore")]
+        debug
+            .field("ignore_files", &self.ignore_files)
+            .field("exclude", &self.exclude)
+            .field("unpack", &self.unpack);
+
+        debug
+            .field("integrity", &self.integrity)
+            .field("verification", &self.verification)
+            .field("temp_dir", &self.temp_dir)
+            .field("split_size", &self.split_size)
+            .field("lock_retry", &self.lock_retry)
+            .field("lock_policy", &self.lock_policy)
+            .field("hasher", &self.hasher)
+            .field("scanner", &self.scanner)
+            .field("transformer", &self.transformer.is_some())
+            .field("hooks", &self.hooks)
+            .field("retry", &self.retry)
+            .field("throttle", &self.throttle)
+            .field("budget", &self.budget)
+            .finish()
+    }
+}
+
+impl PackOptions {
+
+    /// Returns the default options: every file is packed.
+    pub fn new() -> PackOptions {
+        PackOptions::default()
+    }
+
+    /// Sets the policy called for every file found while generating the header.
+    ///
+    /// All rejected files are collected and reported together in `Error::PolicyViolation`.
+    pub fn policy<F>(mut self, policy: F) -> PackOptions
+    where
+        F: Fn(&Path, u64) -> PolicyDecision + Send + Sync + 'static,
+    {
+        self.policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Aligns the start of every file (and of the body itself) to a multiple of `bytes` within the
+    /// archive, padding with zeroed bytes, so files can be mapped into memory directly.
+    ///
+    /// Readers locate files by their offsets, so aligned archives remain readable by standard readers.
+    pub fn align(mut self, bytes: u64) -> PackOptions {
+        self.layout.align = bytes;
+        self
+    }
+
+    /// Places the files at the provided full paths (or below the provided folders) first within
+    /// the archive, in the order listed, e.g. the files read at startup. Other files follow, sorted by path.
+    pub fn order<I, P>(mut self, paths: I) -> PackOptions
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.layout.order = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Leaves debug artifacts (source maps, program databases, and debug symbol bundles, see
+    /// `debug::artifact_kind`) out of the archive, before the policy is called.
+    ///
+    /// `sourceMappingURL` comments within scripts are kept; see `Asar::find_debug_artifacts`.
+    pub fn strip_debug_artifacts(mut self) -> PackOptions {
+        self.strip_debug = true;
+        self
+    }
+
+    /// Stores the content of hard links (files sharing a device and inode, as laid out by
+    /// package managers such as pnpm) once, pointing every linked path at the same offset rather
+    /// than doubling the size of the archive. See `Asar::link_groups` for the files linked.
+    ///
+    /// Hard links are only detected on Unix. Transformed files are always stored themselves.
+    pub fn dedupe_hard_links(mut self) -> PackOptions {
+        self.hard_links = true;
+        self
+    }
+
+    /// Packs the symlinked `node_modules` layout of pnpm: every path is packed as seen by Node
+    /// (e.g. `node_modules/lodash/index.js`), while the real file it resolves to within the
+    /// `.pnpm` store is stored once and shared by all of them, as done by `dedupe_hard_links`.
+    ///
+    /// Dangling symlinks, and symlinks to a folder being packed (which would recurse forever),
+    /// are left out rather than failing. Yarn Plug'n'Play installs have no `node_modules` to
+    /// resolve and are packed as-is.
+    pub fn resolve_pnpm(mut self) -> PackOptions {
+        self.pnpm = true;
+        self
+    }
+
+    /// Leaves out the files and folders matched by the `.asarignore` file at the root of the
+    /// packed directory (if any), using gitignore syntax, so exclusions live with the project
+    /// rather than within every build script. The ignore file itself is left out too.
+    ///
+    /// Patterns are matched against paths relative to the packed directory, before the policy
+    /// is called. Ignore files within subfolders are not read.
+    #[cfg(feature = "ignore")]
+    pub fn asarignore(mut self) -> PackOptions {
+        if !self.ignore_files.contains(&ASARIGNORE) {
+            self.ignore_files.push(ASARIGNORE);
+        }
+
+        self
+    }
+
+    /// Leaves out the files and folders matched by the `.gitignore` file at the root of the
+    /// packed directory (if any), as does `asarignore`. Both can be combined, in which case
+    /// the `.asarignore` patterns take precedence (e.g. to re-include a built folder with `!dist/`).
+    #[cfg(feature = "ignore")]
+    pub fn gitignore(mut self) -> PackOptions {
+        if !self.ignore_files.contains(&".gitignore") {
+            self.ignore_files.insert(0, ".gitignore");
+        }
+
+        self
+    }
+
+    /// Leaves out the files and folders matched by the provided patterns, using gitignore syntax
+    /// relative to the packed directory (e.g. `*.md` or `/test/`), as does `asarignore`.
+    /// Patterns take precedence over ignore files.
+    #[cfg(feature = "ignore")]
+    pub fn exclude<I, S>(mut self, patterns: I) -> PackOptions
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Leaves the files matched by the provided patterns (gitignore syntax, relative to the
+    /// packed directory, e.g. `*.node`) out of the archive body, as done by Electron for native
+    /// modules: their header entries are marked `unpacked`, and `pack` copies them next to the
+    /// archive, to `<destination>.unpacked/` (e.g. `app.asar.unpacked/`).
+    #[cfg(feature = "ignore")]
+    pub fn unpack<I, S>(mut self, patterns: I) -> PackOptions
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.unpack.extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Records the `integrity` of every file within the header (see `cas::integrity`), as done
+    /// by Electron's packer, so Electron can validate the archive at runtime. Every file is read
+    /// once more while generating the header.
+    pub fn integrity(mut self) -> PackOptions {
+        self.integrity = true;
+        self
+    }
+
+    /// Sets the hash algorithm of the recorded `integrity` (see `integrity`) and of the opened
+    /// directory (see `Asar::set_hasher`), SHA256 by default.
+    ///
+    /// Electron only validates SHA256 integrity, so other algorithms suit archives read by this
+    /// crate or other tools.
+    pub fn hash_algorithm(mut self, hasher: Arc<dyn Hasher>) -> PackOptions {
+        self.hasher = HashHook(hasher);
+        self
+    }
+
+    /// Verifies the archive once `pack` has written it: the archive is re-opened, its header and
+    /// size are validated, and the digests of its files (every file with `Verification::Full`, or
+    /// an even sample with `Verification::Sample`) are compared with their sources, guarding
+    /// against silent corruption.
+    ///
+    /// If verification fails, the archive is removed and Error is returned with every file differing.
+    pub fn verify(mut self, verification: Verification) -> PackOptions {
+        self.verification = verification;
+        self
+    }
+
+    /// Writes the archive being packed to a temporary file within the provided folder (rather
+    /// than the folder of the destination), moved over the destination once complete, e.g. on
+    /// systems whose destination folder is too small to hold both archives.
+    ///
+    /// If the folder is on another filesystem than the destination, the archive is copied once
+    /// complete rather than renamed, so the destination is no longer replaced atomically.
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> PackOptions {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+
+    /// Splits the archive written by `pack` into volumes of at most `bytes` bytes each, e.g. for
+    /// distribution channels limiting the size of a file: packing `app.asar` writes `app.asar.001`,
+    /// `app.asar.002`, and so on (see `volume::volume_path`) rather than `app.asar`.
+    ///
+    /// The header, held whole by the first volume (which grows to fit it if needed), lists the
+    /// size of every volume. `Asar::open` reads the volumes as one archive, given either path.
+    pub fn split_size(mut self, bytes: u64) -> PackOptions {
+        self.split_size = Some(bytes);
+        self
+    }
+
+    /// Sets how often a file locked by another process (see `locked::is_locked`, e.g. opened by
+    /// an antivirus on Windows) is opened again, doubling the backoff before every retry, while
+    /// generating the header and while packing. Defaults to 3 retries after 50 milliseconds.
+    pub fn retry_locked(mut self, attempts: u32, backoff: Duration) -> PackOptions {
+        self.lock_retry = LockRetry { attempts, backoff };
+        self
+    }
+
+    /// Sets what happens to a file still locked once every retry failed. With
+    /// `LockPolicy::SkipLocked`, every file is opened while generating the header, and those
+    /// still locked are left out rather than failing the whole pack (see `Asar::skipped_locked`).
+    pub fn on_locked(mut self, policy: LockPolicy) -> PackOptions {
+        self.lock_policy = policy;
+        self
+    }
+
+    /// Sets the scanner called on the content of every file as it is packed, e.g. to block archives
+    /// containing API keys or `.env` files before they ship.
+    ///
+    /// Content is streamed to the scanner in chunks of at most `scan::SCAN_CHUNK_LEN` bytes (a
+    /// pattern may straddle two chunks), and every file is scanned at least once, empty files with
+    /// an empty chunk. Files copied from other archives are scanned too.
+    ///
+    /// Once a chunk is blocked, packing stops, the partially written archive is removed, and
+    /// `Error::PolicyViolation` is returned with the file and reason.
+    pub fn scan<F>(mut self, scanner: F) -> PackOptions
+    where
+        F: Fn(&Path, &[u8]) -> ScanResult + Send + Sync + 'static,
+    {
+        self.scanner = ScanHook::new(Arc::new(scanner));
+        self
+    }
+
+    /// Sets the transformer called on the content of every file as it is packed (after the
+    /// policy), e.g. to minify scripts or inject a banner without staging a build directory.
+    ///
+    /// The header records the size of the transformed content. Transformed content is held in
+    /// memory from opening the directory until it is packed. Files copied from other archives
+    /// are not transformed.
+    ///
+    /// ```
+    /// use rust_asar::{pack::PackOptions, transform::TransformResult};
+    ///
+    /// let options = PackOptions::new().transform(|path, content| {
+    ///     if path.extension().is_some_and(|ext| ext == "js") {
+    ///         let mut banner = b"/* (c) Example */\n".to_vec();
+    ///         banner.extend(content);
+    ///         TransformResult::Content(banner)
+    ///     } else {
+    ///         TransformResult::Content(content)
+    ///     }
+    /// });
+    /// ```
+    pub fn transform<F>(mut self, transformer: F) -> PackOptions
+    where
+        F: Fn(&Path, Vec<u8>) -> TransformResult + Send + Sync + 'static,
+    {
+        self.transformer = Some(Arc::new(transformer));
+        self
+    }
+
+    /// Sets the Hooks called for every file of the directory while `pack` writes it, e.g. to retry
+    /// or skip files that can not be read. Skipped files are left out of the archive (see `Hooks`).
+    pub fn hooks(mut self, hooks: Hooks) -> PackOptions {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Sets how a file of the directory is read again after a transient IO error (see
+    /// `retry::is_transient`) while `pack` writes it, e.g. from a network filesystem. Errors left
+    /// once every retry failed go to `Hooks::on_error`, aborting by default.
+    pub fn retry(mut self, policy: RetryPolicy) -> PackOptions {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Limits writing the archive to about `bytes_per_sec` bytes per second, e.g. so a background
+    /// update does not saturate the disk of the user. Also applies when repacking an archive.
+    ///
+    /// The limit is shared by every archive packed with these options (and their clones).
+    pub fn throttle(mut self, bytes_per_sec: u64) -> PackOptions {
+        self.throttle = Some(Arc::new(Throttle::new(bytes_per_sec)));
+        self
+    }
+
+    /// Caps the buffers used to read the files of the directory while `pack` writes them (see
+    /// MemoryBudget), streaming every file rather than reading it whole.
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> PackOptions {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub(crate) fn entry_hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    // The context of a run packing with these options, with its RetryPolicy, throttle, and budget.
+    pub(crate) fn run_context(&self) -> RunContext<'static> {
+        RunContext {
+            retry: self.retry,
+            throttle: self.throttle.clone(),
+            budget: self.budget.clone(),
+            ..RunContext::default()
+        }
+    }
+
+    // The options applied when an Asar archive file is opened, and repacked (see `Asar::open_with`).
+    pub(crate) fn repacking(&self) -> PackOptions {
+        PackOptions {
+            split_size: self.split_size,
+            hooks: self.hooks.clone(),
+            retry: self.retry,
+            throttle: self.throttle.clone(),
+            budget: self.budget.clone(),
+            ..PackOptions::default()
+        }
+    }
+
+    pub(crate) fn alignment(&self) -> u64 {
+        self.layout.align.max(1)
+    }
+
+    pub(crate) fn dedupes_hard_links(&self) -> bool {
+        self.hard_links
+    }
+
+    pub(crate) fn records_integrity(&self) -> bool {
+        self.integrity
+    }
+
+    pub(crate) fn resolves_pnpm(&self) -> bool {
+        self.pnpm
+    }
+
+    pub(crate) fn lock_retry(&self) -> LockRetry {
+        self.lock_retry
+    }
+
+    pub(crate) fn skips_locked(&self) -> bool {
+        self.lock_policy == LockPolicy::SkipLocked
+    }
+
+    pub(crate) fn temp_directory(&self) -> Option<&Path> {
+        self.temp_dir.as_deref()
+    }
+
+    pub(crate) fn volume_size(&self) -> Option<u64> {
+        self.split_size
+    }
+
+    pub(crate) fn verification(&self) -> Verification {
+        self.verification
+    }
+
+    pub(crate) fn hasher(&self) -> &HashHook {
+        &self.hasher
+    }
+
+    pub(crate) fn scanner(&self) -> &ScanHook {
+        &self.scanner
+    }
+
+    // The LayoutOptions of a packed directory, whose files are always sorted.
+    pub(crate) fn layout(&self) -> LayoutOptions {
+        LayoutOptions {
+            sort: true,
+            ..self.layout.clone()
+        }
+    }
+
+    pub(crate) fn transformer(&self) -> Option<&Transformer> {
+        self.transformer.as_deref()
+    }
+
+    // Returns the options with the ignore files of the packed directory read, if any.
+    #[cfg(feature = "ignore")]
+    pub(crate) fn with_ignore_files(&self, root: &Path) -> Result<PackOptions, asar_error::Error> {
+        let mut options = self.clone();
+
+        if !self.ignore_files.is_empty() || !self.exclude.is_empty() {
+            let mut builder = GitignoreBuilder::new(root);
+
+            for name in self.ignore_files.iter() {
+                let path = root.join(name);
+
+                if path.is_file() {
+                    if let Some(err) = builder.add(path) {
+                        return Err(Error::InvalidPattern(format!("{}: {}", name, err)));
+                    }
+                }
+            }
+
+            options.ignore = Some(Arc::new(matcher(builder, &self.exclude)?));
+        }
+
+        if !self.unpack.is_empty() {
+            options.unpacked = Some(Arc::new(matcher(GitignoreBuilder::new(root), &self.unpack)?));
+        }
+
+        Ok(options)
+    }
+
+    // Returns true if the file (relative to the packed directory) is left out of the body.
+    #[cfg(feature = "ignore")]
+    pub(crate) fn unpacks(&self, path: &Path) -> bool {
+        self.unpacked
+            .as_ref()
+            .is_some_and(|matcher| matcher.matched_path_or_any_parents(path, false).is_ignore())
+    }
+
+    #[cfg(not(feature = "ignore"))]
+    pub(crate) fn unpacks(&self, _path: &Path) -> bool {
+        false
+    }
+
+    #[cfg(not(feature = "ignore"))]
+    pub(crate) fn with_ignore_files(&self, _root: &Path) -> Result<PackOptions, asar_error::Error> {
+        Ok(self.clone())
+    }
+
+    // Returns true if the path (relative to the packed directory) is left out by an ignore file.
+    #[cfg(feature = "ignore")]
+    pub(crate) fn ignores(&self, path: &Path, is_dir: bool) -> bool {
+        if self.ignore_files.iter().any(|name| path == Path::new(name)) {
+            return true;
+        }
+
+        self.ignore
+            .as_ref()
+            .is_some_and(|matcher| matcher.matched_path_or_any_parents(path, is_dir).is_ignore())
+    }
+
+    #[cfg(not(feature = "ignore"))]
+    pub(crate) fn ignores(&self, _path: &Path, _is_dir: bool) -> bool {
+        false
+    }
+
+    pub(crate) fn check(&self, path: &Path, size: u64) -> PolicyDecision {
+        if self.strip_debug && debug::artifact_kind(path).is_some() {
+            return PolicyDecision::Skip;
+        }
+
+        match &self.policy {
+            Some(policy) => policy(path, size),
+            None => PolicyDecision::Allow,
+        }
+    }
+}
+
+// Builds a matcher of the gitignore patterns, following any already added to the builder.
+#[cfg(feature = "ignore")]
+fn matcher(mut builder: GitignoreBuilder, patterns: &[String]) -> Result<Gitignore, asar_error::Error> {
+    for pattern in patterns {
+        builder
+            .add_line(None, pattern)
+            .map_err(|err| Error::InvalidPattern(format!("{}: {}", pattern, err)))?;
+    }
+
+    builder.build().map_err(|err| Error::InvalidPattern(err.to_string()))
+}
+
+// Hooks applied by `Content::dir_to_asar_recorded` to every file of an opened directory.
+//
+// - scanner: Scanner called on the content of every file
+// - transformed: Content written in place of the files it holds
+// - root: Opened directory, which full paths within the archive are relative to
+// - retry: How files locked by another process are opened again
+// - entries: Hooks called for every file
+// - run: State of the run, such as its report and memory budget
+pub(crate) struct PackHooks<'a> {
+    pub(crate) scanner: &'a ScanHook,
+    pub(crate) transformed: &'a Transformed,
+    pub(crate) root: &'a Path,
+    pub(crate) retry: LockRetry,
+    pub(crate) entries: &'a Hooks,
+    pub(crate) run: &'a RunContext<'a>,
+}
+
+// Where the content of a file added to a Packer comes from.
+enum Source {
+    File(PathBuf),
+    Reader(Box<dyn Read>),
+    Reserved,
+}
+
+/// Packer builds an Asar archive file entry by entry, rather than from a single directory.
+///
+/// Every entry is declared with its size up front so the header can be generated before any
+/// content is written. Reserved entries leave a pre-allocated slot in the archive body that is
+/// filled afterwards through a `ReservedEntry`, e.g. by a bundler streaming its output:
+///
+/// ```no_run
+/// use std::io::Write;
+/// use rust_asar::pack::Packer;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let bundle: Vec<u8> = b"console.log(1)".to_vec();
+///
+/// let mut packer = Packer::new();
+/// packer.add_file("package.json", "app/package.json")?;
+/// packer.reserve("dist/bundle.js", bundle.len() as u64)?;
+///
+/// let archive = packer.write("app.asar")?;
+///
+/// let mut slot = archive.slot("dist/bundle.js")?;
+/// slot.write_all(&bundle)?;
+/// slot.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Packer {
+    header: Map<String, Value>,
+    entries: Vec<(PathBuf, Source, u64)>,
+    offset: u64,
+}
+
+impl Default for Packer {
+    fn default() -> Packer {
+        let mut header = Map::new();
+        header.insert("files".to_string(), Value::Object(Map::new()));
+
+        Packer {
+            header,
+            entries: Vec::new(),
+            offset: 0,
+        }
+    }
+}
+
+impl Packer {
+
+    /// Returns an empty Packer.
+    pub fn new() -> Packer {
+        Packer::default()
+    }
+
+    /// Adds the file at `src` to the archive at the full path `path`, using its current size.
+    ///
+    /// Returns (), otherwise Error if the file can not be read or the path was already added.
+    pub fn add_file<A: AsRef<Path>, S: AsRef<Path>>(&mut self, path: A, src: S) -> Result<(), asar_error::Error> {
+        let size = src.as_ref().metadata()?.len();
+
+        self.push(path.as_ref(), Source::File(src.as_ref().to_path_buf()), size)
+    }
+
+    /// Adds the content read from `reader` to the archive at the full path `path`.
+    ///
+    /// The reader must provide exactly `size` bytes, which is verified while the archive is written.
+    ///
+    /// Returns (), otherwise Error if the path was already added.
+    pub fn add_reader<A: AsRef<Path>>(&mut self, path: A, reader: Box<dyn Read>, size: u64) -> Result<(), asar_error::Error> {
+        self.push(path.as_ref(), Source::Reader(reader), size)
+    }
+
+    /// Reserves a slot of exactly `size` bytes at the full path `path`, to be written through
+    /// `PackedArchive::slot` once the archive is written.
+    ///
+    /// Returns (), otherwise Error if the path was already added.
+    pub fn reserve<A: AsRef<Path>>(&mut self, path: A, size: u64) -> Result<(), asar_error::Error> {
+        self.push(path.as_ref(), Source::Reserved, size)
+    }
+
+    fn push(&mut self, path: &Path, source: Source, size: u64) -> Result<(), asar_error::Error> {
+        content::insert_path(
+            &mut self.header,
+            path,
+            json!({ "size": size, "offset": self.offset.to_string() }),
+        )?;
+
+        self.entries.push((path.to_path_buf(), source, size));
+        self.offset += size;
+
+        Ok(())
+    }
+
+    /// Returns the header generated from every entry added so far.
+    pub fn header(&self) -> Value {
+        Value::Object(self.header.clone())
+    }
+
+    /// Writes the header and every added file to the Asar archive file at `destination`, replacing
+    /// it if it exists. Reserved slots are pre-allocated as zeroed bytes.
+    ///
+    /// Returns a PackedArchive to fill the reserved slots, otherwise Error.
+    pub fn write<P: AsRef<Path>>(self, destination: P) -> Result<PackedArchive, asar_error::Error> {
+        let mut asar = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .read(true)
+            .truncate(true)
+            .open(destination.as_ref())?;
+
+        let _span = trace::span!(DEBUG, "pack", path = %destination.as_ref().display());
+        let header = Value::Object(self.header);
+
+        asar::preallocate(&asar, Asar::header_bytes(&header, 0)?.len() as u64 + self.offset)?;
+
+        let start = Asar::write_header(&mut asar, &header)?;
+        let mut slots: Vec<(PathBuf, u64, u64)> = Vec::new();
+        let mut offset: u64 = 0;
+
+        for (path, source, size) in self.entries {
+            match source {
+                Source::File(src) => copy_exact(File::open(&src)?, &mut asar, size, &path)?,
+                Source::Reader(reader) => copy_exact(reader, &mut asar, size, &path)?,
+                Source::Reserved => {
+                    asar.seek(SeekFrom::Current(size as i64))?;
+                    slots.push((path, offset, size));
+                    offset += size;
+                    continue;
+                }
+            }
+
+            trace::event!(TRACE, path = %path.display(), size, "packed file");
+            offset += size;
+        }
+
+        asar.set_len(start + offset)?;
+
+        trace::event!(DEBUG, start, size = start + offset, reserved = slots.len(), "packed archive");
+
+        Ok(PackedArchive { file: asar, start, slots })
+    }
+}
+
+// Copies exactly `size` bytes from reader to the archive, failing if the reader provides fewer or more.
+fn copy_exact<R: Read, W: Write>(mut reader: R, asar: &mut W, size: u64, path: &Path) -> Result<(), asar_error::Error> {
+    let copied = io::copy(&mut (&mut reader).take(size), asar)?;
+
+    if copied != size || reader.read(&mut [0; 1])? != 0 {
+        return Err(Error::IoError(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} does not match its declared size of {} bytes", path.display(), size),
+        )));
+    }
+
+    Ok(())
+}
+
+/// An Asar archive file written by a Packer, holding the reserved slots still to be filled.
+pub struct PackedArchive {
+    file: File,
+    start: u64,
+    slots: Vec<(PathBuf, u64, u64)>,
+}
+
+impl PackedArchive {
+
+    /// Returns a writer bound to the reserved slot at the full path `path`.
+    ///
+    /// Several slots may be written at the same time, each through its own ReservedEntry.
+    pub fn slot<P: AsRef<Path>>(&self, path: P) -> Result<ReservedEntry, asar_error::Error> {
+        match self.slots.iter().find(|(slot, _, _)| slot == path.as_ref()) {
+            Some((_, offset, size)) => Ok(ReservedEntry {
+                file: self.file.try_clone()?,
+                offset: self.start + offset,
+                size: *size,
+                written: 0,
+            }),
+            None => Err(Error::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No reserved slot: {}", path.as_ref().display()),
+            ))),
+        }
+    }
+
+    /// Returns the offset at which file content begins (start).
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+}
+
+/// A writer bound to a reserved slot of an Asar archive file, returned by `PackedArchive::slot`.
+///
+/// Writing past the reserved size fails, and `finish` verifies the slot was filled entirely.
+pub struct ReservedEntry {
+    file: File,
+    offset: u64,
+    size: u64,
+    written: u64,
+}
+
+impl ReservedEntry {
+
+    /// Returns the number of bytes left to be written to the slot.
+    pub fn remaining(&self) -> u64 {
+        self.size - self.written
+    }
+
+    /// Flushes the slot, returning an Error if it was not written entirely.
+    pub fn finish(mut self) -> Result<(), asar_error::Error> {
+        self.flush()?;
+
+        if self.written != self.size {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} of {} reserved bytes written", self.written, self.size),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for ReservedEntry {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let avail = self.remaining().min(buf.len() as u64) as usize;
+
+        if avail == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "write exceeds reserved slot size",
+            ));
+        }
+
+        let written = self.file.write_at(self.offset + self.written, &buf[..avail])?;
+        self.written += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(&mut self.file)
+    }
+}
+
+/// Size of the buffer used when shifting the archive body to make room for the header.
+const SHIFT_BUF_LEN: u64 = 64 * 1024;
+
+/// StreamPacker packs an Asar archive file from content whose size is not known up front,
+/// such as generated output.
+///
+/// File content is written to the destination as it is added, after `header_capacity` bytes
+/// reserved for the JSON header. `finish` then back-patches the header into the reserved space,
+/// padding what is left over. If the header outgrows the reserved space, the body is shifted
+/// forward to make room (a single extra pass over the body).
+///
+/// ```no_run
+/// use rust_asar::pack::StreamPacker;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let mut packer = StreamPacker::create("app.asar", 4096)?;
+/// packer.add("index.js", &b"require('./main')"[..])?;
+/// packer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamPacker {
+    file: File,
+    header: Value,
+    files: usize,
+    capacity: u64,
+    offset: u64,
+}
+
+impl StreamPacker {
+
+    /// Creates (or replaces) the Asar archive file at `destination`, reserving `header_capacity`
+    /// bytes for the JSON header.
+    pub fn create<P: AsRef<Path>>(destination: P, header_capacity: u64) -> Result<StreamPacker, asar_error::Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .read(true)
+            .truncate(true)
+            .open(destination)?;
+
+        Ok(StreamPacker {
+            file,
+            header: json!({ "files": {} }),
+            files: 0,
+            capacity: header_capacity,
+            offset: 0,
+        })
+    }
+
+    /// Copies everything read from `reader` into the archive at the full path `path`.
+    ///
+    /// Returns the size of the added file, otherwise Error if the path was already added.
+    pub fn add<A: AsRef<Path>, R: Read>(&mut self, path: A, mut reader: R) -> Result<u64, asar_error::Error> {
+        let path = path.as_ref();
+
+        let Value::Object(header) = &mut self.header else {
+            return Err(Error::ParseHeaderError("Expected header object".to_string()));
+        };
+
+        // the entry is added before its content is written, so a path that can not be added
+        // leaves the body untouched; its size is filled in once known
+        content::insert_path(header, path, json!({ "size": 0, "offset": self.offset.to_string() }))?;
+
+        self.file.seek(SeekFrom::Start(16 + self.capacity + self.offset))?;
+        let size = io::copy(&mut reader, &mut self.file)?;
+
+        if let Some(entry) = content::entry_mut(&mut self.header, path) {
+            entry.insert("size".to_string(), json!(size));
+        }
+
+        self.files += 1;
+        self.offset += size;
+
+        trace::event!(TRACE, path = %path.display(), size, "packed file");
+
+        Ok(size)
+    }
+
+    /// Writes the header generated from every added file, completing the archive.
+    ///
+    /// Returns the offset at which file content begins (start), otherwise Error.
+    pub fn finish(mut self) -> Result<u64, asar_error::Error> {
+        let header = self.header;
+        let json_len = serde_json::to_vec(&header)?.len() as u64;
+
+        let old_start = 16 + self.capacity;
+        let new_start = 16 + json_len.max(self.capacity);
+
+        if new_start > old_start {
+            trace::event!(DEBUG, old_start, new_start, "header outgrew its capacity, shifting body");
+
+            // shift body forward, starting from its end so nothing is overwritten before being read
+            let mut buf: Vec<u8> = vec![0; SHIFT_BUF_LEN as usize];
+            let mut end = self.offset;
+
+            while end > 0 {
+                let len = end.min(SHIFT_BUF_LEN);
+                let pos = end - len;
+
+                self.file.read_exact_at(old_start + pos, &mut buf[..len as usize])?;
+                self.file.write_all_at(new_start + pos, &buf[..len as usize])?;
+
+                end = pos;
+            }
+        }
+
+        let bytes = Asar::header_bytes(&header, new_start - 16 - json_len)?;
+        self.file.write_all_at(0, &bytes)?;
+        self.file.set_len(new_start + self.offset)?;
+        Write::flush(&mut self.file)?;
+
+        trace::event!(DEBUG, start = new_start, size = new_start + self.offset, files = self.files, "packed archive");
+
+        Ok(new_start)
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::{asar::Asar, index::EntryRef};
+
+/// Name of the manifest of a package.
+pub const PACKAGE_JSON: &str = "package.json";
+
+impl Asar {
+
+    /// Returns the full path of the manifest of the packaged application: `package.json` at the
+    /// root of the archive, otherwise the shallowest one outside of `node_modules` (e.g.
+    /// `app/package.json`), or `None` if there is none.
+    pub fn package_json_path(&self) -> Option<PathBuf> {
+        self.index
+            .iter()
+            .filter(|(path, entry)| {
+                matches!(entry, EntryRef::File(..))
+                    && path.file_name().is_some_and(|name| name == PACKAGE_JSON)
+                    && !path.iter().any(|name| name == "node_modules")
+            })
+            .map(|(path, _)| path)
+            .min_by_key(|path| (path.components().count(), path.to_path_buf()))
+            .cloned()
+    }
+
+    /// Reads and parses the manifest of the packaged application (see `package_json_path`).
+    ///
+    /// Returns `None` if there is none, or if it can not be read or parsed.
+    pub fn package_json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.get_file(self.package_json_path()?)?).ok()
+    }
+
+    /// Returns the `name` of the packaged application, read from its `package.json`.
+    pub fn app_name(&self) -> Option<String> {
+        self.package_json()?.get("name")?.as_str().map(str::to_string)
+    }
+
+    /// Returns the `version` of the packaged application, read from its `package.json`.
+    pub fn app_version(&self) -> Option<String> {
+        self.package_json()?.get("version")?.as_str().map(str::to_string)
+    }
+
+    /// Returns the full path of the script Electron runs first: the `main` field of the
+    /// `package.json` of the packaged application (`index.js` if not declared), relative to the
+    /// folder holding it.
+    ///
+    /// The path is not checked to exist within the archive.
+    pub fn main_entry(&self) -> Option<PathBuf> {
+        let manifest_path = self.package_json_path()?;
+        let manifest = self.package_json()?;
+
+        let main = manifest.get("main").and_then(Value::as_str).unwrap_or("index.js");
+        let mut entry = manifest_path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+        for component in Path::new(main).components() {
+            match component {
+                Component::Normal(name) => entry.push(name),
+                Component::ParentDir => {
+                    entry.pop();
+                }
+                _ => {} // `./`, and a leading `/` which resolves within the archive
+            }
+        }
+
+        Some(entry)
+    }
+}
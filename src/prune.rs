@@ -0,0 +1,150 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    content::Content,
+    index::build_index,
+};
+
+/// The entries kept by `Asar::prune`: full paths of files, or of folders kept with everything
+/// below them, e.g. from a reachability analysis or an explicit allowlist.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Manifest {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Manifest {
+
+    /// Returns a Manifest keeping the provided full paths.
+    pub fn new<I, P>(paths: I) -> Manifest
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Manifest {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Parses an allowlist, one full path per line. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(list: &str) -> Manifest {
+        Manifest::new(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| line.trim_matches('/')),
+        )
+    }
+
+    /// Reads and parses the allowlist file at the provided path (see `parse`).
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Manifest, asar_error::Error> {
+        Ok(Manifest::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Returns true if the file at the provided full path, or a folder above it, is kept.
+    pub fn keeps<P: AsRef<Path>>(&self, path: P) -> bool {
+        path.as_ref().ancestors().any(|ancestor| self.paths.contains(ancestor))
+    }
+}
+
+#[cfg(feature = "jsgraph")]
+impl From<&crate::jsgraph::Reachability> for Manifest {
+    fn from(report: &crate::jsgraph::Reachability) -> Manifest {
+        Manifest::new(report.reachable.iter().cloned())
+    }
+}
+
+/// The outcome of `Asar::prune`.
+///
+/// - removed: `(full_file_path, size)` of every file left out, in header order
+///
+/// - bytes_saved: How much smaller the pruned archive is than the original, in bytes
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PruneReport {
+    pub removed: Vec<(PathBuf, u64)>,
+    pub bytes_saved: u64,
+}
+
+// Removes every file of the folder not kept by the manifest, then every folder left empty by
+// doing so. Links and unpacked files are kept, as they take no space within the body.
+fn prune_dir(dir: &mut Map<String, Value>, parent: &Path, keep: &Manifest, removed: &mut Vec<(PathBuf, u64)>) {
+    dir.retain(|name, entry| {
+        let path = parent.join(name);
+
+        match entry.get_mut("files") {
+            Some(Value::Object(files)) => {
+                let was_empty = files.is_empty();
+                prune_dir(files, &path, keep, removed);
+
+                was_empty || !files.is_empty()
+            }
+            _ => {
+                let size = entry.get("size").and_then(Value::as_u64);
+
+                match (entry.get("offset"), size) {
+                    (Some(_), Some(size)) if !keep.keeps(&path) => {
+                        removed.push((path, size));
+                        false
+                    }
+                    _ => true,
+                }
+            }
+        }
+    });
+}
+
+impl Asar {
+
+    /// Repacks an opened Asar archive file to `destination` with only the files kept by the
+    /// manifest, streaming them from its body, e.g. to drop unused locales or test files.
+    /// Folders left empty are removed.
+    ///
+    /// ```no_run
+    /// use rust_asar::{asar::Asar, prune::Manifest};
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let keep = Manifest::new(["package.json", "dist", "locales/en-US.pak"]);
+    /// let report = Asar::open("app.asar")?.prune(&keep, "app.pruned.asar")?;
+    ///
+    /// println!("{} files removed, {} bytes saved", report.removed.len(), report.bytes_saved);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns the PruneReport, otherwise Error if a directory is open or the destination is the
+    /// opened archive itself.
+    pub fn prune<P: AsRef<Path>>(&self, keep: &Manifest, destination: P) -> Result<PruneReport, asar_error::Error> {
+        let dir = match (&self.header, &self.content) {
+            (None, Content::Home(dir)) => dir,
+            _ => {
+                return Err(Error::UnknownContentType(
+                    "Asar archive file must be src_path".to_string(),
+                ))
+            }
+        };
+
+        let mut pruned = dir.clone();
+        let mut removed: Vec<(PathBuf, u64)> = Vec::new();
+        prune_dir(&mut pruned, Path::new(""), keep, &mut removed);
+
+        let mut asar = self.clone();
+        asar.content = Content::Home(pruned);
+        asar.index = build_index(&asar.content)?;
+        asar.pack(&destination)?;
+
+        let before = fs::metadata(&self.src_path)?.len();
+        let after = fs::metadata(destination.as_ref())?.len();
+
+        Ok(PruneReport {
+            removed,
+            bytes_saved: before.saturating_sub(after),
+        })
+    }
+}
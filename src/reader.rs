@@ -0,0 +1,227 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Lines, Read, Seek, SeekFrom},
+    ops::Range,
+    path::Path,
+    time::Instant,
+};
+
+use positioned_io::ReadAt;
+
+use crate::{asar::Asar, asar_error, index::EntryRef, volume::ArchiveFile};
+
+/// A reader over a region of an Asar archive file, either a single file (`Asar::open_entry`) or
+/// the whole body (`Asar::body_reader`).
+///
+/// Positions are relative to the start of the region, and seeking is bounded by its size.
+#[derive(Debug)]
+pub struct EntryReader {
+    file: ArchiveFile,
+    offset: u64,
+    size: u64,
+    pos: u64,
+}
+
+impl EntryReader {
+
+    /// Returns a reader over `size` bytes of the provided file, starting at `offset`.
+    pub fn new(file: File, offset: u64, size: u64) -> EntryReader {
+        EntryReader::from_archive(file.into(), offset, size)
+    }
+
+    // Returns a reader over `size` bytes of the provided archive, starting at `offset`.
+    pub(crate) fn from_archive(file: ArchiveFile, offset: u64, size: u64) -> EntryReader {
+        EntryReader { file, offset, size, pos: 0 }
+    }
+
+    /// Returns the size of the region in bytes.
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns true if the region is empty.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
+impl Read for EntryReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let avail = self.size.saturating_sub(self.pos).min(buf.len() as u64) as usize;
+        let read = self.file.read_at(self.offset + self.pos, &mut buf[..avail])?;
+
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for EntryReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+        };
+
+        match pos {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative position",
+            )),
+        }
+    }
+}
+
+/// Iterator over fixed-size chunks of a reader, returned by `Asar::read_chunks`.
+///
+/// Every chunk is `chunk_size` bytes long, except for the last which holds the remainder.
+pub struct Chunks<R> {
+    reader: R,
+    chunk_size: usize,
+}
+
+impl<R: Read> Iterator for Chunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk: Vec<u8> = Vec::with_capacity(self.chunk_size);
+
+        match (&mut self.reader).take(self.chunk_size as u64).read_to_end(&mut chunk) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(chunk)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl Asar {
+
+    /// Opens a reader over a single file within an opened Asar archive file, without reading the file
+    /// into memory.
+    ///
+    /// Returns the reader, otherwise Error if the path is not a file within the archive.
+    pub fn open_entry<P: AsRef<Path>>(&self, path: P) -> Result<EntryReader, asar_error::Error> {
+        if let Some(EntryRef::File(offset, size)) = self.stat(&path) {
+            let file = self.archive_file()?;
+
+            return Ok(EntryReader::from_archive(file, self.start + offset, size));
+        }
+
+        Err(asar_error::Error::IoError(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("File not found in archive: {}", path.as_ref().display()),
+        )))
+    }
+
+    /// Returns at most the first `n` bytes of a file within an opened Asar archive file (fewer if
+    /// the file is smaller), e.g. to sniff its magic bytes or preview it, without allocating or
+    /// reading the whole file as `get_file` does.
+    ///
+    /// Returns `None` if the path is not a file within the archive or can not be read.
+    pub fn peek<P: AsRef<Path>>(&self, path: P, n: usize) -> Option<Vec<u8>> {
+        if self.src_path.is_dir() {
+            return None;
+        }
+
+        let Some(EntryRef::File(offset, size)) = self.stat(&path) else {
+            return None;
+        };
+
+        let started = Instant::now();
+        let file = self.archive_file().ok()?;
+
+        let mut result: Vec<u8> = vec![0; size.min(n as u64) as usize];
+        file.read_exact_at(self.start + offset, &mut result).ok()?;
+
+        self.metrics.file_read(path.as_ref(), result.len() as u64, started);
+
+        Some(result)
+    }
+
+    /// Reads the bytes within `range` of a file within an opened Asar archive file, e.g. to serve an
+    /// HTTP Range request for a bundled video. Positions are relative to the start of the file.
+    ///
+    /// Returns the bytes, otherwise Error if the path is not a file within the archive, or the
+    /// range is reversed or extends past the end of the file.
+    pub fn read_range<P: AsRef<Path>>(&self, path: P, range: Range<u64>) -> Result<Vec<u8>, asar_error::Error> {
+        let path = path.as_ref();
+        let size = self.entry_size(path)?;
+
+        if range.start > range.end || range.end > size {
+            return Err(asar_error::Error::IoError(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Range {}..{} is out of bounds of {} ({} bytes)", range.start, range.end, path.display(), size),
+            )));
+        }
+
+        let mut reader = self.open_entry(path)?;
+        reader.seek(SeekFrom::Start(range.start))?;
+
+        let mut result: Vec<u8> = vec![0; (range.end - range.start) as usize];
+        reader.read_exact(&mut result)?;
+
+        Ok(result)
+    }
+
+    /// Reads the last `n` bytes of a file within an opened Asar archive file (the whole file if it
+    /// is smaller), e.g. to read a trailer or the end of a log.
+    ///
+    /// Returns the bytes, otherwise Error if the path is not a file within the archive.
+    pub fn read_tail<P: AsRef<Path>>(&self, path: P, n: u64) -> Result<Vec<u8>, asar_error::Error> {
+        let size = self.entry_size(path.as_ref())?;
+
+        self.read_range(path, size.saturating_sub(n)..size)
+    }
+
+    // Returns the size of the file at the path, otherwise NotFound.
+    fn entry_size(&self, path: &Path) -> Result<u64, asar_error::Error> {
+        match self.stat(path) {
+            Some(EntryRef::File(_, size)) if !self.src_path.is_dir() => Ok(size),
+            _ => Err(asar_error::Error::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File not found in archive: {}", path.display()),
+            ))),
+        }
+    }
+
+    /// Returns an iterator over the lines of a file within an opened Asar archive file, as does
+    /// `BufRead::lines`.
+    ///
+    /// Only one line is held in memory at a time.
+    pub fn read_lines<P: AsRef<Path>>(&self, path: P) -> Result<Lines<BufReader<EntryReader>>, asar_error::Error> {
+        Ok(BufReader::new(self.open_entry(path)?).lines())
+    }
+
+    /// Returns an iterator over chunks of at most `chunk_size` bytes of a file within an opened
+    /// Asar archive file.
+    ///
+    /// > A `chunk_size` of 0 is treated as 1.
+    pub fn read_chunks<P: AsRef<Path>>(&self, path: P, chunk_size: usize) -> Result<Chunks<EntryReader>, asar_error::Error> {
+        Ok(Chunks {
+            reader: self.open_entry(path)?,
+            chunk_size: chunk_size.max(1),
+        })
+    }
+
+    /// Returns a buffered reader over the whole body (every file, concatenated) of an opened Asar
+    /// archive file, skipping the header.
+    ///
+    /// Position 0 of the reader is the `start` offset of the archive, so file offsets within the
+    /// header can be used directly with `Seek`.
+    pub fn body_reader(&self) -> Result<BufReader<EntryReader>, asar_error::Error> {
+        if self.src_path.is_dir() {
+            return Err(asar_error::Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        let file = self.archive_file()?;
+        let size = file.len()?.saturating_sub(self.start);
+
+        Ok(BufReader::new(EntryReader::from_archive(file, self.start, size)))
+    }
+}
@@ -0,0 +1,166 @@
+use std::{fs::File, io};
+
+use positioned_io::ReadAt;
+
+use crate::{asar_error, detect::FileType};
+
+/// Number of bytes scanned for signatures at a time.
+const SCAN_CHUNK_LEN: u64 = 1024 * 1024;
+
+/// Longest signature or terminator searched for, used as the overlap between chunks.
+const MAX_PATTERN_LEN: u64 = 8;
+
+/// A range of an Asar archive file likely holding a single file, as found by `scan_body`.
+///
+/// - offset: Absolute offset of the range within the scanned file
+///
+/// - len: Length of the range in bytes
+///
+/// - kind: FileType detected from the signature at the start of the range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Carve {
+    pub offset: u64,
+    pub len: u64,
+    pub kind: FileType,
+}
+
+// Returns the FileType of a signature starting at `bytes`, if any. `prev` is the byte before it.
+fn signature(bytes: &[u8], prev: Option<u8>) -> Option<FileType> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(FileType::Png),
+        [0xFF, 0xD8, 0xFF, ..] => Some(FileType::Jpeg),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(FileType::Gif),
+        [b'%', b'P', b'D', b'F', b'-', ..] => Some(FileType::Pdf),
+        [b'P', b'K', 0x03, 0x04, ..] => Some(FileType::Zip),
+        [0x1F, 0x8B, 0x08, ..] => Some(FileType::Gzip),
+        [0x7F, b'E', b'L', b'F', ..] => Some(FileType::Elf),
+        [0x00, b'a', b's', b'm', 0x01, 0x00, 0x00, 0x00, ..] => Some(FileType::Wasm),
+        // shebangs only count at the start of a line
+        [b'#', b'!', b'/', ..] if matches!(prev, None | Some(b'\n') | Some(0)) => {
+            Some(FileType::Script(String::new()))
+        }
+        _ => None,
+    }
+}
+
+// Returns the offset of the first occurrence of `pattern` within `file` between `from` and `to`.
+fn find(file: &File, pattern: &[u8], from: u64, to: u64) -> io::Result<Option<u64>> {
+    let mut pos = from;
+    let mut buf: Vec<u8> = Vec::new();
+
+    while pos < to {
+        let len = (to - pos).min(SCAN_CHUNK_LEN + pattern.len() as u64);
+        buf.resize(len as usize, 0);
+        file.read_exact_at(pos, &mut buf)?;
+
+        if let Some(i) = buf.windows(pattern.len()).position(|window| window == pattern) {
+            return Ok(Some(pos + i as u64));
+        }
+
+        if len < pattern.len() as u64 {
+            break;
+        }
+
+        pos += len - pattern.len() as u64 + 1;
+    }
+
+    Ok(None)
+}
+
+// Returns the end of a file of the provided kind starting at `offset`, if its format has a terminator.
+fn terminator(file: &File, kind: &FileType, offset: u64, eof: u64) -> io::Result<Option<u64>> {
+    let end = match kind {
+        FileType::Png => find(file, b"IEND", offset, eof)?.map(|pos| pos + 8),
+        FileType::Jpeg => find(file, &[0xFF, 0xD9], offset + 2, eof)?.map(|pos| pos + 2),
+        FileType::Pdf => find(file, b"%%EOF", offset, eof)?.map(|pos| pos + 5),
+        FileType::Zip => match find(file, &[b'P', b'K', 0x05, 0x06], offset, eof)? {
+            Some(pos) if pos + 22 <= eof => {
+                let mut comment_len = [0_u8; 2];
+                file.read_exact_at(pos + 20, &mut comment_len)?;
+
+                Some(pos + 22 + u16::from_le_bytes(comment_len) as u64)
+            }
+            _ => None,
+        },
+        FileType::Script(_) => find(file, &[0], offset, eof)?,
+        _ => None,
+    };
+
+    Ok(end.map(|end| end.min(eof)))
+}
+
+/// Experimental: scans the provided file (an Asar archive file with a destroyed header) for known
+/// signatures and returns a best-effort listing of carveable ranges, in order, for forensic recovery.
+///
+/// Without a header file boundaries are unknown, so ranges may split or merge files.
+/// PNG, JPEG, PDF, and ZIP ranges end at their format's terminator, scripts (shebang lines)
+/// end at the first null byte, and everything else ends where the next range begins.
+/// Signatures found within an already carved range are ignored.
+///
+/// Returns the ranges, otherwise Error.
+pub fn scan_body(file: &File) -> Result<Vec<Carve>, asar_error::Error> {
+    let eof = file.metadata()?.len();
+    let mut candidates: Vec<(u64, FileType)> = Vec::new();
+    let mut buf: Vec<u8> = Vec::new();
+    let mut prev: Option<u8> = None;
+    let mut pos: u64 = 0;
+
+    while pos < eof {
+        let len = (eof - pos).min(SCAN_CHUNK_LEN + MAX_PATTERN_LEN);
+        let scan = (eof - pos).min(SCAN_CHUNK_LEN);
+
+        buf.resize(len as usize, 0);
+        file.read_exact_at(pos, &mut buf)?;
+
+        for i in 0..scan as usize {
+            if let Some(kind) = signature(&buf[i..], prev) {
+                candidates.push((pos + i as u64, kind));
+            }
+
+            prev = Some(buf[i]);
+        }
+
+        pos += scan;
+    }
+
+    let mut carves: Vec<Carve> = Vec::new();
+    let mut carved_to: u64 = 0;
+
+    for (i, (offset, kind)) in candidates.iter().enumerate() {
+        if *offset < carved_to {
+            continue;
+        }
+
+        let next = candidates[i + 1..]
+            .iter()
+            .map(|(next, _)| *next)
+            .find(|next| next > offset)
+            .unwrap_or(eof);
+
+        let end = match terminator(file, kind, *offset, eof)? {
+            Some(end) if !matches!(kind, FileType::Script(_)) => end,
+            Some(end) => end.min(next),
+            None => next,
+        };
+
+        let kind = match kind {
+            FileType::Script(_) => {
+                let mut line: Vec<u8> = vec![0; (end - offset).min(128) as usize];
+                file.read_exact_at(*offset, &mut line)?;
+
+                FileType::from_bytes(&line)
+            }
+            kind => kind.clone(),
+        };
+
+        carves.push(Carve {
+            offset: *offset,
+            len: end - offset,
+            kind,
+        });
+
+        carved_to = end;
+    }
+
+    Ok(carves)
+}
@@ -0,0 +1,261 @@
+use std::{
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use object_store::{path::Path as ObjectPath, ObjectStore, WriteMultipart};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    content::Content,
+    header::{self, ByteSource},
+    index::{build_index, EntryRef, PathIndex},
+    sink::EntrySink,
+    trace,
+};
+
+/// Number of bytes requested by each ranged GET while streaming a file from object storage.
+pub const RANGE_LEN: u64 = 8 * 1024 * 1024;
+
+/// Maximum number of parts uploaded at once while packing to object storage.
+const MAX_CONCURRENT_PARTS: usize = 8;
+
+// Returns the runtime driving the requests of a single archive. Blocking on it from within
+// another runtime panics, so every adapter here must be used from synchronous code.
+fn runtime() -> io::Result<Runtime> {
+    Builder::new_current_thread().enable_all().build()
+}
+
+/// A ByteSource over an object within an object store (e.g. S3 or GCS), read through ranged GETs.
+#[derive(Debug)]
+pub struct ObjectSource {
+    store: Arc<dyn ObjectStore>,
+    location: ObjectPath,
+    runtime: Runtime,
+}
+
+impl ObjectSource {
+
+    /// Returns a source over the object at `location` within the provided store. Nothing is
+    /// requested until bytes are read.
+    pub fn new(store: Arc<dyn ObjectStore>, location: ObjectPath) -> Result<ObjectSource, asar_error::Error> {
+        Ok(ObjectSource {
+            store,
+            location,
+            runtime: runtime()?,
+        })
+    }
+}
+
+impl ByteSource for ObjectSource {
+    type Error = object_store::Error;
+
+    fn read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let range = pos..pos + buf.len() as u64;
+        let bytes = self.runtime.block_on(self.store.get_range(&self.location, range))?;
+
+        if bytes.len() != buf.len() {
+            return Err(object_store::Error::Generic {
+                store: "rust_asar",
+                source: format!("{} ends within a requested range", self.location).into(),
+            });
+        }
+
+        buf.copy_from_slice(&bytes);
+        Ok(())
+    }
+}
+
+/// An Asar archive file opened directly from object storage, whose header and files are read
+/// through ranged GETs rather than a local copy of the archive.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use object_store::{memory::InMemory, path::Path};
+/// use rust_asar::remote::RemoteAsar;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let store = Arc::new(InMemory::new());
+/// let archive = RemoteAsar::open(store, Path::from("releases/app.asar"))?;
+///
+/// let manifest = archive.read_file("package.json")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// - content: Content of the archive, as that of an opened Asar archive file
+///
+/// - start: Offset at which content begins (after the header) within the object
+#[derive(Debug)]
+pub struct RemoteAsar {
+    pub content: Content,
+    pub start: u64,
+    source: ObjectSource,
+    index: PathIndex,
+}
+
+impl RemoteAsar {
+
+    /// Opens the Asar archive file at `location` within the provided store, requesting only its header.
+    ///
+    /// Returns instantiated struct, otherwise Error if the object can not be read or is not an archive.
+    pub fn open(store: Arc<dyn ObjectStore>, location: ObjectPath) -> Result<RemoteAsar, asar_error::Error> {
+        let _span = trace::span!(DEBUG, "open", path = %location);
+
+        let source = ObjectSource::new(store, location)?;
+        let (header, start) = header::read_header(&source)?;
+        let content = Content::new_json(header)?;
+
+        Ok(RemoteAsar {
+            index: build_index(&content)?,
+            content,
+            start,
+            source,
+        })
+    }
+
+    /// Returns the full path of every file and folder within the archive.
+    pub fn list(&self) -> Result<Vec<PathBuf>, asar_error::Error> {
+        self.content.paths_to_vec()
+    }
+
+    /// Reads the whole file at the provided full path with a single ranged GET.
+    ///
+    /// Returns its content, otherwise Error if it is not a file within the archive or can not be read.
+    pub fn read_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, asar_error::Error> {
+        let path = path.as_ref();
+
+        match self.index.get(path) {
+            Some(EntryRef::File(offset, size)) => {
+                let mut content: Vec<u8> = vec![0; *size as usize];
+                self.source.read_exact_at(self.start + offset, &mut content)?;
+
+                Ok(content)
+            }
+            _ => Err(Error::UnknownContentType(format!(
+                "Not a file within archive: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Extracts the archive to the provided sink, as does `Asar::extract_to_sink`, requesting
+    /// every file in ranges of at most `RANGE_LEN` bytes.
+    ///
+    /// Returns (), otherwise the first Error found or returned by the sink.
+    pub fn extract_to_sink(&self, sink: &mut dyn EntrySink) -> Result<(), asar_error::Error> {
+        let mut entries: Vec<(&PathBuf, &EntryRef)> = self.index.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0)); // folders before their contents
+
+        for (path, entry) in entries {
+            match entry {
+                EntryRef::Folder if path.as_os_str().is_empty() => {} // the archive root
+                EntryRef::Folder => sink.create_dir(path)?,
+                EntryRef::File(offset, size) => {
+                    sink.begin_file(path, *size)?;
+
+                    let mut buf: Vec<u8> = vec![0; RANGE_LEN.min(*size) as usize];
+                    let mut read: u64 = 0;
+
+                    while read < *size {
+                        let len = (*size - read).min(RANGE_LEN) as usize;
+                        self.source.read_exact_at(self.start + offset + read, &mut buf[..len])?;
+                        sink.write_chunk(&buf[..len])?;
+
+                        read += len as u64;
+                    }
+
+                    sink.end_file()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Writes to a multipart upload, waiting for in-flight parts before buffering more.
+struct MultipartWriter<'a> {
+    runtime: &'a Runtime,
+    upload: WriteMultipart,
+}
+
+impl Write for MultipartWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.runtime
+            .block_on(self.upload.wait_for_capacity(MAX_CONCURRENT_PARTS))
+            .map_err(io::Error::other)?;
+
+        let _guard = self.runtime.enter(); // parts are uploaded by tasks spawned on the runtime
+        self.upload.write(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Asar {
+
+    /// Packs an opened directory directly to a multipart upload at `location` within the provided
+    /// store, as does `pack`, without writing the archive to local disk. An opened Asar archive
+    /// file is repacked.
+    ///
+    /// The upload is aborted if packing fails, so no partial archive is left at the location.
+    ///
+    /// Returns (), otherwise Error.
+    pub fn pack_to_store(&self, store: Arc<dyn ObjectStore>, location: &ObjectPath) -> Result<(), asar_error::Error> {
+        self.ensure_writable()?;
+
+        if self.header.is_none() {
+            return self.repacked()?.pack_to_store(store, location);
+        }
+
+        let _span = trace::span!(DEBUG, "pack", path = %location);
+
+        let runtime = runtime()?;
+        let upload = runtime.block_on(store.put_multipart(location))?;
+
+        let mut writer = MultipartWriter {
+            runtime: &runtime,
+            upload: WriteMultipart::new(upload),
+        };
+
+        match self.write_upload(&mut writer) {
+            Ok(()) => {
+                runtime.block_on(writer.upload.finish())?;
+                Ok(())
+            }
+            Err(err) => {
+                runtime.block_on(writer.upload.abort()).ok();
+                Err(err)
+            }
+        }
+    }
+
+    // Writes the archive to the upload. Hooks that skip files write the header after the body,
+    // so the archive is written to a temporary file first.
+    fn write_upload(&self, writer: &mut MultipartWriter) -> Result<(), asar_error::Error> {
+        let run = self.options.run_context();
+
+        if !self.options.entry_hooks().recovers(&run) {
+            return self.write_archive(writer, &run);
+        }
+
+        let mut temp = match self.options.temp_directory() {
+            Some(dir) => tempfile::tempfile_in(dir)?,
+            None => tempfile::tempfile()?,
+        };
+
+        self.write_recovering(&mut temp, &run)?;
+        temp.seek(SeekFrom::Start(0))?;
+        io::copy(&mut temp, writer)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    asar::Asar,
+    asar_error,
+    content,
+    copy::CopiedEntry,
+};
+
+impl Asar {
+
+    // Returns an Asar ready to be packed that rewrites this opened Asar archive file: a canonical
+    // header regenerated from Content (see `Content::to_header_value`), with every file copied
+    // from the body of this archive and aligned to `align`.
+    pub(crate) fn repacked(&self) -> Result<Asar, asar_error::Error> {
+        let (header, layout) = self.content.to_header_layout(self.align())?;
+
+        let mut asar = Asar::from_header(&self.src_path, header, Vec::new())?;
+
+        asar.options = self.options.clone();
+        asar.start = content::align_up(asar.start, asar.align());
+        asar.metrics = self.metrics.clone();
+        asar.copies = layout
+            .into_iter()
+            .map(|(path, source, offset, size)| CopiedEntry {
+                path,
+                archive: self.src_path.clone(),
+                from: self.source.outermost(),
+                source: self.base() + self.start + source,
+                offset,
+                size,
+            })
+            .collect();
+
+        Ok(asar)
+    }
+}
+
+// Returns true if both paths refer to the same existing file.
+pub(crate) fn same_file(a: &Path, b: &Path) -> io::Result<bool> {
+    if !b.try_exists()? {
+        return Ok(false);
+    }
+
+    Ok(fs::canonicalize(a)? == fs::canonicalize(b)?)
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error,
+    content::Content,
+};
+
+/// How `Asar::size_report` groups files.
+///
+/// - Directory (depth): By the first `depth` folders of their path (files above that depth are
+///   grouped by their own folder, and files at the root as `.`)
+///
+/// - Extension: By their extension, lowercased (files without one as `(none)`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Directory(usize),
+    Extension,
+}
+
+impl Default for GroupBy {
+    fn default() -> GroupBy {
+        GroupBy::Directory(1)
+    }
+}
+
+/// Options used by `Asar::size_report`.
+///
+/// - group_by: How files are grouped
+///
+/// - top_n: Maximum number of groups and of largest files listed (unlimited if `None`)
+///
+/// - threshold: Size budget in bytes of every file combined (see `SizeReport::exceeds_threshold`)
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    pub group_by: GroupBy,
+    pub top_n: Option<usize>,
+    pub threshold: Option<u64>,
+}
+
+/// Files sharing a directory or extension, as listed by a SizeReport.
+///
+/// - name: The directory (joined by `/`) or extension shared by the files
+///
+/// - files: Number of files within the group
+///
+/// - size: Total size in bytes of the files within the group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeGroup {
+    pub name: String,
+    pub files: u64,
+    pub size: u64,
+}
+
+/// The sizes of the files within an archive, as returned by `Asar::size_report`.
+///
+/// - files: Number of files
+///
+/// - size: Total size in bytes of every file
+///
+/// - groups: Groups of files, largest first
+///
+/// - largest: Full path and size of the largest files, largest first
+///
+/// - threshold: Size budget the report was made against, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+    pub files: u64,
+    pub size: u64,
+    pub groups: Vec<SizeGroup>,
+    pub largest: Vec<(PathBuf, u64)>,
+    pub threshold: Option<u64>,
+}
+
+impl SizeReport {
+
+    /// Returns true if the total size exceeds the threshold, false if there is none.
+    pub fn exceeds_threshold(&self) -> bool {
+        self.threshold.is_some_and(|threshold| self.size > threshold)
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} bytes in {} files", self.size, self.files)?;
+
+        if let Some(threshold) = self.threshold {
+            let status = if self.exceeds_threshold() { "exceeded" } else { "ok" };
+            writeln!(f, "budget: {} bytes ({})", threshold, status)?;
+        }
+
+        writeln!(f, "\ngroups:")?;
+        for group in self.groups.iter() {
+            writeln!(f, "{:>12}  {:>6}  {}", group.size, group.files, group.name)?;
+        }
+
+        writeln!(f, "\nlargest files:")?;
+        for (path, size) in self.largest.iter() {
+            writeln!(f, "{:>12}  {}", size, path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Asar {
+
+    /// Reports the sizes of the files within an opened Asar archive file or directory, grouped
+    /// as configured by the provided ReportOptions.
+    ///
+    /// ```no_run
+    /// use rust_asar::{asar::Asar, report::{GroupBy, ReportOptions}};
+    ///
+    /// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+    /// let options = ReportOptions {
+    ///     group_by: GroupBy::Extension,
+    ///     top_n: Some(10),
+    ///     threshold: Some(50 * 1024 * 1024),
+    /// };
+    ///
+    /// let report = Asar::open("app.asar")?.size_report(&options)?;
+    /// assert!(!report.exceeds_threshold(), "{}", report);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Returns the SizeReport, otherwise Error.
+    pub fn size_report(&self, options: &ReportOptions) -> Result<SizeReport, asar_error::Error> {
+        let files = self.file_sizes()?;
+        let mut groups: BTreeMap<String, SizeGroup> = BTreeMap::new();
+
+        for (path, size) in files.iter() {
+            let name = group_name(path, options.group_by);
+            let group = groups.entry(name.clone()).or_insert(SizeGroup { name, files: 0, size: 0 });
+
+            group.files += 1;
+            group.size += size;
+        }
+
+        let mut groups: Vec<SizeGroup> = groups.into_values().collect();
+        groups.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        let mut largest = files.clone();
+        largest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        if let Some(top_n) = options.top_n {
+            groups.truncate(top_n);
+            largest.truncate(top_n);
+        }
+
+        Ok(SizeReport {
+            files: files.len() as u64,
+            size: files.iter().map(|(_, size)| size).sum(),
+            groups,
+            largest,
+            threshold: options.threshold,
+        })
+    }
+
+    // Returns the full path (relative to the archive or directory) and size of every file.
+    fn file_sizes(&self) -> Result<Vec<(PathBuf, u64)>, asar_error::Error> {
+        match &self.content {
+            Content::List(list) => Ok(list
+                .iter()
+                .map(|(path, size)| {
+                    let path = path.strip_prefix(&self.src_path).unwrap_or(path);
+                    (path.to_path_buf(), *size)
+                })
+                .collect()),
+            content => Ok(content
+                .files_to_vec()?
+                .into_iter()
+                .map(|(path, _, size)| (path, size))
+                .collect()),
+        }
+    }
+}
+
+// Returns the name of the group of the file at the provided path.
+fn group_name(path: &Path, group_by: GroupBy) -> String {
+    match group_by {
+        GroupBy::Directory(depth) => {
+            let folders: Vec<String> = path
+                .parent()
+                .into_iter()
+                .flat_map(Path::components)
+                .take(depth)
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect();
+
+            if folders.is_empty() {
+                ".".to_string()
+            } else {
+                folders.join("/")
+            }
+        }
+        GroupBy::Extension => match path.extension() {
+            Some(ext) => ext.to_string_lossy().to_lowercase(),
+            None => "(none)".to_string(),
+        },
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    io, thread,
+    time::Duration,
+};
+
+use crate::asar_error::Error;
+
+/// How a file is read (and, when extracting, written) again after a transient IO error (see
+/// `is_transient`), e.g. on a network filesystem or flaky media, before the error is handed to
+/// `Hooks::on_error` (see `PackOptions::retry` and `ExtractOptions::retry`).
+///
+/// - attempts: Number of retries after the first attempt
+///
+/// - backoff: Delay before the first retry, doubled before every following retry
+///
+/// - max_backoff: Upper bound of the delay before a retry
+///
+/// - jitter: Fraction of every delay (from 0.0 to 1.0) randomly taken off, so concurrent readers
+///   retrying the same share do not retry in lockstep
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+
+    /// Returns the default policy: 3 retries, starting after 100 milliseconds, at most 5 seconds
+    /// apart, with half of every delay jittered.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Sets the number of retries after the first attempt.
+    pub fn attempts(mut self, attempts: u32) -> RetryPolicy {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Sets the delay before the first retry, and the upper bound of the delay before any retry.
+    pub fn backoff(mut self, backoff: Duration, max_backoff: Duration) -> RetryPolicy {
+        self.backoff = backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the fraction of every delay randomly taken off, clamped from 0.0 (none) to 1.0.
+    pub fn jitter(mut self, jitter: f64) -> RetryPolicy {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the delay before the provided retry (starting at 0), before jitter.
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    // Sleeps before the provided retry, for its delay less a random share of its jitter.
+    pub(crate) fn wait(&self, retry: u32) {
+        let delay = self.delay(retry);
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+
+        thread::sleep(delay.mul_f64(1.0 - self.jitter.clamp(0.0, 1.0) * random));
+    }
+}
+
+/// Returns true if the Error is an IO error likely to go away when retried: an interrupted,
+/// timed out, or would-block call, a dropped connection, a stale network file handle, a busy
+/// resource, or (on Unix) a low-level IO error (`EIO`) from flaky media.
+pub fn is_transient(err: &Error) -> bool {
+    let Error::IoError(err) = err else {
+        return false;
+    };
+
+    if cfg!(unix) && err.raw_os_error() == Some(5) {
+        return true;
+    }
+
+    matches!(
+        err.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::StaleNetworkFileHandle
+            | io::ErrorKind::ResourceBusy
+    )
+}
@@ -0,0 +1,173 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    cas,
+};
+
+// Distinguishes the temporary folders of concurrent checks within a process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// The differences between a folder and the folder extracted from its packed archive, as
+/// returned by `roundtrip_check`. Every list is sorted by path.
+///
+/// - files: Number of files within the original folder
+///
+/// - missing: Full paths of the files and folders left out of the extracted folder
+///
+/// - extra: Full paths of the files and folders found only within the extracted folder
+///
+/// - changed: Full paths of the files whose content (or kind) differs after extracting
+///
+/// - permissions: Full paths of the files whose executable bit differs after extracting (Unix
+///   only, as it is the only permission an Asar archive records)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundTripReport {
+    pub files: usize,
+    pub missing: Vec<PathBuf>,
+    pub extra: Vec<PathBuf>,
+    pub changed: Vec<PathBuf>,
+    pub permissions: Vec<PathBuf>,
+}
+
+impl RoundTripReport {
+
+    /// Returns true if the extracted folder is identical to the original.
+    pub fn is_identical(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.changed.is_empty() && self.permissions.is_empty()
+    }
+}
+
+// A file or folder of a tree, as compared by `roundtrip_check`.
+#[derive(Debug, PartialEq, Eq)]
+enum Node {
+    Folder,
+    File { hash: String, executable: bool },
+}
+
+/// Packs the folder at the provided path, extracts the archive to a temporary folder, and
+/// compares both trees (content, kinds, and executable bits), confirming that this crate packs
+/// and extracts the exact data of a project correctly.
+///
+/// Temporary files are written to `std::env::temp_dir()` and removed afterwards.
+///
+/// ```no_run
+/// use rust_asar::roundtrip::roundtrip_check;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let report = roundtrip_check("app")?;
+///
+/// for path in report.changed.iter() {
+///     println!("{} differs after extracting", path.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Returns the report, otherwise Error if the folder can not be read, packed, or extracted.
+pub fn roundtrip_check<P: AsRef<Path>>(dir: P) -> Result<RoundTripReport, asar_error::Error> {
+    let dir = dir.as_ref();
+
+    if !dir.is_dir() {
+        return Err(Error::UnknownContentType(format!("Directory must be src_path: {}", dir.display())));
+    }
+
+    let temp = std::env::temp_dir().join(format!(
+        "rust_asar-selftest-{}-{}",
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let report = compare_round_trip(dir, &temp);
+
+    fs::remove_dir_all(&temp).ok();
+
+    report
+}
+
+fn compare_round_trip(dir: &Path, temp: &Path) -> Result<RoundTripReport, asar_error::Error> {
+    let archive = temp.join("archive.asar");
+    let extracted = temp.join("extracted");
+
+    fs::create_dir_all(temp)?;
+
+    Asar::open(dir)?.pack(&archive)?;
+    Asar::open(&archive)?.extract(&extracted)?;
+
+    let mut expected = BTreeMap::new();
+    let mut actual = BTreeMap::new();
+
+    read_tree(dir, dir, &mut expected)?;
+    read_tree(&extracted, &extracted, &mut actual)?;
+
+    let mut report = RoundTripReport {
+        files: expected.values().filter(|node| matches!(node, Node::File { .. })).count(),
+        ..RoundTripReport::default()
+    };
+
+    for (path, node) in expected.iter() {
+        match (node, actual.get(path)) {
+            (_, None) => report.missing.push(path.clone()),
+            (
+                Node::File { hash, executable },
+                Some(Node::File { hash: actual_hash, executable: actual_executable }),
+            ) => {
+                if hash != actual_hash {
+                    report.changed.push(path.clone());
+                } else if executable != actual_executable {
+                    report.permissions.push(path.clone());
+                }
+            }
+            (node, Some(actual)) if node != actual => report.changed.push(path.clone()),
+            _ => {}
+        }
+    }
+
+    report.extra = actual
+        .keys()
+        .filter(|path| !expected.contains_key(*path))
+        .cloned()
+        .collect();
+
+    Ok(report)
+}
+
+// Collects every file and folder below the folder, by full path relative to the root.
+fn read_tree(root: &Path, folder: &Path, tree: &mut BTreeMap<PathBuf, Node>) -> Result<(), asar_error::Error> {
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        let metadata = fs::metadata(&path)?;
+
+        if metadata.is_dir() {
+            tree.insert(relative, Node::Folder);
+            read_tree(root, &path, tree)?;
+        } else {
+            tree.insert(relative, Node::File {
+                hash: cas::sha256_hex(File::open(&path)?)?,
+                executable: is_executable(&metadata),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
@@ -0,0 +1,176 @@
+use std::{
+    fs::{DirBuilder, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    asar::{Asar, Source},
+    asar_error::{self, Error},
+    content::Content,
+    index::{build_index_lossy, EntryRef},
+    metrics::Recorder,
+    mode::OpenMode,
+    pack::PackOptions,
+};
+
+/// The recoverable state of a single file within a damaged Asar archive file.
+///
+/// - Intact: The whole file lies within the archive
+///
+/// - Truncated (available): The archive ends within the file, only `available` bytes remain
+///
+/// - Missing: The archive ends before the file starts
+///
+/// - Malformed (reason): The header entry could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryStatus {
+    Intact,
+    Truncated(u64),
+    Missing,
+    Malformed(String),
+}
+
+/// Report of every file within a damaged Asar archive file, returned by `Asar::open_salvage`
+/// and `Asar::extract_salvage`.
+///
+/// Entries are `(full_file_path, status)`, sorted by path.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SalvageReport {
+    pub entries: Vec<(PathBuf, EntryStatus)>,
+}
+
+impl SalvageReport {
+
+    /// Returns true if every file is intact.
+    pub fn is_intact(&self) -> bool {
+        self.entries.iter().all(|(_, status)| *status == EntryStatus::Intact)
+    }
+
+    /// Returns the files that are not intact, along with their status.
+    pub fn damaged(&self) -> Vec<&(PathBuf, EntryStatus)> {
+        self.entries
+            .iter()
+            .filter(|(_, status)| *status != EntryStatus::Intact)
+            .collect()
+    }
+}
+
+impl Asar {
+
+    /// Opens a possibly damaged Asar archive file, as does `open`, tolerating header entries that
+    /// can not be parsed and files that extend past the end of the archive.
+    ///
+    /// Every parsable entry remains accessible (e.g. with `get_file` for intact files), and each
+    /// file is classified in the returned SalvageReport.
+    ///
+    /// The JSON header itself must be readable, otherwise Error is returned.
+    pub fn open_salvage<P: AsRef<Path>>(src_path: P) -> Result<(Asar, SalvageReport), asar_error::Error> {
+        let src_path = src_path.as_ref();
+
+        if src_path.is_dir() {
+            return Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        let file = File::open(src_path)?;
+        let (header, start) = Self::get_asar_header(&file)?;
+        let content = Content::new_json(header)?;
+
+        let mut errors: Vec<(PathBuf, asar_error::Error)> = Vec::new();
+        let index = build_index_lossy(&content, &mut errors);
+
+        let asar = Asar {
+            src_path: src_path.to_path_buf(),
+            content,
+            start,
+            header: None,
+            index,
+            options: PackOptions::default(),
+            metrics: Recorder::default(),
+            copies: Vec::new(),
+            transformed: Arc::default(),
+            mode: OpenMode::default(),
+            unpacked: Vec::new(),
+            skipped: Vec::new(),
+            source: Source::Path,
+        };
+
+        let mut report = asar.salvage_report()?;
+
+        for (path, err) in errors {
+            report.entries.push((path, EntryStatus::Malformed(err.to_string())));
+        }
+
+        report.entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok((asar, report))
+    }
+
+    /// Classifies every indexed file of an opened Asar archive file by how much of it remains
+    /// within the archive.
+    pub fn salvage_report(&self) -> Result<SalvageReport, asar_error::Error> {
+        let len = File::open(self.src_path.as_path())?.metadata()?.len();
+        let body = len.saturating_sub(self.start);
+
+        let mut entries: Vec<(PathBuf, EntryStatus)> = self
+            .index
+            .iter()
+            .filter_map(|(path, entry)| match entry {
+                EntryRef::File(offset, size) => {
+                    let status = if offset + size <= body {
+                        EntryStatus::Intact
+                    } else if *offset < body {
+                        EntryStatus::Truncated(body - offset)
+                    } else {
+                        EntryStatus::Missing
+                    };
+
+                    Some((path.clone(), status))
+                }
+                EntryRef::Folder => None,
+            })
+            .collect();
+
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(SalvageReport { entries })
+    }
+
+    /// Extracts whatever can be recovered from a possibly damaged Asar archive file to the
+    /// provided destination folder.
+    ///
+    /// Intact files are written whole, truncated files are written up to the end of the archive,
+    /// and missing or malformed files are skipped. Every folder of the index is created.
+    ///
+    /// Returns the SalvageReport of the extracted files, otherwise Error.
+    pub fn extract_salvage<P: AsRef<Path>>(&self, destination: P) -> Result<SalvageReport, asar_error::Error> {
+        let destination = destination.as_ref();
+        let file = self.archive_file()?;
+        let report = self.salvage_report()?;
+
+        DirBuilder::new().recursive(true).create(destination)?;
+
+        for (path, entry) in self.index.iter() {
+            if *entry == EntryRef::Folder {
+                DirBuilder::new().recursive(true).create(destination.join(path))?;
+            }
+        }
+
+        for (path, status) in report.entries.iter() {
+            let (offset, size) = match (self.stat(path), status) {
+                (Some(EntryRef::File(offset, size)), EntryStatus::Intact) => (offset, size),
+                (Some(EntryRef::File(offset, _)), EntryStatus::Truncated(available)) => (offset, *available),
+                _ => continue,
+            };
+
+            let mut out = File::create(destination.join(path))?;
+            io::copy(&mut self.entry_reader(&file, offset, size), &mut out)?;
+            out.flush()?;
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use crate::{asar::Asar, asar_error, index::EntryRef};
+
+/// A package found within an archive by `Asar::sbom`, described by its `package.json`.
+///
+/// - name: Name of the package
+///
+/// - version: Version of the package, if declared
+///
+/// - license: License expression of the package, if declared (e.g. `MIT`)
+///
+/// - path: Full path of the folder holding the package within the archive
+///
+/// - license_files: Full paths of the license files shipped with the package (see `Asar::collect_license_files`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub name: String,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub path: PathBuf,
+    pub license_files: Vec<PathBuf>,
+}
+
+/// A software bill of materials of an archive, as returned by `Asar::sbom`.
+///
+/// - components: Every package found, sorted by the path of its folder (the application itself
+///   first, if packed with its `package.json`)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Sbom {
+    pub components: Vec<Component>,
+}
+
+impl Sbom {
+
+    /// Returns the bill of materials as a CycloneDX (1.5) JSON document, listing every component as
+    /// a library along with its path within the archive.
+    pub fn to_cyclonedx(&self) -> Value {
+        let components: Vec<Value> = self
+            .components
+            .iter()
+            .map(|component| {
+                let mut value = json!({
+                    "type": "library",
+                    "name": component.name,
+                    "properties": [{ "name": "asar:path", "value": component.path.to_string_lossy() }],
+                });
+
+                if let Some(version) = &component.version {
+                    value["version"] = json!(version);
+                }
+
+                if let Some(license) = &component.license {
+                    value["licenses"] = json!([{ "expression": license }]);
+                }
+
+                value
+            })
+            .collect();
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "components": components,
+        })
+    }
+}
+
+// Returns true if the file name is that of a license file.
+fn is_license_file(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+
+    name.starts_with("LICENSE") || name.starts_with("LICENCE") || name.starts_with("COPYING")
+}
+
+impl Asar {
+
+    /// Returns the full paths of every package manifest (`package.json`) and license file
+    /// (`LICENSE*`, `LICENCE*`, `COPYING*`, regardless of case) within an opened Asar archive
+    /// file, including those of every package below `node_modules`, sorted.
+    pub fn collect_license_files(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .index
+            .iter()
+            .filter(|(_, entry)| matches!(entry, EntryRef::File(..)))
+            .map(|(path, _)| path)
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name == "package.json" || is_license_file(name))
+            })
+            .cloned()
+            .collect();
+
+        paths.sort();
+        paths
+    }
+
+    /// Builds a software bill of materials of an opened Asar archive file from the package
+    /// manifests found by `collect_license_files`, for compliance audits (see `Sbom::to_cyclonedx`).
+    ///
+    /// Manifests without a name (e.g. `{"type": "module"}` within a package) are skipped. The
+    /// license is read from the `license` field, or the legacy `{"type": ...}` object.
+    ///
+    /// Returns the Sbom, otherwise Error if a manifest can not be read or parsed.
+    pub fn sbom(&self) -> Result<Sbom, asar_error::Error> {
+        let files = self.collect_license_files();
+        let mut components: Vec<Component> = Vec::new();
+
+        for manifest in files.iter().filter(|path| path.ends_with("package.json")) {
+            let content = self.get_file(manifest).ok_or_else(|| {
+                asar_error::Error::UnknownContentType(format!("{} can not be read", manifest.display()))
+            })?;
+            let value: Value = serde_json::from_slice(&content)?;
+
+            let Some(name) = value.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+
+            let path = manifest.parent().unwrap_or(Path::new("")).to_path_buf();
+
+            let license = match value.get("license") {
+                Some(Value::String(license)) => Some(license.clone()),
+                Some(Value::Object(license)) => license.get("type").and_then(Value::as_str).map(str::to_string),
+                _ => None,
+            };
+
+            let license_files = files
+                .iter()
+                .filter(|file| file.parent() == Some(path.as_path()) && !file.ends_with("package.json"))
+                .cloned()
+                .collect();
+
+            components.push(Component {
+                name: name.to_string(),
+                version: value.get("version").and_then(Value::as_str).map(str::to_string),
+                license,
+                path,
+                license_files,
+            });
+        }
+
+        components.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Sbom { components })
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    fmt,
+    io::{Read, Write},
+    path::Path,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
+};
+
+use crate::asar_error::{self, Error};
+
+/// Number of bytes passed to a scanner at once.
+pub const SCAN_CHUNK_LEN: usize = 64 * 1024;
+
+/// The result returned by a pack scanner for a chunk of a file.
+///
+/// - Clean: Packing continues
+///
+/// - Block (reason): The file must not ship, failing the pack with `Error::PolicyViolation`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanResult {
+    Clean,
+    Block(String),
+}
+
+/// A pack scanner, called with the full path of each file within the archive and a chunk of its content.
+pub type Scanner = dyn Fn(&Path, &[u8]) -> ScanResult + Send + Sync;
+
+// The Scanner set through `PackOptions::scan`, if any.
+#[derive(Clone, Default)]
+pub(crate) struct ScanHook(Option<Arc<Scanner>>);
+
+impl fmt::Debug for ScanHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ScanHook").field(&self.0.is_some()).finish()
+    }
+}
+
+impl ScanHook {
+    pub(crate) fn new(scanner: Arc<Scanner>) -> ScanHook {
+        ScanHook(Some(scanner))
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    // Copies exactly `size` bytes from reader to writer in chunks, scanning each chunk before it is
+    // written (and an empty chunk for an empty file), and checking the cancel token (if any).
+    pub(crate) fn copy<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        writer: &mut W,
+        size: u64,
+        path: &Path,
+        cancel: Option<&AtomicBool>,
+    ) -> Result<(), asar_error::Error> {
+        let mut buf: Vec<u8> = vec![0; SCAN_CHUNK_LEN.min(size as usize)];
+        let mut remaining = size;
+
+        loop {
+            if cancel.is_some_and(|token| token.load(Ordering::Relaxed)) {
+                return Err(Error::Cancelled);
+            }
+
+            let len = remaining.min(buf.len() as u64) as usize;
+            reader.read_exact(&mut buf[..len])?;
+
+            if let Some(scanner) = &self.0 {
+                if let ScanResult::Block(reason) = scanner(path, &buf[..len]) {
+                    return Err(Error::PolicyViolation(vec![(path.to_path_buf(), reason)]));
+                }
+            }
+
+            writer.write_all(&buf[..len])?;
+            remaining -= len as u64;
+
+            if remaining == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+};
+
+/// The maximum length in bytes of a line held in memory by `Asar::search`. Longer lines are
+/// searched in pieces of this length, so a match spanning two pieces is not found.
+pub const MAX_LINE_LEN: usize = 64 * 1024;
+
+/// Options used by `Asar::search` to control how the pattern is matched.
+///
+/// - case_insensitive: Match regardless of ASCII case
+///
+/// - regex: Treat the pattern as a regular expression (requires the `regex` feature)
+///
+/// - max_matches: Stop searching once this many matches have been found
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    pub case_insensitive: bool,
+    pub regex: bool,
+    pub max_matches: Option<usize>,
+}
+
+/// A single line within an archived file that matched the searched pattern.
+///
+/// - path: Full path of the file within the Asar archive
+///
+/// - line: Line number (starting at 1) of the match
+///
+/// - offset: Byte offset of the start of the match, relative to the start of the file
+///
+/// - text: The matched line (lossy UTF-8, line ending removed)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub path: PathBuf,
+    pub line: u64,
+    pub offset: u64,
+    pub text: String,
+}
+
+enum Matcher {
+    Literal(Vec<u8>, bool),
+    #[cfg(feature = "regex")]
+    Regex(regex::bytes::Regex),
+}
+
+impl Matcher {
+    fn new(pattern: &str, options: &SearchOptions) -> Result<Matcher, asar_error::Error> {
+        if options.regex {
+            #[cfg(feature = "regex")]
+            {
+                return regex::bytes::RegexBuilder::new(pattern)
+                    .case_insensitive(options.case_insensitive)
+                    .build()
+                    .map(Matcher::Regex)
+                    .map_err(|err| Error::InvalidPattern(err.to_string()));
+            }
+
+            #[cfg(not(feature = "regex"))]
+            return Err(Error::InvalidPattern(
+                "Regular expressions require the `regex` feature".to_string(),
+            ));
+        }
+
+        if options.case_insensitive {
+            Ok(Matcher::Literal(pattern.to_ascii_lowercase().into_bytes(), true))
+        } else {
+            Ok(Matcher::Literal(pattern.as_bytes().to_vec(), false))
+        }
+    }
+
+    // Returns the byte offset of the first match within the line, matched as raw bytes so it is
+    // exact even where the line is not valid UTF-8.
+    fn find(&self, line: &[u8]) -> Option<usize> {
+        match self {
+            Matcher::Literal(pat, true) => find_bytes(&line.to_ascii_lowercase(), pat),
+            Matcher::Literal(pat, false) => find_bytes(line, pat),
+            #[cfg(feature = "regex")]
+            Matcher::Regex(re) => re.find(line).map(|found| found.start()),
+        }
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+impl Asar {
+
+    /// Searches the contents of every file within an opened Asar archive file for the provided pattern,
+    /// line by line, without extracting the archive.
+    ///
+    /// Files are streamed from the archive so only one line, of at most MAX_LINE_LEN bytes, is held
+    /// in memory at a time.
+    ///
+    /// Returns a vector of all matches in archive order, otherwise Error.
+    /// > An Error is returned if a directory is opened or the pattern is invalid.
+    pub fn search(&self, pattern: &str, options: &SearchOptions) -> Result<Vec<Match>, asar_error::Error> {
+        let matcher = Matcher::new(pattern, options)?;
+        let limit = options.max_matches.unwrap_or(usize::MAX);
+
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let file = self.archive_file()?;
+        let mut matches: Vec<Match> = Vec::new();
+
+        for (path, offset, size) in self.content.files_to_vec()? {
+            let mut reader = BufReader::new(self.entry_reader(&file, offset, size));
+            let mut buf: Vec<u8> = Vec::new();
+            let mut line: u64 = 0;
+            let mut pos: u64 = 0;
+            let mut continued = false;
+
+            loop {
+                buf.clear();
+                let read = (&mut reader).take(MAX_LINE_LEN as u64).read_until(b'\n', &mut buf)?;
+
+                if read == 0 {
+                    break;
+                }
+
+                // a piece of a line longer than MAX_LINE_LEN keeps the line number of its start
+                if !continued {
+                    line += 1;
+                }
+
+                continued = buf.last() != Some(&b'\n');
+
+                let mut end = buf.len();
+
+                while end > 0 && matches!(buf[end - 1], b'\n' | b'\r') {
+                    end -= 1;
+                }
+
+                if let Some(start) = matcher.find(&buf[..end]) {
+                    matches.push(Match {
+                        path: path.clone(),
+                        line,
+                        offset: pos + start as u64,
+                        text: String::from_utf8_lossy(&buf[..end]).into_owned(),
+                    });
+
+                    if matches.len() >= limit {
+                        return Ok(matches);
+                    }
+                }
+
+                pos += read as u64;
+            }
+        }
+
+        Ok(matches)
+    }
+}
@@ -0,0 +1,130 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    asar::Asar,
+    asar_error,
+    index::EntryRef,
+    walk::Entry,
+};
+
+/// A collection of opened Asar archive files (e.g. `app.asar` along with locale packs) presenting
+/// a single tree, where lookups are resolved across every archive in priority order.
+///
+/// Archives added first take priority: a path found in several archives resolves to the first of
+/// them, and a file hides anything below the same path in archives of lower priority.
+///
+/// ```no_run
+/// use rust_asar::set::AsarSet;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let set = AsarSet::open(["locales/fr.asar", "app.asar"])?;
+///
+/// let strings = set.get_file("strings.json"); // from fr.asar if it holds it, otherwise app.asar
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AsarSet {
+    archives: Vec<Asar>,
+}
+
+impl AsarSet {
+
+    /// Returns an empty set.
+    pub fn new() -> AsarSet {
+        AsarSet::default()
+    }
+
+    /// Opens every provided Asar archive file, in priority order (highest first).
+    ///
+    /// Returns the set, otherwise the Error of the first archive that fails to open.
+    pub fn open<I, P>(paths: I) -> Result<AsarSet, asar_error::Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut set = AsarSet::new();
+
+        for path in paths {
+            set.push(Asar::open(path)?);
+        }
+
+        Ok(set)
+    }
+
+    /// Adds an opened Asar archive file with a lower priority than every archive already added.
+    pub fn push(&mut self, asar: Asar) {
+        self.archives.push(asar);
+    }
+
+    /// Returns the archives of the set, in priority order.
+    pub fn archives(&self) -> &[Asar] {
+        &self.archives
+    }
+
+    /// Returns the archive the provided path resolves to, along with its EntryRef, or `None` if
+    /// no archive holds the path (or it is hidden by a file of higher priority).
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<(&Asar, EntryRef)> {
+        let path = path.as_ref();
+
+        for (i, asar) in self.archives.iter().enumerate() {
+            if let Some(entry) = asar.stat(path) {
+                if self.is_shadowed(i, path) {
+                    return None;
+                }
+
+                return Some((asar, entry));
+            }
+        }
+
+        None
+    }
+
+    /// Provides the file at the provided path as a vector of bytes, read from the archive it
+    /// resolves to (see `find`).
+    ///
+    /// Returns `None` if the path does not resolve to a file.
+    pub fn get_file<P: AsRef<Path>>(&self, path: P) -> Option<Vec<u8>> {
+        match self.find(&path)? {
+            (asar, EntryRef::File(..)) => asar.get_file(path),
+            (_, EntryRef::Folder) => None,
+        }
+    }
+
+    /// Returns every file and folder of the merged tree, depth-first with folders listed before
+    /// their contents. Each path is listed once, with the Entry of the archive it resolves to.
+    ///
+    /// Returns the entries, otherwise the first Error found while walking an archive.
+    pub fn walk(&self) -> Result<Vec<Entry>, asar_error::Error> {
+        let mut merged: BTreeMap<PathBuf, Entry> = BTreeMap::new();
+
+        for asar in self.archives.iter() {
+            for entry in asar.walk()? {
+                let hidden = merged.contains_key(&entry.path)
+                    || entry
+                        .path
+                        .ancestors()
+                        .skip(1)
+                        .any(|ancestor| merged.get(ancestor).is_some_and(Entry::is_file));
+
+                if !hidden {
+                    merged.insert(entry.path.clone(), entry);
+                }
+            }
+        }
+
+        Ok(merged.into_values().collect())
+    }
+
+    // Returns true if an archive of higher priority than `index` holds a file above the path.
+    fn is_shadowed(&self, index: usize, path: &Path) -> bool {
+        self.archives[..index].iter().any(|asar| {
+            path.ancestors()
+                .skip(1)
+                .any(|ancestor| matches!(asar.stat(ancestor), Some(EntryRef::File(..))))
+        })
+    }
+}
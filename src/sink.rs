@@ -0,0 +1,123 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    index::EntryRef,
+    metrics::Operation,
+    trace,
+};
+
+/// Number of bytes passed to a sink at once.
+pub const SINK_CHUNK_LEN: usize = 64 * 1024;
+
+/// A destination for the files and folders of an archive, written by `Asar::extract_to_sink`, e.g.
+/// to route extracted files to object storage, a database, or a virtual filesystem.
+///
+/// Folders are created before their contents. The chunks of a file are written between its
+/// `begin_file` and `end_file`, one file at a time, and add up to exactly its size.
+///
+/// ```
+/// use std::{collections::HashMap, path::{Path, PathBuf}};
+/// use rust_asar::{asar_error, sink::EntrySink};
+///
+/// #[derive(Default)]
+/// struct MemorySink {
+///     files: HashMap<PathBuf, Vec<u8>>,
+///     current: PathBuf,
+/// }
+///
+/// impl EntrySink for MemorySink {
+///     fn begin_file(&mut self, path: &Path, size: u64) -> Result<(), asar_error::Error> {
+///         self.current = path.to_path_buf();
+///         self.files.insert(self.current.clone(), Vec::with_capacity(size as usize));
+///         Ok(())
+///     }
+///
+///     fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), asar_error::Error> {
+///         self.files.get_mut(&self.current).unwrap().extend_from_slice(chunk);
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait EntrySink {
+
+    /// Called for every folder, with its full path within the archive.
+    fn create_dir(&mut self, _path: &Path) -> Result<(), asar_error::Error> {
+        Ok(())
+    }
+
+    /// Called before the content of a file is written, with its full path within the archive and its size.
+    fn begin_file(&mut self, path: &Path, size: u64) -> Result<(), asar_error::Error>;
+
+    /// Called with each chunk of the content of the current file, of at most `SINK_CHUNK_LEN` bytes.
+    fn write_chunk(&mut self, chunk: &[u8]) -> Result<(), asar_error::Error>;
+
+    /// Called once the whole content of the current file was written.
+    fn end_file(&mut self) -> Result<(), asar_error::Error> {
+        Ok(())
+    }
+}
+
+impl Asar {
+
+    /// Extracts an opened Asar archive file to the provided sink rather than the filesystem,
+    /// streaming every file in chunks, in path order.
+    ///
+    /// Returns (), otherwise the first Error found or returned by the sink.
+    pub fn extract_to_sink(&self, sink: &mut dyn EntrySink) -> Result<(), asar_error::Error> {
+        let _span = trace::span!(DEBUG, "extract", path = %self.src_path.display());
+
+        if self.src_path.is_dir() {
+            return Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        let file = self.archive_file()?;
+        let started = Instant::now();
+        let (mut files, mut bytes) = (0, 0);
+
+        let mut entries: Vec<(&PathBuf, &EntryRef)> = self.index.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0)); // folders before their contents
+
+        let mut buf: Vec<u8> = vec![0; SINK_CHUNK_LEN];
+
+        for (path, entry) in entries {
+            match entry {
+                EntryRef::Folder if path.as_os_str().is_empty() => {} // the archive root
+                EntryRef::Folder => sink.create_dir(path)?,
+                EntryRef::File(offset, size) => {
+                    let read_started = Instant::now();
+                    let mut reader = self.entry_reader(&file, *offset, *size);
+                    let mut remaining = *size;
+
+                    sink.begin_file(path, *size)?;
+
+                    while remaining > 0 {
+                        let len = remaining.min(buf.len() as u64) as usize;
+                        reader.read_exact(&mut buf[..len])?;
+                        sink.write_chunk(&buf[..len])?;
+
+                        remaining -= len as u64;
+                    }
+
+                    sink.end_file()?;
+
+                    self.metrics.file_read(path, *size, read_started);
+                    (files, bytes) = (files + 1, bytes + size);
+
+                    trace::event!(TRACE, path = %path.display(), size = *size, "extracted file");
+                }
+            }
+        }
+
+        self.metrics.operation(Operation::Extract, files, bytes, started);
+
+        Ok(())
+    }
+}
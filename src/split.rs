@@ -0,0 +1,176 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, DirBuilder},
+    path::{Component, Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    pack::Packer,
+};
+
+/// A rule moving part of a directory out of the core archive written by `by_top_level_dirs`.
+///
+/// - Folder (prefix): Every file below `prefix` is packed into an archive of its own
+///
+/// - EachChild (prefix): Every folder directly below `prefix` (e.g. each `locales/<lang>`) is
+///   packed into an archive of its own; files directly below `prefix` stay in the core archive
+///
+/// Rules are tried in order, the first matching rule wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitRule {
+    Folder(PathBuf),
+    EachChild(PathBuf),
+}
+
+impl SplitRule {
+
+    // Returns the folder (relative to the source) of the archive holding the file, if the rule matches.
+    fn group(&self, path: &Path) -> Option<PathBuf> {
+        match self {
+            SplitRule::Folder(prefix) => path.starts_with(prefix).then(|| prefix.clone()),
+            SplitRule::EachChild(prefix) => {
+                let rest = path.strip_prefix(prefix).ok()?;
+                let mut components = rest.components();
+
+                match (components.next(), components.next()) {
+                    (Some(Component::Normal(child)), Some(_)) => Some(prefix.join(child)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// An archive written by `by_top_level_dirs`.
+///
+/// - name: File name of the archive within the destination folder
+///
+/// - prefix: Folder (relative to the source) the archive holds, `None` for the core archive
+///
+/// - files: Number of files within the archive
+///
+/// - size: Total size in bytes of the files within the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitArchive {
+    pub name: String,
+    pub prefix: Option<PathBuf>,
+    pub files: u64,
+    pub size: u64,
+}
+
+/// The archives written by `by_top_level_dirs`, core archive first, also written to the
+/// destination folder as `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitManifest {
+    pub archives: Vec<SplitArchive>,
+}
+
+impl SplitManifest {
+
+    /// Returns the manifest as the JSON value written to `manifest.json`.
+    pub fn to_value(&self) -> Value {
+        let archives: Vec<Value> = self
+            .archives
+            .iter()
+            .map(|archive| {
+                json!({
+                    "name": archive.name,
+                    "prefix": archive.prefix.as_ref().map(|prefix| prefix.to_string_lossy()),
+                    "files": archive.files,
+                    "size": archive.size,
+                })
+            })
+            .collect();
+
+        json!({ "archives": archives })
+    }
+}
+
+/// Packs the directory at `src` into several Asar archive files within the folder at `destination`:
+/// `core.asar` holding every file not matched by a SplitRule, and one archive per matched folder,
+/// named after its path (e.g. `locales-fr.asar`).
+///
+/// Files keep their full paths within every archive, so opening all of them with `AsarSet`
+/// presents the original tree. Archives that would be empty are not written.
+///
+/// ```no_run
+/// use rust_asar::split::{self, SplitRule};
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let manifest = split::by_top_level_dirs("app", &[SplitRule::EachChild("locales".into())], "dist")?;
+///
+/// for archive in manifest.archives.iter() {
+///     println!("{}: {} bytes", archive.name, archive.size);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Returns the manifest, otherwise Error.
+pub fn by_top_level_dirs<S: AsRef<Path>, D: AsRef<Path>>(src: S, rules: &[SplitRule], destination: D) -> Result<SplitManifest, asar_error::Error> {
+    let src = src.as_ref();
+    let destination = destination.as_ref();
+
+    if !src.is_dir() {
+        return Err(Error::UnknownContentType(format!(
+            "{} must be a directory to be split",
+            src.display()
+        )));
+    }
+
+    let (_, list) = Asar::gen_header_from_dir(src)?;
+    let mut groups: BTreeMap<Option<PathBuf>, Vec<(PathBuf, PathBuf, u64)>> = BTreeMap::new();
+
+    for (path, size) in list {
+        let relative = path.strip_prefix(src).unwrap_or(&path).to_path_buf();
+        let group = rules.iter().find_map(|rule| rule.group(&relative));
+
+        groups.entry(group).or_default().push((relative, path, size));
+    }
+
+    DirBuilder::new().recursive(true).create(destination)?;
+
+    let mut archives: Vec<SplitArchive> = Vec::new();
+
+    for (prefix, files) in groups {
+        let name = match &prefix {
+            Some(prefix) => archive_name(prefix),
+            None => "core.asar".to_string(),
+        };
+
+        let mut packer = Packer::new();
+
+        for (relative, path, _) in files.iter() {
+            packer.add_file(relative, path)?;
+        }
+
+        packer.write(destination.join(&name))?;
+
+        archives.push(SplitArchive {
+            name,
+            prefix,
+            files: files.len() as u64,
+            size: files.iter().map(|(_, _, size)| size).sum(),
+        });
+    }
+
+    let manifest = SplitManifest { archives };
+
+    fs::write(destination.join("manifest.json"), serde_json::to_vec_pretty(&manifest.to_value())?)?;
+
+    Ok(manifest)
+}
+
+// Names the archive of a folder after its components joined by `-`, e.g. `locales-fr.asar`.
+fn archive_name(prefix: &Path) -> String {
+    let components: Vec<String> = prefix
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+
+    format!("{}.asar", components.join("-"))
+}
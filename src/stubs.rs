@@ -0,0 +1,74 @@
+use std::{
+    fs::{self, DirBuilder, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    index::EntryRef,
+};
+
+/// Name of the manifest written at the root of the destination by `Asar::extract_stubs`.
+pub const STUBS_MANIFEST: &str = ".asar-stubs.json";
+
+impl Asar {
+
+    /// Recreates the folders of an opened Asar archive file at the destination with a stub of
+    /// every file holding only its first `preview_len` bytes (0 for empty stubs), so tools can
+    /// reason about the layout of very large archives without reading their bodies.
+    ///
+    /// The real size of every file is recorded in a manifest written to `STUBS_MANIFEST` at the
+    /// root of the destination:
+    ///
+    /// ```json
+    /// { "archive": "app.asar", "preview_len": 0, "files": { "dist/main.js": { "size": 1024 } } }
+    /// ```
+    ///
+    /// Returns the manifest, otherwise Error if a directory is open or a stub can not be written.
+    pub fn extract_stubs<P: AsRef<Path>>(&self, destination: P, preview_len: u64) -> Result<Value, asar_error::Error> {
+        if self.src_path.is_dir() {
+            return Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        let destination = destination.as_ref();
+        let file = self.archive_file()?;
+
+        let mut entries: Vec<(&PathBuf, &EntryRef)> = self.index.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0)); // folders before their contents
+
+        let mut files = Map::new();
+
+        for (path, entry) in entries {
+            let out_path = destination.join(path);
+
+            match entry {
+                EntryRef::Folder => DirBuilder::new().recursive(true).create(&out_path)?,
+                EntryRef::File(offset, size) => {
+                    let mut stub = File::create(&out_path)?;
+
+                    if preview_len > 0 {
+                        io::copy(&mut self.entry_reader(&file, *offset, *size).take(preview_len.min(*size)), &mut stub)?;
+                    }
+
+                    files.insert(path.to_string_lossy().into_owned(), json!({ "size": size }));
+                }
+            }
+        }
+
+        let manifest = json!({
+            "archive": self.src_path.to_string_lossy(),
+            "preview_len": preview_len,
+            "files": files,
+        });
+
+        fs::write(destination.join(STUBS_MANIFEST), serde_json::to_vec_pretty(&manifest)?)?;
+
+        Ok(manifest)
+    }
+}
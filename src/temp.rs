@@ -0,0 +1,65 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use tempfile::NamedTempFile;
+
+/// A temporary file written in place of a destination, e.g. by `Asar::pack`, so a failed,
+/// cancelled, or panicking operation never leaves a partial file behind or replaces an existing one.
+///
+/// The file is removed once the guard is dropped (including while unwinding from a panic),
+/// unless it is persisted to its destination.
+#[derive(Debug)]
+pub struct TempGuard {
+    file: NamedTempFile,
+}
+
+impl TempGuard {
+
+    /// Creates a temporary file within `temp_dir`, otherwise within the folder of the destination,
+    /// so persisting it is an atomic rename.
+    ///
+    /// Returns the guard, otherwise Error if the file can not be created.
+    pub fn new_for(destination: &Path, temp_dir: Option<&Path>) -> io::Result<TempGuard> {
+        let dir = match (temp_dir, destination.parent()) {
+            (Some(dir), _) => dir,
+            (None, Some(parent)) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+
+        let file = tempfile::Builder::new()
+            .prefix(".rust_asar-")
+            .suffix(".tmp")
+            .tempfile_in(dir)?;
+
+        Ok(TempGuard { file })
+    }
+
+    /// Returns the path of the temporary file.
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// Returns the temporary file, opened for writing.
+    pub fn file(&mut self) -> &mut File {
+        self.file.as_file_mut()
+    }
+
+    /// Moves the temporary file to the destination, replacing any file already there. If the
+    /// destination is on another filesystem than the temporary file, it is copied instead.
+    ///
+    /// Returns (), otherwise Error if the file can not be moved or copied.
+    pub fn persist(self, destination: &Path) -> io::Result<()> {
+        match self.file.persist(destination) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                // can not rename across filesystems; the temporary file is removed once copied
+                fs::copy(err.file.path(), destination)?;
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, DirBuilder},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use proptest::{collection, prelude::*};
+use serde_json::Value;
+
+use crate::{asar::Asar, index::EntryRef, pack::Packer};
+
+/// A directory tree as `full_file_path -> file content`, as generated by `tree`.
+///
+/// Folders are implied by the paths of their files.
+pub type Tree = BTreeMap<PathBuf, Vec<u8>>;
+
+// Distinguishes the temporary folders of concurrent round trips within a process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a proptest Strategy generating random directory trees of 1 to `max_files` files, nested
+/// at most `max_depth` folders deep, each holding at most `max_size` random bytes.
+///
+/// File names always have an extension and folder names never do, so no path is both a file
+/// and a folder.
+pub fn tree(max_files: usize, max_depth: usize, max_size: usize) -> impl Strategy<Value = Tree> {
+    let folders = collection::vec("[a-z][a-z0-9_-]{0,7}", 0..=max_depth);
+    let file = "[a-z][a-z0-9_-]{0,7}\\.[a-z]{1,3}";
+
+    let path = (folders, file).prop_map(|(folders, file)| {
+        let mut path: PathBuf = folders.into_iter().collect();
+        path.push(file);
+        path
+    });
+
+    collection::btree_map(path, collection::vec(any::<u8>(), 0..=max_size), 1..=max_files.max(1))
+}
+
+/// Returns a proptest Strategy generating random valid headers (JSON values) of Asar archive
+/// files, with offsets and sizes laid out as a packer would for a random tree (see `tree`).
+pub fn header(max_files: usize, max_depth: usize, max_size: usize) -> impl Strategy<Value = Value> {
+    tree(max_files, max_depth, max_size).prop_map(|tree| {
+        let mut packer = Packer::new();
+
+        for (path, content) in tree.iter() {
+            packer
+                .reserve(path, content.len() as u64)
+                .expect("generated paths are unique");
+        }
+
+        packer.header()
+    })
+}
+
+/// Writes the files of the provided tree to the folder at `dir`, creating it and every
+/// intermediate folder.
+pub fn write_tree<P: AsRef<Path>>(tree: &Tree, dir: P) -> io::Result<()> {
+    for (path, content) in tree.iter() {
+        let path = dir.as_ref().join(path);
+
+        if let Some(parent) = path.parent() {
+            DirBuilder::new().recursive(true).create(parent)?;
+        }
+
+        fs::write(path, content)?;
+    }
+
+    Ok(())
+}
+
+/// Reads every file (recursively) of the folder at `dir` into a Tree, with paths relative to `dir`.
+pub fn read_tree<P: AsRef<Path>>(dir: P) -> io::Result<Tree> {
+    let mut tree = Tree::new();
+    read_tree_aux(dir.as_ref(), Path::new(""), &mut tree)?;
+
+    Ok(tree)
+}
+
+fn read_tree_aux(dir: &Path, rel: &Path, tree: &mut Tree) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let rel = rel.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            read_tree_aux(&entry.path(), &rel, tree)?;
+        } else {
+            tree.insert(rel, fs::read(entry.path())?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Asserts that the folder at `dir` survives a round trip: packing it, reading every file back
+/// from the archive with `get_file`, and extracting the archive, must all reproduce its files exactly.
+///
+/// Temporary files are written to `std::env::temp_dir()` and removed once the round trip succeeds.
+///
+/// > Panics, naming the offending path, if any step fails or any file differs.
+pub fn assert_round_trips<P: AsRef<Path>>(dir: P) {
+    let dir = dir.as_ref();
+    let temp = std::env::temp_dir().join(format!(
+        "rust_asar-round-trip-{}-{}",
+        process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let archive = temp.join("archive.asar");
+    let extracted = temp.join("extracted");
+
+    let expected = read_tree(dir).unwrap_or_else(|err| panic!("failed to read {}: {}", dir.display(), err));
+
+    DirBuilder::new().recursive(true).create(&temp).expect("failed to create temporary folder");
+
+    Asar::open(dir)
+        .and_then(|asar| asar.pack(&archive))
+        .unwrap_or_else(|err| panic!("failed to pack {}: {}", dir.display(), err));
+
+    let asar = Asar::open(&archive).unwrap_or_else(|err| panic!("failed to open packed archive: {}", err));
+
+    let files = asar.index().values().filter(|entry| matches!(entry, EntryRef::File(..))).count();
+    assert_eq!(files, expected.len(), "packed archive holds {} files, expected {}", files, expected.len());
+
+    for (path, content) in expected.iter() {
+        assert!(
+            asar.get_file(path).as_ref() == Some(content),
+            "{} differs after packing",
+            path.display()
+        );
+    }
+
+    asar.extract(&extracted)
+        .unwrap_or_else(|err| panic!("failed to extract packed archive: {}", err));
+
+    let actual = read_tree(&extracted).expect("failed to read extracted folder");
+
+    for (path, content) in expected.iter() {
+        assert!(actual.get(path) == Some(content), "{} differs after extracting", path.display());
+    }
+
+    assert_eq!(actual.len(), expected.len(), "extracted folder holds unexpected files");
+
+    fs::remove_dir_all(&temp).ok();
+}
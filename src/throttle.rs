@@ -0,0 +1,77 @@
+use std::{
+    io::{self, Write},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+// Largest write paced at once, so a large write is spread over time rather than made in a burst.
+const THROTTLE_CHUNK_LEN: usize = 64 * 1024;
+
+// A token bucket limiting writes to `bytes_per_sec`, shared by every writer throttled with it.
+// The bucket holds up to a tenth of a second worth of bytes, so short bursts are not delayed.
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    bytes_per_sec: u64,
+    // tokens available (negative once overdrawn), and when they were last refilled
+    state: Mutex<(f64, Instant)>,
+}
+
+impl Throttle {
+    pub(crate) fn new(bytes_per_sec: u64) -> Throttle {
+        Throttle {
+            bytes_per_sec: bytes_per_sec.max(1),
+            state: Mutex::new((0.0, Instant::now())),
+        }
+    }
+
+    // Takes `bytes` tokens from the bucket, sleeping until they are paid back if overdrawn.
+    fn take(&self, bytes: usize) {
+        let rate = self.bytes_per_sec as f64;
+
+        let deficit = {
+            let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+            let (tokens, refilled) = &mut *state;
+            let now = Instant::now();
+
+            *tokens = (*tokens + now.duration_since(*refilled).as_secs_f64() * rate).min(rate / 10.0);
+            *refilled = now;
+            *tokens -= bytes as f64;
+
+            -*tokens
+        };
+
+        if deficit > 0.0 {
+            thread::sleep(Duration::from_secs_f64(deficit / rate));
+        }
+    }
+}
+
+// A writer paced by a Throttle, if any, in chunks of at most THROTTLE_CHUNK_LEN bytes.
+pub(crate) struct Throttled<'a, W: Write> {
+    inner: W,
+    throttle: Option<&'a Throttle>,
+}
+
+impl<'a, W: Write> Throttled<'a, W> {
+    pub(crate) fn new(inner: W, throttle: Option<&'a Throttle>) -> Throttled<'a, W> {
+        Throttled { inner, throttle }
+    }
+}
+
+impl<W: Write> Write for Throttled<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(throttle) = self.throttle else {
+            return self.inner.write(buf);
+        };
+
+        let len = buf.len().min(THROTTLE_CHUNK_LEN);
+        throttle.take(len);
+
+        self.inner.write(&buf[..len])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    cancel,
+};
+
+// Runs `operation` on a worker thread, returning Error::TimedOut if it does not complete within
+// `timeout`. The worker is passed a token that is set on timeout, and joined before returning, so
+// the operation stops at its next check and cleans up after itself (a worker blocked within a
+// read is waited on until that read returns).
+fn run_with_timeout<T, F>(timeout: Duration, operation: F) -> Result<T, asar_error::Error>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> Result<T, asar_error::Error> + Send + 'static,
+{
+    let token = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+
+    let worker_token = token.clone();
+    let worker = thread::spawn(move || {
+        sender.send(operation(&worker_token)).ok(); // the receiver is gone after a timeout
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(_) => {
+            token.store(true, Ordering::Relaxed);
+            worker.join().ok();
+
+            Err(Error::TimedOut)
+        }
+    }
+}
+
+impl Asar {
+
+    /// Provides a file within an opened Asar archive file as a vector of bytes, as does `get_file`,
+    /// returning `Error::TimedOut` if it can not be read within `timeout` (e.g. if the underlying
+    /// storage stalls). The file is read in chunks of 64 KiB, the last of which is waited on.
+    ///
+    /// Returns the file, otherwise Error (`Error::IoError` of kind NotFound if the path is not a file).
+    pub fn get_file_with_timeout<P: AsRef<Path>>(&self, path: P, timeout: Duration) -> Result<Vec<u8>, asar_error::Error> {
+        let mut reader = self.open_entry(path)?;
+
+        run_with_timeout(timeout, move |token| {
+            let size = reader.len();
+            let mut result: Vec<u8> = Vec::with_capacity(size as usize);
+            cancel::copy_cancellable(&mut reader, &mut result, size, token)?;
+
+            Ok(result)
+        })
+    }
+
+    /// Extracts an opened Asar archive file to the provided destination folder, as does
+    /// `extract_with_cancel`, returning `Error::TimedOut` if extraction does not complete within `timeout`.
+    ///
+    /// On timeout, extraction is cancelled and its partial output removed before this function
+    /// returns.
+    pub fn extract_with_timeout<P: AsRef<Path>>(&self, destination: P, timeout: Duration) -> Result<(), asar_error::Error> {
+        let asar = self.clone();
+        let destination = destination.as_ref().to_path_buf();
+
+        run_with_timeout(timeout, move |token| asar.extract_with_cancel(destination, token))
+    }
+}
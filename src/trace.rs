@@ -0,0 +1,38 @@
+// Optional tracing instrumentation (feature `tracing`).
+//
+// `span!` and `event!` forward to the `tracing` crate when the feature is enabled, taking the
+// level by name (e.g. `event!(DEBUG, size, "wrote file")`), and expand to nothing otherwise
+// so instrumented code compiles without the dependency.
+
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($level:ident, $($arg:tt)*) => {
+        tracing::span!(tracing::Level::$level, $($arg)*).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($level:ident, $($arg:tt)*) => {
+        $crate::trace::NoSpan
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($level:ident, $($arg:tt)*) => {
+        tracing::event!(tracing::Level::$level, $($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+    ($level:ident, $($arg:tt)*) => {};
+}
+
+pub(crate) use event;
+pub(crate) use span;
+
+// Stand-in for an entered span when tracing is disabled.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
@@ -0,0 +1,22 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// The result returned by a transformer for the content of a single file.
+///
+/// - Content (bytes): The content written in place of the original, of any size
+///
+/// - Skip: The file is silently left out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransformResult {
+    Content(Vec<u8>),
+    Skip,
+}
+
+/// A transformer, called with the full path of each file within the archive and its whole
+/// content, e.g. to minify scripts, inject a banner, or rewrite configuration files.
+pub type Transformer = dyn Fn(&Path, Vec<u8>) -> TransformResult + Send + Sync;
+
+// Transformed content of the files of an opened directory, by filesystem path.
+pub(crate) type Transformed = HashMap<PathBuf, Vec<u8>>;
@@ -0,0 +1,189 @@
+use std::{
+    ops::Deref,
+    path::Path,
+    sync::atomic::AtomicBool,
+};
+
+use serde_json::Value;
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    pack::PackOptions,
+};
+
+/// An opened Asar archive file, for reading and extraction only.
+///
+/// Every reading operation of `Asar` is available through `Deref`, while operations only valid
+/// for an opened directory (e.g. `set_entry_meta` or `copy_entry_from`) are not, so misusing them
+/// is a compile error rather than a runtime Error. Use `into_packer` to repack the archive.
+///
+/// ```no_run
+/// use rust_asar::typed::AsarReader;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let reader = AsarReader::open("app.asar")?;
+///
+/// let manifest = reader.get_file("package.json");
+/// reader.extract("app")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsarReader {
+    asar: Asar,
+}
+
+impl AsarReader {
+
+    /// Opens an Asar archive file.
+    ///
+    /// Returns instantiated struct, otherwise Error if the path is a directory or not an archive.
+    pub fn open<P: AsRef<Path>>(src_path: P) -> Result<AsarReader, asar_error::Error> {
+        AsarReader::try_from(Asar::open(src_path)?)
+    }
+
+    /// Converts the archive into an AsarPacker holding its entries, which reads the body of
+    /// the archive while packing, so entries can be modified before repacking.
+    ///
+    /// Returns the AsarPacker, otherwise Error if the header can not be regenerated.
+    pub fn into_packer(self) -> Result<AsarPacker, asar_error::Error> {
+        Ok(AsarPacker {
+            asar: self.asar.repacked()?,
+        })
+    }
+
+    /// Returns the underlying Asar.
+    pub fn into_inner(self) -> Asar {
+        self.asar
+    }
+}
+
+impl Deref for AsarReader {
+    type Target = Asar;
+
+    fn deref(&self) -> &Asar {
+        &self.asar
+    }
+}
+
+impl TryFrom<Asar> for AsarReader {
+    type Error = asar_error::Error;
+
+    /// Returns the AsarReader, otherwise Error if the Asar is an opened directory.
+    fn try_from(asar: Asar) -> Result<AsarReader, asar_error::Error> {
+        if asar.header.is_some() || asar.src_path.is_dir() {
+            return Err(Error::UnknownContentType(
+                "Asar archive file must be src_path".to_string(),
+            ));
+        }
+
+        Ok(AsarReader { asar })
+    }
+}
+
+impl From<AsarReader> for Asar {
+    fn from(reader: AsarReader) -> Asar {
+        reader.asar
+    }
+}
+
+/// An opened directory (or repacked archive, see `AsarReader::into_packer`), for modification
+/// and packing only.
+///
+/// Reading operations of `Asar` are not available, as they require an opened Asar archive file,
+/// so misusing them is a compile error rather than a runtime Error.
+///
+/// ```no_run
+/// use rust_asar::typed::AsarPacker;
+/// use serde_json::json;
+///
+/// # fn main() -> Result<(), rust_asar::asar_error::Error> {
+/// let mut packer = AsarPacker::open("app")?;
+///
+/// packer.set_entry_meta("main.js", "license", json!("MIT"))?;
+/// packer.pack("app.asar")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsarPacker {
+    asar: Asar,
+}
+
+impl AsarPacker {
+
+    /// Opens a directory, generating its header.
+    ///
+    /// Returns instantiated struct, otherwise Error if the path is not a directory.
+    pub fn open<P: AsRef<Path>>(src_path: P) -> Result<AsarPacker, asar_error::Error> {
+        Self::open_with(src_path, &PackOptions::default())
+    }
+
+    /// Opens a directory, as does `open`, applying the provided PackOptions while generating its header.
+    pub fn open_with<P: AsRef<Path>>(src_path: P, options: &PackOptions) -> Result<AsarPacker, asar_error::Error> {
+        if !src_path.as_ref().is_dir() {
+            return Err(Error::UnknownContentType(
+                "Directory must be src_path".to_string(),
+            ));
+        }
+
+        AsarPacker::try_from(Asar::open_with(src_path, options)?)
+    }
+
+    /// Returns the header that will be written by `pack`.
+    pub fn header(&self) -> &Value {
+        self.asar.header.as_ref().expect("AsarPacker holds a generated header")
+    }
+
+    /// Packs to the Asar archive file at `destination`, as does `Asar::pack`.
+    pub fn pack<P: AsRef<Path>>(&self, destination: P) -> Result<(), asar_error::Error> {
+        self.asar.pack(destination)
+    }
+
+    /// Packs to the Asar archive file at `destination`, as does `Asar::pack_with_cancel`.
+    pub fn pack_with_cancel<P: AsRef<Path>>(&self, destination: P, token: &AtomicBool) -> Result<(), asar_error::Error> {
+        self.asar.pack_with_cancel(destination, token)
+    }
+
+    /// Returns the exact size in bytes of the archive `pack` would write, as does `Asar::packed_size_estimate`.
+    pub fn packed_size_estimate(&self) -> Result<u64, asar_error::Error> {
+        self.asar.packed_size_estimate()
+    }
+
+    /// Sets a metadata field of an entry, as does `Asar::set_entry_meta`.
+    pub fn set_entry_meta<P: AsRef<Path>, K: Into<String>>(&mut self, path: P, key: K, value: Value) -> Result<(), asar_error::Error> {
+        self.asar.set_entry_meta(path, key, value)
+    }
+
+    /// Copies a file or folder from an opened archive, as does `Asar::copy_entry_from`.
+    pub fn copy_entry_from<S: AsRef<Path>, D: AsRef<Path>>(&mut self, other: &AsarReader, src_path: S, dest_path: D) -> Result<(), asar_error::Error> {
+        self.asar.copy_entry_from(other, src_path, dest_path)
+    }
+
+    /// Returns the underlying Asar.
+    pub fn into_inner(self) -> Asar {
+        self.asar
+    }
+}
+
+impl TryFrom<Asar> for AsarPacker {
+    type Error = asar_error::Error;
+
+    /// Returns the AsarPacker, otherwise Error if the Asar is an opened Asar archive file.
+    fn try_from(asar: Asar) -> Result<AsarPacker, asar_error::Error> {
+        if asar.header.is_none() {
+            return Err(Error::UnknownContentType(
+                "Directory must be src_path".to_string(),
+            ));
+        }
+
+        Ok(AsarPacker { asar })
+    }
+}
+
+impl From<AsarPacker> for Asar {
+    fn from(packer: AsarPacker) -> Asar {
+        packer.asar
+    }
+}
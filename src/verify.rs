@@ -0,0 +1,127 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use positioned_io::{Cursor, Slice};
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    hash,
+    walk::WalkOptions,
+};
+
+/// How a packed archive is verified by `pack` (see `PackOptions::verify`).
+///
+/// - Off: The archive is not verified
+///
+/// - Sample: The header is validated, and the content of at most the provided number of files
+///   (spread evenly through the archive) is compared with its source
+///
+/// - Full: The header is validated, and the content of every file is compared with its source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Verification {
+    #[default]
+    Off,
+    Sample(usize),
+    Full,
+}
+
+impl Asar {
+
+    // Re-opens the archive just packed to the destination, validating its header and size, then
+    // compares the digests of its files with their sources (as selected by the Verification).
+    pub(crate) fn verify_packed(&self, destination: &Path) -> Result<(), asar_error::Error> {
+        let (header, start) = Self::get_asar_header(&File::open(destination)?)?;
+
+        if self.header.as_ref() != Some(&header) || start != self.start {
+            return Err(Error::ParseHeaderError(
+                "Packed archive failed verification: its header differs from the one packed".to_string(),
+            ));
+        }
+
+        let expected = self.packed_size_estimate()?;
+        let len = destination.metadata()?.len();
+
+        if len != expected {
+            return Err(Error::ParseHeaderError(format!(
+                "Packed archive failed verification: {} bytes written, expected {}",
+                len,
+                expected
+            )));
+        }
+
+        let packed = Asar::open(destination)?;
+        let files: Vec<(PathBuf, u64)> = packed
+            .walk_with(&WalkOptions::default())?
+            .into_iter()
+            .filter(|entry| entry.is_file())
+            .map(|entry| {
+                let size = entry.size();
+                (entry.path, size)
+            })
+            .collect();
+
+        let sampled: Vec<&(PathBuf, u64)> = match self.options.verification() {
+            Verification::Off => Vec::new(),
+            Verification::Full => files.iter().collect(),
+            Verification::Sample(count) if count < files.len() => {
+                (0..count).map(|i| &files[i * files.len() / count]).collect()
+            }
+            Verification::Sample(_) => files.iter().collect(),
+        };
+
+        let mut mismatches = Vec::new();
+
+        for (path, size) in sampled {
+            let packed_hash = hash::hex_digest(self.hasher(), packed.open_entry(path)?)?;
+
+            match self.source_hash(path)? {
+                (source_size, _) if source_size != *size => mismatches.push(format!(
+                    "{}: {} bytes, expected {}",
+                    path.display(),
+                    size,
+                    source_size
+                )),
+                (_, hash) if hash != packed_hash => mismatches.push(format!("{}: content differs", path.display())),
+                _ => {}
+            }
+        }
+
+        if !mismatches.is_empty() {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Packed archive failed verification: {} file(s) differ\n  {}",
+                    mismatches.len(),
+                    mismatches.join("\n  ")
+                ),
+            )));
+        }
+
+        Ok(())
+    }
+
+    // Returns the size and digest of the source of a file packed at the full path: the other
+    // archive it was copied from, its transformed content, or the file within the directory.
+    fn source_hash(&self, path: &Path) -> Result<(u64, String), asar_error::Error> {
+        if let Some(copy) = self.copies.iter().find(|copy| copy.path == path) {
+            let archive = copy.from.open(&copy.archive)?;
+            let reader = Cursor::new(Slice::new(&archive, copy.source, Some(copy.size)));
+
+            return Ok((copy.size, hash::hex_digest(self.hasher(), reader.take(copy.size))?));
+        }
+
+        let full_path = self.src_path.join(path);
+
+        if let Some(content) = self.transformed.get(&full_path) {
+            return Ok((content.len() as u64, hash::hex_digest(self.hasher(), content.as_slice())?));
+        }
+
+        let file = File::open(&full_path)?;
+
+        Ok((file.metadata()?.len(), hash::hex_digest(self.hasher(), file)?))
+    }
+}
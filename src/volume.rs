@@ -0,0 +1,346 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use positioned_io::ReadAt;
+use serde_json::{json, Value};
+
+use crate::{
+    asar::{self, Asar, Source},
+    asar_error::{self, Error},
+    content,
+    header::ByteSource,
+    temp::TempGuard,
+};
+
+/// Returns the path of a volume of a split archive (see `PackOptions::split_size`), numbered from
+/// 1, e.g. `app.asar.001` for the first volume of `app.asar`.
+pub fn volume_path<P: AsRef<Path>>(archive: P, number: usize) -> PathBuf {
+    let mut path = archive.as_ref().as_os_str().to_os_string();
+    path.push(format!(".{:03}", number));
+
+    PathBuf::from(path)
+}
+
+/// Returns the paths of the volumes of the split archive at the provided path (either the path
+/// passed to `pack`, e.g. `app.asar`, or its first volume `app.asar.001`), in order, as listed by
+/// the header of the first volume.
+///
+/// Returns an empty vector if the archive is not split, otherwise Error if it can not be read.
+pub fn volumes<P: AsRef<Path>>(archive: P) -> Result<Vec<PathBuf>, asar_error::Error> {
+    let Some(first) = first_volume(archive.as_ref()) else {
+        return Ok(Vec::new());
+    };
+
+    let (header, _) = Asar::get_asar_header(&File::open(&first)?)?;
+
+    Ok(volume_sizes(&first, &header)?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect())
+}
+
+// Returns the first volume of a split archive, given either its base path or the first volume.
+pub(crate) fn first_volume(path: &Path) -> Option<PathBuf> {
+    if path.extension().is_some_and(|extension| extension == "001") {
+        return Some(path.to_path_buf());
+    }
+
+    let first = volume_path(path, 1);
+
+    (!path.exists() && first.is_file()).then_some(first)
+}
+
+// Returns every volume `(path, size)` listed by the header of the first volume, or an empty vector
+// if the header lists none. Fails if a volume is missing or its size differs from the header.
+pub(crate) fn volume_sizes(first: &Path, header: &Value) -> Result<Vec<(PathBuf, u64)>, asar_error::Error> {
+    let Some(sizes) = header.get("volumes") else {
+        return Ok(Vec::new());
+    };
+
+    let sizes: Vec<u64> = match sizes.as_array().and_then(|sizes| sizes.iter().map(Value::as_u64).collect::<Option<Vec<u64>>>()) {
+        Some(sizes) if !sizes.is_empty() => sizes,
+        _ => {
+            return Err(Error::ParseHeaderError(
+                "'volumes' must list the size of every volume".to_string(),
+            ))
+        }
+    };
+
+    let base = match first.extension() {
+        Some(extension) if extension == "001" => first.with_extension(""),
+        _ => {
+            return Err(Error::ParseHeaderError(format!(
+                "{} is a volume of a split archive, open its first volume (.001)",
+                first.display()
+            )))
+        }
+    };
+
+    let mut volumes: Vec<(PathBuf, u64)> = Vec::with_capacity(sizes.len());
+
+    for (i, size) in sizes.into_iter().enumerate() {
+        let path = volume_path(&base, i + 1);
+
+        let len = match fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::IoError(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Missing volume {} of split archive", path.display()),
+                )))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        if len != size {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Volume {} is {} bytes, expected {}", path.display(), len, size),
+            )));
+        }
+
+        volumes.push((path, size));
+    }
+
+    Ok(volumes)
+}
+
+/// An opened Asar archive file, read at arbitrary offsets with `positioned_io::ReadAt`.
+///
+/// - Single (file): An archive written as a single file
+///
+/// - Split (volumes): The volumes `(start, size, file)` of a split archive, read as if they were
+///   concatenated; `start` is the offset of the volume within the archive
+///
+/// - Region (archive, base, len): An archive nested within another (see `Asar::open_nested`),
+///   stored as the `len` bytes at offset `base` of the outer archive
+///
+/// - Memory (bytes): An archive held in memory, such as the plaintext of an encrypted archive (see
+///   `Asar::open_encrypted`)
+#[derive(Debug)]
+pub enum ArchiveFile {
+    Single(File),
+    Split(Vec<(u64, u64, File)>),
+    Region(Box<ArchiveFile>, u64, u64),
+    Memory(Arc<[u8]>),
+}
+
+impl ArchiveFile {
+
+    // Opens the volumes `(path, size)`, or the file at `path` if there are none.
+    pub(crate) fn open(path: &Path, volumes: &[(PathBuf, u64)]) -> io::Result<ArchiveFile> {
+        if volumes.is_empty() {
+            return Ok(ArchiveFile::Single(File::open(path)?));
+        }
+
+        let mut start = 0;
+        let mut files = Vec::with_capacity(volumes.len());
+
+        for (path, size) in volumes.iter() {
+            files.push((start, *size, File::open(path)?));
+            start += size;
+        }
+
+        Ok(ArchiveFile::Split(files))
+    }
+
+    // Returns the length in bytes of the archive, every volume combined.
+    pub(crate) fn len(&self) -> io::Result<u64> {
+        match self {
+            ArchiveFile::Single(file) => Ok(file.metadata()?.len()),
+            ArchiveFile::Split(volumes) => Ok(volumes.iter().map(|(_, size, _)| size).sum()),
+            ArchiveFile::Region(_, _, len) => Ok(*len),
+            ArchiveFile::Memory(bytes) => Ok(bytes.len() as u64),
+        }
+    }
+}
+
+impl From<File> for ArchiveFile {
+    fn from(file: File) -> Self {
+        ArchiveFile::Single(file)
+    }
+}
+
+impl ReadAt for ArchiveFile {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ArchiveFile::Single(file) => file.read_at(pos, buf),
+            ArchiveFile::Split(volumes) => {
+                // the volume holding `pos`, reads never cross into the next volume
+                let i = volumes.partition_point(|(start, _, _)| *start <= pos);
+
+                match i.checked_sub(1).map(|i| &volumes[i]) {
+                    Some((start, size, file)) if pos < start + size => {
+                        let avail = (start + size - pos).min(buf.len() as u64) as usize;
+
+                        file.read_at(pos - start, &mut buf[..avail])
+                    }
+                    _ => Ok(0),
+                }
+            }
+            ArchiveFile::Region(archive, base, len) => {
+                let avail = len.saturating_sub(pos).min(buf.len() as u64) as usize;
+
+                archive.read_at(base + pos, &mut buf[..avail])
+            }
+            ArchiveFile::Memory(bytes) => {
+                let rest = bytes.get(pos.min(bytes.len() as u64) as usize..).unwrap_or_default();
+                let avail = rest.len().min(buf.len());
+
+                buf[..avail].copy_from_slice(&rest[..avail]);
+
+                Ok(avail)
+            }
+        }
+    }
+}
+
+impl ByteSource for ArchiveFile {
+    type Error = io::Error;
+
+    fn read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), Self::Error> {
+        ReadAt::read_exact_at(self, pos, buf)
+    }
+}
+
+// Returns the sizes of the volumes of an archive of `len` bytes split every `split_size` bytes,
+// the first volume extended to hold the whole header (`start` bytes).
+fn split_sizes(len: u64, start: u64, split_size: u64) -> Vec<u64> {
+    let first = split_size.max(start).min(len);
+    let mut sizes = vec![first];
+    let mut rest = len - first;
+
+    while rest > 0 {
+        sizes.push(rest.min(split_size));
+        rest -= rest.min(split_size);
+    }
+
+    sizes
+}
+
+// Splits the packed archive at `packed` into volumes next to `destination` (`<destination>.001`,
+// ...), adding the size of every volume to its header, so the body keeps its offsets and alignment.
+// Volumes left over from an earlier split into more volumes are removed.
+pub(crate) fn write_volumes(
+    packed: &Path,
+    destination: &Path,
+    split_size: u64,
+    align: u64,
+    temp_dir: Option<&Path>,
+) -> Result<(), asar_error::Error> {
+    if split_size == 0 {
+        return Err(Error::IoError(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Split size must be at least 1 byte",
+        )));
+    }
+
+    let mut file = File::open(packed)?;
+    let (mut header, old_start) = Asar::get_asar_header(&file)?;
+    let body = file.metadata()?.len().saturating_sub(old_start);
+
+    // the header lists the sizes of the volumes, which depend on the length of the header
+    let mut sizes: Vec<u64> = Vec::new();
+
+    let start = loop {
+        header["volumes"] = json!(sizes);
+
+        let start = content::align_up(asar::json_len(&header)? + 16, align);
+        let next = split_sizes(start + body, start, split_size);
+
+        if next == sizes {
+            break start;
+        }
+
+        sizes = next;
+    };
+
+    file.seek(SeekFrom::Start(old_start))?;
+
+    let mut guards: Vec<TempGuard> = Vec::with_capacity(sizes.len());
+
+    for (i, size) in sizes.iter().enumerate() {
+        let mut guard = TempGuard::new_for(destination, temp_dir)?;
+
+        let mut size = *size;
+
+        if i == 0 {
+            Asar::write_header_padded(guard.file(), &header, start)?;
+            size -= start;
+        }
+
+        let copied = io::copy(&mut (&mut file).take(size), guard.file())?;
+
+        if copied != size {
+            return Err(Error::IoError(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Packed archive {} ended early", packed.display()),
+            )));
+        }
+
+        guards.push(guard);
+    }
+
+    for (i, guard) in guards.into_iter().enumerate() {
+        guard.persist(&volume_path(destination, i + 1))?;
+    }
+
+    for number in sizes.len() + 1.. {
+        match fs::remove_file(volume_path(destination, number)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(())
+}
+
+impl Asar {
+
+    /// Returns the paths of the volumes of an opened split archive (see `PackOptions::split_size`),
+    /// in order, or an empty vector if the archive is a single file.
+    pub fn volumes(&self) -> Vec<&Path> {
+        self.source.volumes().iter().map(|(path, _)| path.as_path()).collect()
+    }
+
+    // Opens the Asar archive file, stitching the volumes of a split archive together, or the region
+    // of the outer archive holding a nested archive.
+    pub(crate) fn archive_file(&self) -> io::Result<ArchiveFile> {
+        self.source.open(&self.src_path)
+    }
+}
+
+impl Source {
+
+    // The volumes `(path, size)` of the outermost archive, if split.
+    fn volumes(&self) -> &[(PathBuf, u64)] {
+        match self {
+            Source::Volumes(volumes) => volumes,
+            Source::Nested(outermost, _, _) => outermost.volumes(),
+            _ => &[],
+        }
+    }
+
+    // The source of the outermost archive, which this source is itself unless nested.
+    pub(crate) fn outermost(&self) -> Source {
+        match self {
+            Source::Nested(outermost, _, _) => (**outermost).clone(),
+            source => source.clone(),
+        }
+    }
+
+    // Opens the archive read from this source, whose outermost archive file is at `path`.
+    pub(crate) fn open(&self, path: &Path) -> io::Result<ArchiveFile> {
+        match self {
+            Source::Nested(outermost, base, len) => Ok(ArchiveFile::Region(Box::new(outermost.open(path)?), *base, *len)),
+            #[cfg(feature = "encryption")]
+            Source::Decrypted(bytes) => Ok(ArchiveFile::Memory(bytes.clone())),
+            source => ArchiveFile::open(path, source.volumes()),
+        }
+    }
+}
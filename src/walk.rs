@@ -0,0 +1,170 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+
+use crate::{
+    asar::Asar,
+    asar_error::{self, Error},
+    content, header,
+    index::EntryRef,
+};
+
+/// A file or folder within an Asar archive file, as listed by `Asar::read_dir`.
+///
+/// - path: Full path of the entry within the archive
+///
+/// - name: Name of the entry (last component of its path)
+///
+/// - kind: EntryRef of the entry, holding the offset and size of files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub name: String,
+    pub kind: EntryRef,
+}
+
+impl Entry {
+
+    /// Returns true if the entry is a folder.
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryRef::Folder
+    }
+
+    /// Returns true if the entry is a file.
+    pub fn is_file(&self) -> bool {
+        matches!(self.kind, EntryRef::File(..))
+    }
+
+    /// Returns the size of a file, or 0 for a folder.
+    pub fn size(&self) -> u64 {
+        match self.kind {
+            EntryRef::File(_, size) => size,
+            EntryRef::Folder => 0,
+        }
+    }
+}
+
+/// Options used by `Asar::walk_with` to limit traversal.
+///
+/// - max_depth: Maximum depth to descend, where 1 lists only the immediate children of the prefix (unlimited if `None`)
+///
+/// - prefix: Folder to start from, the archive root if `None`
+///
+/// - follow_links: Resolve entries with a `link` field to their target; otherwise they are skipped.
+///   Links to folders are listed but not descended into, so link cycles can not recurse forever.
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    pub max_depth: Option<usize>,
+    pub prefix: Option<PathBuf>,
+    pub follow_links: bool,
+}
+
+impl Asar {
+
+    /// Lists the immediate children of the folder at the provided path within an opened Asar archive file,
+    /// where an empty path refers to the archive root.
+    ///
+    /// Returns a vector of entries sorted by name, otherwise Error if the path is not a folder.
+    pub fn read_dir<P: AsRef<Path>>(&self, path: P) -> Result<Vec<Entry>, asar_error::Error> {
+        Ok(self
+            .children(path.as_ref(), false)?
+            .into_iter()
+            .map(|(entry, _)| entry)
+            .collect())
+    }
+
+    /// Returns every file and folder within an opened Asar archive file, depth-first with folders
+    /// listed before their contents.
+    pub fn walk(&self) -> Result<Vec<Entry>, asar_error::Error> {
+        self.walk_with(&WalkOptions::default())
+    }
+
+    /// Returns the files and folders within an opened Asar archive file, depth-first, limited by the
+    /// provided WalkOptions.
+    ///
+    /// Only the folders that are walked are parsed, so enumerating a single subtree or the first
+    /// levels of a very large archive is cheap.
+    ///
+    /// Returns a vector of entries, otherwise Error if the prefix is not a folder.
+    pub fn walk_with(&self, options: &WalkOptions) -> Result<Vec<Entry>, asar_error::Error> {
+        let mut entries: Vec<Entry> = Vec::new();
+        let prefix = options.prefix.clone().unwrap_or_default();
+
+        self.walk_aux(&prefix, 1, options, &mut entries)?;
+
+        Ok(entries)
+    }
+
+    fn walk_aux(&self, path: &Path, depth: usize, options: &WalkOptions, entries: &mut Vec<Entry>) -> Result<(), asar_error::Error> {
+        if options.max_depth.is_some_and(|max| depth > max) {
+            return Ok(());
+        }
+
+        for (entry, is_link) in self.children(path, options.follow_links)? {
+            let recurse = entry.is_dir() && !is_link;
+            let child = entry.path.clone();
+
+            entries.push(entry);
+
+            if recurse {
+                self.walk_aux(&child, depth + 1, options, entries)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Lists the immediate children of a folder along with whether each is a followed link.
+    fn children(&self, path: &Path, follow_links: bool) -> Result<Vec<(Entry, bool)>, asar_error::Error> {
+        let dir = self.content.folder_contents(path).ok_or_else(|| {
+            Error::UnknownContentType(format!("Not a folder within archive: {}", path.display()))
+        })?;
+
+        let mut entries: Vec<(Entry, bool)> = Vec::new();
+
+        for (name, value) in dir.iter() {
+            if let Value::Object(item) = value {
+                let is_link = item.contains_key("link");
+
+                let kind = if let Some(link) = item.get("link") {
+                    if !follow_links {
+                        continue;
+                    }
+
+                    // links are relative to the archive root
+                    match link.as_str().and_then(|target| self.stat(target)) {
+                        Some(kind) => kind,
+                        None => continue,
+                    }
+                } else if content::is_external(item) {
+                    continue; // unpacked
+                } else {
+                    // read from the borrowed entry, as a folder would be cloned as Content
+                    match header::file_entry::<io::Error>(name, item)? {
+                        Some((offset, size)) => EntryRef::File(offset, size),
+                        None if matches!(item.get("files"), Some(Value::Object(_))) => EntryRef::Folder,
+                        None => {
+                            return Err(Error::ParseHeaderError(format!(
+                                "Error parsing header for entity: {}",
+                                name
+                            )))
+                        }
+                    }
+                };
+
+                let entry = Entry {
+                    path: path.join(name),
+                    name: name.clone(),
+                    kind,
+                };
+
+                entries.push((entry, is_link));
+            }
+        }
+
+        Ok(entries)
+    }
+}